@@ -0,0 +1,198 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::Display;
+use std::path::Path;
+
+use crate::error::Result;
+
+/// Files that `pdc` writes into every pdx image, which are not assets of the game.
+const PDX_FILES: [&str; 6] = [
+  "pdxinfo",
+  "pdex.bin",
+  "pdex.dll",
+  "pdex.so",
+  "pdex.dylib",
+  "pdex.elf",
+];
+/// Keys in the `pdxinfo` manifest which name an asset file.
+const MANIFEST_FILE_KEYS: [&str; 1] = ["launchSoundPath"];
+/// Keys in the `pdxinfo` manifest which name a folder of assets, all of which are used.
+const MANIFEST_FOLDER_KEYS: [&str; 1] = ["imagePath"];
+
+/// A place where an asset is named, by the `craydate::asset!()` macro or the `pdxinfo` manifest.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct AssetReference {
+  /// The asset's path in the pdx image, without a file extension.
+  pub asset: String,
+  /// Where the asset was named, as a `file:line` string.
+  pub location: String,
+}
+
+/// The result of `audit_assets()`, comparing the assets packaged in a pdx image with the assets
+/// that the game names.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AssetAudit {
+  /// Assets which are packaged in the pdx image but never named. They make the game larger than
+  /// it needs to be.
+  pub unused: Vec<String>,
+  /// Assets which are named but not packaged in the pdx image. Loading them will fail at runtime.
+  pub missing: Vec<AssetReference>,
+}
+impl AssetAudit {
+  /// Whether there are no unused or missing assets.
+  pub fn is_clean(&self) -> bool {
+    self.unused.is_empty() && self.missing.is_empty()
+  }
+}
+impl Display for AssetAudit {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    if self.is_clean() {
+      return writeln!(
+        f,
+        "All packaged assets are used, and all used assets are packaged."
+      );
+    }
+    for r in &self.missing {
+      writeln!(f, "missing asset: {} (named at {})", r.asset, r.location)?;
+    }
+    for asset in &self.unused {
+      writeln!(f, "unused asset: {}", asset)?;
+    }
+    Ok(())
+  }
+}
+
+/// Compares the assets packaged in the pdx image at `pdx_dir` against the assets the game names,
+/// to find assets that are unused or missing before shipping the game.
+///
+/// Assets are named by uses of the `craydate::asset!()` macro in the Rust files under
+/// `source_dirs`, and by the `pdxinfo` manifest in the pdx image. Asset paths are compared without
+/// their file extension, as `pdc` changes the extension of the files it compiles.
+///
+/// Paths passed directly to a load function, rather than through `asset!()`, can not be seen and
+/// their assets will be reported as unused.
+pub fn audit_assets(
+  pdx_dir: impl AsRef<Path>,
+  source_dirs: &[impl AsRef<Path>],
+) -> Result<AssetAudit> {
+  let pdx_dir = pdx_dir.as_ref();
+
+  let mut packaged = BTreeSet::new();
+  for file in list_files(pdx_dir)? {
+    let relative = file.strip_prefix(pdx_dir).unwrap();
+    let relative = relative.to_string_lossy().replace('\\', "/");
+    if !PDX_FILES.contains(&relative.as_str()) {
+      packaged.insert(strip_extension(&relative).to_string());
+    }
+  }
+
+  let mut references = Vec::new();
+  let mut used_folders = Vec::new();
+  let manifest_path = pdx_dir.join("pdxinfo");
+  if manifest_path.exists() {
+    let manifest = std::fs::read_to_string(&manifest_path)?;
+    for (i, line) in manifest.lines().enumerate() {
+      let location = format!("{}:{}", manifest_path.display(), i + 1);
+      let (key, value) = match line.split_once('=') {
+        Some((key, value)) => (key.trim(), value.trim().trim_matches('/')),
+        None => continue,
+      };
+      if MANIFEST_FILE_KEYS.contains(&key) {
+        references.push(AssetReference {
+          asset: strip_extension(value).to_string(),
+          location,
+        });
+      } else if MANIFEST_FOLDER_KEYS.contains(&key) {
+        used_folders.push((format!("{}/", value), location));
+      }
+    }
+  }
+
+  for dir in source_dirs {
+    for file in list_files(dir.as_ref())? {
+      if file.extension().is_some_and(|e| e == "rs") {
+        let source = std::fs::read_to_string(&file)?;
+        for (line, asset) in find_asset_macros(&source) {
+          references.push(AssetReference {
+            asset: strip_extension(asset.trim_matches('/')).to_string(),
+            location: format!("{}:{}", file.display(), line),
+          });
+        }
+      }
+    }
+  }
+
+  let mut used = BTreeSet::new();
+  let mut missing = BTreeMap::new();
+  for r in references {
+    if packaged.contains(&r.asset) {
+      used.insert(r.asset);
+    } else {
+      missing.entry(r.asset.clone()).or_insert(r);
+    }
+  }
+  for (folder, location) in used_folders {
+    let in_folder: Vec<_> = packaged.iter().filter(|a| a.starts_with(&folder)).cloned().collect();
+    if in_folder.is_empty() {
+      missing.insert(
+        folder.clone(),
+        AssetReference {
+          asset: folder,
+          location,
+        },
+      );
+    }
+    used.extend(in_folder);
+  }
+
+  Ok(AssetAudit {
+    unused: packaged.difference(&used).cloned().collect(),
+    missing: missing.into_values().collect(),
+  })
+}
+
+/// Returns every file under `dir`, recursively.
+fn list_files(dir: &Path) -> Result<Vec<std::path::PathBuf>> {
+  let mut files = Vec::new();
+  let mut dirs = vec![dir.to_path_buf()];
+  while let Some(dir) = dirs.pop() {
+    for entry in std::fs::read_dir(dir)? {
+      let path = entry?.path();
+      if path.is_dir() {
+        dirs.push(path);
+      } else {
+        files.push(path);
+      }
+    }
+  }
+  files.sort();
+  Ok(files)
+}
+
+fn strip_extension(path: &str) -> &str {
+  let name_start = path.rfind('/').map_or(0, |i| i + 1);
+  match path[name_start..].rfind('.') {
+    Some(dot) if dot > 0 => &path[..name_start + dot],
+    _ => path,
+  }
+}
+
+/// Finds the string literals passed to `asset!()` in Rust source code, along with their line
+/// numbers.
+fn find_asset_macros(source: &str) -> Vec<(usize, &str)> {
+  const MACRO: &str = "asset!(";
+  let mut found = Vec::new();
+  for (i, _) in source.match_indices(MACRO) {
+    // Don't match other macros whose name ends in `asset`.
+    let before = source[..i].chars().next_back();
+    if before.is_some_and(|c| c.is_alphanumeric() || c == '_') {
+      continue;
+    }
+    let args = source[i + MACRO.len()..].trim_start();
+    let literal = args.strip_prefix('"').and_then(|rest| rest.find('"').map(|end| &rest[..end]));
+    if let Some(literal) = literal {
+      let line = source[..i].matches('\n').count() + 1;
+      found.push((line, literal));
+    }
+  }
+  found
+}
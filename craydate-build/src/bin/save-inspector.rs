@@ -0,0 +1,122 @@
+//! Inspects and edits a game's save files on the host, to debug save issues without the device.
+//!
+//! Usage:
+//!   save-inspector <file>
+//!   save-inspector <file> set <path> <json value> [--out <file>]
+//!   save-inspector <file> patch <offset> <hex bytes> [--out <file>]
+//!
+//! With no command, the file is printed. Images, image tables and audio compiled by `pdc` can also
+//! be printed. JSON files are edited by `set` at a path of object keys and array indices separated
+//! by '.', and any file can have its (decompressed) bytes overwritten by `patch`. Edits are written back to the file, or to the `--out` file if one is given.
+
+use craydate_build::{CompiledAsset, CraydateBuildError, Result, SaveFile};
+
+fn parse_hex(s: &str) -> Result<Vec<u8>> {
+  let s = s.trim_start_matches("0x");
+  if s.len() % 2 != 0 {
+    return Err(CraydateBuildError::String(format!(
+      "`{}` is not a whole number of hex bytes",
+      s
+    )));
+  }
+  (0..s.len())
+    .step_by(2)
+    .map(|i| {
+      u8::from_str_radix(&s[i..i + 2], 16)
+        .map_err(|_| CraydateBuildError::String(format!("`{}` is not hex", s)))
+    })
+    .collect()
+}
+
+fn parse_offset(s: &str) -> Result<usize> {
+  let parsed = match s.strip_prefix("0x") {
+    Some(hex) => usize::from_str_radix(hex, 16),
+    None => s.parse(),
+  };
+  parsed.map_err(|_| CraydateBuildError::String(format!("`{}` is not an offset", s)))
+}
+
+fn describe_asset(asset: &CompiledAsset) -> String {
+  match asset {
+    CompiledAsset::Image(image) => {
+      format!(
+        "# image, {}x{}\n{}",
+        image.width,
+        image.height,
+        image.to_ascii()
+      )
+    }
+    CompiledAsset::ImageTable(table) => {
+      let mut out = format!(
+        "# image table, {} images, {} per row\n",
+        table.images.len(),
+        table.images_per_row
+      );
+      for (i, image) in table.images.iter().enumerate() {
+        out += &format!(
+          "# image {}, {}x{}\n{}",
+          i,
+          image.width,
+          image.height,
+          image.to_ascii()
+        );
+      }
+      out
+    }
+    CompiledAsset::Audio(audio) => format!(
+      "# audio, {:?} at {} Hz, {} bytes\n",
+      audio.format,
+      audio.sample_rate,
+      audio.data.len()
+    ),
+  }
+}
+
+fn run(mut args: Vec<String>) -> Result<()> {
+  let out = match args.iter().position(|a| a == "--out") {
+    Some(i) if i + 1 < args.len() => {
+      let out = args.remove(i + 1);
+      args.remove(i);
+      Some(out)
+    }
+    Some(_) => return Err(CraydateBuildError::String("--out needs a file".to_string())),
+    None => None,
+  };
+
+  let usage = || {
+    CraydateBuildError::String(
+      "usage: save-inspector <file> [set <path> <value> | patch <offset> <hex>] [--out <file>]"
+        .to_string(),
+    )
+  };
+  let path = args.first().ok_or_else(usage)?;
+  if args.len() == 1 {
+    // Assets compiled by pdc can be printed, to check what was included in the game.
+    if let Ok(asset) = CompiledAsset::open(path) {
+      print!("{}", describe_asset(&asset));
+      return Ok(());
+    }
+  }
+  let mut file = SaveFile::open(path)?;
+  match args.get(1).map(String::as_str) {
+    None => {
+      print!("{}", file.to_pretty_string());
+      return Ok(());
+    }
+    Some("set") if args.len() == 4 => file.set_json(&args[2], &args[3])?,
+    Some("patch") if args.len() == 4 => {
+      file.patch(parse_offset(&args[2])?, &parse_hex(&args[3])?)?
+    }
+    _ => return Err(usage()),
+  }
+  file.save(out.as_ref().unwrap_or(path))?;
+  print!("{}", file.to_pretty_string());
+  Ok(())
+}
+
+fn main() {
+  if let Err(e) = run(std::env::args().skip(1).collect()) {
+    eprintln!("{}", e);
+    std::process::exit(1);
+  }
+}
@@ -0,0 +1,230 @@
+use std::path::Path;
+
+use crate::error::{CraydateBuildError, Result};
+
+const PDI_MAGIC: &[u8; 12] = b"Playdate IMG";
+const PDT_MAGIC: &[u8; 12] = b"Playdate IMT";
+const PDA_MAGIC: &[u8; 12] = b"Playdate AUD";
+
+/// An image decoded from a `.pdi` file, or a cell of a `.pdt` file, as produced by `pdc`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CompiledImage {
+  /// The width of the image, in pixels.
+  pub width: u32,
+  /// The height of the image, in pixels.
+  pub height: u32,
+  /// Whether each pixel is white, in rows from the top left.
+  pub white: Vec<bool>,
+  /// Whether each pixel is opaque, in rows from the top left. `None` if the image has no alpha
+  /// mask, in which case every pixel is opaque.
+  pub opaque: Option<Vec<bool>>,
+}
+impl CompiledImage {
+  /// Decodes the contents of a `.pdi` file.
+  pub fn decode_pdi(bytes: &[u8]) -> Result<CompiledImage> {
+    Ok(craydate_formats::compiled_image::CompiledImage::from_bytes(bytes)?.into())
+  }
+
+  /// Returns whether the pixel at `(x, y)` is white, or `None` if it is transparent or outside the
+  /// image.
+  pub fn pixel(&self, x: u32, y: u32) -> Option<bool> {
+    if x >= self.width || y >= self.height {
+      return None;
+    }
+    let i = (y * self.width + x) as usize;
+    match &self.opaque {
+      Some(opaque) if !opaque[i] => None,
+      _ => Some(self.white[i]),
+    }
+  }
+
+  /// Draws the image as text, one line per row, with '#' for black, '.' for white, and ' ' for
+  /// transparent pixels. This is useful for comparing images in tests or printing them.
+  pub fn to_ascii(&self) -> String {
+    let mut out = String::with_capacity(((self.width + 1) * self.height) as usize);
+    for y in 0..self.height {
+      for x in 0..self.width {
+        out.push(match self.pixel(x, y) {
+          Some(true) => '.',
+          Some(false) => '#',
+          None => ' ',
+        });
+      }
+      out.push('\n');
+    }
+    out
+  }
+}
+impl From<craydate_formats::compiled_image::CompiledImage> for CompiledImage {
+  fn from(image: craydate_formats::compiled_image::CompiledImage) -> Self {
+    let (width, height) = (image.width(), image.height());
+    let plane = |f: &dyn Fn(usize, usize) -> bool| {
+      (0..height).flat_map(|y| (0..width).map(move |x| (x, y))).map(|(x, y)| f(x, y)).collect()
+    };
+    CompiledImage {
+      width: width as u32,
+      height: height as u32,
+      white: plane(&|x, y| image.is_white(x, y)),
+      opaque: match image.has_mask() {
+        true => Some(plane(&|x, y| image.is_opaque(x, y))),
+        false => None,
+      },
+    }
+  }
+}
+
+/// An image table decoded from a `.pdt` file, as produced by `pdc`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CompiledImageTable {
+  /// The number of images in each row of the table, when it was made from a grid image.
+  pub images_per_row: u32,
+  /// The images in the table.
+  pub images: Vec<CompiledImage>,
+}
+impl CompiledImageTable {
+  /// Decodes the contents of a `.pdt` file.
+  pub fn decode_pdt(bytes: &[u8]) -> Result<CompiledImageTable> {
+    let table = craydate_formats::compiled_image::CompiledImageTable::from_bytes(bytes)?;
+    Ok(CompiledImageTable {
+      images_per_row: table.cells_per_row() as u32,
+      images: table.into_images().into_iter().map(CompiledImage::from).collect(),
+    })
+  }
+}
+
+/// The format of the samples in a `.pda` file.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum CompiledAudioFormat {
+  Mono8Bit,
+  Stereo8Bit,
+  Mono16Bit,
+  Stereo16Bit,
+  MonoAdpcm,
+  StereoAdpcm,
+}
+impl CompiledAudioFormat {
+  /// The number of channels in the audio.
+  pub fn channels(self) -> u32 {
+    match self {
+      Self::Mono8Bit | Self::Mono16Bit | Self::MonoAdpcm => 1,
+      Self::Stereo8Bit | Self::Stereo16Bit | Self::StereoAdpcm => 2,
+    }
+  }
+}
+
+/// Audio decoded from a `.pda` file, as produced by `pdc`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CompiledAudio {
+  /// The number of sample frames per second.
+  pub sample_rate: u32,
+  /// The format of the samples in `data`.
+  pub format: CompiledAudioFormat,
+  /// For ADPCM audio, the size in bytes of each block of encoded samples.
+  pub adpcm_block_size: Option<u16>,
+  /// The encoded samples. PCM samples are signed, little-endian, and interleaved by channel.
+  pub data: Vec<u8>,
+}
+impl CompiledAudio {
+  /// Decodes the contents of a `.pda` file.
+  pub fn decode_pda(bytes: &[u8]) -> Result<CompiledAudio> {
+    let mut r = Reader::new(bytes, ".pda");
+    if r.bytes(12)? != PDA_MAGIC {
+      return Err(r.error("missing the \"Playdate AUD\" header"));
+    }
+    let header = r.u32()?;
+    let sample_rate = header & 0x00ff_ffff;
+    let format = match header >> 24 {
+      0 => CompiledAudioFormat::Mono8Bit,
+      1 => CompiledAudioFormat::Stereo8Bit,
+      2 => CompiledAudioFormat::Mono16Bit,
+      3 => CompiledAudioFormat::Stereo16Bit,
+      4 => CompiledAudioFormat::MonoAdpcm,
+      5 => CompiledAudioFormat::StereoAdpcm,
+      f => return Err(r.error(&format!("unknown sample format {}", f))),
+    };
+    let adpcm_block_size = match format {
+      CompiledAudioFormat::MonoAdpcm | CompiledAudioFormat::StereoAdpcm => Some(r.u16()?),
+      _ => None,
+    };
+    Ok(CompiledAudio {
+      sample_rate,
+      format,
+      adpcm_block_size,
+      data: r.rest().to_vec(),
+    })
+  }
+
+  /// The PCM samples in the audio, converted to 16 bits and interleaved by channel. Returns
+  /// `None` for ADPCM audio, which must be decoded first.
+  pub fn pcm_samples(&self) -> Option<Vec<i16>> {
+    match self.format {
+      CompiledAudioFormat::Mono8Bit | CompiledAudioFormat::Stereo8Bit => {
+        Some(self.data.iter().map(|b| (*b as i8 as i16) << 8).collect())
+      }
+      CompiledAudioFormat::Mono16Bit | CompiledAudioFormat::Stereo16Bit => {
+        Some(self.data.chunks_exact(2).map(|s| i16::from_le_bytes([s[0], s[1]])).collect())
+      }
+      CompiledAudioFormat::MonoAdpcm | CompiledAudioFormat::StereoAdpcm => None,
+    }
+  }
+}
+
+/// An asset compiled by `pdc`, decoded according to the header at the start of its file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CompiledAsset {
+  Image(CompiledImage),
+  ImageTable(CompiledImageTable),
+  Audio(CompiledAudio),
+}
+impl CompiledAsset {
+  /// Decodes the contents of a `.pdi`, `.pdt` or `.pda` file.
+  pub fn decode(bytes: &[u8]) -> Result<CompiledAsset> {
+    match bytes.get(..12) {
+      Some(magic) if magic == PDI_MAGIC => CompiledImage::decode_pdi(bytes).map(Self::Image),
+      Some(magic) if magic == PDT_MAGIC => {
+        CompiledImageTable::decode_pdt(bytes).map(Self::ImageTable)
+      }
+      Some(magic) if magic == PDA_MAGIC => CompiledAudio::decode_pda(bytes).map(Self::Audio),
+      _ => Err(CraydateBuildError::String(
+        "not a compiled image, image table, or audio file".to_string(),
+      )),
+    }
+  }
+
+  /// Reads and decodes the `.pdi`, `.pdt` or `.pda` file at `path`.
+  pub fn open(path: impl AsRef<Path>) -> Result<CompiledAsset> {
+    Self::decode(&std::fs::read(path)?)
+  }
+}
+
+struct Reader<'a> {
+  data: &'a [u8],
+  pos: usize,
+  kind: &'a str,
+}
+impl<'a> Reader<'a> {
+  fn new(data: &'a [u8], kind: &'a str) -> Self {
+    Reader { data, pos: 0, kind }
+  }
+  fn error(&self, msg: &str) -> CraydateBuildError {
+    CraydateBuildError::String(format!("invalid {} file: {}", self.kind, msg))
+  }
+  fn bytes(&mut self, len: usize) -> Result<&'a [u8]> {
+    let data = self.data;
+    let bytes =
+      data.get(self.pos..self.pos + len).ok_or_else(|| self.error("unexpected end of file"))?;
+    self.pos += len;
+    Ok(bytes)
+  }
+  fn u16(&mut self) -> Result<u16> {
+    let b = self.bytes(2)?;
+    Ok(u16::from_le_bytes([b[0], b[1]]))
+  }
+  fn u32(&mut self) -> Result<u32> {
+    let b = self.bytes(4)?;
+    Ok(u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+  }
+  fn rest(&self) -> &'a [u8] {
+    &self.data[self.pos..]
+  }
+}
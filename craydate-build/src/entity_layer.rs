@@ -0,0 +1,194 @@
+use crate::error::Result;
+use crate::json::JsonValue;
+
+/// The bytes at the start of the entity layer format read by `craydate::EntityLayer`.
+const MAGIC: &[u8; 4] = b"CDEL";
+/// The version of the entity layer format that is read and written.
+const VERSION: u8 = 1;
+
+const TYPE_BOOL: u8 = 0;
+const TYPE_INT: u8 = 1;
+const TYPE_FLOAT: u8 = 2;
+const TYPE_STRING: u8 = 3;
+
+/// Converts an entity layer from its JSON form into the binary format read by
+/// `craydate::EntityLayer::from_bytes()` in the game.
+///
+/// The JSON form is what level editor importers produce, and is meant to be easy to write from
+/// any tool:
+/// ```json
+/// {
+///   "version": 1,
+///   "entities": [
+///     { "kind": "Door", "x": 64, "y": 32, "fields": { "targetLevel": "cave", "locked": true } }
+///   ]
+/// }
+/// ```
+/// Field values may be booleans, numbers or strings. Whole numbers become ints and other numbers
+/// become floats, which a float field in the game also accepts from an int. A `null` field is
+/// left out, which an `Option` field in the game reads as `None`.
+pub fn entity_layer_from_json(json: &JsonValue) -> Result<Vec<u8>> {
+  match json.get_path("version") {
+    Some(JsonValue::Number(v)) if *v == VERSION as f64 => (),
+    Some(v) => Err(format!(
+      "unsupported entity layer version {}",
+      v.to_pretty_string()
+    ))?,
+    None => Err("the entity layer is missing its `version`".to_string())?,
+  }
+  let entities = match json.get_path("entities") {
+    Some(JsonValue::Array(entities)) => entities,
+    _ => Err("the entity layer is missing its `entities` array".to_string())?,
+  };
+
+  let mut out = Vec::new();
+  out.extend_from_slice(MAGIC);
+  out.push(VERSION);
+  out.extend_from_slice(&(entities.len() as u32).to_le_bytes());
+  for (i, entity) in entities.iter().enumerate() {
+    let kind = match entity.get_path("kind") {
+      Some(JsonValue::String(kind)) => kind,
+      _ => Err(format!("entity {} is missing its `kind` string", i))?,
+    };
+    write_string(&mut out, kind)?;
+    for axis in ["x", "y"] {
+      let pos = match entity.get_path(axis) {
+        Some(JsonValue::Number(n)) => to_i32(*n),
+        _ => None,
+      };
+      let pos =
+        pos.ok_or_else(|| format!("entity {} (`{}`) needs an integer `{}`", i, kind, axis))?;
+      out.extend_from_slice(&pos.to_le_bytes());
+    }
+
+    let fields = match entity.get_path("fields") {
+      Some(JsonValue::Object(fields)) => fields.as_slice(),
+      None => &[],
+      _ => Err(format!(
+        "entity {} (`{}`) has `fields` which is not an object",
+        i, kind
+      ))?,
+    };
+    let fields: Vec<_> = fields.iter().filter(|(_, v)| *v != JsonValue::Null).collect();
+    if fields.len() > u16::MAX as usize {
+      Err(format!("entity {} (`{}`) has too many fields", i, kind))?;
+    }
+    out.extend_from_slice(&(fields.len() as u16).to_le_bytes());
+    for (name, value) in fields {
+      write_string(&mut out, name)?;
+      match value {
+        JsonValue::Bool(b) => out.extend_from_slice(&[TYPE_BOOL, *b as u8]),
+        JsonValue::Number(n) => match to_i32(*n) {
+          Some(n) => {
+            out.push(TYPE_INT);
+            out.extend_from_slice(&n.to_le_bytes());
+          }
+          None => {
+            out.push(TYPE_FLOAT);
+            out.extend_from_slice(&(*n as f32).to_bits().to_le_bytes());
+          }
+        },
+        JsonValue::String(s) => {
+          out.push(TYPE_STRING);
+          write_string(&mut out, s)?;
+        }
+        _ => Err(format!(
+          "entity {} (`{}`) field `{}` must be a boolean, number or string",
+          i, kind, name
+        ))?,
+      }
+    }
+  }
+  Ok(out)
+}
+
+/// Converts an entity layer from the binary format written by `craydate::EntityLayer::to_bytes()`
+/// back into its JSON form, such as for editing a level saved by the game.
+pub fn entity_layer_to_json(bytes: &[u8]) -> Result<JsonValue> {
+  let mut r = Reader { bytes, pos: 0 };
+  if r.take(4)? != MAGIC {
+    Err("not an entity layer".to_string())?;
+  }
+  let version = r.take(1)?[0];
+  if version != VERSION {
+    Err(format!("unsupported entity layer version {}", version))?;
+  }
+  let count = r.u32()?;
+  let mut entities = Vec::new();
+  for _ in 0..count {
+    let kind = r.string()?;
+    let x = r.u32()? as i32;
+    let y = r.u32()? as i32;
+    let mut fields = Vec::new();
+    for _ in 0..r.u16()? {
+      let name = r.string()?;
+      let value = match r.take(1)?[0] {
+        TYPE_BOOL => JsonValue::Bool(r.take(1)?[0] != 0),
+        TYPE_INT => JsonValue::Number(r.u32()? as i32 as f64),
+        TYPE_FLOAT => JsonValue::Number(f32::from_bits(r.u32()?) as f64),
+        TYPE_STRING => JsonValue::String(r.string()?),
+        t => Err(format!("unknown type {} for field `{}`", t, name))?,
+      };
+      fields.push((name, value));
+    }
+    entities.push(JsonValue::Object(vec![
+      ("kind".to_string(), JsonValue::String(kind)),
+      ("x".to_string(), JsonValue::Number(x as f64)),
+      ("y".to_string(), JsonValue::Number(y as f64)),
+      ("fields".to_string(), JsonValue::Object(fields)),
+    ]));
+  }
+  if r.pos != bytes.len() {
+    Err("unexpected bytes after the last entity".to_string())?;
+  }
+  Ok(JsonValue::Object(vec![
+    ("version".to_string(), JsonValue::Number(VERSION as f64)),
+    ("entities".to_string(), JsonValue::Array(entities)),
+  ]))
+}
+
+fn to_i32(n: f64) -> Option<i32> {
+  if n.fract() == 0.0 && n >= i32::MIN as f64 && n <= i32::MAX as f64 {
+    Some(n as i32)
+  } else {
+    None
+  }
+}
+
+fn write_string(out: &mut Vec<u8>, s: &str) -> Result<()> {
+  if s.len() > u16::MAX as usize {
+    Err(format!("a string of {} bytes is too long", s.len()))?;
+  }
+  out.extend_from_slice(&(s.len() as u16).to_le_bytes());
+  out.extend_from_slice(s.as_bytes());
+  Ok(())
+}
+
+struct Reader<'a> {
+  bytes: &'a [u8],
+  pos: usize,
+}
+impl<'a> Reader<'a> {
+  fn take(&mut self, len: usize) -> Result<&'a [u8]> {
+    let end = self.pos + len;
+    if end > self.bytes.len() {
+      Err("the entity layer ends early".to_string())?;
+    }
+    let bytes = &self.bytes[self.pos..end];
+    self.pos = end;
+    Ok(bytes)
+  }
+  fn u16(&mut self) -> Result<u16> {
+    let b = self.take(2)?;
+    Ok(u16::from_le_bytes([b[0], b[1]]))
+  }
+  fn u32(&mut self) -> Result<u32> {
+    let b = self.take(4)?;
+    Ok(u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+  }
+  fn string(&mut self) -> Result<String> {
+    let len = self.u16()? as usize;
+    String::from_utf8(self.take(len)?.to_vec())
+      .map_err(|_| "a string in the entity layer is not valid UTF-8".to_string().into())
+  }
+}
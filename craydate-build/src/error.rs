@@ -39,3 +39,8 @@ impl From<String> for CraydateBuildError {
     Self::String(s)
   }
 }
+impl From<craydate_formats::FormatError> for CraydateBuildError {
+  fn from(e: craydate_formats::FormatError) -> Self {
+    Self::String(e.into())
+  }
+}
@@ -0,0 +1,308 @@
+use crate::error::{CraydateBuildError, Result};
+
+/// A JSON value, as written by the Playdate json API or by games.
+///
+/// Objects keep their keys in the order they were read, so that a file can be edited and written
+/// back without reordering it.
+#[derive(Debug, Clone, PartialEq)]
+pub enum JsonValue {
+  Null,
+  Bool(bool),
+  Number(f64),
+  String(String),
+  Array(Vec<JsonValue>),
+  Object(Vec<(String, JsonValue)>),
+}
+
+impl JsonValue {
+  /// Parses a JSON document.
+  pub fn parse(text: &str) -> Result<JsonValue> {
+    let mut p = Parser {
+      text,
+      bytes: text.as_bytes(),
+      pos: 0,
+    };
+    let value = p.value()?;
+    p.skip_whitespace();
+    if p.pos != p.bytes.len() {
+      return Err(p.error("unexpected text after the JSON value"));
+    }
+    Ok(value)
+  }
+
+  /// Writes the value as indented JSON text.
+  pub fn to_pretty_string(&self) -> String {
+    let mut out = String::new();
+    self.write_pretty(&mut out, 0);
+    out
+  }
+
+  /// Returns the value at `path`, where `path` is a list of object keys and array indices
+  /// separated by '.', such as `players.0.name`.
+  pub fn get_path(&self, path: &str) -> Option<&JsonValue> {
+    let mut value = self;
+    for key in path.split('.').filter(|k| !k.is_empty()) {
+      value = match value {
+        JsonValue::Object(fields) => &fields.iter().find(|(k, _)| k == key)?.1,
+        JsonValue::Array(items) => items.get(key.parse::<usize>().ok()?)?,
+        _ => return None,
+      };
+    }
+    Some(value)
+  }
+
+  /// Replaces the value at `path` with `new_value`, where `path` is in the format described in
+  /// `get_path()`. A missing key in an object is added to it.
+  pub fn set_path(&mut self, path: &str, new_value: JsonValue) -> Result<()> {
+    let mut value = self;
+    for key in path.split('.').filter(|k| !k.is_empty()) {
+      value = match value {
+        JsonValue::Object(fields) => {
+          let index = match fields.iter().position(|(k, _)| k == key) {
+            Some(index) => index,
+            None => {
+              fields.push((key.to_string(), JsonValue::Null));
+              fields.len() - 1
+            }
+          };
+          &mut fields[index].1
+        }
+        JsonValue::Array(items) => {
+          let len = items.len();
+          key
+            .parse::<usize>()
+            .ok()
+            .and_then(|i| items.get_mut(i))
+            .ok_or_else(|| format!("index `{}` is not in an array of length {}", key, len))?
+        }
+        _ => Err(format!(
+          "`{}` can not be looked up in a value that is not an object or array",
+          key
+        ))?,
+      };
+    }
+    *value = new_value;
+    Ok(())
+  }
+
+  fn write_pretty(&self, out: &mut String, indent: usize) {
+    const INDENT: &str = "  ";
+    match self {
+      JsonValue::Null => out.push_str("null"),
+      JsonValue::Bool(b) => out.push_str(if *b { "true" } else { "false" }),
+      JsonValue::Number(n) => out.push_str(&format_number(*n)),
+      JsonValue::String(s) => write_string(out, s),
+      JsonValue::Array(items) if items.is_empty() => out.push_str("[]"),
+      JsonValue::Array(items) => {
+        out.push_str("[\n");
+        for (i, item) in items.iter().enumerate() {
+          out.push_str(&INDENT.repeat(indent + 1));
+          item.write_pretty(out, indent + 1);
+          out.push_str(if i + 1 < items.len() { ",\n" } else { "\n" });
+        }
+        out.push_str(&INDENT.repeat(indent));
+        out.push(']');
+      }
+      JsonValue::Object(fields) if fields.is_empty() => out.push_str("{}"),
+      JsonValue::Object(fields) => {
+        out.push_str("{\n");
+        for (i, (key, value)) in fields.iter().enumerate() {
+          out.push_str(&INDENT.repeat(indent + 1));
+          write_string(out, key);
+          out.push_str(": ");
+          value.write_pretty(out, indent + 1);
+          out.push_str(if i + 1 < fields.len() { ",\n" } else { "\n" });
+        }
+        out.push_str(&INDENT.repeat(indent));
+        out.push('}');
+      }
+    }
+  }
+}
+
+fn format_number(n: f64) -> String {
+  if n.fract() == 0.0 && n.abs() < 1e15 {
+    format!("{}", n as i64)
+  } else {
+    format!("{}", n)
+  }
+}
+
+fn write_string(out: &mut String, s: &str) {
+  out.push('"');
+  for c in s.chars() {
+    match c {
+      '"' => out.push_str("\\\""),
+      '\\' => out.push_str("\\\\"),
+      '\n' => out.push_str("\\n"),
+      '\r' => out.push_str("\\r"),
+      '\t' => out.push_str("\\t"),
+      c if (c as u32) < 0x20 => out.push_str(&format!("\\u{:04x}", c as u32)),
+      c => out.push(c),
+    }
+  }
+  out.push('"');
+}
+
+struct Parser<'a> {
+  text: &'a str,
+  bytes: &'a [u8],
+  pos: usize,
+}
+impl Parser<'_> {
+  fn error(&self, msg: &str) -> CraydateBuildError {
+    CraydateBuildError::String(format!("invalid JSON at byte {}: {}", self.pos, msg))
+  }
+
+  fn skip_whitespace(&mut self) {
+    while let Some(b' ' | b'\t' | b'\n' | b'\r') = self.bytes.get(self.pos) {
+      self.pos += 1;
+    }
+  }
+
+  fn expect(&mut self, literal: &str) -> Result<()> {
+    if self.bytes[self.pos..].starts_with(literal.as_bytes()) {
+      self.pos += literal.len();
+      Ok(())
+    } else {
+      Err(self.error(&format!("expected `{}`", literal)))
+    }
+  }
+
+  fn value(&mut self) -> Result<JsonValue> {
+    self.skip_whitespace();
+    match self.bytes.get(self.pos) {
+      Some(b'n') => self.expect("null").map(|_| JsonValue::Null),
+      Some(b't') => self.expect("true").map(|_| JsonValue::Bool(true)),
+      Some(b'f') => self.expect("false").map(|_| JsonValue::Bool(false)),
+      Some(b'"') => self.string().map(JsonValue::String),
+      Some(b'[') => {
+        self.pos += 1;
+        let mut items = Vec::new();
+        self.skip_whitespace();
+        if self.bytes.get(self.pos) == Some(&b']') {
+          self.pos += 1;
+          return Ok(JsonValue::Array(items));
+        }
+        loop {
+          items.push(self.value()?);
+          self.skip_whitespace();
+          match self.bytes.get(self.pos) {
+            Some(b',') => self.pos += 1,
+            Some(b']') => {
+              self.pos += 1;
+              return Ok(JsonValue::Array(items));
+            }
+            _ => return Err(self.error("expected `,` or `]`")),
+          }
+        }
+      }
+      Some(b'{') => {
+        self.pos += 1;
+        let mut fields = Vec::new();
+        self.skip_whitespace();
+        if self.bytes.get(self.pos) == Some(&b'}') {
+          self.pos += 1;
+          return Ok(JsonValue::Object(fields));
+        }
+        loop {
+          self.skip_whitespace();
+          if self.bytes.get(self.pos) != Some(&b'"') {
+            return Err(self.error("expected a string key"));
+          }
+          let key = self.string()?;
+          self.skip_whitespace();
+          self.expect(":")?;
+          fields.push((key, self.value()?));
+          self.skip_whitespace();
+          match self.bytes.get(self.pos) {
+            Some(b',') => self.pos += 1,
+            Some(b'}') => {
+              self.pos += 1;
+              return Ok(JsonValue::Object(fields));
+            }
+            _ => return Err(self.error("expected `,` or `}`")),
+          }
+        }
+      }
+      Some(b'-' | b'0'..=b'9') => {
+        let start = self.pos;
+        while let Some(b'-' | b'+' | b'.' | b'e' | b'E' | b'0'..=b'9') = self.bytes.get(self.pos) {
+          self.pos += 1;
+        }
+        self.text[start..self.pos]
+          .parse()
+          .map(JsonValue::Number)
+          .map_err(|_| self.error("invalid number"))
+      }
+      Some(_) => Err(self.error("expected a value")),
+      None => Err(self.error("unexpected end of text")),
+    }
+  }
+
+  fn string(&mut self) -> Result<String> {
+    // Skip the opening quote.
+    self.pos += 1;
+    let mut s = String::new();
+    loop {
+      // The position is always at a character boundary, as only whole characters are consumed.
+      let c =
+        self.text[self.pos..].chars().next().ok_or_else(|| self.error("unterminated string"))?;
+      self.pos += c.len_utf8();
+      match c {
+        '"' => return Ok(s),
+        '\\' => {
+          let escape = self.bytes.get(self.pos).copied();
+          self.pos += 1;
+          match escape {
+            Some(b'"') => s.push('"'),
+            Some(b'\\') => s.push('\\'),
+            Some(b'/') => s.push('/'),
+            Some(b'b') => s.push('\u{8}'),
+            Some(b'f') => s.push('\u{c}'),
+            Some(b'n') => s.push('\n'),
+            Some(b'r') => s.push('\r'),
+            Some(b't') => s.push('\t'),
+            Some(b'u') => {
+              let unit = self.unicode_escape()?;
+              let c = match unit {
+                // Characters outside the Basic Multilingual Plane are escaped as a UTF-16
+                // surrogate pair, with the high surrogate first.
+                0xd800..=0xdbff if self.bytes.get(self.pos..self.pos + 2) == Some(b"\\u") => {
+                  let high_end = self.pos;
+                  self.pos += 2;
+                  match self.unicode_escape()? {
+                    low @ 0xdc00..=0xdfff => {
+                      char::from_u32(0x10000 + ((unit - 0xd800) << 10) + (low - 0xdc00))
+                    }
+                    // The next escape is read again on its own.
+                    _ => {
+                      self.pos = high_end;
+                      None
+                    }
+                  }
+                }
+                _ => char::from_u32(unit),
+              };
+              s.push(c.unwrap_or(char::REPLACEMENT_CHARACTER));
+            }
+            _ => return Err(self.error("invalid escape")),
+          }
+        }
+        c => s.push(c),
+      }
+    }
+  }
+
+  /// Reads the 4 hex digits of a `\u` escape, which are a UTF-16 code unit.
+  fn unicode_escape(&mut self) -> Result<u32> {
+    let hex = self
+      .bytes
+      .get(self.pos..self.pos + 4)
+      .and_then(|h| std::str::from_utf8(h).ok())
+      .and_then(|h| u32::from_str_radix(h, 16).ok())
+      .ok_or_else(|| self.error("invalid unicode escape"))?;
+    self.pos += 4;
+    Ok(hex)
+  }
+}
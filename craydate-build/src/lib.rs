@@ -1,15 +1,33 @@
 #![deny(clippy::all)]
 
+/// Finding assets which are unused or missing from a pdx image.
+mod asset_audit;
+/// Decoding the images, image tables and audio compiled by `pdc`, for host tools.
+mod compiled_assets;
 /// Consts used to configure behaviour that may be controlled by cfgs.
 mod consts;
+/// Converting level entity layers between JSON and the format read by the game, for host tools.
+mod entity_layer;
 /// Errors that can be returned from the crate.
 mod error;
+/// Reading and writing JSON, for host tools.
+mod json;
+/// Inspecting and editing save files, for host tools.
+mod savefile;
 
 use std::env::consts::EXE_SUFFIX;
 use std::path::PathBuf;
 use std::process::Command;
 
+pub use asset_audit::{audit_assets, AssetAudit, AssetReference};
+pub use compiled_assets::{
+  CompiledAsset, CompiledAudio, CompiledAudioFormat, CompiledImage, CompiledImageTable,
+};
+pub use craydate_formats::zlib;
+pub use entity_layer::{entity_layer_from_json, entity_layer_to_json};
 pub use error::{CraydateBuildError, Result};
+pub use json::JsonValue;
+pub use savefile::{hex_dump, SaveContents, SaveFile};
 
 pub const WINDOWS: (&str, &str) = ("", ".dll");
 pub const LINUX: (&str, &str) = ("lib", ".so");
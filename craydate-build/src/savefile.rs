@@ -0,0 +1,164 @@
+use std::path::Path;
+
+use crate::error::{CraydateBuildError, Result};
+use crate::json::JsonValue;
+use crate::zlib;
+
+/// The contents of a save file, decoded by the format they were found to be in.
+#[derive(Debug, Clone, PartialEq)]
+pub enum SaveContents {
+  /// A JSON document, such as one written with the Playdate json API.
+  Json(JsonValue),
+  /// UTF-8 text which is not JSON, such as a report written by `SoakTest`.
+  Text(String),
+  /// Any other data.
+  Binary(Vec<u8>),
+}
+
+/// A save file from a game's data folder, opened on the host to inspect or edit it.
+///
+/// Files compressed with zlib are decompressed when opened, and compressed again when written.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SaveFile {
+  compressed: bool,
+  contents: SaveContents,
+}
+impl SaveFile {
+  /// Reads and decodes the file at `path`.
+  pub fn open(path: impl AsRef<Path>) -> Result<SaveFile> {
+    Self::from_bytes(std::fs::read(path)?)
+  }
+
+  /// Decodes the contents of a save file.
+  pub fn from_bytes(bytes: Vec<u8>) -> Result<SaveFile> {
+    // A JSON or text file can start with bytes that look like a zlib header, so only treat the
+    // file as compressed if it also decompresses successfully.
+    let (compressed, bytes) = match zlib::is_zlib(&bytes) {
+      true => match zlib::decompress(&bytes) {
+        Ok(decompressed) => (true, decompressed),
+        Err(_) => (false, bytes),
+      },
+      false => (false, bytes),
+    };
+    let contents = match String::from_utf8(bytes) {
+      Ok(text) => match JsonValue::parse(&text) {
+        Ok(json @ (JsonValue::Object(_) | JsonValue::Array(_))) => SaveContents::Json(json),
+        _ => SaveContents::Text(text),
+      },
+      Err(e) => SaveContents::Binary(e.into_bytes()),
+    };
+    Ok(SaveFile {
+      compressed,
+      contents,
+    })
+  }
+
+  /// Whether the file was compressed with zlib.
+  pub fn is_compressed(&self) -> bool {
+    self.compressed
+  }
+  /// The decoded contents of the file.
+  pub fn contents(&self) -> &SaveContents {
+    &self.contents
+  }
+
+  /// Returns a human-readable description of the file's contents. JSON is indented, and binary
+  /// data is shown as a hex dump.
+  pub fn to_pretty_string(&self) -> String {
+    let body = match &self.contents {
+      SaveContents::Json(json) => json.to_pretty_string(),
+      SaveContents::Text(text) => text.clone(),
+      SaveContents::Binary(bytes) => hex_dump(bytes),
+    };
+    let kind = match &self.contents {
+      SaveContents::Json(_) => "JSON",
+      SaveContents::Text(_) => "text",
+      SaveContents::Binary(_) => "binary",
+    };
+    let compressed = if self.compressed {
+      ", zlib compressed"
+    } else {
+      ""
+    };
+    format!("# {}{}\n{}\n", kind, compressed, body)
+  }
+
+  /// Replaces the value at `path` in a JSON file with `value`, which is parsed as JSON. See
+  /// `JsonValue::get_path()` for the format of `path`.
+  pub fn set_json(&mut self, path: &str, value: &str) -> Result<()> {
+    match &mut self.contents {
+      SaveContents::Json(json) => {
+        // Allow unquoted strings for convenience on the command line.
+        let value =
+          JsonValue::parse(value).unwrap_or_else(|_| JsonValue::String(value.to_string()));
+        json.set_path(path, value)
+      }
+      _ => Err(CraydateBuildError::String(
+        "the file is not JSON, so values can not be set by path".to_string(),
+      )),
+    }
+  }
+
+  /// Overwrites bytes of a file, starting at `offset` in the decompressed contents.
+  pub fn patch(&mut self, offset: usize, new_bytes: &[u8]) -> Result<()> {
+    let mut bytes = self.to_uncompressed_bytes();
+    let end = offset + new_bytes.len();
+    if end > bytes.len() {
+      return Err(CraydateBuildError::String(format!(
+        "patch ends at byte {}, past the end of the {} byte file",
+        end,
+        bytes.len()
+      )));
+    }
+    bytes[offset..end].copy_from_slice(new_bytes);
+    self.contents = Self::from_bytes(bytes)?.contents;
+    Ok(())
+  }
+
+  /// Encodes the file as it would be written to disk, compressing it if it was compressed.
+  pub fn to_bytes(&self) -> Vec<u8> {
+    let bytes = self.to_uncompressed_bytes();
+    if self.compressed {
+      zlib::compress_stored(&bytes)
+    } else {
+      bytes
+    }
+  }
+
+  /// Writes the file to `path`.
+  pub fn save(&self, path: impl AsRef<Path>) -> Result<()> {
+    Ok(std::fs::write(path, self.to_bytes())?)
+  }
+
+  fn to_uncompressed_bytes(&self) -> Vec<u8> {
+    match &self.contents {
+      SaveContents::Json(json) => json.to_pretty_string().into_bytes(),
+      SaveContents::Text(text) => text.clone().into_bytes(),
+      SaveContents::Binary(bytes) => bytes.clone(),
+    }
+  }
+}
+
+/// Formats `bytes` as lines of 16 hex bytes, with an offset and the printable ASCII characters.
+pub fn hex_dump(bytes: &[u8]) -> String {
+  let mut out = String::new();
+  for (i, line) in bytes.chunks(16).enumerate() {
+    out.push_str(&format!("{:08x}  ", i * 16));
+    for j in 0..16 {
+      match line.get(j) {
+        Some(b) => out.push_str(&format!("{:02x} ", b)),
+        None => out.push_str("   "),
+      }
+    }
+    out.push(' ');
+    out.extend(line.iter().map(|b| {
+      if b.is_ascii_graphic() || *b == b' ' {
+        *b as char
+      } else {
+        '.'
+      }
+    }));
+    out.push('\n');
+  }
+  out
+}
@@ -0,0 +1,282 @@
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use crate::FormatError;
+
+const PDI_MAGIC: &[u8; 12] = b"Playdate IMG";
+const PDT_MAGIC: &[u8; 12] = b"Playdate IMT";
+/// Set in the flags of an image or image table when its data is compressed with zlib.
+const FLAG_COMPRESSED: u32 = 0x8000_0000;
+const CELL_FLAG_HAS_MASK: u16 = 0x3;
+
+/// An image read from the contents of a `.pdi` file, or a cell of a `.pdt` file, which `pdc`
+/// compiles from a `.png` file.
+///
+/// Compressed images, which `pdc` makes when it's given the `-c` flag, can only be read when the
+/// `zlib` feature is enabled.
+///
+/// UNCLEAR: Playdate does not document the `.pdi` format, so this follows the format as it has been
+/// observed in files made by `pdc`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CompiledImage {
+  width: usize,
+  height: usize,
+  // The stored pixels are clipped to the part of the image that isn't transparent, and these are
+  // the number of transparent pixels removed from each side.
+  clip_left: usize,
+  clip_top: usize,
+  stored_width: usize,
+  stored_height: usize,
+  stride: usize,
+  pixels: Vec<u8>,
+  mask: Option<Vec<u8>>,
+}
+impl CompiledImage {
+  /// Reads the contents of a `.pdi` file.
+  pub fn from_bytes(bytes: &[u8]) -> Result<CompiledImage, FormatError> {
+    let data = read_container(bytes, PDI_MAGIC, ".pdi")?;
+    Self::read_cell(&mut Reader::new(&data, ".pdi"))
+  }
+
+  /// The width of the image in pixels.
+  pub fn width(&self) -> usize {
+    self.width
+  }
+  /// The height of the image in pixels.
+  pub fn height(&self) -> usize {
+    self.height
+  }
+  /// Whether the image has a mask, which makes some of its pixels transparent.
+  pub fn has_mask(&self) -> bool {
+    self.mask.is_some()
+  }
+
+  /// Returns whether the pixel at `(x, y)` is white, rather than black.
+  ///
+  /// Transparent pixels, and pixels outside the image, are reported as white.
+  pub fn is_white(&self, x: usize, y: usize) -> bool {
+    !self.is_opaque(x, y) || self.stored_bit(&self.pixels, x, y).unwrap_or(true)
+  }
+  /// Returns whether the pixel at `(x, y)` is opaque, which is always true inside the image if it
+  /// has no mask.
+  pub fn is_opaque(&self, x: usize, y: usize) -> bool {
+    match &self.mask {
+      Some(mask) => self.stored_bit(mask, x, y).unwrap_or(false),
+      None => x < self.width && y < self.height,
+    }
+  }
+
+  fn stored_bit(&self, bits: &[u8], x: usize, y: usize) -> Option<bool> {
+    let x = x.checked_sub(self.clip_left).filter(|x| *x < self.stored_width)?;
+    let y = y.checked_sub(self.clip_top).filter(|y| *y < self.stored_height)?;
+    let byte = bits[y * self.stride + x / 8];
+    Some((byte >> (7 - x % 8)) & 0x1 == 0x1)
+  }
+
+  fn read_cell(reader: &mut Reader) -> Result<CompiledImage, FormatError> {
+    let stored_width = reader.u16()? as usize;
+    let stored_height = reader.u16()? as usize;
+    let stride = reader.u16()? as usize;
+    let clip_left = reader.u16()? as usize;
+    let clip_right = reader.u16()? as usize;
+    let clip_top = reader.u16()? as usize;
+    let clip_bottom = reader.u16()? as usize;
+    let flags = reader.u16()?;
+    if stride * 8 < stored_width {
+      return Err(reader.error("row stride is smaller than the image width"));
+    }
+    let pixels = reader.bytes(stride * stored_height)?.into();
+    let mask = match flags & CELL_FLAG_HAS_MASK {
+      0 => None,
+      _ => Some(reader.bytes(stride * stored_height)?.into()),
+    };
+    Ok(CompiledImage {
+      width: clip_left + stored_width + clip_right,
+      height: clip_top + stored_height + clip_bottom,
+      clip_left,
+      clip_top,
+      stored_width,
+      stored_height,
+      stride,
+      pixels,
+      mask,
+    })
+  }
+}
+
+/// An image table read from the contents of a `.pdt` file, which `pdc` compiles from a folder of
+/// images or a single image that is a grid of cells.
+///
+/// As with `CompiledImage`, compressed image tables can only be read when the `zlib` feature is
+/// enabled.
+///
+/// UNCLEAR: Playdate does not document the `.pdt` format, so this follows the format as it has been
+/// observed in files made by `pdc`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CompiledImageTable {
+  images: Vec<CompiledImage>,
+  cells_per_row: usize,
+}
+impl CompiledImageTable {
+  /// Reads the contents of a `.pdt` file.
+  pub fn from_bytes(bytes: &[u8]) -> Result<CompiledImageTable, FormatError> {
+    let data = read_container(bytes, PDT_MAGIC, ".pdt")?;
+    let mut reader = Reader::new(&data, ".pdt");
+    let len = reader.u16()? as usize;
+    let cells_per_row = reader.u16()? as usize;
+    // The offset of the end of each cell, from the end of the offset table. The first cell starts
+    // right after the table.
+    let ends = (0..len).map(|_| reader.u32()).collect::<Result<Vec<_>, _>>()?;
+    let cells = reader.rest();
+    let mut images = Vec::with_capacity(len);
+    let mut start = 0;
+    for end in ends {
+      let end = end as usize;
+      let cell =
+        cells.get(start..end).ok_or_else(|| reader.error("the table has a cell past its end"))?;
+      images.push(CompiledImage::read_cell(&mut Reader::new(cell, ".pdt"))?);
+      start = end;
+    }
+    Ok(CompiledImageTable {
+      images,
+      cells_per_row,
+    })
+  }
+
+  /// The number of images in the table.
+  pub fn len(&self) -> usize {
+    self.images.len()
+  }
+  /// Whether the table has no images.
+  pub fn is_empty(&self) -> bool {
+    self.images.is_empty()
+  }
+  /// The number of images in each row, when the table was made from a grid of cells.
+  pub fn cells_per_row(&self) -> usize {
+    self.cells_per_row
+  }
+  /// Returns the images in the table, in order.
+  pub fn images(&self) -> &[CompiledImage] {
+    &self.images
+  }
+  /// Returns the images in the table, in order, consuming the table.
+  pub fn into_images(self) -> Vec<CompiledImage> {
+    self.images
+  }
+}
+
+/// Checks the file's `magic` header, and returns the data that follows it, decompressed if it was
+/// compressed.
+fn read_container(bytes: &[u8], magic: &[u8; 12], kind: &str) -> Result<Vec<u8>, FormatError> {
+  let mut reader = Reader::new(bytes, kind);
+  if reader.bytes(magic.len()).ok() != Some(magic) {
+    return Err(reader.error(&format!(
+      "missing the \"{}\" header",
+      String::from_utf8_lossy(magic)
+    )));
+  }
+  let flags = reader.u32()?;
+  if flags & FLAG_COMPRESSED == 0 {
+    return Ok(reader.rest().into());
+  }
+  // The compressed data is preceded by its decompressed size, and the width and height of the
+  // image (or first image of a table), followed by a reserved field or the number of images in
+  // the table. These are repeated in the decompressed data.
+  let decompressed_size = reader.u32()? as usize;
+  reader.bytes(12)?;
+  #[cfg(feature = "zlib")]
+  {
+    let data = crate::zlib::decompress(reader.rest())?;
+    if data.len() != decompressed_size {
+      return Err(reader.error("decompressed data is not the size given in the header"));
+    }
+    Ok(data)
+  }
+  #[cfg(not(feature = "zlib"))]
+  {
+    let _ = decompressed_size;
+    Err(reader.error("the data is compressed, which needs zlib support"))
+  }
+}
+
+struct Reader<'a> {
+  bytes: &'a [u8],
+  kind: &'a str,
+}
+impl<'a> Reader<'a> {
+  fn new(bytes: &'a [u8], kind: &'a str) -> Self {
+    Reader { bytes, kind }
+  }
+  fn error(&self, msg: &str) -> FormatError {
+    FormatError::new(format!("invalid {} file: {}", self.kind, msg))
+  }
+  fn bytes(&mut self, len: usize) -> Result<&'a [u8], FormatError> {
+    if self.bytes.len() < len {
+      return Err(self.error("the data ended early"));
+    }
+    let (read, rest) = self.bytes.split_at(len);
+    self.bytes = rest;
+    Ok(read)
+  }
+  fn u16(&mut self) -> Result<u16, FormatError> {
+    Ok(u16::from_le_bytes(self.bytes(2)?.try_into().unwrap()))
+  }
+  fn u32(&mut self) -> Result<u32, FormatError> {
+    Ok(u32::from_le_bytes(self.bytes(4)?.try_into().unwrap()))
+  }
+  fn rest(&self) -> &'a [u8] {
+    self.bytes
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  /// A 3x2 image cell, with one transparent column clipped from its left side. The stored pixels
+  /// are white then black, and the mask makes the bottom right pixel transparent.
+  fn cell() -> Vec<u8> {
+    let header: [u16; 8] = [2, 2, 1, 1, 0, 0, 0, CELL_FLAG_HAS_MASK];
+    let mut cell: Vec<u8> = header.iter().flat_map(|v| v.to_le_bytes()).collect();
+    cell.extend_from_slice(&[0b1000_0000, 0b1000_0000, 0b1100_0000, 0b1000_0000]);
+    cell
+  }
+
+  #[test]
+  fn image() {
+    let mut bytes = PDI_MAGIC.to_vec();
+    bytes.extend_from_slice(&0u32.to_le_bytes());
+    bytes.extend_from_slice(&cell());
+    let image = CompiledImage::from_bytes(&bytes).unwrap();
+    assert_eq!((image.width(), image.height()), (3, 2));
+    assert!(image.has_mask());
+    let white = |y| [0, 1, 2].map(|x| image.is_white(x, y));
+    let opaque = |y| [0, 1, 2].map(|x| image.is_opaque(x, y));
+    assert_eq!(white(0), [true, true, false]);
+    assert_eq!(opaque(0), [false, true, true]);
+    assert_eq!(white(1), [true, true, true]);
+    assert_eq!(opaque(1), [false, true, false]);
+
+    assert!(CompiledImage::from_bytes(&bytes[..bytes.len() - 1]).is_err());
+    assert!(CompiledImage::from_bytes(b"Playdate IMT").is_err());
+  }
+
+  #[test]
+  fn image_table() {
+    let mut bytes = PDT_MAGIC.to_vec();
+    bytes.extend_from_slice(&0u32.to_le_bytes());
+    bytes.extend_from_slice(&2u16.to_le_bytes());
+    bytes.extend_from_slice(&1u16.to_le_bytes());
+    let len = cell().len() as u32;
+    bytes.extend_from_slice(&len.to_le_bytes());
+    bytes.extend_from_slice(&(len * 2).to_le_bytes());
+    bytes.extend_from_slice(&cell());
+    bytes.extend_from_slice(&cell());
+    let table = CompiledImageTable::from_bytes(&bytes).unwrap();
+    assert_eq!((table.len(), table.cells_per_row()), (2, 1));
+    assert!(table.images().iter().all(|image| image.width() == 3));
+
+    assert!(CompiledImageTable::from_bytes(&bytes[..bytes.len() - 1]).is_err());
+  }
+}
@@ -0,0 +1,28 @@
+use alloc::string::String;
+use core::fmt::Display;
+
+/// An error for data that is not in the format it was read as, with a description of what was
+/// wrong with it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FormatError(String);
+impl FormatError {
+  pub(crate) fn new(msg: String) -> Self {
+    FormatError(msg)
+  }
+  /// The description of what was wrong with the data.
+  pub fn message(&self) -> &str {
+    &self.0
+  }
+}
+
+impl Display for FormatError {
+  fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+    write!(f, "{}", self.0)
+  }
+}
+
+impl From<FormatError> for String {
+  fn from(e: FormatError) -> Self {
+    e.0
+  }
+}
@@ -0,0 +1,20 @@
+//! Readers and writers for the file formats used by Playdate games.
+//!
+//! This crate is shared by `craydate`, which runs on the Playdate, and `craydate-build`, which
+//! runs on the host, so that both read the formats the same way. It is `no_std`, and only needs
+//! an allocator.
+
+#![no_std]
+#![deny(clippy::all)]
+
+extern crate alloc;
+
+/// Reading the images and image tables compiled by `pdc`.
+pub mod compiled_image;
+/// Errors that can be returned from the crate.
+mod error;
+/// Reading and writing of zlib compressed data.
+#[cfg(feature = "zlib")]
+pub mod zlib;
+
+pub use error::FormatError;
@@ -0,0 +1,456 @@
+//! Reading and writing of zlib compressed data (RFC 1950 and RFC 1951).
+
+use alloc::format;
+use alloc::vec::Vec;
+
+use crate::FormatError;
+
+/// Returns whether `data` starts with a valid zlib header.
+pub fn is_zlib(data: &[u8]) -> bool {
+  data.len() >= 2
+    && data[0] & 0x0f == 8
+    && data[0] >> 4 <= 7
+    && ((data[0] as u16) << 8 | data[1] as u16) % 31 == 0
+    && data[1] & 0x20 == 0
+}
+
+/// Compresses `data` into the zlib format, which is deflate compressed data with a small header
+/// and a checksum.
+///
+/// The compression favours speed and a small amount of memory over the smallest output.
+pub fn compress(data: &[u8]) -> Vec<u8> {
+  let mut out = BitWriter::default();
+  // The header is for deflate with a 32KB window, and the default compression level.
+  out.bytes.extend_from_slice(&[0x78, 0x9c]);
+  deflate(data, &mut out);
+  out.align_to_byte();
+  // Data that doesn't compress, such as data that is already compressed, is stored as is instead.
+  let stored_len = 2 + data.len() + 5 * data.len().div_ceil(u16::MAX as usize).max(1);
+  if out.bytes.len() > stored_len {
+    out.bytes.truncate(2);
+    store(data, &mut out);
+  }
+  out.bytes.extend_from_slice(&adler32(data).to_be_bytes());
+  out.bytes
+}
+
+/// Wraps `data` in the zlib format without compressing it, using stored blocks.
+///
+/// The output can be read by any zlib decompressor, including `decompress()`, and is useful to
+/// write back a file that was edited after decompressing it.
+pub fn compress_stored(data: &[u8]) -> Vec<u8> {
+  let mut out = BitWriter::default();
+  // The header is for deflate with a 32KB window, and the fastest compression level.
+  out.bytes.extend_from_slice(&[0x78, 0x01]);
+  store(data, &mut out);
+  out.bytes.extend_from_slice(&adler32(data).to_be_bytes());
+  out.bytes
+}
+
+/// Decompresses `data` in the zlib format, as made by `compress()` or another zlib encoder.
+///
+/// Returns an error if the data is not valid zlib data, or its checksum does not match.
+pub fn decompress(data: &[u8]) -> Result<Vec<u8>, FormatError> {
+  if data.len() < 6 {
+    return Err(error("the data is too short"));
+  }
+  if !is_zlib(data) {
+    return Err(match data[1] & 0x20 {
+      0 => error("the header is not valid"),
+      _ => error("preset dictionaries are not supported"),
+    });
+  }
+  let mut reader = BitReader::new(&data[2..]);
+  let mut out = Vec::new();
+  inflate(&mut reader, &mut out)?;
+  let checksum = reader.remaining_bytes();
+  if checksum.len() < 4 || u32::from_be_bytes(checksum[..4].try_into().unwrap()) != adler32(&out) {
+    return Err(error("the checksum does not match"));
+  }
+  Ok(out)
+}
+
+fn error(msg: &str) -> FormatError {
+  FormatError::new(format!("invalid zlib data: {}", msg))
+}
+
+fn adler32(data: &[u8]) -> u32 {
+  const MOD: u32 = 65521;
+  let (mut a, mut b) = (1u32, 0u32);
+  // The sums can't overflow within a chunk of this size before being reduced.
+  for chunk in data.chunks(5552) {
+    for byte in chunk {
+      a += *byte as u32;
+      b += a;
+    }
+    a %= MOD;
+    b %= MOD;
+  }
+  (b << 16) | a
+}
+
+// The base values and number of extra bits for each length and distance code, from RFC 1951.
+const LENGTH_BASE: [u16; 29] = [
+  3, 4, 5, 6, 7, 8, 9, 10, 11, 13, 15, 17, 19, 23, 27, 31, 35, 43, 51, 59, 67, 83, 99, 115, 131,
+  163, 195, 227, 258,
+];
+const LENGTH_EXTRA: [u8; 29] = [
+  0, 0, 0, 0, 0, 0, 0, 0, 1, 1, 1, 1, 2, 2, 2, 2, 3, 3, 3, 3, 4, 4, 4, 4, 5, 5, 5, 5, 0,
+];
+const DIST_BASE: [u16; 30] = [
+  1, 2, 3, 4, 5, 7, 9, 13, 17, 25, 33, 49, 65, 97, 129, 193, 257, 385, 513, 769, 1025, 1537, 2049,
+  3073, 4097, 6145, 8193, 12289, 16385, 24577,
+];
+const DIST_EXTRA: [u8; 30] = [
+  0, 0, 0, 0, 1, 1, 2, 2, 3, 3, 4, 4, 5, 5, 6, 6, 7, 7, 8, 8, 9, 9, 10, 10, 11, 11, 12, 12, 13, 13,
+];
+// The order that code length code lengths are given in a dynamic block.
+const CODE_LENGTH_ORDER: [usize; 19] = [
+  16, 17, 18, 0, 8, 7, 9, 6, 10, 5, 11, 4, 12, 3, 13, 2, 14, 1, 15,
+];
+
+const WINDOW_SIZE: usize = 32768;
+const MIN_MATCH: usize = 3;
+const MAX_MATCH: usize = 258;
+
+/// Reads bits from the least significant bit of each byte first, as deflate data is packed.
+struct BitReader<'a> {
+  data: &'a [u8],
+  pos: usize,
+  bits: u32,
+  count: u32,
+}
+impl<'a> BitReader<'a> {
+  fn new(data: &'a [u8]) -> Self {
+    BitReader {
+      data,
+      pos: 0,
+      bits: 0,
+      count: 0,
+    }
+  }
+  fn bits(&mut self, n: u32) -> Result<u32, FormatError> {
+    while self.count < n {
+      let byte = *self.data.get(self.pos).ok_or_else(|| error("the data ended early"))?;
+      self.pos += 1;
+      self.bits |= (byte as u32) << self.count;
+      self.count += 8;
+    }
+    let value = self.bits & ((1u32 << n) - 1);
+    self.bits >>= n;
+    self.count -= n;
+    Ok(value)
+  }
+  fn align_to_byte(&mut self) {
+    self.bits = 0;
+    self.count = 0;
+  }
+  fn remaining_bytes(&self) -> &'a [u8] {
+    // Whole bytes left in the bit buffer were read ahead, and are given back.
+    &self.data[self.pos - (self.count / 8) as usize..]
+  }
+}
+
+/// A canonical Huffman code, stored as the number of codes of each length and the symbols in
+/// order of their codes.
+struct Huffman {
+  counts: [u16; 16],
+  symbols: Vec<u16>,
+}
+impl Huffman {
+  fn new(lengths: &[u8]) -> Self {
+    let mut counts = [0u16; 16];
+    for len in lengths {
+      counts[*len as usize] += 1;
+    }
+    counts[0] = 0;
+    let mut offsets = [0u16; 16];
+    for len in 1..16 {
+      offsets[len] = offsets[len - 1] + counts[len - 1];
+    }
+    let mut symbols = alloc::vec![0; lengths.len()];
+    for (symbol, len) in lengths.iter().enumerate().filter(|(_, len)| **len != 0) {
+      symbols[offsets[*len as usize] as usize] = symbol as u16;
+      offsets[*len as usize] += 1;
+    }
+    Huffman { counts, symbols }
+  }
+  fn decode(&self, reader: &mut BitReader) -> Result<u16, FormatError> {
+    // The codes of each length are consecutive, and follow on from the codes of the previous
+    // length, so the code is built up one bit at a time until it falls in a length's range.
+    let (mut code, mut first, mut index) = (0i32, 0i32, 0i32);
+    for len in 1..16 {
+      code |= reader.bits(1)? as i32;
+      let count = self.counts[len] as i32;
+      if code - first < count {
+        return Ok(self.symbols[(index + code - first) as usize]);
+      }
+      index += count;
+      first = (first + count) << 1;
+      code <<= 1;
+    }
+    Err(error("the data has an invalid code"))
+  }
+}
+
+fn fixed_huffman() -> (Huffman, Huffman) {
+  let mut lengths = [0u8; 288];
+  lengths[..144].fill(8);
+  lengths[144..256].fill(9);
+  lengths[256..280].fill(7);
+  lengths[280..].fill(8);
+  (Huffman::new(&lengths), Huffman::new(&[5; 30]))
+}
+
+fn dynamic_huffman(reader: &mut BitReader) -> Result<(Huffman, Huffman), FormatError> {
+  let literals = reader.bits(5)? as usize + 257;
+  let distances = reader.bits(5)? as usize + 1;
+  let code_lengths = reader.bits(4)? as usize + 4;
+  let mut lengths = [0u8; 19];
+  for i in CODE_LENGTH_ORDER.iter().take(code_lengths) {
+    lengths[*i] = reader.bits(3)? as u8;
+  }
+  let code_length_huffman = Huffman::new(&lengths);
+
+  let mut lengths = Vec::with_capacity(literals + distances);
+  while lengths.len() < literals + distances {
+    let (value, repeat) = match code_length_huffman.decode(reader)? {
+      len @ 0..=15 => (len as u8, 1),
+      16 => {
+        let previous = *lengths.last().ok_or_else(|| error("the data repeats a missing length"))?;
+        (previous, 3 + reader.bits(2)?)
+      }
+      17 => (0, 3 + reader.bits(3)?),
+      _ => (0, 11 + reader.bits(7)?),
+    };
+    for _ in 0..repeat {
+      lengths.push(value);
+    }
+  }
+  if lengths.len() != literals + distances {
+    return Err(error("the data has too many code lengths"));
+  }
+  Ok((
+    Huffman::new(&lengths[..literals]),
+    Huffman::new(&lengths[literals..]),
+  ))
+}
+
+fn inflate(reader: &mut BitReader, out: &mut Vec<u8>) -> Result<(), FormatError> {
+  loop {
+    let last = reader.bits(1)? == 1;
+    match reader.bits(2)? {
+      0 => {
+        reader.align_to_byte();
+        let header = reader.remaining_bytes();
+        if header.len() < 4 {
+          return Err(error("the data ended early"));
+        }
+        let len = u16::from_le_bytes([header[0], header[1]]) as usize;
+        let nlen = u16::from_le_bytes([header[2], header[3]]) as usize;
+        if len != !nlen & 0xffff || header.len() < 4 + len {
+          return Err(error("the data has an invalid stored block"));
+        }
+        out.extend_from_slice(&header[4..4 + len]);
+        reader.pos = reader.data.len() - header.len() + 4 + len;
+      }
+      1 => {
+        let (literals, distances) = fixed_huffman();
+        inflate_block(reader, out, &literals, &distances)?;
+      }
+      2 => {
+        let (literals, distances) = dynamic_huffman(reader)?;
+        inflate_block(reader, out, &literals, &distances)?;
+      }
+      _ => return Err(error("the data has an invalid block type")),
+    }
+    if last {
+      return Ok(());
+    }
+  }
+}
+
+fn inflate_block(
+  reader: &mut BitReader,
+  out: &mut Vec<u8>,
+  literals: &Huffman,
+  distances: &Huffman,
+) -> Result<(), FormatError> {
+  loop {
+    let symbol = literals.decode(reader)? as usize;
+    match symbol {
+      0..=255 => out.push(symbol as u8),
+      256 => return Ok(()),
+      _ => {
+        let i = symbol - 257;
+        if i >= LENGTH_BASE.len() {
+          return Err(error("the data has an invalid length"));
+        }
+        let len = LENGTH_BASE[i] as usize + reader.bits(LENGTH_EXTRA[i] as u32)? as usize;
+        let d = distances.decode(reader)? as usize;
+        if d >= DIST_BASE.len() {
+          return Err(error("the data has an invalid distance"));
+        }
+        let dist = DIST_BASE[d] as usize + reader.bits(DIST_EXTRA[d] as u32)? as usize;
+        if dist > out.len() {
+          return Err(error("the data refers to bytes before its start"));
+        }
+        // The copy can overlap the bytes it is making, so it goes one byte at a time.
+        let start = out.len() - dist;
+        for j in 0..len {
+          out.push(out[start + j]);
+        }
+      }
+    }
+  }
+}
+
+/// Writes bits from the least significant bit of each byte first, as deflate data is packed.
+#[derive(Default)]
+struct BitWriter {
+  bytes: Vec<u8>,
+  bits: u32,
+  count: u32,
+}
+impl BitWriter {
+  fn bits(&mut self, value: u32, n: u32) {
+    self.bits |= value << self.count;
+    self.count += n;
+    while self.count >= 8 {
+      self.bytes.push(self.bits as u8);
+      self.bits >>= 8;
+      self.count -= 8;
+    }
+  }
+  /// Writes a Huffman code, which is packed starting from its most significant bit.
+  fn code(&mut self, code: u32, n: u32) {
+    self.bits(code.reverse_bits() >> (32 - n), n)
+  }
+  fn align_to_byte(&mut self) {
+    if self.count > 0 {
+      self.bytes.push(self.bits as u8);
+      self.bits = 0;
+      self.count = 0;
+    }
+  }
+
+  fn literal(&mut self, symbol: u32) {
+    match symbol {
+      0..=143 => self.code(0x30 + symbol, 8),
+      144..=255 => self.code(0x190 + symbol - 144, 9),
+      256..=279 => self.code(symbol - 256, 7),
+      _ => self.code(0xc0 + symbol - 280, 8),
+    }
+  }
+  fn length_and_distance(&mut self, len: usize, dist: usize) {
+    let i = LENGTH_BASE.iter().rposition(|base| *base as usize <= len).unwrap();
+    self.literal(257 + i as u32);
+    self.bits(
+      (len - LENGTH_BASE[i] as usize) as u32,
+      LENGTH_EXTRA[i] as u32,
+    );
+    let d = DIST_BASE.iter().rposition(|base| *base as usize <= dist).unwrap();
+    self.code(d as u32, 5);
+    self.bits((dist - DIST_BASE[d] as usize) as u32, DIST_EXTRA[d] as u32);
+  }
+}
+
+/// Writes `data` as deflate blocks which are stored without compression.
+fn store(data: &[u8], out: &mut BitWriter) {
+  let mut chunks = data.chunks(u16::MAX as usize);
+  // Empty data still needs one block, to mark the end.
+  let empty: &[u8] = &[];
+  let mut next = chunks.next().or(Some(empty));
+  while let Some(chunk) = next {
+    next = chunks.next();
+    out.bytes.push(next.is_none() as u8);
+    out.bytes.extend_from_slice(&(chunk.len() as u16).to_le_bytes());
+    out.bytes.extend_from_slice(&(!(chunk.len() as u16)).to_le_bytes());
+    out.bytes.extend_from_slice(chunk);
+  }
+}
+
+/// Compresses `data` as a single deflate block with the fixed Huffman codes, finding repeated
+/// bytes through a hash of each 3 bytes.
+fn deflate(data: &[u8], out: &mut BitWriter) {
+  const HASH_BITS: u32 = 12;
+  const MAX_CHAIN: usize = 32;
+  let hash = |i: usize| {
+    let v = (data[i] as u32) << 16 | (data[i + 1] as u32) << 8 | data[i + 2] as u32;
+    (v.wrapping_mul(2654435761) >> (32 - HASH_BITS)) as usize
+  };
+  // The most recent position of each hash, and the previous position with the same hash as each
+  // position, which are followed to find matches.
+  let mut head = alloc::vec![usize::MAX; 1 << HASH_BITS];
+  let mut prev = alloc::vec![usize::MAX; data.len()];
+  let insert = |i: usize, head: &mut Vec<usize>, prev: &mut Vec<usize>| {
+    if i + MIN_MATCH <= data.len() {
+      let h = hash(i);
+      prev[i] = head[h];
+      head[h] = i;
+    }
+  };
+
+  // The final block, with the fixed codes.
+  out.bits(1, 1);
+  out.bits(1, 2);
+  let mut i = 0;
+  while i < data.len() {
+    let mut best = (0, 0);
+    if i + MIN_MATCH <= data.len() {
+      let mut candidate = head[hash(i)];
+      let max_len = MAX_MATCH.min(data.len() - i);
+      for _ in 0..MAX_CHAIN {
+        if candidate == usize::MAX || i - candidate > WINDOW_SIZE {
+          break;
+        }
+        let len = data[candidate..].iter().zip(&data[i..i + max_len]).take_while(|(a, b)| a == b);
+        let len = len.count();
+        if len > best.0 {
+          best = (len, i - candidate);
+          if len == max_len {
+            break;
+          }
+        }
+        candidate = prev[candidate];
+      }
+    }
+    let (len, dist) = best;
+    if len >= MIN_MATCH {
+      out.length_and_distance(len, dist);
+      for j in i..i + len {
+        insert(j, &mut head, &mut prev);
+      }
+      i += len;
+    } else {
+      out.literal(data[i] as u32);
+      insert(i, &mut head, &mut prev);
+      i += 1;
+    }
+  }
+  out.literal(256);
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn round_trip() {
+    let text = b"a level, a level, a level with repeated text in it".repeat(50);
+    for data in [&[][..], b"x", &text] {
+      assert_eq!(decompress(&compress(data)).unwrap(), data);
+      assert_eq!(decompress(&compress_stored(data)).unwrap(), data);
+      assert!(is_zlib(&compress(data)));
+    }
+    assert!(compress(&text).len() < text.len() / 4);
+  }
+
+  #[test]
+  fn decompress_rejects_changed_data() {
+    let mut compressed = compress(b"some save data");
+    *compressed.last_mut().unwrap() ^= 1;
+    assert!(decompress(&compressed).is_err());
+    assert!(decompress(b"not zlib data").is_err());
+  }
+}
@@ -8,7 +8,7 @@ extern crate syn;
 use proc_macro::TokenStream;
 use quote::{quote, quote_spanned};
 use syn::spanned::Spanned;
-use syn::{parse_macro_input, ItemFn};
+use syn::{parse_macro_input, Data, DeriveInput, Fields, ItemFn, Lit, Meta, NestedMeta};
 
 #[proc_macro_attribute]
 pub fn main(_attr: TokenStream, item: TokenStream) -> TokenStream {
@@ -54,3 +54,186 @@ pub fn main(_attr: TokenStream, item: TokenStream) -> TokenStream {
     }
   }.into()
 }
+
+/// Embeds every file in a folder into the game's binary, and expands to a `craydate::EmbeddedAssets`
+/// which can unpack them into the game's data folder.
+///
+/// The folder is relative to the root of the crate that uses the macro, where its Cargo.toml is.
+/// Files are rebuilt into the binary when they change, but adding or removing a file needs the
+/// crate to be rebuilt.
+#[proc_macro]
+pub fn include_assets(item: TokenStream) -> TokenStream {
+  let folder = parse_macro_input!(item as syn::LitStr);
+  match include_assets_impl(&folder) {
+    Ok(tokens) => tokens.into(),
+    Err(e) => e.to_compile_error().into(),
+  }
+}
+
+fn include_assets_impl(folder: &syn::LitStr) -> syn::Result<proc_macro2::TokenStream> {
+  let manifest_dir = std::env::var("CARGO_MANIFEST_DIR")
+    .map_err(|_| syn::Error::new(folder.span(), "CARGO_MANIFEST_DIR is not set"))?;
+  let root = std::path::Path::new(&manifest_dir).join(folder.value());
+  let mut files = Vec::new();
+  collect_files(&root, &root, &mut files).map_err(|e| {
+    syn::Error::new(
+      folder.span(),
+      format!("unable to read the folder '{}': {}", root.display(), e),
+    )
+  })?;
+  files.sort();
+
+  let assets = files.iter().map(|(path, full_path)| {
+    quote! {
+      ::craydate::EmbeddedAsset { path: #path, bytes: include_bytes!(#full_path) }
+    }
+  });
+  Ok(quote! {
+    ::craydate::EmbeddedAssets::new(&[#(#assets),*])
+  })
+}
+
+/// Adds each file inside `folder` to `files`, as its path relative to `root` with '/' separators,
+/// and its full path.
+fn collect_files(
+  root: &std::path::Path,
+  folder: &std::path::Path,
+  files: &mut Vec<(String, String)>,
+) -> std::io::Result<()> {
+  for entry in std::fs::read_dir(folder)? {
+    let path = entry?.path();
+    if path.is_dir() {
+      collect_files(root, &path, files)?;
+    } else {
+      let relative = path.strip_prefix(root).unwrap();
+      let names: Vec<_> = relative.components().map(|c| c.as_os_str().to_string_lossy()).collect();
+      files.push((names.join("/"), path.to_string_lossy().into_owned()));
+    }
+  }
+  Ok(())
+}
+
+/// Implements `craydate::LevelEntity` for a struct with named fields, reading each field from the
+/// level editor field of the same name. See the `LevelEntity` trait for the attributes it accepts.
+#[proc_macro_derive(LevelEntity, attributes(entity))]
+pub fn derive_level_entity(item: TokenStream) -> TokenStream {
+  let input = parse_macro_input!(item as DeriveInput);
+  match level_entity_impl(&input) {
+    Ok(tokens) => tokens.into(),
+    Err(e) => e.to_compile_error().into(),
+  }
+}
+
+#[derive(Default)]
+struct EntityAttrs {
+  kind: Option<String>,
+  rename: Option<String>,
+  default: bool,
+  position: bool,
+}
+
+fn entity_attrs(attrs: &[syn::Attribute]) -> syn::Result<EntityAttrs> {
+  let mut out = EntityAttrs::default();
+  for attr in attrs.iter().filter(|a| a.path.is_ident("entity")) {
+    let list = match attr.parse_meta()? {
+      Meta::List(list) => list,
+      meta => return Err(syn::Error::new(meta.span(), "expected #[entity(...)]")),
+    };
+    for nested in list.nested {
+      match nested {
+        NestedMeta::Meta(Meta::NameValue(nv)) if nv.path.is_ident("kind") => match nv.lit {
+          Lit::Str(s) => out.kind = Some(s.value()),
+          lit => return Err(syn::Error::new(lit.span(), "expected a string")),
+        },
+        NestedMeta::Meta(Meta::NameValue(nv)) if nv.path.is_ident("rename") => match nv.lit {
+          Lit::Str(s) => out.rename = Some(s.value()),
+          lit => return Err(syn::Error::new(lit.span(), "expected a string")),
+        },
+        NestedMeta::Meta(Meta::Path(p)) if p.is_ident("default") => out.default = true,
+        NestedMeta::Meta(Meta::Path(p)) if p.is_ident("position") => out.position = true,
+        nested => return Err(syn::Error::new(nested.span(), "unknown entity attribute")),
+      }
+    }
+  }
+  Ok(out)
+}
+
+fn level_entity_impl(input: &DeriveInput) -> syn::Result<proc_macro2::TokenStream> {
+  let ident = &input.ident;
+  let fields = match &input.data {
+    Data::Struct(data) => match &data.fields {
+      Fields::Named(fields) => &fields.named,
+      _ => {
+        return Err(syn::Error::new(
+          input.span(),
+          "LevelEntity can only be derived for a struct with named fields",
+        ))
+      }
+    },
+    _ => {
+      return Err(syn::Error::new(
+        input.span(),
+        "LevelEntity can only be derived for a struct",
+      ))
+    }
+  };
+  let struct_attrs = entity_attrs(&input.attrs)?;
+  if struct_attrs.rename.is_some() || struct_attrs.default || struct_attrs.position {
+    return Err(syn::Error::new(
+      input.span(),
+      "only #[entity(kind = \"...\")] can be used on the struct",
+    ));
+  }
+  let kind = struct_attrs.kind.unwrap_or_else(|| ident.to_string());
+
+  let mut reads = Vec::new();
+  let mut writes = Vec::new();
+  let mut position = None;
+  for field in fields {
+    let field_ident = field.ident.as_ref().unwrap();
+    let attrs = entity_attrs(&field.attrs)?;
+    if attrs.kind.is_some() {
+      return Err(syn::Error::new(field.span(), "#[entity(kind)] can only be used on the struct"));
+    }
+    if attrs.position {
+      if position.is_some() {
+        return Err(syn::Error::new(field.span(), "only one field can be the position"));
+      }
+      position = Some(field_ident);
+      reads.push(quote! { #field_ident: record.position });
+      continue;
+    }
+    let name = attrs.rename.unwrap_or_else(|| field_ident.to_string());
+    if attrs.default {
+      reads.push(quote! { #field_ident: record.get_or_default(#name)? });
+    } else {
+      reads.push(quote! { #field_ident: record.get(#name)? });
+    }
+    writes.push(quote! { record.set(#name, &self.#field_ident); });
+  }
+  let write_position = match position {
+    Some(field_ident) => quote! { self.#field_ident },
+    None => quote! { ::core::default::Default::default() },
+  };
+
+  let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+  Ok(quote! {
+    impl #impl_generics ::craydate::LevelEntity for #ident #ty_generics #where_clause {
+      const KIND: &'static str = #kind;
+
+      fn from_record(
+        record: &::craydate::EntityRecord,
+      ) -> ::core::result::Result<Self, ::craydate::EntityError> {
+        record.expect_kind(Self::KIND)?;
+        ::core::result::Result::Ok(#ident {
+          #(#reads,)*
+        })
+      }
+      fn to_record(&self) -> ::craydate::EntityRecord {
+        let mut record = ::craydate::EntityRecord::new(Self::KIND, #write_position);
+        #(#writes)*
+        record
+      }
+    }
+  })
+}
@@ -65,7 +65,9 @@ async fn main(mut api: craydate::Api) -> ! {
 
   {
     let _stencil_holder = graphics.set_stencil(&stencil);
-    graphics.draw_text("Bloop", 30, 20);
+    if let Err(e) = graphics.draw_text("Bloop", 30, 20) {
+      log(format!("ERROR: drawing text {}", e));
+    }
   }
 
   let mut copy = graphics.working_frame_bitmap();
@@ -312,6 +314,7 @@ async fn main(mut api: craydate::Api) -> ! {
       SystemEvent::NextFrame {
         inputs,
         frame_number,
+        ..
       } => (inputs, frame_number),
       SystemEvent::WillLock => {
         log("locked");
@@ -21,6 +21,16 @@ fn main() {
   let r = craydate_build::build_pdx(srcdir, env!("PDX_OUT_DIR"), env!("PDX_NAME"));
   match r {
     Ok(stdout) => println!("{}", stdout),
-    Err(e) => println!("Failed\n{}", e),
+    Err(e) => {
+      println!("Failed\n{}", e);
+      return;
+    }
+  }
+
+  // Reports assets that were packaged but not used, or used but not packaged.
+  let pdx_dir = format!("{}/{}.pdx", env!("PDX_OUT_DIR"), env!("PDX_NAME"));
+  match craydate_build::audit_assets(pdx_dir, &["playground/src"]) {
+    Ok(audit) => print!("{}", audit),
+    Err(e) => println!("Failed auditing assets\n{}", e),
   }
 }
@@ -1,6 +1,7 @@
 use core::alloc::Layout;
 use core::ffi::c_void;
 use core::ptr::null_mut;
+use core::sync::atomic::{AtomicUsize, Ordering};
 
 use static_assertions::*;
 
@@ -56,6 +57,50 @@ const fn calc_shift_for_align(addr: u64, align: usize) -> usize {
   }
 }
 
+static ALLOCATED_BYTES: AtomicUsize = AtomicUsize::new(0);
+static PEAK_ALLOCATED_BYTES: AtomicUsize = AtomicUsize::new(0);
+static LIVE_ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+static TOTAL_ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+/// Statistics about the memory allocated through the global allocator, from
+/// `System::memory_stats()`.
+///
+/// The sizes are those requested by the game, and do not include the overhead of the Playdate
+/// system allocator, or of aligning the allocations.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct MemoryStats {
+  /// The number of bytes currently allocated.
+  pub allocated_bytes: usize,
+  /// The most bytes that have been allocated at once since the game started.
+  pub peak_allocated_bytes: usize,
+  /// The number of allocations that have not been freed.
+  pub live_allocations: usize,
+  /// The number of allocations made since the game started, including those that were freed.
+  pub total_allocations: usize,
+}
+impl MemoryStats {
+  pub(crate) fn get() -> Self {
+    MemoryStats {
+      allocated_bytes: ALLOCATED_BYTES.load(Ordering::Relaxed),
+      peak_allocated_bytes: PEAK_ALLOCATED_BYTES.load(Ordering::Relaxed),
+      live_allocations: LIVE_ALLOCATIONS.load(Ordering::Relaxed),
+      total_allocations: TOTAL_ALLOCATIONS.load(Ordering::Relaxed),
+    }
+  }
+}
+
+fn record_alloc(size: usize) {
+  let allocated = ALLOCATED_BYTES.fetch_add(size, Ordering::Relaxed) + size;
+  PEAK_ALLOCATED_BYTES.fetch_max(allocated, Ordering::Relaxed);
+  LIVE_ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+  TOTAL_ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+}
+
+fn record_dealloc(size: usize) {
+  ALLOCATED_BYTES.fetch_sub(size, Ordering::Relaxed);
+  LIVE_ALLOCATIONS.fetch_sub(1, Ordering::Relaxed);
+}
+
 /// The global allocator implementation.
 pub struct Allocator {
   /// Static reference to the Playdate C Api where the `realloc()` function pointer lives. No
@@ -166,12 +211,14 @@ unsafe impl core::alloc::GlobalAlloc for Allocator {
 
     let ptr = ptr.add(shift);
     Self::write_shift_behind_ptr(ptr, shift);
+    record_alloc(layout.size());
     ptr
   }
 
-  unsafe fn dealloc(&self, ptr: *mut u8, _layout: Layout) {
+  unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
     let shift = core::ptr::read_unaligned(ptr.sub(core::mem::size_of::<usize>()) as *mut usize);
     self.alloc_fn(ptr.sub(shift), 0);
+    record_dealloc(layout.size());
   }
 
   unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
@@ -186,6 +233,9 @@ unsafe impl core::alloc::GlobalAlloc for Allocator {
 
     let ptr = ptr.add(new_shift);
     Self::write_shift_behind_ptr(ptr, new_shift);
+    record_dealloc(layout.size());
+    record_alloc(new_size);
+    TOTAL_ALLOCATIONS.fetch_sub(1, Ordering::Relaxed);
     ptr
   }
 }
@@ -1,5 +1,7 @@
 use crate::display::Display;
 use crate::files::File;
+#[cfg(feature = "unstable-api")]
+use crate::scoreboards::Scoreboards;
 use crate::system::System;
 use crate::graphics::Graphics;
 use crate::sound::Sound;
@@ -15,6 +17,8 @@ pub struct Api {
   pub graphics: Graphics,
   pub file: File,
   pub sound: Sound,
+  #[cfg(feature = "unstable-api")]
+  pub scoreboards: Scoreboards,
 }
 impl Api {
   pub(crate) fn new() -> Api {
@@ -24,6 +28,8 @@ impl Api {
       graphics: Graphics::new(),
       file: File::new(),
       sound: Sound::new(),
+      #[cfg(feature = "unstable-api")]
+      scoreboards: Scoreboards::new(),
     }
   }
 }
@@ -0,0 +1,18 @@
+/// Names the path of an asset in the game's pdx image, such as an image or sound to load.
+///
+/// The macro expands to the path as a `&'static str`. Naming assets through it lets the asset
+/// auditor in craydate-build find every asset that the game uses, and report assets that are
+/// missing from the pdx image, or packaged into it but never used.
+///
+/// The path should be relative to the root of the pdx image, and may leave out the file extension.
+///
+/// # Example
+/// ```
+/// let music = craydate::FilePlayer::from_file(craydate::asset!("sounds/theme"))?;
+/// ```
+#[macro_export]
+macro_rules! asset {
+  ($path:literal) => {
+    $path
+  };
+}
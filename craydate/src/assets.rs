@@ -0,0 +1,134 @@
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use crate::error::{Error, FilePathError};
+use crate::files::File;
+use crate::graphics::{Bitmap, BitmapTable, Font};
+use crate::sound::AudioSample;
+
+/// Where an `Assets` looks for a file first.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum AssetOrder {
+  /// Look in the game's pdx bundle, then in the game's data folder.
+  BundleFirst,
+  /// Look in the game's data folder, then in the game's pdx bundle. This lets files downloaded or
+  /// made by the game replace the ones it shipped with.
+  DataFirst,
+}
+
+/// Where an `Assets` found a file.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum AssetLocation {
+  /// The file is in the game's pdx bundle.
+  Bundle,
+  /// The file is in the game's data folder.
+  Data,
+}
+
+/// Loads the game's assets through one API, from paths inside a `root` folder.
+///
+/// Every function returns `Error::FilePathError` with the full path of the asset when it can not
+/// be loaded, whichever type of asset it is.
+///
+/// UNCLEAR: Playdate's loaders for bitmaps, fonts and audio samples choose between the data folder
+/// and the pdx bundle themselves, so the `AssetOrder` is only used by `read_bytes()` and `locate()`.
+///
+/// # Example
+/// ```
+/// let assets = Assets::with_root(AssetOrder::DataFirst, "assets");
+/// let player = assets.bitmap("images/player")?;
+/// let level = assets.read_bytes("levels/1.bin")?;
+/// ```
+#[derive(Debug)]
+pub struct Assets {
+  order: AssetOrder,
+  root: String,
+}
+impl Assets {
+  /// Constructs an `Assets` which loads paths as they are given.
+  pub fn new(order: AssetOrder) -> Self {
+    Self::with_root(order, "")
+  }
+  /// Constructs an `Assets` which loads paths inside the `root` folder.
+  pub fn with_root(order: AssetOrder, root: &str) -> Self {
+    Assets {
+      order,
+      root: String::from(root.trim_end_matches('/')),
+    }
+  }
+
+  /// Returns the full path of the asset at `path`, inside the root folder.
+  pub fn path(&self, path: &str) -> String {
+    match self.root.is_empty() {
+      true => String::from(path),
+      false => format!("{}/{}", self.root, path),
+    }
+  }
+
+  /// Returns where the file at `path` is found, looking in the order given by the `AssetOrder`, or
+  /// `None` if it is not found.
+  pub fn locate(&self, path: &str) -> Option<AssetLocation> {
+    let full_path = self.path(path);
+    self.locations().into_iter().find(|location| Self::open(&full_path, *location).is_ok())
+  }
+
+  /// Reads the entire contents of the file at `path`, looking in the order given by the
+  /// `AssetOrder`.
+  pub fn read_bytes(&self, path: &str) -> Result<Vec<u8>, Error> {
+    let full_path = self.path(path);
+    let mut last_error = None;
+    for location in self.locations() {
+      match Self::open(&full_path, location) {
+        Ok(mut f) => {
+          let bytes = f.read_file();
+          let _close_result = f.close(); // We don't care if close() fails on a read.
+          return bytes.ok_or_else(|| Self::error(&full_path, "unable to read the file"));
+        }
+        Err(e) => last_error = Some(e),
+      }
+    }
+    Err(last_error.unwrap().into())
+  }
+
+  /// Loads the bitmap at `path`.
+  pub fn bitmap(&self, path: &str) -> Result<Bitmap, Error> {
+    let full_path = self.path(path);
+    Bitmap::from_file(&full_path).map_err(|e| Self::error(&full_path, e))
+  }
+  /// Loads the bitmap table at `path`.
+  pub fn bitmap_table(&self, path: &str) -> Result<BitmapTable, Error> {
+    let full_path = self.path(path);
+    BitmapTable::from_file(&full_path).map_err(|e| Self::error(&full_path, e))
+  }
+  /// Loads the font at `path`.
+  pub fn font(&self, path: &str) -> Result<Font, Error> {
+    let full_path = self.path(path);
+    Font::from_file(&full_path).map_err(|e| Self::error(&full_path, e))
+  }
+  /// Loads the audio sample at `path` into memory.
+  pub fn audio_sample(&self, path: &str) -> Result<AudioSample, Error> {
+    let full_path = self.path(path);
+    AudioSample::from_file(&full_path).ok_or_else(|| Self::error(&full_path, "file not found"))
+  }
+
+  fn locations(&self) -> [AssetLocation; 2] {
+    match self.order {
+      AssetOrder::BundleFirst => [AssetLocation::Bundle, AssetLocation::Data],
+      AssetOrder::DataFirst => [AssetLocation::Data, AssetLocation::Bundle],
+    }
+  }
+  fn open(path: &str, location: AssetLocation) -> Result<crate::files::OpenFile, FilePathError> {
+    let options = match location {
+      AssetLocation::Bundle => craydate_sys::FileOptions::kFileRead,
+      AssetLocation::Data => craydate_sys::FileOptions::kFileReadData,
+    };
+    File::open(path, options)
+  }
+  fn error(path: &str, e: impl core::fmt::Display) -> Error {
+    Error::FilePathError(FilePathError {
+      path: String::from(path),
+      playdate: format!("{}", e),
+    })
+  }
+}
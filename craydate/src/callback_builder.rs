@@ -1,6 +1,5 @@
 use crate::callbacks::Callbacks;
 
-pub enum NoNull {}
 pub enum AllowNull {}
 pub enum Unconstructed {}
 pub enum WithCallacks {}
@@ -4,10 +4,23 @@ use core::cell::{Cell, RefCell};
 use core::ptr::NonNull;
 
 use crate::callbacks::RegisteredCallback;
+#[cfg(feature = "cheats")]
+use crate::cheat_menu::CheatRegistry;
 use crate::ctypes::*;
 use crate::executor::Executor;
-use crate::graphics::ContextStack;
+use crate::file_logger::FileLoggerState;
+use crate::graphics::{ContextStack, DirtyRects};
+use crate::menu::MenuEvents;
+#[cfg(feature = "unstable-api")]
+use crate::scoreboards::ScoreboardRequests;
+#[cfg(feature = "unstable-api")]
+use crate::soak_test::SoakTestState;
+use crate::sound::audio_fade::AudioFade;
+use crate::sound::audio_suspend::AudioSuspend;
+use crate::sound::source_events::SourceEvents;
+use crate::system::AutoLock;
 use crate::system_event::{SystemEvent, SystemEventWatcherState};
+use crate::time::TimeTicks;
 
 static mut GLOBAL_CAPI_STATE: Option<&'static CApiState> = None;
 
@@ -18,6 +31,9 @@ pub(crate) struct CApiState {
   pub cfile: &'static CFileApi,
   pub cgraphics: &'static CGraphicsApi,
   pub csound: &'static CSoundApi,
+  #[cfg(feature = "unstable-api")]
+  pub cscoreboards: &'static CScoreboardsApi,
+  pub cjson: &'static CJsonApi,
   pub executor: NonNull<Executor>,
 
   pub frame_number: Cell<u64>,
@@ -25,15 +41,65 @@ pub(crate) struct CApiState {
   // Tracks the button state for the current and previous frame respectively.
   pub button_state_per_frame: Cell<[Option<PDButtonsSet>; 2]>,
   pub stack: RefCell<ContextStack>,
+  // Tracks the areas drawn to each frame, when enabled by the game.
+  pub dirty_rects: RefCell<DirtyRects>,
   // Tracks how many times the stencil was set.
   pub stencil_generation: Cell<usize>,
   // Tracks how many times the font was set.
   pub font_generation: Cell<usize>,
+  // The font, draw mode and text tracking last set, as Playdate has no functions to read them back.
+  // A null font is the system font.
+  pub active_font: Cell<*mut CFont>,
+  pub draw_mode: Cell<BitmapDrawMode>,
+  pub text_tracking: Cell<i32>,
+  // The display settings last set, as Playdate has no functions to read them back.
+  pub display_scale: Cell<u32>,
+  pub display_flipped: Cell<(bool, bool)>,
+  pub display_inverted: Cell<bool>,
+  pub display_mosaic: Cell<(u32, u32)>,
+  pub display_offset: Cell<(i32, i32)>,
+  // The display refresh rate last set, as Playdate has no function to read it back.
+  pub refresh_rate: Cell<Option<f32>>,
+  // The time of the last `SystemEvent::NextFrame`, to find the time between frames.
+  pub last_frame_time: Cell<Option<TimeTicks>>,
+  // The auto-lock state last set, as Playdate has no function to read it back.
+  pub auto_lock: Cell<AutoLock>,
+  // The battery percentage below which `SystemEvent::LowBattery` is sent, and if it has been sent
+  // since the battery was last above it.
+  pub low_battery_threshold: Cell<Option<f32>>,
+  pub low_battery_reported: Cell<bool>,
+  // Whether the crank was docked at the last frame, to report when it changes.
+  pub crank_docked: Cell<Option<bool>>,
+  // Called before `SystemEvent::WillTerminate` is sent, to give the game a chance to save.
+  pub terminate_handler: RefCell<Option<Box<dyn FnMut()>>>,
+  // Set by the game to replace the "reduce flashing" system setting.
+  pub reduce_flashing_override: Cell<Option<bool>>,
   pub system_event_watcher_state: RefCell<Rc<SystemEventWatcherState>>,
   // Tracks how many times the callback was set.
   pub headphone_change_generation: Cell<usize>,
   pub headphone_change_callback: RefCell<Option<RegisteredCallback>>,
   pub headphone_change_func: RefCell<Option<unsafe extern "C" fn(i32, i32)>>,
+  #[cfg(feature = "cheats")]
+  pub cheats: RefCell<CheatRegistry>,
+  // What the panic handler needs from the running soak test, if any.
+  #[cfg(feature = "unstable-api")]
+  pub soak_test: RefCell<Option<SoakTestState>>,
+  // Tracks how many soak tests were started.
+  #[cfg(feature = "unstable-api")]
+  pub soak_test_generation: Cell<usize>,
+  // Fades the default sound channel, which is created along with the `Sound` api.
+  pub audio_fade: RefCell<Option<AudioFade>>,
+  // Tracks the sound players that are paused while audio is suspended.
+  pub audio_suspend: RefCell<AudioSuspend>,
+  // Counts the finish and loop events of each SoundSource.
+  pub source_events: RefCell<SourceEvents>,
+  // Counts the callbacks of each MenuItem.
+  pub menu_events: RefCell<MenuEvents>,
+  // The scoreboard requests waiting for a response.
+  #[cfg(feature = "unstable-api")]
+  pub scoreboard_requests: RefCell<ScoreboardRequests>,
+  // The running FileLogger, if any, which log() also writes to.
+  pub file_logger: RefCell<Option<FileLoggerState>>,
 }
 impl CApiState {
   pub fn new(capi: &'static CPlaydateApi) -> CApiState {
@@ -43,17 +109,50 @@ impl CApiState {
       cdisplay: unsafe { &*capi.display },
       cfile: unsafe { &*capi.file },
       csound: unsafe { &*capi.sound },
+      #[cfg(feature = "unstable-api")]
+      cscoreboards: unsafe { &*capi.scoreboards },
+      cjson: unsafe { &*capi.json },
       executor: unsafe { NonNull::new_unchecked(Box::into_raw(Box::new(Executor::new()))) },
       frame_number: Cell::new(0),
       peripherals_enabled: Cell::new(Peripherals::kNone),
       button_state_per_frame: Cell::new([None, None]),
       stack: RefCell::new(ContextStack::new()),
+      dirty_rects: RefCell::new(DirtyRects::new()),
       stencil_generation: Cell::new(0),
       font_generation: Cell::new(0),
+      active_font: Cell::new(core::ptr::null_mut()),
+      draw_mode: Cell::new(BitmapDrawMode::kDrawModeCopy),
+      text_tracking: Cell::new(0),
+      display_scale: Cell::new(1),
+      display_flipped: Cell::new((false, false)),
+      display_inverted: Cell::new(false),
+      display_mosaic: Cell::new((0, 0)),
+      display_offset: Cell::new((0, 0)),
+      refresh_rate: Cell::new(None),
+      last_frame_time: Cell::new(None),
+      auto_lock: Cell::new(AutoLock::Enabled),
+      low_battery_threshold: Cell::new(None),
+      low_battery_reported: Cell::new(false),
+      crank_docked: Cell::new(None),
+      terminate_handler: RefCell::new(None),
+      reduce_flashing_override: Cell::new(None),
       system_event_watcher_state: RefCell::new(Rc::new(SystemEventWatcherState::new())),
       headphone_change_generation: Cell::new(0),
       headphone_change_callback: RefCell::new(None),
       headphone_change_func: RefCell::new(None),
+      #[cfg(feature = "cheats")]
+      cheats: RefCell::new(CheatRegistry::new()),
+      #[cfg(feature = "unstable-api")]
+      soak_test: RefCell::new(None),
+      #[cfg(feature = "unstable-api")]
+      soak_test_generation: Cell::new(0),
+      audio_fade: RefCell::new(None),
+      audio_suspend: RefCell::new(AudioSuspend::new()),
+      source_events: RefCell::new(SourceEvents::new()),
+      menu_events: RefCell::new(MenuEvents::new()),
+      #[cfg(feature = "unstable-api")]
+      scoreboard_requests: RefCell::new(ScoreboardRequests::new()),
+      file_logger: RefCell::new(None),
     }
   }
   pub fn set_instance(capi: &'static CApiState) {
@@ -88,6 +187,44 @@ impl CApiState {
   pub fn add_system_event(&self, event: SystemEvent) {
     let state = self.system_event_watcher_state.borrow_mut();
     assert!(state.next_event.take().is_none());
+    // Keep a copy for any `SystemEventSubscriber`s.
+    state.last_event.replace(Some(event.clone()));
+    state.event_count.set(state.event_count.get() + 1);
     state.next_event.set(Some(event));
   }
+
+  /// Returns whether the crank is docked, if that has changed since the last time this was called.
+  /// The first call only records the state.
+  pub fn check_crank_docked(&self) -> Option<bool> {
+    let docked = unsafe { self.csystem.isCrankDocked.unwrap()() != 0 };
+    match self.crank_docked.replace(Some(docked)) {
+      Some(was_docked) if was_docked != docked => Some(docked),
+      _ => None,
+    }
+  }
+
+  /// Returns the battery percentage if it has fallen below the low battery threshold since the
+  /// last time it was reported.
+  pub fn check_low_battery(&self) -> Option<f32> {
+    let threshold = self.low_battery_threshold.get()?;
+    let percentage = unsafe { self.csystem.getBatteryPercentage.unwrap()() / 100f32 };
+    if percentage >= threshold {
+      self.low_battery_reported.set(false);
+      None
+    } else if self.low_battery_reported.replace(true) {
+      None
+    } else {
+      Some(percentage)
+    }
+  }
+
+  /// Fades and suspends the sound for events where the game is paused or locked, before the game
+  /// hears about them, as the game may not get another frame update before the system menu is
+  /// shown.
+  pub fn update_audio_for_event(&self, event: &SystemEvent) {
+    if let Some(fade) = self.audio_fade.borrow_mut().as_mut() {
+      fade.on_system_event(event);
+    }
+    self.audio_suspend.borrow_mut().on_system_event(event);
+  }
 }
@@ -0,0 +1,247 @@
+//! A developer menu of cheats, which is only present when the "cheats" feature is enabled.
+//!
+//! Without the feature, the `cheat!()` macro expands to nothing, so cheats registered with it are
+//! excluded entirely from the build.
+
+#[cfg(feature = "cheats")]
+use alloc::boxed::Box;
+#[cfg(feature = "cheats")]
+use alloc::string::String;
+#[cfg(feature = "cheats")]
+use alloc::vec::Vec;
+
+#[cfg(feature = "cheats")]
+use crate::capi_state::CApiState;
+#[cfg(feature = "cheats")]
+use crate::ctypes::*;
+#[cfg(feature = "cheats")]
+use crate::graphics::{Color, Graphics};
+#[cfg(feature = "cheats")]
+use crate::inputs::{ButtonEvent, Buttons};
+
+/// Registers a cheat in the `CheatMenu`, if the "cheats" feature is enabled. Otherwise, it expands
+/// to nothing and the cheat is not compiled at all.
+///
+/// There are three kinds of cheats:
+/// * An action, which runs the function when chosen from the menu.
+/// * A toggle, which has an on/off state, and runs the function with the new state when changed.
+/// * A choice, which has a list of options to pick from, and runs the function with the index of
+///   the chosen option.
+///
+/// The functions must be `'static`, so they can capture shared state such as an
+/// `Rc<RefCell<GameState>>` to modify.
+///
+/// # Example
+/// ```
+/// craydate::cheat!("Restart level", action: move || state.borrow_mut().restart());
+/// craydate::cheat!("Invincible", toggle(false): move |on| state.borrow_mut().invincible = on);
+/// craydate::cheat!("Level select", choice(["1-1", "1-2", "2-1"]): move |i| {
+///   state.borrow_mut().load_level(i)
+/// });
+/// ```
+#[cfg(feature = "cheats")]
+#[macro_export]
+macro_rules! cheat {
+  ($name:expr, action: $f:expr) => {
+    $crate::CheatMenu::register_action($name, $f)
+  };
+  ($name:expr, toggle($initial:expr): $f:expr) => {
+    $crate::CheatMenu::register_toggle($name, $initial, $f)
+  };
+  ($name:expr, choice($options:expr): $f:expr) => {
+    $crate::CheatMenu::register_choice($name, $options, $f)
+  };
+}
+/// Registers a cheat in the `CheatMenu`, if the "cheats" feature is enabled. Otherwise, it expands
+/// to nothing and the cheat is not compiled at all.
+#[cfg(not(feature = "cheats"))]
+#[macro_export]
+macro_rules! cheat {
+  ($($t:tt)*) => {
+    ()
+  };
+}
+
+#[cfg(feature = "cheats")]
+enum CheatKind {
+  Action(Box<dyn FnMut()>),
+  Toggle(bool, Box<dyn FnMut(bool)>),
+  Choice(Vec<String>, usize, Box<dyn FnMut(usize)>),
+}
+
+#[cfg(feature = "cheats")]
+struct Cheat {
+  name: String,
+  kind: CheatKind,
+}
+
+/// The set of registered cheats, and the state of the menu showing them.
+#[cfg(feature = "cheats")]
+pub(crate) struct CheatRegistry {
+  cheats: Vec<Cheat>,
+  open: bool,
+  selected: usize,
+}
+#[cfg(feature = "cheats")]
+impl CheatRegistry {
+  pub fn new() -> Self {
+    CheatRegistry {
+      cheats: Vec::new(),
+      open: false,
+      selected: 0,
+    }
+  }
+}
+
+/// A developer menu of cheats, such as level select, or toggling invincibility, which is only
+/// present when the "cheats" feature is enabled.
+///
+/// Cheats are registered from anywhere in the game with the `cheat!()` macro. The menu is drawn
+/// over the game with `draw()` while it is open, and is controlled by passing the frame's buttons to
+/// `update()`: up and down choose a cheat, A runs an action or flips a toggle, and left and right
+/// change the option of a choice. B closes the menu.
+#[cfg(feature = "cheats")]
+#[derive(Debug)]
+#[non_exhaustive]
+pub struct CheatMenu;
+#[cfg(feature = "cheats")]
+impl CheatMenu {
+  const ROW_HEIGHT: i32 = 20;
+  const MARGIN: i32 = 8;
+
+  /// Registers a cheat which runs `f` when chosen. Prefer the `cheat!()` macro, which is removed
+  /// when the "cheats" feature is disabled.
+  pub fn register_action<F: FnMut() + 'static>(name: &str, f: F) {
+    Self::register(name, CheatKind::Action(Box::new(f)))
+  }
+  /// Registers a cheat with an on/off state, which starts as `initial` and runs `f` with the new
+  /// state when changed. Prefer the `cheat!()` macro, which is removed when the "cheats" feature is
+  /// disabled.
+  pub fn register_toggle<F: FnMut(bool) + 'static>(name: &str, initial: bool, f: F) {
+    Self::register(name, CheatKind::Toggle(initial, Box::new(f)))
+  }
+  /// Registers a cheat with a list of `options`, which runs `f` with the index of the option when
+  /// one is chosen. Prefer the `cheat!()` macro, which is removed when the "cheats" feature is
+  /// disabled.
+  pub fn register_choice<S: AsRef<str>, I: IntoIterator<Item = S>, F: FnMut(usize) + 'static>(
+    name: &str,
+    options: I,
+    f: F,
+  ) {
+    let options = options.into_iter().map(|s| String::from(s.as_ref())).collect();
+    Self::register(name, CheatKind::Choice(options, 0, Box::new(f)))
+  }
+
+  fn register(name: &str, kind: CheatKind) {
+    CApiState::get().cheats.borrow_mut().cheats.push(Cheat {
+      name: String::from(name),
+      kind,
+    })
+  }
+
+  /// Opens the menu.
+  pub fn open() {
+    CApiState::get().cheats.borrow_mut().open = true;
+  }
+  /// Closes the menu.
+  pub fn close() {
+    CApiState::get().cheats.borrow_mut().open = false;
+  }
+  /// Whether the menu is open. The game should usually not respond to input while it is.
+  pub fn is_open() -> bool {
+    CApiState::get().cheats.borrow().open
+  }
+
+  /// Moves through the menu and runs cheats in response to `buttons`, if the menu is open.
+  pub fn update(buttons: &Buttons) {
+    if !Self::is_open() {
+      return;
+    }
+    for (button, event) in buttons.all_events() {
+      if event != ButtonEvent::Push {
+        continue;
+      }
+      match button {
+        crate::inputs::Button::Up => Self::move_selection(-1),
+        crate::inputs::Button::Down => Self::move_selection(1),
+        crate::inputs::Button::Left => Self::activate(Some(-1)),
+        crate::inputs::Button::Right => Self::activate(Some(1)),
+        crate::inputs::Button::A => Self::activate(None),
+        crate::inputs::Button::B => Self::close(),
+      }
+    }
+  }
+
+  fn move_selection(by: i32) {
+    let mut reg = CApiState::get().cheats.borrow_mut();
+    let len = reg.cheats.len() as i32;
+    if len > 0 {
+      reg.selected = (reg.selected as i32 + by).rem_euclid(len) as usize;
+    }
+  }
+
+  /// Runs the selected cheat. For a choice, `step` moves to another option, and `None` picks the
+  /// current one.
+  fn activate(step: Option<i32>) {
+    // The cheat is taken out of the registry while it runs, so that it may register more cheats.
+    let (selected, mut cheats) = {
+      let mut reg = CApiState::get().cheats.borrow_mut();
+      (reg.selected, core::mem::take(&mut reg.cheats))
+    };
+    if let Some(cheat) = cheats.get_mut(selected) {
+      match (&mut cheat.kind, step) {
+        (CheatKind::Action(f), None) => f(),
+        (CheatKind::Action(_), Some(_)) => (),
+        (CheatKind::Toggle(on, f), _) => {
+          *on = !*on;
+          f(*on)
+        }
+        (CheatKind::Choice(options, index, _), Some(step)) if !options.is_empty() => {
+          *index = (*index as i32 + step).rem_euclid(options.len() as i32) as usize;
+        }
+        (CheatKind::Choice(options, index, f), None) if !options.is_empty() => f(*index),
+        (CheatKind::Choice(..), _) => (),
+      }
+    }
+    let mut reg = CApiState::get().cheats.borrow_mut();
+    cheats.append(&mut reg.cheats);
+    reg.cheats = cheats;
+  }
+
+  /// Draws the menu over the screen, if it is open.
+  pub fn draw(graphics: &mut Graphics) {
+    let reg = CApiState::get().cheats.borrow();
+    if !reg.open {
+      return;
+    }
+    let height = Self::ROW_HEIGHT * reg.cheats.len().max(1) as i32 + Self::MARGIN * 2;
+    let rect = euclid::default::Rect::new(
+      euclid::default::Point2D::new(Self::MARGIN, Self::MARGIN),
+      euclid::default::Size2D::new(LCD_COLUMNS as i32 - Self::MARGIN * 2, height),
+    );
+    graphics.fill_rect(rect, Color::from(SolidColor::kColorWhite));
+    graphics.draw_rect(rect, Color::from(SolidColor::kColorBlack));
+
+    let x = Self::MARGIN * 3;
+    if reg.cheats.is_empty() {
+      let _ = graphics.draw_text("No cheats registered", x, Self::MARGIN * 2);
+    }
+    for (i, cheat) in reg.cheats.iter().enumerate() {
+      let y = Self::MARGIN * 2 + Self::ROW_HEIGHT * i as i32;
+      let text = match &cheat.kind {
+        CheatKind::Action(_) => cheat.name.clone(),
+        CheatKind::Toggle(on, _) => {
+          alloc::format!("{}: {}", cheat.name, if *on { "on" } else { "off" })
+        }
+        CheatKind::Choice(options, index, _) => match options.get(*index) {
+          Some(option) => alloc::format!("{}: < {} >", cheat.name, option),
+          None => cheat.name.clone(),
+        },
+      };
+      if i == reg.selected {
+        let _ = graphics.draw_text(">", Self::MARGIN * 2, y);
+      }
+      let _ = graphics.draw_text(&text, x, y);
+    }
+  }
+}
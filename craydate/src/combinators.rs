@@ -0,0 +1,86 @@
+use core::future::{poll_fn, Future};
+use core::pin::pin;
+use core::task::Poll;
+
+/// The output of `select()`, which is from whichever future finished first.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum Either<A, B> {
+  /// The first future finished first.
+  Left(A),
+  /// The second future finished first.
+  Right(B),
+}
+
+/// Runs two futures at the same time, and waits for both of them to finish.
+///
+/// Both futures are polled each time the game's main function is woken, such as at each system
+/// event, so they work with any of the futures in this crate that wait for an event.
+///
+/// # Example
+/// ```
+/// let ((), ()) = craydate::join2(
+///   fade_out_music(&music),
+///   craydate::sleep(TimeDelta::from_seconds(1)),
+/// )
+/// .await;
+/// ```
+pub async fn join2<A: Future, B: Future>(a: A, b: B) -> (A::Output, B::Output) {
+  let (mut a, mut b) = (pin!(a), pin!(b));
+  let (mut a_out, mut b_out) = (None, None);
+  poll_fn(|ctxt| {
+    if a_out.is_none() {
+      if let Poll::Ready(out) = a.as_mut().poll(ctxt) {
+        a_out = Some(out);
+      }
+    }
+    if b_out.is_none() {
+      if let Poll::Ready(out) = b.as_mut().poll(ctxt) {
+        b_out = Some(out);
+      }
+    }
+    match a_out.is_some() && b_out.is_some() {
+      true => Poll::Ready((a_out.take().unwrap(), b_out.take().unwrap())),
+      false => Poll::Pending,
+    }
+  })
+  .await
+}
+
+/// Runs three futures at the same time, and waits for all of them to finish.
+///
+/// See `join2()`.
+pub async fn join3<A: Future, B: Future, C: Future>(
+  a: A,
+  b: B,
+  c: C,
+) -> (A::Output, B::Output, C::Output) {
+  let ((a, b), c) = join2(join2(a, b), c).await;
+  (a, b, c)
+}
+
+/// Runs two futures at the same time, and waits for either one of them to finish. The other future
+/// is dropped without finishing.
+///
+/// The first future is polled before the second, so if both are ready at the same time, the first
+/// one's output is returned.
+///
+/// # Example
+/// ```
+/// match craydate::select(api.system.crank_undocked(), craydate::sleep(timeout)).await {
+///   Either::Left(()) => start_crank_minigame(),
+///   Either::Right(()) => show_crank_hint(),
+/// }
+/// ```
+pub async fn select<A: Future, B: Future>(a: A, b: B) -> Either<A::Output, B::Output> {
+  let (mut a, mut b) = (pin!(a), pin!(b));
+  poll_fn(|ctxt| {
+    if let Poll::Ready(out) = a.as_mut().poll(ctxt) {
+      return Poll::Ready(Either::Left(out));
+    }
+    if let Poll::Ready(out) = b.as_mut().poll(ctxt) {
+      return Poll::Ready(Either::Right(out));
+    }
+    Poll::Pending
+  })
+  .await
+}
@@ -0,0 +1,177 @@
+use crate::time::{TimeDelta, TimeTicks, WallClockTime};
+
+/// A cooldown which becomes ready again a fixed amount of time after it is triggered, such as for
+/// an ability or an attack, within a single run of the game.
+///
+/// Time is measured with the monotonic clock from `System::current_time()`, so the cooldown is not
+/// affected by changes to the device's clock. For a wait that should continue while the game is
+/// not running, use `TimedUnlock`.
+#[derive(Debug, Clone)]
+pub struct Cooldown {
+  duration: TimeDelta,
+  ready_at: Option<TimeTicks>,
+}
+impl Cooldown {
+  /// Constructs a `Cooldown` which takes `duration` to become ready after being triggered. It
+  /// starts out ready.
+  pub fn new(duration: TimeDelta) -> Self {
+    Cooldown {
+      duration,
+      ready_at: None,
+    }
+  }
+
+  /// Triggers the cooldown if it is ready at time `now`, and returns whether it was triggered.
+  pub fn trigger(&mut self, now: TimeTicks) -> bool {
+    if self.is_ready(now) {
+      self.ready_at = Some(now + self.duration);
+      true
+    } else {
+      false
+    }
+  }
+  /// Makes the cooldown ready immediately.
+  pub fn reset(&mut self) {
+    self.ready_at = None;
+  }
+
+  /// Whether the cooldown is ready at time `now`.
+  pub fn is_ready(&self, now: TimeTicks) -> bool {
+    self.ready_at.is_none_or(|ready_at| now >= ready_at)
+  }
+  /// The time left at `now` until the cooldown is ready, which is zero if it's ready.
+  pub fn remaining(&self, now: TimeTicks) -> TimeDelta {
+    match self.ready_at {
+      Some(ready_at) if ready_at > now => ready_at - now,
+      _ => TimeDelta::from_milliseconds(0),
+    }
+  }
+  /// The fraction (0.0 - 1.0) of the cooldown that has passed at `now`, such as for drawing a
+  /// progress bar. It is 1.0 when the cooldown is ready.
+  pub fn progress(&self, now: TimeTicks) -> f32 {
+    let duration = self.duration.total_whole_milliseconds();
+    if duration <= 0 {
+      return 1.0;
+    }
+    let remaining = self.remaining(now).total_whole_milliseconds();
+    1.0 - (remaining as f32 / duration as f32).clamp(0.0, 1.0)
+  }
+  /// The time it takes for the cooldown to become ready after being triggered.
+  pub fn duration(&self) -> TimeDelta {
+    self.duration
+  }
+}
+
+/// A wait that continues while the game is not running, such as an energy refill or a daily
+/// reward, which is saved with the game's data and unlocks once enough real time has passed.
+///
+/// The wall clock (from `System::wall_clock_time()`) is the only clock that keeps counting while
+/// the game is closed, but the player can change it. To avoid both cheating and getting stuck,
+/// the `TimedUnlock` keeps track of the latest time it has credited towards the wait:
+/// * While the game is running, time is credited from the monotonic clock (from
+///   `System::current_time()`), or the wall clock if it moved ahead further, such as while the
+///   device was asleep.
+/// * When the game is started again, the time that passed on the wall clock since the last credited
+///   time is added.
+/// * When the wall clock is set backwards, no time is credited from it until it passes the latest
+///   credited time again, so the player can not earn the same time twice by moving the clock back
+///   and forth. The wait still progresses from the monotonic clock while the game runs.
+///
+/// Moving the wall clock forward while the game is closed can not be told apart from time really
+/// passing, and will unlock the wait early.
+///
+/// The wall clock counts seconds in UTC, so moving between timezones does not affect the wait.
+///
+/// # Example
+/// ```
+/// let mut refill = TimedUnlock::from_bytes(saved_bytes);
+/// // Each frame:
+/// refill.update(api.system.wall_clock_time(), api.system.current_time());
+/// if refill.is_unlocked() {
+///   energy += 1;
+///   refill.start(TimeDelta::from_minutes(30));
+/// }
+/// save(refill.to_bytes());
+/// ```
+#[derive(Debug, Clone)]
+pub struct TimedUnlock {
+  /// The seconds left to wait.
+  remaining_seconds: u32,
+  /// The latest wall clock time that has been credited towards the wait. It never goes backwards.
+  credited_until: u32,
+  /// The monotonic time of the last update in this run of the game, and the fraction of a second
+  /// that had not been credited yet.
+  last_ticks: Option<(TimeTicks, u32)>,
+}
+impl TimedUnlock {
+  /// The number of bytes written by `to_bytes()`.
+  pub const SAVED_LEN: usize = 8;
+
+  /// Constructs a `TimedUnlock` that is already unlocked, starting at wall clock time `now`.
+  pub fn new(now: WallClockTime) -> Self {
+    TimedUnlock {
+      remaining_seconds: 0,
+      credited_until: now.seconds_since_epoch(),
+      last_ticks: None,
+    }
+  }
+  /// Restores a `TimedUnlock` from the bytes produced by `to_bytes()`.
+  ///
+  /// Call `update()` once loaded to credit the time that passed while the game was not running.
+  pub fn from_bytes(bytes: [u8; Self::SAVED_LEN]) -> Self {
+    let remaining = [bytes[0], bytes[1], bytes[2], bytes[3]];
+    let credited = [bytes[4], bytes[5], bytes[6], bytes[7]];
+    TimedUnlock {
+      remaining_seconds: u32::from_le_bytes(remaining),
+      credited_until: u32::from_le_bytes(credited),
+      last_ticks: None,
+    }
+  }
+  /// Returns the state of the `TimedUnlock` as bytes to be saved with the game's data.
+  pub fn to_bytes(&self) -> [u8; Self::SAVED_LEN] {
+    let mut bytes = [0; Self::SAVED_LEN];
+    bytes[..4].copy_from_slice(&self.remaining_seconds.to_le_bytes());
+    bytes[4..].copy_from_slice(&self.credited_until.to_le_bytes());
+    bytes
+  }
+
+  /// Starts waiting for `duration`, replacing any wait in progress.
+  ///
+  /// The time is counted from the last `update()`, so call `update()` first if time may have
+  /// passed since then.
+  pub fn start(&mut self, duration: TimeDelta) {
+    self.remaining_seconds = duration.total_whole_seconds().max(0) as u32;
+  }
+  /// Ends the wait immediately.
+  pub fn unlock(&mut self) {
+    self.remaining_seconds = 0;
+  }
+
+  /// Credits the time that has passed towards the wait, given the current wall clock time and
+  /// monotonic time. This should be called regularly while the game runs, such as once per frame.
+  pub fn update(&mut self, wall_clock: WallClockTime, ticks: TimeTicks) {
+    let wall_elapsed = wall_clock.seconds_since_epoch().saturating_sub(self.credited_until);
+    let monotonic_millis = match self.last_ticks {
+      Some((last, leftover)) if ticks >= last => {
+        (ticks - last).total_whole_milliseconds() as u32 + leftover
+      }
+      _ => 0,
+    };
+    let credit = wall_elapsed.max(monotonic_millis / 1000);
+    // Time credited from the wall clock also passed on the monotonic clock, so it is not counted
+    // again.
+    let leftover_millis = monotonic_millis.saturating_sub(credit.saturating_mul(1000));
+    self.last_ticks = Some((ticks, leftover_millis));
+    self.credited_until = self.credited_until.saturating_add(credit);
+    self.remaining_seconds = self.remaining_seconds.saturating_sub(credit);
+  }
+
+  /// Whether the wait has finished, as of the last `update()`.
+  pub fn is_unlocked(&self) -> bool {
+    self.remaining_seconds == 0
+  }
+  /// The time left to wait, as of the last `update()`.
+  pub fn remaining(&self) -> TimeDelta {
+    TimeDelta::from_seconds(self.remaining_seconds.min(i32::MAX as u32 / 1000) as i32)
+  }
+}
@@ -3,6 +3,9 @@
 pub use craydate_sys::playdate_display as CDisplayApi;
 pub use craydate_sys::playdate_file as CFileApi;
 pub use craydate_sys::playdate_graphics as CGraphicsApi;
+pub use craydate_sys::playdate_json as CJsonApi;
+#[cfg(feature = "unstable-api")]
+pub use craydate_sys::playdate_scoreboards as CScoreboardsApi;
 pub use craydate_sys::playdate_sound as CSoundApi;
 pub use craydate_sys::playdate_sound_channel as CSoundChannelApi;
 pub use craydate_sys::playdate_sound_effect as CSoundEffectApi;
@@ -32,6 +35,7 @@ pub use craydate_sys::DelayLineTap as CDelayLineTap;
 pub use craydate_sys::FilePlayer as CFilePlayer;
 pub use craydate_sys::FileStat as CFileStat;
 pub use craydate_sys::LCDBitmap as CBitmap;
+pub use craydate_sys::LCDBitmapTable as CBitmapTable;
 pub use craydate_sys::LCDColor as CLCDColor;
 pub use craydate_sys::LCDFont as CFont;
 pub use craydate_sys::LCDFontGlyph as CFontGlyph;
@@ -42,8 +46,14 @@ pub use craydate_sys::LCDVideoPlayer as CVideoPlayer;
 pub use craydate_sys::LFOType as CSynthLfoType;
 pub use craydate_sys::OnePoleFilter as COnePoleFilter;
 pub use craydate_sys::Overdrive as COverdrive;
+#[cfg(feature = "unstable-api")]
+pub use craydate_sys::PDBoardsList as CBoardsList;
 pub use craydate_sys::PDButtons as CButtons;
 pub use craydate_sys::PDMenuItem as CMenuItem;
+#[cfg(feature = "unstable-api")]
+pub use craydate_sys::PDScore as CScore;
+#[cfg(feature = "unstable-api")]
+pub use craydate_sys::PDScoresList as CScoresList;
 pub use craydate_sys::PDStringEncoding as CStringEncoding;
 pub use craydate_sys::PDSynth as CSynth;
 pub use craydate_sys::PDSynthEnvelope as CSynthEnvelope;
@@ -63,6 +73,10 @@ pub use craydate_sys::SoundSequence as CSoundSequence;
 pub use craydate_sys::SoundSource as CSoundSource;
 pub use craydate_sys::SoundWaveform as CSoundWaveform;
 pub use craydate_sys::TwoPoleFilter as CTwoPoleFilter;
+pub use craydate_sys::json_decoder as CJsonDecoder;
+pub use craydate_sys::json_encoder as CJsonEncoder;
+pub use craydate_sys::json_reader as CJsonReader;
+pub use craydate_sys::json_value as CJsonValue;
 
 pub use crate::ctypes_enums::*;
 
@@ -0,0 +1,302 @@
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::hash::{Hash, Hasher};
+
+/// **Experimental:** A hasher which gives the same result on every build and every run, unlike
+/// hashers that are randomly seeded. It implements 64-bit FNV-1a.
+#[derive(Debug, Clone)]
+pub struct StableHasher(u64);
+impl StableHasher {
+  const OFFSET_BASIS: u64 = 0xcbf29ce484222325;
+  const PRIME: u64 = 0x100000001b3;
+
+  /// Constructs a `StableHasher` with no data written to it.
+  pub fn new() -> Self {
+    StableHasher(Self::OFFSET_BASIS)
+  }
+}
+impl Default for StableHasher {
+  fn default() -> Self {
+    Self::new()
+  }
+}
+impl Hasher for StableHasher {
+  fn finish(&self) -> u64 {
+    self.0
+  }
+  fn write(&mut self, bytes: &[u8]) {
+    for b in bytes {
+      self.0 ^= *b as u64;
+      self.0 = self.0.wrapping_mul(Self::PRIME);
+    }
+  }
+  // The default implementations write integers in the native byte order, so they are replaced to
+  // give the same hash on the device and on the host.
+  fn write_u16(&mut self, i: u16) {
+    self.write(&i.to_le_bytes())
+  }
+  fn write_u32(&mut self, i: u32) {
+    self.write(&i.to_le_bytes())
+  }
+  fn write_u64(&mut self, i: u64) {
+    self.write(&i.to_le_bytes())
+  }
+  fn write_usize(&mut self, i: usize) {
+    // usize is 32 bits on the device and usually 64 bits on the host.
+    self.write_u64(i as u64)
+  }
+  fn write_isize(&mut self, i: isize) {
+    self.write_u64(i as i64 as u64)
+  }
+}
+
+/// **Experimental:** Collects the hashes of each part of a game's state, to be compared between
+/// runs by a `DeterminismVerifier`.
+///
+/// Hashing each part of the state separately means that when two runs diverge, the parts that
+/// differ can be reported, which points toward the source of nondeterminism.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StateHasher {
+  fields: Vec<(String, u64)>,
+}
+impl StateHasher {
+  fn new() -> Self {
+    StateHasher { fields: Vec::new() }
+  }
+
+  /// Hashes a part of the state, which is identified by `name`.
+  ///
+  /// Beware of hashing collections whose iteration order can change between runs, such as a
+  /// `HashMap` with a randomly seeded hasher, as that will be reported as nondeterminism.
+  pub fn field<T: Hash + ?Sized>(&mut self, name: &str, value: &T) {
+    let mut h = StableHasher::new();
+    value.hash(&mut h);
+    self.fields.push((String::from(name), h.finish()));
+  }
+  /// Hashes a floating point part of the state, which is identified by `name`.
+  ///
+  /// The exact bits are hashed, so any difference in rounding, such as from a different order of
+  /// operations or different math library between builds, is reported as nondeterminism.
+  pub fn field_f32(&mut self, name: &str, value: f32) {
+    self.field(name, &value.to_bits())
+  }
+  /// Hashes a list of floating point values as a single part of the state, identified by `name`.
+  pub fn field_f32_slice(&mut self, name: &str, values: &[f32]) {
+    let mut h = StableHasher::new();
+    for v in values {
+      h.write_u32(v.to_bits());
+    }
+    self.fields.push((String::from(name), h.finish()));
+  }
+
+  /// The hash of all the parts of the state together.
+  pub fn combined(&self) -> u64 {
+    let mut h = StableHasher::new();
+    for (name, hash) in &self.fields {
+      h.write(name.as_bytes());
+      h.write_u64(*hash);
+    }
+    h.finish()
+  }
+}
+
+/// **Experimental:** The hashes of a game's state at one frame, recorded by a
+/// `DeterminismVerifier`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StateCheckpoint {
+  /// The frame the state was hashed at.
+  pub frame: u64,
+  /// The hash of the whole state.
+  pub hash: u64,
+  /// The hash of each named part of the state.
+  pub fields: Vec<(String, u64)>,
+}
+
+/// **Experimental:** Where two runs of a simulation first produced different states.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Divergence {
+  /// The first frame where the state differed.
+  pub frame: u64,
+  /// The names of the parts of the state which differed at that frame. This is empty if the
+  /// same parts were not hashed in both runs.
+  pub fields: Vec<String>,
+}
+impl core::fmt::Display for Divergence {
+  fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+    write!(f, "state diverged at frame {}", self.frame)?;
+    if !self.fields.is_empty() {
+      write!(f, " in: {}", self.fields.join(", "))?;
+    }
+    Ok(())
+  }
+}
+
+/// **Experimental:** Checks that a game's simulation is deterministic, by comparing hashes of its
+/// state every few frames between runs that are given the same input.
+///
+/// Determinism matters for features built on replaying recorded input, such as replays with
+/// `ReplayViewer` or ghost data, where the replayed game must arrive at the same state as the
+/// original.
+///
+/// A verifier can compare two runs within one build with `run_twice()`, or compare runs of two
+/// different builds by saving the checkpoints from one build with `to_text()` and loading them in
+/// the other with `from_text()`, then passing the same recorded input to the game.
+#[derive(Debug, Clone)]
+pub struct DeterminismVerifier {
+  interval: u64,
+  expected: Option<Vec<StateCheckpoint>>,
+  recorded: Vec<StateCheckpoint>,
+  divergence: Option<Divergence>,
+}
+impl DeterminismVerifier {
+  /// Constructs a `DeterminismVerifier` which records the state every `interval` frames.
+  pub fn new(interval: u64) -> Self {
+    assert!(interval > 0);
+    DeterminismVerifier {
+      interval,
+      expected: None,
+      recorded: Vec::new(),
+      divergence: None,
+    }
+  }
+  /// Constructs a `DeterminismVerifier` which compares the state every `interval` frames against
+  /// the `expected` checkpoints from an earlier run.
+  pub fn with_expected(interval: u64, expected: Vec<StateCheckpoint>) -> Self {
+    DeterminismVerifier {
+      expected: Some(expected),
+      ..Self::new(interval)
+    }
+  }
+
+  /// Hashes the state at `frame` if it is a checkpoint frame, with `hash_state` hashing each part
+  /// of the state into the `StateHasher`.
+  ///
+  /// Returns the first divergence from the expected checkpoints, if there has been one.
+  pub fn check<F: FnOnce(&mut StateHasher)>(
+    &mut self,
+    frame: u64,
+    hash_state: F,
+  ) -> Option<&Divergence> {
+    if frame.is_multiple_of(self.interval) && self.divergence.is_none() {
+      let mut hasher = StateHasher::new();
+      hash_state(&mut hasher);
+      let checkpoint = StateCheckpoint {
+        frame,
+        hash: hasher.combined(),
+        fields: hasher.fields,
+      };
+      if let Some(expected) = &self.expected {
+        if let Some(e) = expected.iter().find(|e| e.frame == frame) {
+          self.divergence = Self::compare(e, &checkpoint);
+        }
+      }
+      self.recorded.push(checkpoint);
+    }
+    self.divergence.as_ref()
+  }
+
+  /// The first divergence from the expected checkpoints, if there has been one.
+  pub fn divergence(&self) -> Option<&Divergence> {
+    self.divergence.as_ref()
+  }
+  /// The checkpoints recorded so far.
+  pub fn checkpoints(&self) -> &[StateCheckpoint] {
+    &self.recorded
+  }
+
+  /// Runs a simulation twice from the same `initial` state with the same `inputs`, comparing the
+  /// state every `interval` frames, and returns the first divergence, if any.
+  ///
+  /// The `step` function advances the state by one frame with the given input, and `hash_state`
+  /// hashes the state into the `StateHasher`.
+  pub fn run_twice<S, I, Step, HashState>(
+    initial: &S,
+    inputs: &[I],
+    interval: u64,
+    mut step: Step,
+    mut hash_state: HashState,
+  ) -> Option<Divergence>
+  where
+    S: Clone,
+    Step: FnMut(&mut S, &I),
+    HashState: FnMut(&S, &mut StateHasher),
+  {
+    let mut run = |expected: Option<Vec<StateCheckpoint>>| {
+      let mut verifier = match expected {
+        Some(expected) => Self::with_expected(interval, expected),
+        None => Self::new(interval),
+      };
+      let mut state = initial.clone();
+      for (frame, input) in inputs.iter().enumerate() {
+        if verifier.check(frame as u64, |h| hash_state(&state, h)).is_some() {
+          break;
+        }
+        step(&mut state, input);
+      }
+      verifier.check(inputs.len() as u64, |h| hash_state(&state, h));
+      verifier
+    };
+    let first = run(None);
+    let second = run(Some(first.recorded));
+    second.divergence
+  }
+
+  /// Writes the recorded checkpoints as text, one line per checkpoint, to be loaded by
+  /// `from_text()` in another build.
+  pub fn to_text(&self) -> String {
+    use core::fmt::Write;
+    let mut s = String::new();
+    for c in &self.recorded {
+      let _ = write!(s, "{} {:016x}", c.frame, c.hash);
+      for (name, hash) in &c.fields {
+        let _ = write!(s, " {}={:016x}", name, hash);
+      }
+      s.push('\n');
+    }
+    s
+  }
+  /// Reads checkpoints written by `to_text()`. Returns `None` if the text is not in the expected
+  /// format.
+  ///
+  /// Field names containing spaces or '=' can not be read back.
+  pub fn from_text(text: &str) -> Option<Vec<StateCheckpoint>> {
+    text
+      .lines()
+      .filter(|line| !line.is_empty())
+      .map(|line| {
+        let mut parts = line.split(' ');
+        let frame = parts.next()?.parse().ok()?;
+        let hash = u64::from_str_radix(parts.next()?, 16).ok()?;
+        let fields = parts
+          .map(|p| {
+            let (name, hash) = p.split_once('=')?;
+            Some((String::from(name), u64::from_str_radix(hash, 16).ok()?))
+          })
+          .collect::<Option<Vec<_>>>()?;
+        Some(StateCheckpoint {
+          frame,
+          hash,
+          fields,
+        })
+      })
+      .collect()
+  }
+
+  fn compare(expected: &StateCheckpoint, actual: &StateCheckpoint) -> Option<Divergence> {
+    if expected.hash == actual.hash {
+      return None;
+    }
+    let fields = actual
+      .fields
+      .iter()
+      .filter(|(name, hash)| {
+        expected.fields.iter().find(|(n, _)| n == name).is_some_and(|(_, h)| h != hash)
+      })
+      .map(|(name, _)| name.clone())
+      .collect();
+    Some(Divergence {
+      frame: actual.frame,
+      fields,
+    })
+  }
+}
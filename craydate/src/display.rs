@@ -22,29 +22,65 @@ impl Display {
 
   /// If `inverted` is true, the frame buffer is drawn inverted--black instead of white.
   pub fn set_inverted(&mut self, inverted: bool) {
-    // Yes, this function takes an integer??
-    unsafe { Self::fns().setInverted.unwrap()(inverted as i32) }
+    DisplaySetting::Inverted(inverted).apply()
+  }
+  /// Returns whether the display is inverted, as last set by `set_inverted()`.
+  pub fn inverted(&self) -> bool {
+    CApiState::get().display_inverted.get()
+  }
+  /// Inverts the display, or not, until the returned guard is dropped, at which point it is set
+  /// back to its previous state.
+  pub fn push_inverted(&mut self, inverted: bool) -> DisplayGuard {
+    DisplayGuard::push(DisplaySetting::Inverted(inverted))
   }
 
   /// Adds a mosaic effect to the display. Valid x and y values are between 0 and 3, inclusive.
   pub fn set_mosaic(&mut self, x: u32, y: u32) {
-    assert!(x <= 3);
-    assert!(y <= 3);
-    unsafe { Self::fns().setMosaic.unwrap()(x, y) }
+    DisplaySetting::Mosaic(x, y).apply()
+  }
+  /// Returns the mosaic effect on the display, as last set by `set_mosaic()`.
+  pub fn mosaic(&self) -> (u32, u32) {
+    CApiState::get().display_mosaic.get()
+  }
+  /// Adds a mosaic effect to the display until the returned guard is dropped, at which point it is
+  /// set back to its previous state.
+  pub fn push_mosaic(&mut self, x: u32, y: u32) -> DisplayGuard {
+    DisplayGuard::push(DisplaySetting::Mosaic(x, y))
   }
 
   /// Flips the display on the x axis iff `flip_x` is true and on the y axis iff `flip_y` is true.
   pub fn set_flipped(&mut self, flip_x: bool, flip_y: bool) {
-    unsafe { Self::fns().setFlipped.unwrap()(flip_x as i32, flip_y as i32) }
+    DisplaySetting::Flipped(flip_x, flip_y).apply()
+  }
+  /// Returns whether the display is flipped on the x and y axes, as last set by `set_flipped()`.
+  pub fn flipped(&self) -> (bool, bool) {
+    CApiState::get().display_flipped.get()
+  }
+  /// Flips the display until the returned guard is dropped, at which point it is set back to its
+  /// previous state.
+  pub fn push_flipped(&mut self, flip_x: bool, flip_y: bool) -> DisplayGuard {
+    DisplayGuard::push(DisplaySetting::Flipped(flip_x, flip_y))
   }
 
   /// Sets the nominal refresh rate in frames per second.
   ///
   /// Default is 20 fps, the maximum rate supported by the hardware for full-frame updates. Note
   /// that the simulator may have a different default refresh rate.
+  ///
+  /// Lowering the refresh rate saves battery, and the time between frames is reported in the
+  /// `delta` of each `SystemEvent::NextFrame` event.
   pub fn set_refresh_rate(&mut self, rate: f32) {
+    CApiState::get().refresh_rate.set(Some(rate));
     unsafe { Self::fns().setRefreshRate.unwrap()(rate) }
   }
+  /// Returns the refresh rate last set by `set_refresh_rate()`, or `None` if it has not been set,
+  /// in which case the system's default refresh rate is used.
+  ///
+  /// This is the rate being aimed for, and frames may arrive more slowly if the game takes too long
+  /// to draw them.
+  pub fn target_refresh_rate(&self) -> Option<f32> {
+    CApiState::get().refresh_rate.get()
+  }
 
   /// Sets the display scale factor. Valid values for scale are 1, 2, 4, and 8.
   ///
@@ -52,18 +88,127 @@ impl Display {
   /// e.g. if the scale is set to 4, the pixels in rectangle `[0,100]` x `[0,60]` are drawn on the
   /// screen as `4` x `4` squares.
   pub fn set_scale(&mut self, scale: u32) {
-    assert!(scale == 1 || scale == 2 || scale == 4 || scale == 8);
-    unsafe { Self::fns().setScale.unwrap()(scale) }
+    DisplaySetting::Scale(scale).apply()
+  }
+  /// Returns the display scale factor, as last set by `set_scale()`.
+  pub fn scale(&self) -> u32 {
+    CApiState::get().display_scale.get()
+  }
+  /// Sets the display scale factor until the returned guard is dropped, at which point it is set
+  /// back to its previous value.
+  pub fn push_scale(&mut self, scale: u32) -> DisplayGuard {
+    DisplayGuard::push(DisplaySetting::Scale(scale))
   }
 
   /// Offsets the display by the given amount.
   ///
   /// Areas outside of the displayed area are filled with the current background color.
   pub fn set_offset(&mut self, dx: i32, dy: i32) {
-    unsafe { Self::fns().setOffset.unwrap()(dx, dy) }
+    DisplaySetting::Offset(dx, dy).apply()
+  }
+  /// Returns the offset of the display, as last set by `set_offset()`.
+  pub fn offset(&self) -> (i32, i32) {
+    CApiState::get().display_offset.get()
+  }
+  /// Offsets the display until the returned guard is dropped, at which point it is set back to its
+  /// previous offset.
+  ///
+  /// # Example
+  /// ```
+  /// // Shake the screen while the explosion is drawn.
+  /// let _shake = api.display.push_offset(shake_x, shake_y);
+  /// draw_explosion(&mut api.graphics);
+  /// ```
+  pub fn push_offset(&mut self, dx: i32, dy: i32) -> DisplayGuard {
+    DisplayGuard::push(DisplaySetting::Offset(dx, dy))
   }
 
   pub(crate) fn fns() -> &'static craydate_sys::playdate_display {
     CApiState::get().cdisplay
   }
 }
+
+/// A setting of the display, which can be applied to the device.
+#[derive(Debug, Copy, Clone)]
+enum DisplaySetting {
+  Inverted(bool),
+  Mosaic(u32, u32),
+  Flipped(bool, bool),
+  Scale(u32),
+  Offset(i32, i32),
+}
+impl DisplaySetting {
+  /// Returns the current value of the same setting as `self`.
+  fn current(&self) -> DisplaySetting {
+    let capi = CApiState::get();
+    match self {
+      DisplaySetting::Inverted(_) => DisplaySetting::Inverted(capi.display_inverted.get()),
+      DisplaySetting::Mosaic(..) => {
+        let (x, y) = capi.display_mosaic.get();
+        DisplaySetting::Mosaic(x, y)
+      }
+      DisplaySetting::Flipped(..) => {
+        let (x, y) = capi.display_flipped.get();
+        DisplaySetting::Flipped(x, y)
+      }
+      DisplaySetting::Scale(_) => DisplaySetting::Scale(capi.display_scale.get()),
+      DisplaySetting::Offset(..) => {
+        let (dx, dy) = capi.display_offset.get();
+        DisplaySetting::Offset(dx, dy)
+      }
+    }
+  }
+
+  fn apply(self) {
+    let capi = CApiState::get();
+    let fns = Display::fns();
+    match self {
+      DisplaySetting::Inverted(inverted) => {
+        capi.display_inverted.set(inverted);
+        // Yes, this function takes an integer??
+        unsafe { fns.setInverted.unwrap()(inverted as i32) }
+      }
+      DisplaySetting::Mosaic(x, y) => {
+        assert!(x <= 3);
+        assert!(y <= 3);
+        capi.display_mosaic.set((x, y));
+        unsafe { fns.setMosaic.unwrap()(x, y) }
+      }
+      DisplaySetting::Flipped(flip_x, flip_y) => {
+        capi.display_flipped.set((flip_x, flip_y));
+        unsafe { fns.setFlipped.unwrap()(flip_x as i32, flip_y as i32) }
+      }
+      DisplaySetting::Scale(scale) => {
+        assert!(scale == 1 || scale == 2 || scale == 4 || scale == 8);
+        capi.display_scale.set(scale);
+        unsafe { fns.setScale.unwrap()(scale) }
+      }
+      DisplaySetting::Offset(dx, dy) => {
+        capi.display_offset.set((dx, dy));
+        unsafe { fns.setOffset.unwrap()(dx, dy) }
+      }
+    }
+  }
+}
+
+/// Keeps a display setting in place while it is alive, and sets it back to its previous value when
+/// dropped. Made by the `push_*()` functions on `Display`, such as `Display::push_inverted()`.
+///
+/// Guards should be dropped in the reverse order they were made, if they change the same setting.
+#[must_use]
+#[derive(Debug)]
+pub struct DisplayGuard {
+  previous: DisplaySetting,
+}
+impl DisplayGuard {
+  fn push(setting: DisplaySetting) -> Self {
+    let previous = setting.current();
+    setting.apply();
+    DisplayGuard { previous }
+  }
+}
+impl Drop for DisplayGuard {
+  fn drop(&mut self) {
+    self.previous.apply()
+  }
+}
@@ -0,0 +1,81 @@
+use alloc::format;
+use alloc::string::String;
+
+use crate::error::Error;
+use crate::files::File;
+
+/// A file embedded in the game's binary by the `include_assets!()` macro.
+#[derive(Debug, Copy, Clone)]
+pub struct EmbeddedAsset {
+  /// The path of the file, relative to the embedded folder, with '/' between folder names.
+  pub path: &'static str,
+  /// The contents of the file.
+  pub bytes: &'static [u8],
+}
+
+/// A folder of files embedded in the game's binary by the `include_assets!()` macro, which can be
+/// unpacked into the game's data folder.
+///
+/// This is useful for files the player or game is meant to change, such as default saves and mod
+/// templates, which can't be changed in the game's pdx bundle.
+///
+/// # Example
+/// ```
+/// static DEFAULTS: EmbeddedAssets = craydate::include_assets!("defaults");
+///
+/// // Writes any of the files that are not already in the data folder.
+/// DEFAULTS.unpack(&api.file, "")?;
+/// ```
+#[derive(Debug, Copy, Clone)]
+pub struct EmbeddedAssets {
+  assets: &'static [EmbeddedAsset],
+}
+impl EmbeddedAssets {
+  /// Constructs an `EmbeddedAssets` from its files. This is called by `include_assets!()`.
+  pub const fn new(assets: &'static [EmbeddedAsset]) -> Self {
+    EmbeddedAssets { assets }
+  }
+
+  /// The number of embedded files.
+  pub fn len(&self) -> usize {
+    self.assets.len()
+  }
+  /// Whether there are no embedded files.
+  pub fn is_empty(&self) -> bool {
+    self.assets.is_empty()
+  }
+  /// Returns an iterator over the embedded files, sorted by path.
+  pub fn iter(&self) -> impl Iterator<Item = &'static EmbeddedAsset> {
+    self.assets.iter()
+  }
+  /// Returns the contents of the embedded file at `path`, or `None` if there is no such file.
+  pub fn get(&self, path: &str) -> Option<&'static [u8]> {
+    self.assets.iter().find(|asset| asset.path == path).map(|asset| asset.bytes)
+  }
+
+  /// Writes the embedded files into `folder` in the game's data folder, making folders as needed,
+  /// and returns the number of files written.
+  ///
+  /// Files that already exist in the data folder are left as they are, so that changes made after
+  /// the first launch are kept. Calling this on every launch only writes the files that are
+  /// missing, such as ones added in an update to the game.
+  pub fn unpack(&self, file: &File, folder: &str) -> Result<usize, Error> {
+    let folder = folder.trim_end_matches('/');
+    let mut written = 0;
+    for asset in self.assets {
+      let path = match folder.is_empty() {
+        true => String::from(asset.path),
+        false => format!("{}/{}", folder, asset.path),
+      };
+      if file.exists(&path) {
+        continue;
+      }
+      if let Some((parent, _)) = path.rsplit_once('/') {
+        file.make_folders(parent)?;
+      }
+      file.write_atomic(&path, asset.bytes)?;
+      written += 1;
+    }
+    Ok(written)
+  }
+}
@@ -1,5 +1,58 @@
 use alloc::string::String;
 
+/// The cause of a failed file operation, found from the error string reported by Playdate.
+///
+/// UNCLEAR: Playdate reports file errors only as strings, and does not document them, so the kind
+/// is found by looking for the wording of common errors. Errors that are not recognized are
+/// `FileErrorKind::Other`, and the string is still available to look at.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum FileErrorKind {
+  /// The file or folder does not exist.
+  NotFound,
+  /// The file or folder can not be accessed, such as when writing to the game's pdx bundle.
+  PermissionDenied,
+  /// There is not enough space left to write.
+  NoSpace,
+  /// A file or folder already exists at the path.
+  AlreadyExists,
+  /// The folder can not be deleted because it has files or folders in it.
+  NotEmpty,
+  /// The path is to a folder, where a file was needed.
+  IsFolder,
+  /// The path is to a file, or goes through a file, where a folder was needed.
+  NotFolder,
+  /// The path is not valid, such as being too long.
+  InvalidPath,
+  /// An error which is not one of the other kinds.
+  Other,
+}
+impl FileErrorKind {
+  /// Finds the kind of error from the error string reported by Playdate.
+  pub fn from_playdate(playdate: &str) -> Self {
+    let s = playdate.to_ascii_lowercase();
+    let has = |words: &[&str]| words.iter().any(|w| s.contains(w));
+    if has(&["no such file", "not found", "does not exist", "doesn't exist"]) {
+      FileErrorKind::NotFound
+    } else if has(&["permission", "access denied", "read-only", "read only"]) {
+      FileErrorKind::PermissionDenied
+    } else if has(&["no space", "disk full", "not enough space", "no free"]) {
+      FileErrorKind::NoSpace
+    } else if has(&["exists"]) {
+      FileErrorKind::AlreadyExists
+    } else if has(&["not empty"]) {
+      FileErrorKind::NotEmpty
+    } else if has(&["is a directory", "is a folder"]) {
+      FileErrorKind::IsFolder
+    } else if has(&["not a directory", "not a folder"]) {
+      FileErrorKind::NotFolder
+    } else if has(&["invalid", "too long", "bad path"]) {
+      FileErrorKind::InvalidPath
+    } else {
+      FileErrorKind::Other
+    }
+  }
+}
+
 /// An error performing an operation on a filesystem path.
 pub struct FilePathError {
   /// The path of the file operation.
@@ -18,6 +71,19 @@ pub struct RenameFilePathError {
   pub playdate: String,
 }
 
+impl FilePathError {
+  /// The cause of the error, so that calling code can handle some causes, such as a missing file.
+  pub fn kind(&self) -> FileErrorKind {
+    FileErrorKind::from_playdate(&self.playdate)
+  }
+}
+impl RenameFilePathError {
+  /// The cause of the error, so that calling code can handle some causes, such as a missing file.
+  pub fn kind(&self) -> FileErrorKind {
+    FileErrorKind::from_playdate(&self.playdate)
+  }
+}
+
 /// The Error type for all errors in the craydate crate.
 pub enum Error {
   /// A general error which is described by the contained string.
@@ -37,6 +103,17 @@ pub enum Error {
   /// An error occured trying to read from a file to play it as audio.
   PlayFileError,
 }
+impl Error {
+  /// The cause of the error if it is from a file operation, or `None` for other errors.
+  pub fn file_error_kind(&self) -> Option<FileErrorKind> {
+    match self {
+      Error::NotFoundError => Some(FileErrorKind::NotFound),
+      Error::FilePathError(e) => Some(e.kind()),
+      Error::RenameFilePathError(e) => Some(e.kind()),
+      _ => None,
+    }
+  }
+}
 impl From<String> for Error {
   fn from(s: String) -> Self {
     Error::String(s)
@@ -62,6 +139,11 @@ impl From<RenameFilePathError> for Error {
     Error::RenameFilePathError(e)
   }
 }
+impl From<craydate_formats::FormatError> for Error {
+  fn from(e: craydate_formats::FormatError) -> Self {
+    Error::String(e.into())
+  }
+}
 
 impl core::fmt::Debug for FilePathError {
   fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
@@ -99,6 +181,21 @@ impl core::fmt::Debug for Error {
   }
 }
 
+impl core::fmt::Display for FileErrorKind {
+  fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+    match self {
+      FileErrorKind::NotFound => write!(f, "not found"),
+      FileErrorKind::PermissionDenied => write!(f, "permission denied"),
+      FileErrorKind::NoSpace => write!(f, "no space left"),
+      FileErrorKind::AlreadyExists => write!(f, "already exists"),
+      FileErrorKind::NotEmpty => write!(f, "folder is not empty"),
+      FileErrorKind::IsFolder => write!(f, "is a folder"),
+      FileErrorKind::NotFolder => write!(f, "is not a folder"),
+      FileErrorKind::InvalidPath => write!(f, "invalid path"),
+      FileErrorKind::Other => write!(f, "other error"),
+    }
+  }
+}
 impl core::fmt::Display for FilePathError {
   fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
     write!(
@@ -58,6 +58,9 @@ impl Executor {
     exec.first_poll_main = true;
   }
 
+  // Wakes the `waker` at the next system event. A system event happens at least once each frame,
+  // so a Future that waits on state which Playdate has no callback for can check the state when
+  // polled and, if it's not ready, register here to check again no later than the next frame.
   pub fn add_waker_for_system_event(exec_ptr: NonNull<Executor>, waker: &Waker) {
     let exec = unsafe { Self::as_mut_ref(exec_ptr) };
     exec.system_wakers.push(waker.clone());
@@ -0,0 +1,69 @@
+//! A fake of the Playdate C Api, for testing how the crate handles what the C Api returns.
+//!
+//! Every function table is empty, except for the functions that tests call, which return results
+//! based on their inputs:
+//! * Loading a file whose path starts with "missing" fails with the error "no such file".
+//! * Loading any other file returns null without an error.
+//! * Looking up a font page or glyph returns null.
+
+extern crate std;
+
+use alloc::boxed::Box;
+use core::ffi::CStr;
+
+use crate::capi_state::CApiState;
+use crate::ctypes::*;
+
+/// Installs the fake C Api as the one used by the crate. This can be called from every test, and
+/// only installs it the first time.
+pub(crate) fn install() {
+  static INSTALL: std::sync::Once = std::sync::Once::new();
+  INSTALL.call_once(|| {
+    let mut graphics: CGraphicsApi = unsafe { core::mem::zeroed() };
+    graphics.loadBitmap = Some(load_bitmap);
+    graphics.loadFont = Some(load_font);
+    graphics.getFontPage = Some(get_font_page);
+    graphics.getPageGlyph = Some(get_page_glyph);
+
+    let mut capi: CPlaydateApi = unsafe { core::mem::zeroed() };
+    capi.system = Box::leak(Box::new(unsafe { core::mem::zeroed::<CSystemApi>() }));
+    capi.file = Box::leak(Box::new(unsafe { core::mem::zeroed::<CFileApi>() }));
+    capi.graphics = Box::leak(Box::new(graphics));
+    capi.display = Box::leak(Box::new(unsafe { core::mem::zeroed::<CDisplayApi>() }));
+    capi.sound = Box::leak(Box::new(unsafe { core::mem::zeroed::<CSoundApi>() }));
+    capi.json = Box::leak(Box::new(unsafe { core::mem::zeroed::<CJsonApi>() }));
+    capi.scoreboards =
+      Box::leak(Box::new(unsafe { core::mem::zeroed::<craydate_sys::playdate_scoreboards>() }));
+    let capi: &'static CPlaydateApi = Box::leak(Box::new(capi));
+    CApiState::set_instance(Box::leak(Box::new(CApiState::new(capi))));
+  });
+}
+
+/// Returns the error for loading the file at `path`, if it should fail with one.
+unsafe fn load_error(path: *const u8) -> *const u8 {
+  let path = CStr::from_ptr(path as *const core::ffi::c_char);
+  match path.to_bytes().starts_with(b"missing") {
+    true => c"no such file".as_ptr() as *const u8,
+    false => core::ptr::null(),
+  }
+}
+
+unsafe extern "C" fn load_bitmap(path: *const u8, out_err: *mut *const u8) -> *mut CBitmap {
+  *out_err = load_error(path);
+  core::ptr::null_mut()
+}
+unsafe extern "C" fn load_font(path: *const u8, out_err: *mut *const u8) -> *mut CFont {
+  *out_err = load_error(path);
+  core::ptr::null_mut()
+}
+unsafe extern "C" fn get_font_page(_font: *mut CFont, _c: u32) -> *mut CFontPage {
+  core::ptr::null_mut()
+}
+unsafe extern "C" fn get_page_glyph(
+  _page: *mut CFontPage,
+  _c: u32,
+  _bitmap: *mut *mut CBitmap,
+  _advance: *mut i32,
+) -> *mut CFontGlyph {
+  core::ptr::null_mut()
+}
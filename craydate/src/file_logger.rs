@@ -0,0 +1,146 @@
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::sync::atomic::{AtomicUsize, Ordering};
+
+use crate::capi_state::CApiState;
+use crate::error::Error;
+use crate::files::{File, OpenFile};
+
+// Identifies each `FileLogger`, so that dropping one that was replaced does not stop the newer one.
+static NEXT_ID: AtomicUsize = AtomicUsize::new(0);
+
+/// The state of the running `FileLogger`, which `log()` and `log_error()` write to.
+pub(crate) struct FileLoggerState {
+  id: usize,
+  path: String,
+  max_size: u32,
+  max_files: u32,
+  // This is None if the file could not be opened after rotating, in which case nothing more is
+  // written.
+  file: Option<OpenFile>,
+  size: u32,
+}
+impl FileLoggerState {
+  fn write_line(&mut self, prefix: &str, s: &str) {
+    let frame = CApiState::get().frame_number.get();
+    let line = format!("[{}] {}{}\n", frame, prefix, s);
+    if self.size > 0 && self.size + line.len() as u32 > self.max_size {
+      self.rotate();
+    }
+    if let Some(file) = &mut self.file {
+      // Each line is flushed, so that it's in the file even if the game crashes right after.
+      if file.write(line.as_bytes()).is_ok() && file.flush().is_ok() {
+        self.size += line.len() as u32;
+      }
+    }
+  }
+
+  /// Moves each log file along by one, dropping the oldest one, and starts a new empty log file.
+  fn rotate(&mut self) {
+    if let Some(file) = self.file.take() {
+      let _ = file.close();
+    }
+    let file = File::new();
+    let _ = file.delete(&self.rotated_path(self.max_files));
+    for i in (1..self.max_files).rev() {
+      let _ = file.rename(&self.rotated_path(i), &self.rotated_path(i + 1));
+    }
+    if self.max_files == 0 {
+      let _ = file.delete(&self.path);
+    } else {
+      let _ = file.rename(&self.path, &self.rotated_path(1));
+    }
+    self.file = file.open_for_writing(&self.path).ok();
+    self.size = 0;
+  }
+
+  fn rotated_path(&self, i: u32) -> String {
+    format!("{}.{}", self.path, i)
+  }
+}
+
+/// Writes a copy of everything passed to `log()` and `log_error()` to a file in the game's data
+/// folder while the `FileLogger` is alive, so that bugs found on a device can be diagnosed
+/// afterward.
+///
+/// Each line starts with the frame number it was logged on. When the file would grow past its
+/// maximum size, it is renamed with a ".1" suffix, older files are moved along to ".2", ".3", etc.,
+/// and a new file is started. By default 3 of the older files are kept.
+///
+/// Only one `FileLogger` can be running at a time. Constructing another one replaces the first,
+/// which then does nothing.
+///
+/// # Example
+/// ```
+/// let _logger = FileLogger::new("log.txt", 64 * 1024)?;
+/// log("this line is also written to log.txt");
+/// ```
+#[derive(Debug)]
+pub struct FileLogger {
+  id: usize,
+}
+impl FileLogger {
+  /// Starts writing log output to the file at `path` in the game's data folder, appending to it if
+  /// it already exists. The file is rotated when it would grow beyond `max_size` bytes.
+  pub fn new(path: &str, max_size: u32) -> Result<Self, Error> {
+    let file = File::new();
+    let size = file.stat(path).map(|stat| stat.size()).unwrap_or(0);
+    let id = NEXT_ID.fetch_add(1, Ordering::Relaxed);
+    let state = FileLoggerState {
+      id,
+      path: String::from(path),
+      max_size,
+      max_files: 3,
+      file: Some(file.open_for_appending(path)?),
+      size,
+    };
+    *CApiState::get().file_logger.borrow_mut() = Some(state);
+    Ok(FileLogger { id })
+  }
+
+  /// Sets how many of the older log files are kept when rotating. With 0, the log file is deleted
+  /// when it is full and a new one is started.
+  pub fn set_max_files(&mut self, max_files: u32) {
+    self.with_state(|state| state.max_files = max_files);
+  }
+
+  /// Returns the paths of the log files, from the current file to the oldest one. Some of the
+  /// older files may not exist yet.
+  pub fn paths(&self) -> Vec<String> {
+    let paths = self.with_state(|state| {
+      let rotated = (1..=state.max_files).map(|i| state.rotated_path(i));
+      core::iter::once(state.path.clone()).chain(rotated).collect()
+    });
+    paths.unwrap_or_default()
+  }
+
+  /// Runs `f` with the running state, unless this `FileLogger` was replaced by another.
+  fn with_state<R>(&self, f: impl FnOnce(&mut FileLoggerState) -> R) -> Option<R> {
+    let mut state = CApiState::get().file_logger.borrow_mut();
+    state.as_mut().filter(|state| state.id == self.id).map(f)
+  }
+}
+impl Drop for FileLogger {
+  fn drop(&mut self) {
+    if let Some(capi) = CApiState::try_get() {
+      let mut state = capi.file_logger.borrow_mut();
+      if state.as_ref().is_some_and(|state| state.id == self.id) {
+        if let Some(file) = state.take().unwrap().file {
+          let _ = file.close();
+        }
+      }
+    }
+  }
+}
+
+/// Writes the line to the running `FileLogger`, if there is one.
+pub(crate) fn mirror(prefix: &str, s: &str) {
+  // If logging happens while the logger is busy, such as from a panic while writing, the line is
+  // skipped rather than panicking again.
+  if let Ok(mut state) = CApiState::get().file_logger.try_borrow_mut() {
+    if let Some(state) = state.as_mut() {
+      state.write_line(prefix, s);
+    }
+  }
+}
@@ -0,0 +1,18 @@
+use alloc::vec::Vec;
+
+use crate::error::Error;
+
+/// Compresses `data` into the zlib format, which is deflate compressed data with a small header
+/// and a checksum.
+///
+/// The compression favours speed and a small amount of memory over the smallest output.
+pub fn compress_zlib(data: &[u8]) -> Vec<u8> {
+  craydate_formats::zlib::compress(data)
+}
+
+/// Decompresses `data` in the zlib format, as made by `compress_zlib()` or another zlib encoder.
+///
+/// Returns an error if the data is not valid zlib data, or its checksum does not match.
+pub fn decompress_zlib(data: &[u8]) -> Result<Vec<u8>, Error> {
+  Ok(craydate_formats::zlib::decompress(data)?)
+}
@@ -0,0 +1,98 @@
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use super::io::Read;
+use super::line_reader::LineReader;
+use crate::error::Error;
+
+/// Reads records from comma-separated (CSV) or tab-separated (TSV) text, such as a spreadsheet of
+/// game balance values, one record at a time.
+///
+/// Fields may be quoted with '"', in which case they can hold the delimiter, line breaks, and
+/// quotes written as '""'. Only one record is held in memory at a time.
+///
+/// # Example
+/// ```
+/// let mut csv = CsvReader::new(api.file.open_for_reading("enemies.csv")?);
+/// let header = csv.read_record()?;
+/// while let Some(record) = csv.read_record()? {
+///   let hp: i32 = record[1].parse().map_err(|_| "bad hp")?;
+/// }
+/// ```
+#[derive(Debug)]
+pub struct CsvReader<R> {
+  lines: LineReader<R>,
+  delimiter: char,
+  line: String,
+}
+impl<R: Read> CsvReader<R> {
+  /// Constructs a `CsvReader` for comma-separated text.
+  pub fn new(inner: R) -> Self {
+    Self::with_delimiter(inner, ',')
+  }
+  /// Constructs a `CsvReader` for tab-separated text.
+  pub fn new_tsv(inner: R) -> Self {
+    Self::with_delimiter(inner, '\t')
+  }
+  /// Constructs a `CsvReader` for text with fields separated by `delimiter`.
+  pub fn with_delimiter(inner: R, delimiter: char) -> Self {
+    CsvReader {
+      lines: LineReader::new(inner),
+      delimiter,
+      line: String::new(),
+    }
+  }
+
+  /// Reads the next record, and returns its fields, or `None` at the end of the text.
+  ///
+  /// Empty lines are skipped. Returns an error if a quoted field is not closed before the end of
+  /// the text.
+  pub fn read_record(&mut self) -> Result<Option<Vec<String>>, Error> {
+    loop {
+      self.line.clear();
+      if !self.lines.read_line(&mut self.line)? {
+        return Ok(None);
+      }
+      if !self.line.is_empty() {
+        break;
+      }
+    }
+
+    let mut fields = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+    loop {
+      let mut chars = self.line.chars().peekable();
+      while let Some(c) = chars.next() {
+        match (quoted, c) {
+          (true, '"') if chars.peek() == Some(&'"') => {
+            chars.next();
+            field.push('"');
+          }
+          (true, '"') => quoted = false,
+          (false, '"') if field.is_empty() => quoted = true,
+          (false, c) if c == self.delimiter => fields.push(core::mem::take(&mut field)),
+          (_, c) => field.push(c),
+        }
+      }
+      if !quoted {
+        break;
+      }
+      // The quoted field continues onto the next line.
+      field.push('\n');
+      self.line.clear();
+      if !self.lines.read_line(&mut self.line)? {
+        return Err("CsvReader: a quoted field is not closed".into());
+      }
+    }
+    fields.push(field);
+    Ok(Some(fields))
+  }
+}
+impl<R: Read> Iterator for CsvReader<R> {
+  type Item = Result<Vec<String>, Error>;
+
+  fn next(&mut self) -> Option<Self::Item> {
+    self.read_record().transpose()
+  }
+}
@@ -4,6 +4,7 @@ use alloc::vec::Vec;
 use core::ffi::c_void;
 use core::ptr::NonNull;
 
+use super::file_entry::FileEntry;
 use super::file_path_stat::FilePathStat;
 use super::file_path_timestamp::FilePathTimestamp;
 use super::open_file::OpenFile;
@@ -43,7 +44,35 @@ impl File {
     })
   }
 
-  /// Reads information about the filemod or folder at `path`.
+  /// Returns an iterator with every file or subfolder found at `path`, skipping hidden ones whose
+  /// names start with a '.'.
+  ///
+  /// Unlike `list_files()`, each entry says if it is a folder, and folder names do not have a slash
+  /// suffix. `list()` does not recurse into subfolders.
+  ///
+  /// # Example
+  /// ```
+  /// for entry in api.file.list("saves")?.filter(|e| !e.is_folder) {
+  ///   log(format!("save slot: {}", entry.name));
+  /// }
+  /// ```
+  pub fn list(&self, path: &str) -> Result<impl Iterator<Item = FileEntry>, FilePathError> {
+    Ok(self.list_with_hidden(path)?.filter(|e| !e.is_hidden()))
+  }
+  /// Returns an iterator with every file or subfolder found at `path`, including hidden ones whose
+  /// names start with a '.'.
+  ///
+  /// UNCLEAR: Playdate's listfiles() has no option to show or hide hidden files in this version of
+  /// the SDK, so this returns whatever it reports.
+  pub fn list_with_hidden(
+    &self,
+    path: &str,
+  ) -> Result<impl Iterator<Item = FileEntry>, FilePathError> {
+    Ok(self.list_files(path)?.map(FileEntry::from_listed_name))
+  }
+
+  /// Reads information about the file or folder at `path`, such as its size and when it was last
+  /// modified.
   pub fn stat(&self, path: &str) -> Result<FilePathStat, FilePathError> {
     let mut s = core::mem::MaybeUninit::<CFileStat>::uninit();
     let result =
@@ -75,10 +104,15 @@ impl File {
     }
   }
 
-  // Creates a folder at the given `path`.
-  //
-  // This function does not create intermediate folders. The path will be relocated relative to the
-  // Data/<gameid> folder.
+  /// Returns whether a file or folder exists at `path`.
+  pub fn exists(&self, path: &str) -> bool {
+    self.stat(path).is_ok()
+  }
+
+  /// Creates a folder at the given `path`.
+  ///
+  /// This function does not create intermediate folders. The path will be relocated relative to the
+  /// Data/<gameid> folder.
   pub fn make_folder(&self, path: &str) -> Result<(), FilePathError> {
     let result = unsafe { Self::fns().mkdir.unwrap()(path.to_null_terminated_utf8().as_ptr()) };
     match result {
@@ -89,6 +123,28 @@ impl File {
       }),
     }
   }
+  /// Creates a folder at the given `path`, along with any intermediate folders that do not exist.
+  ///
+  /// Folders that already exist are left as they are, so this succeeds if the whole `path` already
+  /// exists. The path will be relocated relative to the Data/<gameid> folder.
+  pub fn make_folders(&self, path: &str) -> Result<(), FilePathError> {
+    let path = path.trim_end_matches('/');
+    let ends = path.match_indices('/').map(|(i, _)| i).chain(core::iter::once(path.len()));
+    for end in ends.filter(|end| *end > 0) {
+      let folder = &path[..end];
+      match self.stat(folder) {
+        Ok(stat) if stat.is_folder() => (),
+        Ok(_) => {
+          return Err(FilePathError {
+            path: String::from(folder),
+            playdate: String::from("A file exists where a folder is needed"),
+          })
+        }
+        Err(_) => self.make_folder(folder)?,
+      }
+    }
+    Ok(())
+  }
 
   /// Renames the file or folder at `from` to `to`.
   ///
@@ -111,6 +167,41 @@ impl File {
     }
   }
 
+  /// Opens the file at `path` to be read, from its start.
+  ///
+  /// The function will try to read from the game's data folder, and if it cannot find the file
+  /// there, it will fallback to look in the game pdx.
+  pub fn open_for_reading(&self, path: &str) -> Result<OpenFile, FilePathError> {
+    Self::open(
+      path,
+      craydate_sys::FileOptions::kFileReadData | craydate_sys::FileOptions::kFileRead,
+    )
+  }
+  /// Opens the file at `path` in the game's data folder to be written, from its start.
+  ///
+  /// If a file exists at `path` it will be overwritten, otherwise a file will be created.
+  pub fn open_for_writing(&self, path: &str) -> Result<OpenFile, FilePathError> {
+    Self::open(path, craydate_sys::FileOptions::kFileWrite)
+  }
+  /// Opens the file at `path` in the game's data folder to be written, from its end.
+  ///
+  /// If no file exists at `path`, a file will be created.
+  pub fn open_for_appending(&self, path: &str) -> Result<OpenFile, FilePathError> {
+    Self::open(path, craydate_sys::FileOptions::kFileAppend)
+  }
+  pub(crate) fn open(
+    path: &str,
+    options: craydate_sys::FileOptions,
+  ) -> Result<OpenFile, FilePathError> {
+    let ptr = NonNull::new(unsafe {
+      Self::fns().open.unwrap()(path.to_null_terminated_utf8().as_ptr(), options)
+    });
+    ptr.map(|handle| OpenFile::new(handle, path)).ok_or_else(|| FilePathError {
+      path: String::from(path),
+      playdate: last_err(),
+    })
+  }
+
   /// Read the entire contents of the file at `path`.
   ///
   /// The function will try to read from the game's data folder, and if it cannot find the file
@@ -130,7 +221,7 @@ impl File {
         playdate: last_err(),
       }),
       Some(handle) => {
-        let mut f = OpenFile::new(handle);
+        let mut f = OpenFile::new(handle, path);
         let read_result = f.read_file();
         let _close_result = f.close(); // We don't care if close() fails on a read.
         read_result.ok_or_else(|| FilePathError {
@@ -159,7 +250,7 @@ impl File {
         playdate: last_err(),
       }),
       Some(handle) => {
-        let mut f = OpenFile::new(handle);
+        let mut f = OpenFile::new(handle, path);
         let write_result = f.write_file(contents);
         // If close() fails on a write, we return an error as the file content may not be complete.
         if f.close() && write_result {
@@ -174,6 +265,85 @@ impl File {
     }
   }
 
+  /// Write `contents` into the file at `path` in the game's data folder, such that the file is
+  /// never left partly written.
+  ///
+  /// The `contents` are written to a temporary file next to `path` first, which is then renamed to
+  /// replace the file at `path`. If the device loses power or the write fails part way, the file at
+  /// `path` still holds what it held before.
+  pub fn write_atomic(&self, path: &str, contents: &[u8]) -> Result<(), FilePathError> {
+    let temp_path = format!("{}.tmp", path);
+    self.write_file(&temp_path, contents)?;
+    self.rename(&temp_path, path).map_err(|e| {
+      let _ = self.delete(&temp_path);
+      FilePathError {
+        path: String::from(path),
+        playdate: e.playdate,
+      }
+    })
+  }
+
+  /// Reads the entire contents of the zlib compressed file at `path`, and returns it decompressed.
+  ///
+  /// The file can be written by `write_compressed()`, or by another zlib encoder.
+  #[cfg(feature = "compression")]
+  pub fn read_compressed(&self, path: &str) -> Result<Vec<u8>, crate::Error> {
+    let compressed = self.read_file(path)?;
+    super::compression::decompress_zlib(&compressed).map_err(|e| {
+      crate::Error::FilePathError(FilePathError {
+        path: String::from(path),
+        playdate: format!("{}", e),
+      })
+    })
+  }
+  /// Compresses the `contents` with zlib and writes them to the file at `path`, with
+  /// `write_atomic()`.
+  #[cfg(feature = "compression")]
+  pub fn write_compressed(&self, path: &str, contents: &[u8]) -> Result<(), FilePathError> {
+    self.write_atomic(path, &super::compression::compress_zlib(contents))
+  }
+
+  /// Estimates the free space in the game's data folder, in bytes, counting no higher than
+  /// `up_to`.
+  ///
+  /// UNCLEAR: Playdate does not report how much free space there is, so this writes a temporary
+  /// file in the game's data folder, in chunks, until it has written `up_to` bytes or a write
+  /// fails, then deletes it. Every byte written wears the flash storage, and writing takes time,
+  /// so `up_to` should be no more than the space the game is about to need, such as the size of a
+  /// replay before recording it, and this should not be called every frame.
+  ///
+  /// Returns an error if the temporary file could not be made or deleted.
+  ///
+  /// BUG: Since `delete()` fails in the simulator, this returns an error there after leaving the
+  /// temporary file behind.
+  ///
+  /// # Example
+  /// ```
+  /// const REPLAY_SIZE: u32 = 512 * 1024;
+  /// if api.file.available_space(REPLAY_SIZE)? < REPLAY_SIZE {
+  ///   log("not enough space to record a replay");
+  /// }
+  /// ```
+  pub fn available_space(&self, up_to: u32) -> Result<u32, FilePathError> {
+    const PROBE_PATH: &str = ".available_space_probe";
+    const CHUNK: [u8; 4096] = [0; 4096];
+    let mut probe = self.open_for_writing(PROBE_PATH)?;
+    let mut written = 0;
+    while written < up_to {
+      let len = (up_to - written).min(CHUNK.len() as u32) as usize;
+      // A write with an error, or which writes nothing, means the storage is full.
+      match probe.write(&CHUNK[..len]).and_then(|n| probe.flush().map(|_| n)) {
+        Ok(n) if n > 0 => written += n as u32,
+        _ => break,
+      }
+    }
+    // The file is deleted even if closing it fails, so that the probe doesn't use up the space it
+    // found.
+    let _ = probe.close();
+    self.delete(PROBE_PATH)?;
+    Ok(written)
+  }
+
   /// Deletes the file or folder at `path` in the game's data folder.
   ///
   /// BUG: This is currently broken, and always reports "permission denied" in the simulator:
@@ -214,6 +384,59 @@ impl File {
     }
   }
 
+  /// Deletes the file or folder at `path` in the game's data folder, as with `delete()`, or
+  /// along with everything inside it if `recursive` is true, as with `delete_recursive()`.
+  pub fn remove(&self, path: &str, recursive: bool) -> Result<(), FilePathError> {
+    match recursive {
+      true => self.delete_recursive(path),
+      false => self.delete(path),
+    }
+  }
+
+  /// Copies the file at `path` in the game's data folder to `host_path` on the computer running
+  /// the simulator, replacing any file already there. This makes it easy to get at files the game
+  /// makes, such as screenshots or recordings, during development.
+  ///
+  /// The `host_path` is opened by the C runtime, so a relative path is relative to the simulator's
+  /// working folder. This is only available when building for the simulator.
+  ///
+  /// # Example
+  /// ```
+  /// api.file.export_to_host("replays/last.bin", "/tmp/last_replay.bin")?;
+  /// ```
+  #[cfg(not(all(target_arch = "arm", target_os = "none")))]
+  pub fn export_to_host(&self, path: &str, host_path: &str) -> Result<(), crate::Error> {
+    extern "C" {
+      fn fopen(path: *const u8, mode: *const u8) -> *mut c_void;
+      fn fwrite(ptr: *const c_void, size: usize, count: usize, file: *mut c_void) -> usize;
+      fn fclose(file: *mut c_void) -> i32;
+    }
+
+    let mut f = Self::open(path, craydate_sys::FileOptions::kFileReadData)?;
+    let contents = f.read_file();
+    let _close_result = f.close(); // We don't care if close() fails on a read.
+    let contents = contents.ok_or_else(|| FilePathError {
+      path: String::from(path),
+      playdate: last_err(),
+    })?;
+
+    let host = unsafe {
+      fopen(
+        host_path.to_null_terminated_utf8().as_ptr(),
+        "wb".to_null_terminated_utf8().as_ptr(),
+      )
+    };
+    if host.is_null() {
+      return Err(format!("export_to_host: unable to open '{}' on the host", host_path).into());
+    }
+    let written = unsafe { fwrite(contents.as_ptr() as *const c_void, 1, contents.len(), host) };
+    let closed = unsafe { fclose(host) } == 0;
+    if written != contents.len() || !closed {
+      return Err(format!("export_to_host: unable to write '{}' on the host", host_path).into());
+    }
+    Ok(())
+  }
+
   pub(crate) fn fns() -> &'static craydate_sys::playdate_file {
     CApiState::get().cfile
   }
@@ -0,0 +1,26 @@
+use alloc::string::String;
+
+/// A file or folder found by listing the contents of a folder.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct FileEntry {
+  /// The name of the file or folder, without the path of the folder it is in, and without a
+  /// trailing slash for folders.
+  pub name: String,
+  /// Whether the entry is a folder.
+  pub is_folder: bool,
+}
+impl FileEntry {
+  /// Makes an entry from a name given by Playdate, where folders have a trailing slash.
+  pub(super) fn from_listed_name(mut name: String) -> Self {
+    let is_folder = name.ends_with('/');
+    if is_folder {
+      name.pop();
+    }
+    FileEntry { name, is_folder }
+  }
+
+  /// Whether the entry is hidden, which is when its name starts with a '.'.
+  pub fn is_hidden(&self) -> bool {
+    self.name.starts_with('.')
+  }
+}
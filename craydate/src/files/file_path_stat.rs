@@ -1,4 +1,5 @@
 use super::file_path_timestamp::FilePathTimestamp;
+use crate::time::DateTime;
 
 /// Information about a file path in the filesystem.
 #[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
@@ -16,3 +17,30 @@ pub enum FilePathStat {
     modified: FilePathTimestamp,
   },
 }
+impl FilePathStat {
+  /// Whether the path refers to a folder.
+  pub fn is_folder(&self) -> bool {
+    matches!(self, FilePathStat::Folder { .. })
+  }
+  /// The size of the file in bytes, or 0 for a folder.
+  pub fn size(&self) -> u32 {
+    match self {
+      FilePathStat::Folder { .. } => 0,
+      FilePathStat::File { size, .. } => *size,
+    }
+  }
+  /// When the file or folder was last modified.
+  pub fn modified(&self) -> FilePathTimestamp {
+    match self {
+      FilePathStat::Folder { modified } => *modified,
+      FilePathStat::File { modified, .. } => *modified,
+    }
+  }
+  /// When the file or folder was last modified, as a `DateTime`.
+  ///
+  /// This can be compared with the time a cached file was made from it, to know if the cache is out
+  /// of date.
+  pub fn modified_date_time(&self) -> DateTime {
+    self.modified().to_date_time()
+  }
+}
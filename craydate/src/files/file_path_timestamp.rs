@@ -1,3 +1,5 @@
+use crate::time::DateTime;
+
 /// A filesystem timestamp, which can represent when a file or folder was last accessed, modified,
 /// etc.
 /// 
@@ -18,3 +20,18 @@ pub struct FilePathTimestamp {
   /// The timestamp's seconds within the minute, normally from 0 to 59. Can be 60 on a leap second.
   pub second: i32,
 }
+impl FilePathTimestamp {
+  /// Returns the timestamp as a `DateTime`, such as to compare with `System::date_time()`.
+  ///
+  /// UNCLEAR: Playdate does not say which timezone file timestamps are in.
+  pub fn to_date_time(&self) -> DateTime {
+    DateTime::new(
+      self.year as u16,
+      self.month as u8,
+      self.day as u8,
+      self.hour as u8,
+      self.minute as u8,
+      self.second as u8,
+    )
+  }
+}
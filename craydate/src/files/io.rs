@@ -0,0 +1,296 @@
+use alloc::vec::Vec;
+
+use super::open_file::{OpenFile, SeekFrom};
+use crate::error::Error;
+
+/// A source of bytes, such as an `OpenFile`.
+///
+/// This is similar to `std::io::Read`, so that parsers can be written against it and read from a
+/// file, a `BufReader`, or a byte slice in memory.
+pub trait Read {
+  /// Reads into `buf`, and returns the number of bytes read, which is 0 at the end of the source.
+  fn read(&mut self, buf: &mut [u8]) -> Result<usize, Error>;
+
+  /// Reads exactly enough bytes to fill `buf`.
+  ///
+  /// Returns an error if the end of the source is reached first, in which case the contents of
+  /// `buf` are unspecified.
+  fn read_exact(&mut self, mut buf: &mut [u8]) -> Result<(), Error> {
+    while !buf.is_empty() {
+      match self.read(buf)? {
+        0 => return Err("Read: reached the end before filling the buffer".into()),
+        n => buf = &mut buf[n..],
+      }
+    }
+    Ok(())
+  }
+  /// Reads every byte until the end of the source, appending them to `out`, and returns the number
+  /// of bytes read.
+  fn read_to_end(&mut self, out: &mut Vec<u8>) -> Result<usize, Error> {
+    let mut buf = [0; 256];
+    let mut total = 0;
+    loop {
+      match self.read(&mut buf)? {
+        0 => return Ok(total),
+        n => {
+          out.extend_from_slice(&buf[..n]);
+          total += n;
+        }
+      }
+    }
+  }
+}
+
+/// A destination for bytes, such as an `OpenFile`.
+///
+/// This is similar to `std::io::Write`, so that encoders can be written against it and write to a
+/// file, a `BufWriter`, or a `Vec` in memory.
+pub trait Write {
+  /// Writes from `buf`, and returns the number of bytes written.
+  fn write(&mut self, buf: &[u8]) -> Result<usize, Error>;
+  /// Writes any buffered data to its destination.
+  fn flush(&mut self) -> Result<(), Error>;
+
+  /// Writes all of `buf`.
+  fn write_all(&mut self, mut buf: &[u8]) -> Result<(), Error> {
+    while !buf.is_empty() {
+      match self.write(buf)? {
+        0 => return Err("Write: unable to write the whole buffer".into()),
+        n => buf = &buf[n..],
+      }
+    }
+    Ok(())
+  }
+}
+
+/// A source or destination of bytes with a position that can be moved, such as an `OpenFile`.
+///
+/// This is similar to `std::io::Seek`.
+pub trait Seek {
+  /// Moves the position to `pos`, and returns the new position as a number of bytes from the
+  /// start.
+  fn seek(&mut self, pos: SeekFrom) -> Result<u32, Error>;
+
+  /// Returns the current position, as a number of bytes from the start.
+  fn stream_position(&mut self) -> Result<u32, Error> {
+    self.seek(SeekFrom::Current(0))
+  }
+}
+
+impl Read for OpenFile {
+  fn read(&mut self, buf: &mut [u8]) -> Result<usize, Error> {
+    Ok(OpenFile::read(self, buf)?)
+  }
+}
+impl Write for OpenFile {
+  fn write(&mut self, buf: &[u8]) -> Result<usize, Error> {
+    Ok(OpenFile::write(self, buf)?)
+  }
+  fn flush(&mut self) -> Result<(), Error> {
+    Ok(OpenFile::flush(self)?)
+  }
+}
+impl Seek for OpenFile {
+  fn seek(&mut self, pos: SeekFrom) -> Result<u32, Error> {
+    Ok(OpenFile::seek(self, pos)?)
+  }
+}
+
+impl Read for &[u8] {
+  fn read(&mut self, buf: &mut [u8]) -> Result<usize, Error> {
+    let n = buf.len().min(self.len());
+    let (read, rest) = self.split_at(n);
+    buf[..n].copy_from_slice(read);
+    *self = rest;
+    Ok(n)
+  }
+}
+impl Write for Vec<u8> {
+  fn write(&mut self, buf: &[u8]) -> Result<usize, Error> {
+    self.extend_from_slice(buf);
+    Ok(buf.len())
+  }
+  fn flush(&mut self) -> Result<(), Error> {
+    Ok(())
+  }
+}
+
+const DEFAULT_BUF_SIZE: usize = 1024;
+
+/// Reads from a source through a buffer in memory, so that many small reads, such as from a
+/// parser, become a few larger reads from the source.
+///
+/// # Example
+/// ```
+/// let mut reader = BufReader::new(api.file.open_for_reading("map.bin")?);
+/// let mut header = [0; 8];
+/// reader.read_exact(&mut header)?;
+/// ```
+#[derive(Debug)]
+pub struct BufReader<R> {
+  inner: R,
+  buf: Vec<u8>,
+  // The part of `buf` which has been read from `inner` but not yet returned.
+  pos: usize,
+  filled: usize,
+}
+impl<R: Read> BufReader<R> {
+  /// Constructs a `BufReader` with a default buffer size of 1KB.
+  pub fn new(inner: R) -> Self {
+    Self::with_capacity(DEFAULT_BUF_SIZE, inner)
+  }
+  /// Constructs a `BufReader` with a buffer of `capacity` bytes.
+  pub fn with_capacity(capacity: usize, inner: R) -> Self {
+    BufReader {
+      inner,
+      buf: alloc::vec![0; capacity],
+      pos: 0,
+      filled: 0,
+    }
+  }
+
+  /// Returns the buffered bytes, reading more from the source if the buffer is empty. The returned
+  /// slice is empty at the end of the source.
+  pub fn fill_buf(&mut self) -> Result<&[u8], Error> {
+    if self.pos == self.filled {
+      self.filled = self.inner.read(&mut self.buf)?;
+      self.pos = 0;
+    }
+    Ok(&self.buf[self.pos..self.filled])
+  }
+  /// Marks `amount` bytes from `fill_buf()` as read, so they are not returned again.
+  pub fn consume(&mut self, amount: usize) {
+    self.pos = (self.pos + amount).min(self.filled);
+  }
+
+  /// Returns the source being read from.
+  pub fn get_ref(&self) -> &R {
+    &self.inner
+  }
+  /// Returns the source being read from. Reading from it directly will skip any buffered bytes.
+  pub fn get_mut(&mut self) -> &mut R {
+    &mut self.inner
+  }
+  /// Returns the source being read from, and drops any buffered bytes.
+  pub fn into_inner(self) -> R {
+    self.inner
+  }
+
+  fn discard_buffer(&mut self) {
+    self.pos = 0;
+    self.filled = 0;
+  }
+}
+impl<R: Read> Read for BufReader<R> {
+  fn read(&mut self, buf: &mut [u8]) -> Result<usize, Error> {
+    // Large reads skip the buffer when it's empty, as there's nothing gained by copying twice.
+    if self.pos == self.filled && buf.len() >= self.buf.len() {
+      return self.inner.read(buf);
+    }
+    let available = self.fill_buf()?;
+    let n = available.len().min(buf.len());
+    buf[..n].copy_from_slice(&available[..n]);
+    self.consume(n);
+    Ok(n)
+  }
+}
+impl<R: Read + Seek> Seek for BufReader<R> {
+  fn seek(&mut self, pos: SeekFrom) -> Result<u32, Error> {
+    let pos = match pos {
+      // The source is ahead of our position by the bytes still in the buffer.
+      SeekFrom::Current(offset) => SeekFrom::Current(offset - (self.filled - self.pos) as i32),
+      pos => pos,
+    };
+    self.discard_buffer();
+    self.inner.seek(pos)
+  }
+}
+
+/// Writes to a destination through a buffer in memory, so that many small writes, such as from an
+/// encoder, become a few larger writes to the destination.
+///
+/// The buffer is written out when it is full, on `flush()`, and when the `BufWriter` is dropped,
+/// though any error is ignored when dropped. Call `flush()` or `into_inner()` to find out if the
+/// last of the data was written.
+///
+/// # Example
+/// ```
+/// let mut writer = BufWriter::new(api.file.open_for_writing("map.bin")?);
+/// for tile in tiles {
+///   writer.write_all(&tile.to_le_bytes())?;
+/// }
+/// writer.flush()?;
+/// ```
+#[derive(Debug)]
+pub struct BufWriter<W: Write> {
+  // This is only None after `into_inner()`, which is why it's an Option.
+  inner: Option<W>,
+  buf: Vec<u8>,
+  capacity: usize,
+}
+impl<W: Write> BufWriter<W> {
+  /// Constructs a `BufWriter` with a default buffer size of 1KB.
+  pub fn new(inner: W) -> Self {
+    Self::with_capacity(DEFAULT_BUF_SIZE, inner)
+  }
+  /// Constructs a `BufWriter` with a buffer of `capacity` bytes.
+  pub fn with_capacity(capacity: usize, inner: W) -> Self {
+    BufWriter {
+      inner: Some(inner),
+      buf: Vec::with_capacity(capacity),
+      capacity,
+    }
+  }
+
+  /// Returns the destination being written to.
+  pub fn get_ref(&self) -> &W {
+    self.inner.as_ref().unwrap()
+  }
+  /// Returns the destination being written to. Writing to it directly will put the bytes before
+  /// any buffered bytes.
+  pub fn get_mut(&mut self) -> &mut W {
+    self.inner.as_mut().unwrap()
+  }
+  /// Writes out the buffer, and returns the destination being written to.
+  pub fn into_inner(mut self) -> Result<W, Error> {
+    self.flush_buf()?;
+    Ok(self.inner.take().unwrap())
+  }
+
+  fn flush_buf(&mut self) -> Result<(), Error> {
+    let result = self.inner.as_mut().unwrap().write_all(&self.buf);
+    self.buf.clear();
+    result
+  }
+}
+impl<W: Write> Write for BufWriter<W> {
+  fn write(&mut self, buf: &[u8]) -> Result<usize, Error> {
+    if self.buf.len() + buf.len() > self.capacity {
+      self.flush_buf()?;
+    }
+    // Large writes skip the buffer, as there's nothing gained by copying twice.
+    if buf.len() >= self.capacity {
+      self.inner.as_mut().unwrap().write(buf)
+    } else {
+      self.buf.extend_from_slice(buf);
+      Ok(buf.len())
+    }
+  }
+  fn flush(&mut self) -> Result<(), Error> {
+    self.flush_buf()?;
+    self.inner.as_mut().unwrap().flush()
+  }
+}
+impl<W: Write + Seek> Seek for BufWriter<W> {
+  fn seek(&mut self, pos: SeekFrom) -> Result<u32, Error> {
+    self.flush_buf()?;
+    self.inner.as_mut().unwrap().seek(pos)
+  }
+}
+impl<W: Write> Drop for BufWriter<W> {
+  fn drop(&mut self) {
+    if self.inner.is_some() {
+      let _ = self.flush_buf();
+    }
+  }
+}
@@ -0,0 +1,88 @@
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use super::io::{BufReader, Read};
+use crate::error::Error;
+
+/// Reads text from a source one line at a time, such as a dialogue script in a file, without
+/// reading the whole source into memory.
+///
+/// Lines may end in "\n" or "\r\n", and the line ending is not included in the returned lines.
+///
+/// # Example
+/// ```
+/// let lines = LineReader::new(api.file.open_for_reading("dialogue.txt")?);
+/// for line in lines {
+///   let line = line?;
+///   log(line);
+/// }
+/// ```
+#[derive(Debug)]
+pub struct LineReader<R> {
+  inner: BufReader<R>,
+  line: Vec<u8>,
+}
+impl<R: Read> LineReader<R> {
+  /// Constructs a `LineReader` which reads from `inner` through a `BufReader`.
+  pub fn new(inner: R) -> Self {
+    Self::from_buf_reader(BufReader::new(inner))
+  }
+  /// Constructs a `LineReader` which reads from a `BufReader`, to choose the size of its buffer.
+  pub fn from_buf_reader(inner: BufReader<R>) -> Self {
+    LineReader {
+      inner,
+      line: Vec::new(),
+    }
+  }
+
+  /// Reads the next line, and appends it to `out` without its line ending.
+  ///
+  /// Returns false if the end of the source was reached without reading a line. The last line
+  /// does not need to end with a line ending.
+  pub fn read_line(&mut self, out: &mut String) -> Result<bool, Error> {
+    self.line.clear();
+    let mut read_any = false;
+    loop {
+      let available = self.inner.fill_buf()?;
+      if available.is_empty() {
+        break;
+      }
+      read_any = true;
+      match available.iter().position(|b| *b == b'\n') {
+        Some(i) => {
+          self.line.extend_from_slice(&available[..i]);
+          self.inner.consume(i + 1);
+          break;
+        }
+        None => {
+          let n = available.len();
+          self.line.extend_from_slice(available);
+          self.inner.consume(n);
+        }
+      }
+    }
+    if self.line.last() == Some(&b'\r') {
+      self.line.pop();
+    }
+    let line = core::str::from_utf8(&self.line).map_err(|_| "LineReader: the line is not UTF-8")?;
+    out.push_str(line);
+    Ok(read_any)
+  }
+
+  /// Returns the source being read from.
+  pub fn into_inner(self) -> BufReader<R> {
+    self.inner
+  }
+}
+impl<R: Read> Iterator for LineReader<R> {
+  type Item = Result<String, Error>;
+
+  fn next(&mut self) -> Option<Self::Item> {
+    let mut line = String::new();
+    match self.read_line(&mut line) {
+      Ok(true) => Some(Ok(line)),
+      Ok(false) => None,
+      Err(e) => Some(Err(e)),
+    }
+  }
+}
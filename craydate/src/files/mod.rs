@@ -1,8 +1,21 @@
+#[cfg(feature = "compression")]
+mod compression;
+mod csv_reader;
 mod file;
+mod file_entry;
 mod file_path_timestamp;
 mod open_file;
 mod file_path_stat;
+mod io;
+mod line_reader;
 
+#[cfg(feature = "compression")]
+pub use compression::{compress_zlib, decompress_zlib};
+pub use csv_reader::CsvReader;
 pub use file::File;
+pub use file_entry::FileEntry;
 pub use file_path_timestamp::FilePathTimestamp;
-pub use file_path_stat::FilePathStat;
\ No newline at end of file
+pub use file_path_stat::FilePathStat;
+pub use io::{BufReader, BufWriter, Read, Seek, Write};
+pub use line_reader::LineReader;
+pub use open_file::{OpenFile, SeekFrom};
\ No newline at end of file
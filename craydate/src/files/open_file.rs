@@ -1,28 +1,118 @@
+use alloc::string::String;
 use alloc::vec::Vec;
 use core::ffi::c_void;
 use core::ptr::NonNull;
 
+use super::file::last_err;
 use crate::capi_state::CApiState;
 use crate::ctypes::*;
+use crate::FilePathError;
 
-/// An open file which can be read from and written to.
+/// A position in an `OpenFile` to move to with `OpenFile::seek()`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum SeekFrom {
+  /// A number of bytes from the start of the file.
+  Start(u32),
+  /// A number of bytes, which may be negative, from the current position in the file.
+  Current(i32),
+  /// A number of bytes, which may be negative, from the end of the file.
+  End(i32),
+}
+
+/// An open file which can be read from and written to, made by `File::open_for_reading()` and
+/// similar functions.
+///
+/// Reading and writing start at the current position in the file, which can be moved with
+/// `seek()`. This allows large files to be read a piece at a time instead of all at once.
 ///
-/// The close() function _must_ be called in order to destroy the `OpenFile` object. Dropping the
-/// OpenFile without calling close() will panic/abort.
+/// The close() function should be called to find out if closing the file was successful, such as
+/// when the last data written may not have been saved. Dropping the `OpenFile` without calling
+/// close() will close it and ignore any error.
+///
+/// # Example
+/// ```
+/// let mut file = api.file.open_for_reading("levels.pack")?;
+/// file.seek(SeekFrom::Start(level_offset))?;
+/// let mut level = vec![0; level_size];
+/// file.read(&mut level)?;
+/// ```
 #[derive(Debug)]
-pub(super) struct OpenFile {
+pub struct OpenFile {
   handle: NonNull<COpenFile>,
+  // The path the file was opened from, for errors.
+  path: String,
   closed: bool,
 }
 impl OpenFile {
-  pub(super) fn new(handle: NonNull<COpenFile>) -> Self {
+  pub(super) fn new(handle: NonNull<COpenFile>, path: &str) -> Self {
     OpenFile {
       handle,
+      path: String::from(path),
       closed: false,
     }
   }
 
-  /// Read the entire contents of the file.
+  /// Reads from the current position in the file into `buf`, and returns the number of bytes read,
+  /// which is 0 at the end of the file.
+  pub fn read(&mut self, buf: &mut [u8]) -> Result<usize, FilePathError> {
+    let result = unsafe {
+      Self::fns().read.unwrap()(self.cptr_mut(), buf.as_mut_ptr() as *mut c_void, buf.len() as u32)
+    };
+    match result {
+      -1 => Err(self.err()),
+      read_bytes_count => Ok(read_bytes_count as usize),
+    }
+  }
+  /// Writes from `buf` at the current position in the file, and returns the number of bytes
+  /// written.
+  pub fn write(&mut self, buf: &[u8]) -> Result<usize, FilePathError> {
+    let result = unsafe {
+      Self::fns().write.unwrap()(self.cptr_mut(), buf.as_ptr() as *const c_void, buf.len() as u32)
+    };
+    match result {
+      -1 => Err(self.err()),
+      written_bytes_count => Ok(written_bytes_count as usize),
+    }
+  }
+  /// Writes any buffered data to the file.
+  pub fn flush(&mut self) -> Result<(), FilePathError> {
+    match unsafe { Self::fns().flush.unwrap()(self.cptr_mut()) } {
+      -1 => Err(self.err()),
+      _ => Ok(()),
+    }
+  }
+
+  /// Moves the current position in the file to `pos`, and returns the new position as a number of
+  /// bytes from the start of the file.
+  pub fn seek(&mut self, pos: SeekFrom) -> Result<u32, FilePathError> {
+    let (offset, whence) = match pos {
+      SeekFrom::Start(offset) => match i32::try_from(offset) {
+        Ok(offset) => (offset, craydate_sys::SEEK_SET),
+        // Playdate takes the offset as an `i32`, so it can't seek past `i32::MAX`.
+        Err(_) => {
+          return Err(FilePathError {
+            path: self.path.clone(),
+            playdate: String::from("The offset is too large to seek to"),
+          })
+        }
+      },
+      SeekFrom::Current(offset) => (offset, craydate_sys::SEEK_CUR),
+      SeekFrom::End(offset) => (offset, craydate_sys::SEEK_END),
+    };
+    match unsafe { Self::fns().seek.unwrap()(self.cptr_mut(), offset, whence as i32) } {
+      -1 => Err(self.err()),
+      _ => self.tell(),
+    }
+  }
+  /// Returns the current position in the file, as a number of bytes from the start of the file.
+  pub fn tell(&mut self) -> Result<u32, FilePathError> {
+    match unsafe { Self::fns().tell.unwrap()(self.cptr_mut()) } {
+      -1 => Err(self.err()),
+      pos => Ok(pos as u32),
+    }
+  }
+
+  /// Read the entire contents of the file, from the current position to the end.
   pub fn read_file(&mut self) -> Option<Vec<u8>> {
     let mut out = Vec::new();
     const BUF_SIZE: u32 = 256;
@@ -43,26 +133,24 @@ impl OpenFile {
     Some(out)
   }
 
-  /// Write the entire contents of the file, returns if the operation was successful.
+  /// Write the entire `contents` at the current position in the file, returns if the operation was
+  /// successful.
   pub fn write_file(&mut self, contents: &[u8]) -> bool {
-    // Note: This would be needed if we support other operations beyond read/write the whole
-    // file.
-    // Self::fns().seek.unwrap()(self.cptr(), 0, craydate_sys::SEEK_SET as i32);
-
     const BUF_SIZE: usize = 256;
     for buf in contents.chunks(BUF_SIZE) {
       let mut written_from_buffer = 0;
       loop {
         let result = unsafe {
           Self::fns().write.unwrap()(
-            self.cptr_mut().add(written_from_buffer),
-            buf.as_ptr() as *const c_void,
+            self.cptr_mut(),
+            buf.as_ptr().add(written_from_buffer) as *const c_void,
             (buf.len() - written_from_buffer) as u32,
           )
         };
         written_from_buffer += match result {
-          // Return immediately on an error.
-          -1 => return false,
+          // Return immediately on an error. Writing nothing would make no progress, so treat it as
+          // an error too.
+          -1 | 0 => return false,
           written_bytes_count => written_bytes_count as usize,
         };
         if written_from_buffer == buf.len() {
@@ -73,9 +161,7 @@ impl OpenFile {
     true
   }
 
-  /// Close the file. This function _must_ be called in order to destroy the `OpenFile` object.
-  ///
-  /// Dropping the OpenFile without calling close() will panic/abort.
+  /// Close the file, and returns if the operation was successful.
   #[must_use]
   pub fn close(mut self) -> bool {
     self.closed = true;
@@ -83,6 +169,13 @@ impl OpenFile {
     result == 0
   }
 
+  fn err(&self) -> FilePathError {
+    FilePathError {
+      path: self.path.clone(),
+      playdate: last_err(),
+    }
+  }
+
   pub(crate) fn cptr_mut(&mut self) -> *mut COpenFile {
     self.handle.as_ptr()
   }
@@ -93,8 +186,7 @@ impl OpenFile {
 impl Drop for OpenFile {
   fn drop(&mut self) {
     if !self.closed {
-      crate::log::log("ERROR: OpenFile dropped without calling close()");
-      assert!(self.closed);
+      unsafe { Self::fns().close.unwrap()(self.cptr_mut()) };
     }
   }
 }
@@ -0,0 +1,172 @@
+use crate::time::{TimeDelta, TimeTicks};
+
+/// Runs game logic at a fixed rate that is independent from the display's refresh rate.
+///
+/// Game logic can be run at a lower rate than the display (for example, 25 or 30 times per second
+/// while the display refreshes 50 times per second with `Display::set_refresh_rate()`) to save CPU
+/// time on the device. Each frame, `advance()` reports how many logic steps to run, and
+/// `alpha()` reports how far the frame is between the last two logic steps, which can be used to
+/// draw values held in `Interpolated` so that motion stays smooth.
+///
+/// # Example
+/// ```
+/// let mut timestep = FixedTimestep::new(30);
+/// let mut position = Interpolated::new(euclid::default::Point2D::new(0.0, 0.0));
+/// loop {
+///   match events.next().await {
+///     SystemEvent::NextFrame { .. } => {
+///       for _ in 0..timestep.advance(api.system.current_time()) {
+///         position.set(*position.current() + velocity);
+///       }
+///       let drawn_at = position.get(timestep.alpha());
+///     }
+///     _ => (),
+///   }
+/// }
+/// ```
+#[derive(Debug)]
+pub struct FixedTimestep {
+  steps_per_second: u32,
+  max_steps_per_frame: u32,
+  // The time since the last step, in units of 1/(1000 * steps_per_second) seconds, so that a step
+  // is exactly 1000 units and no rounding builds up for rates that don't divide 1000 evenly.
+  accumulated: u64,
+  last_time: Option<TimeTicks>,
+}
+impl FixedTimestep {
+  /// Constructs a `FixedTimestep` that runs logic `steps_per_second` times per second.
+  ///
+  /// # Panics
+  ///
+  /// Panics if `steps_per_second` is 0.
+  pub fn new(steps_per_second: u32) -> Self {
+    assert!(steps_per_second > 0, "FixedTimestep needs at least one step per second");
+    FixedTimestep {
+      steps_per_second,
+      max_steps_per_frame: 4,
+      accumulated: 0,
+      last_time: None,
+    }
+  }
+
+  /// Sets the most logic steps that `advance()` will ask for in a single frame.
+  ///
+  /// When a frame takes a long time, such as after loading from disk, the time that would need
+  /// more steps than this is dropped rather than making the game run many steps at once to catch
+  /// up. The default is 4.
+  pub fn set_max_steps_per_frame(&mut self, max_steps: u32) {
+    self.max_steps_per_frame = max_steps.max(1);
+  }
+
+  /// Returns the length of time covered by each logic step, rounded down to whole milliseconds.
+  ///
+  /// The steps are timed exactly, so for a rate such as 30 steps per second, which is not a whole
+  /// number of milliseconds, there are exactly 30 steps in each second even though this returns
+  /// 33 milliseconds.
+  pub fn step(&self) -> TimeDelta {
+    TimeDelta::from_milliseconds((1000 / self.steps_per_second) as i32)
+  }
+
+  /// Moves time forward to `now`, and returns the number of logic steps that should be run this
+  /// frame.
+  ///
+  /// The first call only starts tracking time, and asks for a single step.
+  pub fn advance(&mut self, now: TimeTicks) -> u32 {
+    let elapsed = match self.last_time {
+      Some(last) => {
+        // Time that goes backward, such as from a clock change, runs no steps.
+        let elapsed_ms = (now - last).total_whole_milliseconds().max(0) as u64;
+        elapsed_ms * self.steps_per_second as u64
+      }
+      None => STEP_UNITS,
+    };
+    self.last_time = Some(now);
+
+    self.accumulated += elapsed;
+    let mut steps = self.accumulated / STEP_UNITS;
+    if steps > self.max_steps_per_frame as u64 {
+      steps = self.max_steps_per_frame as u64;
+      self.accumulated = steps * STEP_UNITS;
+    }
+    self.accumulated -= steps * STEP_UNITS;
+    steps as u32
+  }
+
+  /// Returns how far the current frame is between the previous logic step and the next one, from
+  /// 0 to 1.
+  pub fn alpha(&self) -> f32 {
+    (self.accumulated as f32 / STEP_UNITS as f32).clamp(0.0, 1.0)
+  }
+}
+
+/// The length of one step in the units of `FixedTimestep::accumulated`.
+const STEP_UNITS: u64 = 1000;
+
+/// A value which can be interpolated linearly between two instances of itself.
+pub trait Lerp {
+  /// Returns the value `t` of the way from `self` to `other`, where `t` is between 0 and 1.
+  fn lerp(&self, other: &Self, t: f32) -> Self;
+}
+impl Lerp for f32 {
+  fn lerp(&self, other: &Self, t: f32) -> Self {
+    self + (other - self) * t
+  }
+}
+impl Lerp for euclid::default::Point2D<f32> {
+  fn lerp(&self, other: &Self, t: f32) -> Self {
+    euclid::default::Point2D::lerp(*self, *other, t)
+  }
+}
+impl Lerp for euclid::default::Vector2D<f32> {
+  fn lerp(&self, other: &Self, t: f32) -> Self {
+    euclid::default::Vector2D::lerp(*self, *other, t)
+  }
+}
+impl Lerp for euclid::default::Size2D<f32> {
+  fn lerp(&self, other: &Self, t: f32) -> Self {
+    euclid::default::Size2D::lerp(*self, *other, t)
+  }
+}
+
+/// A value that is updated by game logic steps, and drawn between them.
+///
+/// The value remembers its state from the previous and current logic steps, so that it can be
+/// drawn at any point between them with `get()`, using the `FixedTimestep::alpha()` of the frame.
+#[derive(Debug, Clone)]
+pub struct Interpolated<T> {
+  previous: T,
+  current: T,
+}
+impl<T: Lerp + Clone> Interpolated<T> {
+  /// Constructs an `Interpolated` that holds `value` for both the previous and current step.
+  pub fn new(value: T) -> Self {
+    Interpolated {
+      previous: value.clone(),
+      current: value,
+    }
+  }
+
+  /// Sets the value for a new logic step, with the current value becoming the previous one.
+  pub fn set(&mut self, value: T) {
+    self.previous = core::mem::replace(&mut self.current, value);
+  }
+  /// Sets the value without interpolating from the current one, such as when an object is
+  /// teleported to a new position.
+  pub fn reset(&mut self, value: T) {
+    self.previous = value.clone();
+    self.current = value;
+  }
+
+  /// Returns the value from the most recent logic step.
+  pub fn current(&self) -> &T {
+    &self.current
+  }
+  /// Returns the value from the logic step before the most recent one.
+  pub fn previous(&self) -> &T {
+    &self.previous
+  }
+  /// Returns the value `alpha` of the way from the previous logic step to the current one.
+  pub fn get(&self, alpha: f32) -> T {
+    self.previous.lerp(&self.current, alpha)
+  }
+}
@@ -0,0 +1,202 @@
+use alloc::collections::VecDeque;
+
+use crate::graphics::Graphics;
+use crate::time::HighResolutionTimer;
+
+/// A part of the frame measured by a `FrameProfiler`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum FrameStage {
+  /// Running the game's logic.
+  Update,
+  /// Drawing the frame.
+  Draw,
+  /// The game's own audio work on the main thread, such as updating a `MusicManager`.
+  ///
+  /// Playdate renders audio on another thread, and that time is not included.
+  Audio,
+  /// Any time in the frame which was not given to another stage.
+  Other,
+}
+impl FrameStage {
+  const COUNT: usize = 4;
+
+  fn index(self) -> usize {
+    match self {
+      FrameStage::Update => 0,
+      FrameStage::Draw => 1,
+      FrameStage::Audio => 2,
+      FrameStage::Other => 3,
+    }
+  }
+}
+
+/// The per-stage times of one frame, in microseconds.
+#[derive(Debug, Default, Copy, Clone)]
+struct FrameSample {
+  stages: [u32; FrameStage::COUNT],
+}
+impl FrameSample {
+  fn total(&self) -> u32 {
+    self.stages.iter().sum()
+  }
+}
+
+/// Averages and worst cases of the frames recorded by a `FrameProfiler`, with times in
+/// microseconds.
+///
+/// The `Display` format is a single line, such as for `log()`.
+#[derive(Debug, Copy, Clone)]
+pub struct FrameSummary {
+  /// The number of frames summarized.
+  pub frames: usize,
+  /// The number of those frames which took longer than the budget.
+  pub over_budget_frames: usize,
+  /// The per-frame budget.
+  pub budget_micros: u32,
+  /// The average time of a whole frame.
+  pub average_micros: u32,
+  /// The longest time of a whole frame.
+  pub max_micros: u32,
+  average_stage_micros: [u32; FrameStage::COUNT],
+}
+impl FrameSummary {
+  /// The average time spent in the `stage` each frame.
+  pub fn average_stage_micros(&self, stage: FrameStage) -> u32 {
+    self.average_stage_micros[stage.index()]
+  }
+}
+
+impl core::fmt::Display for FrameSummary {
+  fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+    let ms = |micros: u32| micros as f32 / 1000.0;
+    write!(
+      f,
+      "frame {:.1}ms (max {:.1}ms) update {:.1} draw {:.1} audio {:.1} other {:.1}, {}/{} over",
+      ms(self.average_micros),
+      ms(self.max_micros),
+      ms(self.average_stage_micros(FrameStage::Update)),
+      ms(self.average_stage_micros(FrameStage::Draw)),
+      ms(self.average_stage_micros(FrameStage::Audio)),
+      ms(self.average_stage_micros(FrameStage::Other)),
+      self.over_budget_frames,
+      self.frames,
+    )
+  }
+}
+
+/// Measures how long each stage of a frame takes, to find which part of the game is dropping
+/// frames on the device.
+///
+/// Each frame is timed with the system's `HighResolutionTimer`, which is started at the top of the
+/// frame. After each stage of the frame, `lap()` gives the time since the last lap to that stage,
+/// and `end_frame()` gives the rest of the frame to `FrameStage::Other`. The profiler keeps the
+/// last few frames, which are summarized by `summary()`, and can be logged or drawn on the screen.
+///
+/// # Example
+/// ```
+/// let mut profiler = FrameProfiler::for_refresh_rate(30.0);
+/// loop {
+///   let timer = api.system.start_timer();
+///   update();
+///   profiler.lap(FrameStage::Update, &timer);
+///   draw();
+///   profiler.lap(FrameStage::Draw, &timer);
+///   profiler.end_frame(&timer);
+///   profiler.draw_summary(&mut api.graphics, 0, 0);
+/// }
+/// ```
+#[derive(Debug)]
+pub struct FrameProfiler {
+  budget_micros: u32,
+  window: usize,
+  frames: VecDeque<FrameSample>,
+  current: FrameSample,
+  // The timer's elapsed time at the last lap.
+  last_lap_micros: u32,
+}
+impl FrameProfiler {
+  /// Constructs a `FrameProfiler` where each frame has `budget_micros` microseconds to run.
+  pub fn new(budget_micros: u32) -> Self {
+    FrameProfiler {
+      budget_micros,
+      window: 30,
+      frames: VecDeque::new(),
+      current: FrameSample::default(),
+      last_lap_micros: 0,
+    }
+  }
+  /// Constructs a `FrameProfiler` whose budget is the time between frames at `refresh_rate`
+  /// frames per second, as set with `Display::set_refresh_rate()`.
+  pub fn for_refresh_rate(refresh_rate: f32) -> Self {
+    assert!(refresh_rate > 0.0);
+    Self::new((1_000_000.0 / refresh_rate) as u32)
+  }
+
+  /// Sets the number of frames that are kept to be summarized. The default is 30 frames.
+  pub fn set_window(&mut self, frames: usize) {
+    assert!(frames > 0);
+    self.window = frames;
+    while self.frames.len() > frames {
+      self.frames.pop_front();
+    }
+  }
+
+  /// Gives the time since the last lap, or since the `timer` was started, to the `stage`.
+  ///
+  /// A stage can be given more than one lap in a frame, and the times are added together.
+  pub fn lap(&mut self, stage: FrameStage, timer: &HighResolutionTimer) {
+    let now = timer.elapsed_microseconds();
+    self.current.stages[stage.index()] += now.saturating_sub(self.last_lap_micros);
+    self.last_lap_micros = now;
+  }
+  /// Gives the time since the last lap to `FrameStage::Other`, and records the frame. The `timer`
+  /// should be started again for the next frame.
+  pub fn end_frame(&mut self, timer: &HighResolutionTimer) {
+    self.lap(FrameStage::Other, timer);
+    if self.frames.len() == self.window {
+      self.frames.pop_front();
+    }
+    self.frames.push_back(core::mem::take(&mut self.current));
+    self.last_lap_micros = 0;
+  }
+
+  /// Summarizes the recorded frames, or returns `None` if no frames have been recorded.
+  pub fn summary(&self) -> Option<FrameSummary> {
+    let len = self.frames.len();
+    if len == 0 {
+      return None;
+    }
+    let mut stage_sums = [0u64; FrameStage::COUNT];
+    for frame in &self.frames {
+      for (sum, t) in stage_sums.iter_mut().zip(frame.stages) {
+        *sum += t as u64;
+      }
+    }
+    let average_stage_micros = stage_sums.map(|sum| (sum / len as u64) as u32);
+    Some(FrameSummary {
+      frames: len,
+      over_budget_frames: self.frames.iter().filter(|f| f.total() > self.budget_micros).count(),
+      budget_micros: self.budget_micros,
+      average_micros: average_stage_micros.iter().sum(),
+      max_micros: self.frames.iter().map(|f| f.total()).max().unwrap(),
+      average_stage_micros,
+    })
+  }
+  /// Writes the summary of the recorded frames to the console.
+  pub fn log_summary(&self) {
+    if let Some(summary) = self.summary() {
+      crate::log::log(summary)
+    }
+  }
+  /// Draws the summary of the recorded frames as text at (`x`, `y`), with the current font.
+  pub fn draw_summary(&self, graphics: &mut Graphics, x: i32, y: i32) {
+    if let Some(summary) = self.summary() {
+      let _ = graphics.draw_text(&alloc::format!("{}", summary), x, y);
+    }
+  }
+
+  /// The per-frame budget, in microseconds.
+  pub fn budget_micros(&self) -> u32 {
+    self.budget_micros
+  }
+}
@@ -30,6 +30,7 @@ impl Drop for ActiveFont<'_> {
     // Use a generation tag to avoid unsetting the font if another font was set before this
     // object was dropped.
     if self.generation == CApiState::get().font_generation.get() {
+      CApiState::get().active_font.set(core::ptr::null_mut());
       unsafe { Self::fns().setFont.unwrap()(core::ptr::null_mut()) }
     }
   }
@@ -255,8 +255,10 @@ impl Bitmap {
         Err(err) => Err(format!("load_bitmap: unknown error ({})", err).into()),
       }
     } else {
-      assert!(!bitmap_ptr.is_null());
-      Ok(Bitmap::from_owned_ptr(NonNull::new(bitmap_ptr).unwrap()))
+      match NonNull::new(bitmap_ptr) {
+        Some(bitmap_ptr) => Ok(Bitmap::from_owned_ptr(bitmap_ptr)),
+        None => Err(Error::NotFoundError),
+      }
     }
   }
 
@@ -351,4 +353,76 @@ impl BitmapPixelsMut<'_> {
       self.pixels[byte_index] &= !(1u8 << (7 - bit_index));
     }
   }
+
+  /// Fills the area of same-colored pixels connected to `(x, y)` with `color`, like the paint
+  /// bucket of a painting program.
+  ///
+  /// Pixels are connected to their neighbours above, below, left and right. The fill works one
+  /// horizontal span of pixels at a time, with a stack of spans to visit instead of recursion, so
+  /// large areas do not overflow the stack. Nothing happens if `(x, y)` is outside the bitmap or is
+  /// already `color`.
+  pub fn flood_fill(&mut self, x: usize, y: usize, color: PixelColor) {
+    let width = self.data.width() as usize;
+    let height = self.data.height() as usize;
+    if x >= width || y >= height {
+      return;
+    }
+    let target = self.get(x, y);
+    if target == color {
+      return;
+    }
+
+    // Each entry is a point to fill from, where the whole span containing it will be filled.
+    let mut stack = alloc::vec![(x, y)];
+    while let Some((x, y)) = stack.pop() {
+      if self.get(x, y) != target {
+        continue;
+      }
+      let mut left = x;
+      while left > 0 && self.get(left - 1, y) == target {
+        left -= 1;
+      }
+      let mut right = x;
+      while right + 1 < width && self.get(right + 1, y) == target {
+        right += 1;
+      }
+      for fill_x in left..=right {
+        self.set(fill_x, y, color);
+      }
+
+      // Queue one point for each run of target pixels in the rows above and below the span.
+      let rows = [y.checked_sub(1), Some(y + 1).filter(|next| *next < height)];
+      for next_y in rows.into_iter().flatten() {
+        let mut in_run = false;
+        for scan_x in left..=right {
+          let matches = self.get(scan_x, next_y) == target;
+          if matches && !in_run {
+            stack.push((scan_x, next_y));
+          }
+          in_run = matches;
+        }
+      }
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::fake_capi;
+
+  #[test]
+  fn from_file_returns_the_load_error() {
+    fake_capi::install();
+    match Bitmap::from_file("missing.pdi") {
+      Err(Error::String(s)) => assert_eq!(s, "load_bitmap: no such file"),
+      r => panic!("unexpected result {:?}", r),
+    }
+  }
+
+  #[test]
+  fn from_file_without_a_bitmap_is_not_found() {
+    fake_capi::install();
+    assert!(matches!(Bitmap::from_file("image.pdi"), Err(Error::NotFoundError)));
+  }
 }
@@ -0,0 +1,267 @@
+use alloc::vec::Vec;
+
+use super::bitmap::{Bitmap, BitmapRef};
+use super::color::PixelColor;
+use crate::ctypes::*;
+use crate::system::System;
+
+/// The 4x4 ordered dither matrix used by `BitmapEffect::DitherFade`.
+const BAYER_4X4: [[u8; 4]; 4] = [[0, 8, 2, 10], [12, 4, 14, 6], [3, 11, 1, 9], [15, 7, 13, 5]];
+
+/// How `BitmapEffect::Combine` merges the pixels of two bitmaps, where a white pixel is a set bit.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum CombineOp {
+  /// A pixel is white only where both bitmaps are white. Combining with a mask this way makes the
+  /// image black wherever the mask is black.
+  And,
+  /// A pixel is white where either bitmap is white.
+  Or,
+  /// A pixel is white where exactly one of the bitmaps is white.
+  Xor,
+}
+
+/// A step in a `BitmapEffectChain`.
+#[derive(Debug)]
+pub enum BitmapEffect {
+  /// Swaps black and white pixels.
+  ///
+  /// If flashing should be reduced, as found from `System::should_reduce_flashing()`, only every
+  /// other pixel is swapped in a checkerboard pattern, which softens the flash of inverting the
+  /// whole image.
+  Invert,
+  /// Sets a fraction of the pixels to `color` in an ordered dither pattern, to fade the image
+  /// towards that color. A `level` of 0 changes nothing, and 16 (or more) changes every pixel.
+  DitherFade { level: u8, color: PixelColor },
+  /// Moves the image by `(dx, dy)` pixels, filling the uncovered pixels with `fill`.
+  Offset { dx: i32, dy: i32, fill: PixelColor },
+  /// Merges the `bitmap` into the image with `op`, aligned at the top left corner. Pixels outside
+  /// of `bitmap` are unchanged.
+  Combine { bitmap: Bitmap, op: CombineOp },
+}
+
+/// Identifies an effect that was added to a `BitmapEffectChain`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct EffectId(usize);
+
+/// A pipeline of post-processing effects, which are applied in order to an input bitmap to produce
+/// an output bitmap.
+///
+/// Each effect can be turned on and off, or changed, without rebuilding the chain. The
+/// intermediate bitmaps are kept and reused each time the chain is applied, so applying it every
+/// frame does not allocate unless the size of the input changes.
+///
+/// # Example
+/// ```
+/// let mut effects = BitmapEffectChain::new();
+/// let shake = effects.add(BitmapEffect::Offset { dx: 0, dy: 0, fill: PixelColor::BLACK });
+/// let fade = effects.add(BitmapEffect::DitherFade { level: 0, color: PixelColor::BLACK });
+/// // Each frame:
+/// if let Some(BitmapEffect::DitherFade { level, .. }) = effects.effect_mut(fade) {
+///   *level = fade_level;
+/// }
+/// let output = effects.apply(&scene);
+/// api.graphics.draw_bitmap(output, (0, 0), BitmapFlip::kBitmapUnflipped);
+/// ```
+#[derive(Debug, Default)]
+pub struct BitmapEffectChain {
+  effects: Vec<(BitmapEffect, bool)>,
+  // Intermediate bitmaps, where `pool[0]` holds the current image and `pool[1]` is scratch space
+  // for effects that can not be applied in place.
+  pool: Option<[Bitmap; 2]>,
+}
+impl BitmapEffectChain {
+  /// Constructs an empty `BitmapEffectChain`, whose output is a copy of its input.
+  pub fn new() -> Self {
+    BitmapEffectChain {
+      effects: Vec::new(),
+      pool: None,
+    }
+  }
+
+  /// Adds an effect to the end of the chain. It is enabled.
+  pub fn add(&mut self, effect: BitmapEffect) -> EffectId {
+    self.effects.push((effect, true));
+    EffectId(self.effects.len() - 1)
+  }
+  /// Returns the effect identified by `id`, to change its parameters.
+  ///
+  /// Returns `None` if the `id` is from a different `BitmapEffectChain`.
+  pub fn effect_mut(&mut self, id: EffectId) -> Option<&mut BitmapEffect> {
+    self.effects.get_mut(id.0).map(|(effect, _)| effect)
+  }
+  /// Turns the effect identified by `id` on or off. A disabled effect is skipped when applying
+  /// the chain.
+  pub fn set_enabled(&mut self, id: EffectId, enabled: bool) {
+    if let Some((_, e)) = self.effects.get_mut(id.0) {
+      *e = enabled;
+    }
+  }
+  /// Returns whether the effect identified by `id` is applied as part of the chain.
+  pub fn is_enabled(&self, id: EffectId) -> bool {
+    self.effects.get(id.0).is_some_and(|(_, e)| *e)
+  }
+
+  /// Applies the enabled effects in order to `input`, and returns the result.
+  ///
+  /// The returned bitmap is owned by the chain and is overwritten the next time the chain is
+  /// applied.
+  pub fn apply(&mut self, input: &BitmapRef) -> &Bitmap {
+    let reduce_flashing = System::reduce_flashing();
+    let data = input.data();
+    let (width, height) = (data.width(), data.height());
+    let reuse = match &self.pool {
+      Some([b, _]) => b.data().width() == width && b.data().height() == height,
+      None => false,
+    };
+    if !reuse {
+      let new = || Bitmap::new(width, height, SolidColor::kColorBlack);
+      self.pool = Some([new(), new()]);
+    }
+    let [current, scratch] = self.pool.as_mut().unwrap();
+
+    copy_rows(input, current);
+    for (effect, enabled) in &self.effects {
+      if !*enabled {
+        continue;
+      }
+      match effect {
+        BitmapEffect::Invert if reduce_flashing => invert_checkerboard(current),
+        BitmapEffect::Invert => {
+          for byte in current.as_mut_bytes() {
+            *byte = !*byte;
+          }
+        }
+        BitmapEffect::DitherFade { level, color } => dither_fade(current, *level, *color),
+        BitmapEffect::Offset { dx, dy, fill } => {
+          offset(current, scratch, *dx, *dy, *fill);
+          core::mem::swap(current, scratch);
+        }
+        BitmapEffect::Combine { bitmap, op } => combine(current, bitmap, *op),
+      }
+    }
+    &self.pool.as_ref().unwrap()[0]
+  }
+}
+
+/// Copies the pixels of `from` into `to`, which has the same size, but may have a different number
+/// of bytes per row.
+fn copy_rows(from: &BitmapRef, to: &mut BitmapRef) {
+  let (from_data, to_data) = (from.data(), to.data());
+  let from_stride = from_data.row_bytes() as usize;
+  let to_stride = to_data.row_bytes() as usize;
+  let len = from_stride.min(to_stride);
+  let src = from.as_bytes();
+  let dst = to.as_mut_bytes();
+  for y in 0..from_data.height() as usize {
+    dst[y * to_stride..][..len].copy_from_slice(&src[y * from_stride..][..len]);
+  }
+}
+
+fn fill_byte(fill: PixelColor) -> u8 {
+  if fill.to_bit() { 0xff } else { 0 }
+}
+
+fn invert_checkerboard(bitmap: &mut BitmapRef) {
+  let stride = bitmap.data().row_bytes() as usize;
+  let bytes = bitmap.as_mut_bytes();
+  for (y, row) in bytes.chunks_exact_mut(stride).enumerate() {
+    let mask = if y % 2 == 0 { 0xaa } else { 0x55 };
+    for byte in row {
+      *byte ^= mask;
+    }
+  }
+}
+
+fn dither_fade(bitmap: &mut BitmapRef, level: u8, color: PixelColor) {
+  let stride = bitmap.data().row_bytes() as usize;
+  let bytes = bitmap.as_mut_bytes();
+  for (y, row) in bytes.chunks_exact_mut(stride).enumerate() {
+    // The dither pattern repeats every 4 pixels, so each byte of a row gets the same mask.
+    let thresholds = &BAYER_4X4[y % 4];
+    let mut mask = 0u8;
+    for bit in 0..8 {
+      if thresholds[bit % 4] < level {
+        mask |= 0x80 >> bit;
+      }
+    }
+    for byte in row {
+      match color.to_bit() {
+        true => *byte |= mask,
+        false => *byte &= !mask,
+      }
+    }
+  }
+}
+
+fn offset(from: &BitmapRef, to: &mut BitmapRef, dx: i32, dy: i32, fill: PixelColor) {
+  let data = from.data();
+  let (width, height) = (data.width(), data.height());
+  let stride = data.row_bytes() as usize;
+  let fill_byte = fill_byte(fill);
+  let src = from.as_bytes();
+  let dst = to.as_mut_bytes();
+
+  // Moving right by `dx` pixels is a shift of `byte_shift` whole bytes and `bit_shift` bits.
+  let byte_shift = dx.div_euclid(8) as isize;
+  let bit_shift = dx.rem_euclid(8) as u32;
+  for y in 0..height {
+    let dst_row = &mut dst[y as usize * stride..][..stride];
+    let src_y = y - dy;
+    if src_y < 0 || src_y >= height {
+      dst_row.fill(fill_byte);
+      continue;
+    }
+    let src_row = &src[src_y as usize * stride..][..stride];
+    let src_byte = |i: isize| match usize::try_from(i) {
+      Ok(i) if i < stride => src_row[i],
+      _ => fill_byte,
+    };
+    for (i, byte) in dst_row.iter_mut().enumerate() {
+      let i = i as isize - byte_shift;
+      *byte = match bit_shift {
+        0 => src_byte(i),
+        s => (src_byte(i) >> s) | (src_byte(i - 1) << (8 - s)),
+      };
+    }
+  }
+
+  // Moving left brings in the padding bits past the end of each row, which need to be filled.
+  if dx < 0 {
+    let mut pixels = to.as_pixels_mut();
+    for y in 0..height as usize {
+      for x in (width + dx).max(0)..width {
+        pixels.set(x as usize, y, fill);
+      }
+    }
+  }
+}
+
+fn combine(bitmap: &mut BitmapRef, other: &BitmapRef, op: CombineOp) {
+  let (data, other_data) = (bitmap.data(), other.data());
+  let stride = data.row_bytes() as usize;
+  let other_stride = other_data.row_bytes() as usize;
+  let height = data.height().min(other_data.height()) as usize;
+  let width = data.width().min(other_data.width()) as usize;
+  let whole_bytes = width / 8;
+  let src = other.as_bytes();
+  let dst = bitmap.as_mut_bytes();
+  let apply = |a: u8, b: u8| match op {
+    CombineOp::And => a & b,
+    CombineOp::Or => a | b,
+    CombineOp::Xor => a ^ b,
+  };
+  for y in 0..height {
+    let dst_row = &mut dst[y * stride..][..stride];
+    let src_row = &src[y * other_stride..][..other_stride];
+    for i in 0..whole_bytes {
+      dst_row[i] = apply(dst_row[i], src_row[i]);
+    }
+    // Only change pixels of the last partial byte which are inside `other`.
+    let extra = width % 8;
+    if extra > 0 {
+      let mask = !(0xffu8 >> extra);
+      let i = whole_bytes;
+      dst_row[i] = (apply(dst_row[i], src_row[i]) & mask) | (dst_row[i] & !mask);
+    }
+  }
+}
@@ -0,0 +1,88 @@
+use alloc::format;
+use core::ptr::NonNull;
+
+use super::unowned_bitmap::UnownedBitmapRef;
+use crate::Error;
+use crate::capi_state::CApiState;
+use crate::ctypes::*;
+use crate::null_terminated::ToNullTerminatedString;
+
+/// A table of bitmaps of the same size, such as the frames of an animation or the tiles of a
+/// tileset, loaded from an image table file.
+///
+/// Image tables are made by `pdc` from either a folder of images named `name-table-1.png`,
+/// `name-table-2.png`, etc., or from a single image that is a grid of cells, named
+/// `name-table-<width>-<height>.png`.
+#[derive(Debug)]
+pub struct BitmapTable {
+  ptr: NonNull<CBitmapTable>,
+  len: usize,
+}
+impl BitmapTable {
+  /// Loads a `BitmapTable` from the image table at `path`, without its `-table-...` suffix.
+  pub fn from_file(path: &str) -> Result<BitmapTable, Error> {
+    let mut out_err: *const u8 = core::ptr::null_mut();
+
+    // UNCLEAR: As with loadBitmap(), we assume the out_err string does not need to be freed.
+    let table_ptr = unsafe {
+      Self::fns().loadBitmapTable.unwrap()(path.to_null_terminated_utf8().as_ptr(), &mut out_err)
+    };
+
+    if !out_err.is_null() {
+      let result = unsafe { crate::null_terminated::parse_null_terminated_utf8(out_err) };
+      match result {
+        // A valid error string.
+        Ok(err) => Err(format!("load_bitmap_table: {}", err).into()),
+        // An invalid error string.
+        Err(err) => Err(format!("load_bitmap_table: unknown error ({})", err).into()),
+      }
+    } else {
+      match NonNull::new(table_ptr) {
+        Some(ptr) => Ok(BitmapTable {
+          ptr,
+          len: Self::count_bitmaps(ptr),
+        }),
+        None => Err(Error::NotFoundError),
+      }
+    }
+  }
+
+  /// The number of bitmaps in the table.
+  pub fn len(&self) -> usize {
+    self.len
+  }
+  /// Whether the table has no bitmaps.
+  pub fn is_empty(&self) -> bool {
+    self.len == 0
+  }
+
+  /// Returns the bitmap at `index` in the table, or `None` if `index` is past the end of the table.
+  pub fn bitmap(&self, index: usize) -> Option<UnownedBitmapRef<'_>> {
+    if index >= self.len {
+      return None;
+    }
+    // getTableBitmap() takes a mutable pointer but doesn't change any visible state.
+    let ptr = unsafe { Self::fns().getTableBitmap.unwrap()(self.ptr.as_ptr(), index as i32) };
+    NonNull::new(ptr).map(UnownedBitmapRef::from_ptr)
+  }
+
+  // BUG: The C Api has no function to get the number of bitmaps in a table, but getTableBitmap()
+  // returns null for an index past the end, so we find the end that way.
+  fn count_bitmaps(ptr: NonNull<CBitmapTable>) -> usize {
+    let mut len = 0;
+    while !unsafe { Self::fns().getTableBitmap.unwrap()(ptr.as_ptr(), len as i32) }.is_null() {
+      len += 1;
+    }
+    len
+  }
+
+  pub(crate) fn fns() -> &'static craydate_sys::playdate_graphics {
+    CApiState::get().cgraphics
+  }
+}
+
+impl Drop for BitmapTable {
+  fn drop(&mut self) {
+    unsafe { Self::fns().freeBitmapTable.unwrap()(self.ptr.as_ptr()) }
+  }
+}
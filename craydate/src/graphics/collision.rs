@@ -0,0 +1,225 @@
+use alloc::vec::Vec;
+
+use super::bitmap::BitmapRef;
+use super::bitmap_collider::BitmapCollider;
+use super::graphics::Graphics;
+use crate::ctypes::*;
+
+/// An object that can collide with others, such as a sprite.
+#[derive(Debug, Clone, Copy)]
+pub struct CollisionBody<'a> {
+  /// The bounds of the object.
+  pub rect: euclid::default::Rect<i32>,
+  /// An optional bitmap drawn at the `rect`'s origin, whose opaque pixels make up the shape of the
+  /// object. When two bodies both have a mask, they only collide if their opaque pixels overlap.
+  /// Otherwise their `rect`s are used.
+  pub mask: Option<(&'a BitmapRef, BitmapFlip)>,
+}
+impl<'a> CollisionBody<'a> {
+  /// Constructs a `CollisionBody` which is shaped like its bounding `rect`.
+  pub fn from_rect(rect: euclid::default::Rect<i32>) -> Self {
+    CollisionBody { rect, mask: None }
+  }
+  /// Constructs a `CollisionBody` shaped by the opaque pixels of `bitmap` drawn at `x`, `y`.
+  pub fn from_bitmap(bitmap: &'a BitmapRef, flip: BitmapFlip, x: i32, y: i32) -> Self {
+    let data = bitmap.data();
+    CollisionBody {
+      rect: euclid::default::Rect::new(
+        euclid::default::Point2D::new(x, y),
+        euclid::default::Size2D::new(data.width(), data.height()),
+      ),
+      mask: Some((bitmap, flip)),
+    }
+  }
+
+  fn moved_to(&self, origin: euclid::default::Point2D<i32>) -> Self {
+    CollisionBody {
+      rect: euclid::default::Rect::new(origin, self.rect.size),
+      mask: self.mask,
+    }
+  }
+}
+
+/// Information about how two `CollisionBody` objects overlap.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Contact {
+  /// The intersection of the bounds of the two bodies.
+  pub overlap: euclid::default::Rect<i32>,
+  /// The shortest movement of the first body that would separate the bounds of the two bodies.
+  pub push_out: euclid::default::Vector2D<i32>,
+}
+
+/// How a moving body responds to colliding with another, in `Graphics::move_with_collisions()`.
+///
+/// These match the collision responses of the Playdate sprite API.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CollisionResponse {
+  /// The body stops moving in the direction of the collision, but continues along the other axis.
+  Slide,
+  /// The body stops moving entirely at the point of collision.
+  Freeze,
+  /// The body reverses its movement in the direction of the collision for the rest of the move.
+  Bounce,
+  /// The collision is reported, but the body moves through the other without stopping.
+  Overlap,
+}
+
+/// A collision that happened during `Graphics::move_with_collisions()`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Collision {
+  /// The index of the other body in the slice given to `move_with_collisions()`.
+  pub other: usize,
+  /// The response that was applied to the collision.
+  pub response: CollisionResponse,
+  /// The position of the moving body when it touched the other body. For `Overlap` collisions,
+  /// this is the first position where the bodies overlapped.
+  pub touch: euclid::default::Point2D<i32>,
+  /// The direction the moving body was pushed back in, such as `(-1, 0)` when it hit the left side
+  /// of the other body. This is zero for `Overlap` collisions.
+  pub normal: euclid::default::Vector2D<i32>,
+  /// How the bodies overlap, with the moving body at its `touch` position, or one pixel further
+  /// along its movement for collisions that block the movement.
+  pub contact: Contact,
+}
+
+/// The result of `Graphics::move_with_collisions()`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MoveResult {
+  /// Where the moving body ended up.
+  pub position: euclid::default::Point2D<i32>,
+  /// The collisions that happened along the way, in the order they happened. Each other body is
+  /// reported at most once.
+  pub collisions: Vec<Collision>,
+}
+
+pub(crate) fn contact(
+  graphics: &Graphics,
+  a: &CollisionBody,
+  b: &CollisionBody,
+) -> Option<Contact> {
+  let overlap = a.rect.intersection(&b.rect)?;
+  if let (Some((a_bitmap, a_flip)), Some((b_bitmap, b_flip))) = (a.mask, b.mask) {
+    let collide = graphics.bitmaps_collide(
+      BitmapCollider {
+        bitmap: a_bitmap,
+        flipped: a_flip,
+        x: a.rect.origin.x,
+        y: a.rect.origin.y,
+      },
+      BitmapCollider {
+        bitmap: b_bitmap,
+        flipped: b_flip,
+        x: b.rect.origin.x,
+        y: b.rect.origin.y,
+      },
+      overlap,
+    );
+    if !collide {
+      return None;
+    }
+  }
+
+  let left = b.rect.min_x() - a.rect.max_x();
+  let right = b.rect.max_x() - a.rect.min_x();
+  let up = b.rect.min_y() - a.rect.max_y();
+  let down = b.rect.max_y() - a.rect.min_y();
+  let x = if -left < right { left } else { right };
+  let y = if -up < down { up } else { down };
+  let push_out = if x.abs() <= y.abs() {
+    euclid::default::Vector2D::new(x, 0)
+  } else {
+    euclid::default::Vector2D::new(0, y)
+  };
+  Some(Contact { overlap, push_out })
+}
+
+pub(crate) fn move_with_collisions<F: FnMut(usize) -> CollisionResponse>(
+  graphics: &Graphics,
+  body: &CollisionBody,
+  goal: euclid::default::Point2D<i32>,
+  others: &[CollisionBody],
+  mut response: F,
+) -> MoveResult {
+  let mut position = body.rect.origin;
+  let mut velocity = goal - position;
+  let mut collisions: Vec<Collision> = Vec::new();
+
+  // Step one pixel at a time along the line to the goal, so that fast moving bodies can not pass
+  // through others.
+  let steps = velocity.x.abs().max(velocity.y.abs());
+  let mut accumulated = euclid::default::Vector2D::new(0, 0);
+  'steps: for _ in 0..steps {
+    for axis in 0..2 {
+      let speed = if axis == 0 {
+        &mut velocity.x
+      } else {
+        &mut velocity.y
+      };
+      let acc = if axis == 0 {
+        &mut accumulated.x
+      } else {
+        &mut accumulated.y
+      };
+      *acc += speed.abs();
+      if *acc < steps {
+        continue;
+      }
+      *acc -= steps;
+
+      let dir = speed.signum();
+      let unit = if axis == 0 {
+        euclid::default::Vector2D::new(dir, 0)
+      } else {
+        euclid::default::Vector2D::new(0, dir)
+      };
+      let here = body.moved_to(position);
+      let there = body.moved_to(position + unit);
+
+      let mut blocked = None;
+      for (i, other) in others.iter().enumerate() {
+        // Bodies that are already overlapping do not block each other from moving apart.
+        if contact(graphics, &here, other).is_some() {
+          continue;
+        }
+        let found = match contact(graphics, &there, other) {
+          Some(found) => found,
+          None => continue,
+        };
+        let r = match collisions.iter().find(|c| c.other == i) {
+          Some(earlier) => earlier.response,
+          None => {
+            let r = response(i);
+            let (touch, normal) = match r {
+              CollisionResponse::Overlap => (position + unit, euclid::default::Vector2D::zero()),
+              _ => (position, -unit),
+            };
+            collisions.push(Collision {
+              other: i,
+              response: r,
+              touch,
+              normal,
+              contact: found,
+            });
+            r
+          }
+        };
+        if r != CollisionResponse::Overlap && blocked != Some(CollisionResponse::Freeze) {
+          blocked = Some(r);
+        }
+      }
+
+      match blocked {
+        None => position += unit,
+        Some(CollisionResponse::Freeze) => break 'steps,
+        Some(CollisionResponse::Slide) => *speed = 0,
+        Some(CollisionResponse::Bounce) => *speed = -*speed,
+        Some(CollisionResponse::Overlap) => unreachable!(),
+      }
+    }
+  }
+
+  MoveResult {
+    position,
+    collisions,
+  }
+}
@@ -7,7 +7,7 @@ use crate::ctypes::*;
 const PATTERN_SIZE: usize = 8 + 8;
 
 /// Represents a method used for operations that draw to the display or a bitmap.
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy)]
 pub enum Color<'a> {
   /// A single color, which is one of `SolidColor`.
   Solid(SolidColor),
@@ -21,9 +21,9 @@ impl Color<'_> {
   /// # Safety
   ///
   /// The returned usize for patterns is technically a raw pointer to the Pattern array itself. Thus
-  /// the caller must ensure that the Color outlives the returned usize. Also, yes really, Color can be
-  /// both an enum and a pointer.
-  pub(crate) unsafe fn to_c_color(&self) -> usize {
+  /// the caller must ensure that the Pattern outlives the returned usize. Also, yes really, Color
+  /// can be both an enum and a pointer.
+  pub(crate) unsafe fn to_c_color(self) -> usize {
     match self {
       Color::Solid(solid) => solid.0 as usize,
       Color::Pattern(pattern) => pattern.0.as_ptr() as usize,
@@ -0,0 +1,143 @@
+use alloc::vec::Vec;
+
+use super::bitmap::Bitmap;
+use super::color::PixelColor;
+use crate::Error;
+use crate::ctypes::*;
+
+/// An image parsed from the contents of a `.pdi` file, which `pdc` compiles from a `.png` file.
+///
+/// This allows images to be loaded from bytes in memory, such as bytes that were downloaded or
+/// embedded in the game, where `Bitmap::from_file()` can only load images from a file.
+///
+/// Compressed images, which `pdc` makes when it's given the `-c` flag, can only be parsed when the
+/// `compression` feature is enabled.
+///
+/// UNCLEAR: Playdate does not document the `.pdi` format, so this follows the format as it has been
+/// observed in files made by `pdc`.
+///
+/// # Example
+/// ```
+/// let image = CompiledImage::from_bytes(include_bytes!("../assets/player.pdi"))?;
+/// let bitmap = image.to_bitmap();
+/// ```
+#[derive(Debug, Clone)]
+pub struct CompiledImage {
+  image: craydate_formats::compiled_image::CompiledImage,
+}
+impl CompiledImage {
+  /// Parses the contents of a `.pdi` file.
+  pub fn from_bytes(bytes: &[u8]) -> Result<CompiledImage, Error> {
+    let image = craydate_formats::compiled_image::CompiledImage::from_bytes(bytes)?;
+    Ok(CompiledImage { image })
+  }
+
+  /// The width of the image in pixels.
+  pub fn width(&self) -> usize {
+    self.image.width()
+  }
+  /// The height of the image in pixels.
+  pub fn height(&self) -> usize {
+    self.image.height()
+  }
+  /// Whether the image has a mask, which makes some of its pixels transparent.
+  pub fn has_mask(&self) -> bool {
+    self.image.has_mask()
+  }
+
+  /// Returns the color of the pixel at `(x, y)`.
+  ///
+  /// Transparent pixels are reported as white.
+  pub fn pixel(&self, x: usize, y: usize) -> PixelColor {
+    self.image.is_white(x, y).into()
+  }
+  /// Returns whether the pixel at `(x, y)` is opaque, which is always true if the image has no
+  /// mask.
+  pub fn is_opaque(&self, x: usize, y: usize) -> bool {
+    self.image.is_opaque(x, y)
+  }
+
+  /// Makes a `Bitmap` with the image's pixels, and its mask if it has one.
+  pub fn to_bitmap(&self) -> Bitmap {
+    let mut bitmap = Bitmap::new(
+      self.width() as i32,
+      self.height() as i32,
+      SolidColor::kColorWhite,
+    );
+    let mut pixels = bitmap.as_pixels_mut();
+    for y in 0..self.height() {
+      for x in 0..self.width() {
+        pixels.set(x, y, self.pixel(x, y));
+      }
+    }
+    if self.has_mask() {
+      let mut mask = Bitmap::new(
+        self.width() as i32,
+        self.height() as i32,
+        SolidColor::kColorBlack,
+      );
+      let mut mask_pixels = mask.as_pixels_mut();
+      for y in 0..self.height() {
+        for x in 0..self.width() {
+          mask_pixels.set(x, y, self.is_opaque(x, y).into());
+        }
+      }
+      // The mask is made with the same size as the bitmap, so this can't fail.
+      bitmap.set_mask_bitmap(&mask).unwrap();
+    }
+    bitmap
+  }
+}
+
+/// An image table parsed from the contents of a `.pdt` file, which `pdc` compiles from a folder of
+/// images or a single image that is a grid of cells.
+///
+/// This allows image tables to be loaded from bytes in memory, where `BitmapTable::from_file()` can
+/// only load them from a file. As with `CompiledImage`, compressed image tables can only be parsed
+/// when the `compression` feature is enabled.
+///
+/// UNCLEAR: Playdate does not document the `.pdt` format, so this follows the format as it has been
+/// observed in files made by `pdc`.
+#[derive(Debug, Clone)]
+pub struct CompiledImageTable {
+  images: Vec<CompiledImage>,
+  cells_per_row: usize,
+}
+impl CompiledImageTable {
+  /// Parses the contents of a `.pdt` file.
+  pub fn from_bytes(bytes: &[u8]) -> Result<CompiledImageTable, Error> {
+    let table = craydate_formats::compiled_image::CompiledImageTable::from_bytes(bytes)?;
+    let cells_per_row = table.cells_per_row();
+    let images = table.into_images().into_iter().map(|image| CompiledImage { image }).collect();
+    Ok(CompiledImageTable {
+      images,
+      cells_per_row,
+    })
+  }
+
+  /// The number of images in the table.
+  pub fn len(&self) -> usize {
+    self.images.len()
+  }
+  /// Whether the table has no images.
+  pub fn is_empty(&self) -> bool {
+    self.images.is_empty()
+  }
+  /// The number of images in each row, when the table was made from a grid of cells.
+  pub fn cells_per_row(&self) -> usize {
+    self.cells_per_row
+  }
+  /// Returns the image at `index`, or `None` if the index is past the end of the table.
+  pub fn image(&self, index: usize) -> Option<&CompiledImage> {
+    self.images.get(index)
+  }
+  /// Returns an iterator over the images in the table, in order.
+  pub fn images(&self) -> impl Iterator<Item = &CompiledImage> {
+    self.images.iter()
+  }
+
+  /// Makes a `Bitmap` for each image in the table, in order.
+  pub fn to_bitmaps(&self) -> Vec<Bitmap> {
+    self.images.iter().map(CompiledImage::to_bitmap).collect()
+  }
+}
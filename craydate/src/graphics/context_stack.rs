@@ -83,6 +83,10 @@ impl ContextStack {
       }
     })
   }
+  /// Whether drawing is currently targeting the display framebuffer, rather than a bitmap.
+  pub fn is_drawing_to_framebuffer(&self) -> bool {
+    matches!(self.stack.last(), None | Some(None))
+  }
   pub fn take_bitmap(&mut self, id: ContextStackId) -> Option<Bitmap> {
     let r = self.holding.remove(&id.id).and_then(|held| held.bitmap);
     // We can forget the ContextStackId as no id can refer to the bitmap once it's removed, and we
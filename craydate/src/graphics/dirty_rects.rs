@@ -0,0 +1,133 @@
+use alloc::vec::Vec;
+
+use crate::capi_state::CApiState;
+use crate::ctypes::*;
+
+/// Records the screen areas touched by drawing calls during a frame, and keeps the areas from the
+/// previous frame, so that the game can redraw only the parts of the screen that changed.
+///
+/// Tracking is opt-in through `Graphics::set_dirty_rect_tracking()`. While disabled, nothing is
+/// recorded.
+#[derive(Debug)]
+pub(crate) struct DirtyRects {
+  enabled: bool,
+  /// The offset set by `Graphics::set_draw_offset()`, which moves every drawing call.
+  draw_offset: euclid::default::Vector2D<i32>,
+  /// Rects in screen coordinates touched during the current frame.
+  rects: Vec<euclid::default::Rect<i32>>,
+  /// Rects in screen coordinates touched during the previous frame, with overlapping rects merged.
+  previous: Vec<euclid::default::Rect<i32>>,
+}
+impl DirtyRects {
+  pub fn new() -> Self {
+    DirtyRects {
+      enabled: false,
+      draw_offset: euclid::default::Vector2D::zero(),
+      rects: Vec::new(),
+      previous: Vec::new(),
+    }
+  }
+
+  pub fn set_enabled(&mut self, enabled: bool) {
+    self.enabled = enabled;
+    self.rects.clear();
+    self.previous.clear();
+  }
+
+  /// Whether drawing calls are being recorded.
+  pub fn is_enabled(&self) -> bool {
+    self.enabled
+  }
+
+  pub fn set_draw_offset(&mut self, dx: i32, dy: i32) {
+    self.draw_offset = euclid::default::Vector2D::new(dx, dy);
+  }
+
+  /// Records `rect`, given in drawing coordinates, as touched this frame.
+  ///
+  /// Drawing into a bitmap does not change the screen, so nothing is recorded unless the top of the
+  /// context stack is the framebuffer.
+  pub fn add_rect(&mut self, rect: euclid::default::Rect<i32>) {
+    if !self.enabled || rect.is_empty() {
+      return;
+    }
+    if !CApiState::get().stack.borrow().is_drawing_to_framebuffer() {
+      return;
+    }
+    let screen = euclid::default::Rect::new(
+      euclid::default::Point2D::zero(),
+      euclid::default::Size2D::new(LCD_COLUMNS as i32, LCD_ROWS as i32),
+    );
+    if let Some(r) = rect.translate(self.draw_offset).intersection(&screen) {
+      self.rects.push(r)
+    }
+  }
+
+  /// Records the whole screen as touched this frame.
+  pub fn add_screen(&mut self) {
+    if self.enabled {
+      self.rects.push(euclid::default::Rect::new(
+        euclid::default::Point2D::zero(),
+        euclid::default::Size2D::new(LCD_COLUMNS as i32, LCD_ROWS as i32),
+      ));
+    }
+  }
+
+  /// Returns the rects touched so far this frame, in screen coordinates.
+  pub fn rects(&self) -> &[euclid::default::Rect<i32>] {
+    &self.rects
+  }
+
+  /// Returns the rects touched during the previous frame, in screen coordinates.
+  pub fn previous_rects(&self) -> &[euclid::default::Rect<i32>] {
+    &self.previous
+  }
+
+  /// Marks the rows touched during the frame as updated, keeps the rects touched during the frame as
+  /// the previous frame's, merging any that overlap or touch, and starts tracking a new frame.
+  pub fn finish_frame(&mut self) {
+    if !self.enabled {
+      return;
+    }
+    mark_rows(&self.rects);
+
+    self.previous.clear();
+    for mut rect in self.rects.drain(..) {
+      // Merging two rects can make the result overlap another rect that was already kept, so keep
+      // merging until none overlap.
+      while let Some(i) = self.previous.iter().position(|other| touches(&rect, other)) {
+        rect = rect.union(&self.previous.swap_remove(i));
+      }
+      self.previous.push(rect);
+    }
+  }
+}
+
+/// Marks the rows covered by `rects` as updated with one `markUpdatedRows()` call per run of
+/// overlapping or adjacent rows.
+fn mark_rows(rects: &[euclid::default::Rect<i32>]) {
+  let mut rows: Vec<(i32, i32)> = rects.iter().map(|r| (r.min_y(), r.max_y() - 1)).collect();
+  rows.sort_unstable();
+  let mut merged: Option<(i32, i32)> = None;
+  for (start, end) in rows {
+    merged = match merged {
+      Some((mstart, mend)) if start <= mend + 1 => Some((mstart, mend.max(end))),
+      Some((mstart, mend)) => {
+        unsafe { CApiState::get().cgraphics.markUpdatedRows.unwrap()(mstart, mend) };
+        Some((start, end))
+      }
+      None => Some((start, end)),
+    }
+  }
+  if let Some((mstart, mend)) = merged {
+    unsafe { CApiState::get().cgraphics.markUpdatedRows.unwrap()(mstart, mend) };
+  }
+}
+
+/// Whether `a` and `b` overlap or share an edge.
+fn touches(a: &euclid::default::Rect<i32>, b: &euclid::default::Rect<i32>) -> bool {
+  a.min_x() <= b.max_x()
+    && b.min_x() <= a.max_x()
+    && a.min_y() <= b.max_y()
+    && b.min_y() <= a.max_y()
+}
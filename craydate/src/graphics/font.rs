@@ -13,10 +13,8 @@ pub struct Font {
   font_ptr: NonNull<CFont>,
 }
 impl Font {
-  pub(crate) fn from_ptr(font_ptr: *mut CFont) -> Self {
-    Font {
-      font_ptr: unsafe { NonNull::new_unchecked(font_ptr) },
-    }
+  pub(crate) fn from_ptr(font_ptr: NonNull<CFont>) -> Self {
+    Font { font_ptr }
   }
 
   /// Returns the Font object for the font file at `path`.
@@ -40,8 +38,10 @@ impl Font {
         Err(err) => Err(format!("load_font: unknown error ({})", err).into()),
       }
     } else {
-      assert!(!font_ptr.is_null());
-      Ok(Font::from_ptr(font_ptr))
+      match NonNull::new(font_ptr) {
+        Some(font_ptr) => Ok(Font::from_ptr(font_ptr)),
+        None => Err(Error::NotFoundError),
+      }
     }
   }
 
@@ -74,13 +74,28 @@ impl Font {
   /// Each FontPage contains information for 256 characters. All chars with the same high 24 bits
   /// share a page; specifically, if `(c1 & ~0xff) == (c2 & ~0xff)`, then c1 and c2 belong to the
   /// same page. The FontPage can be used to query information about all characters in the page.
+  ///
+  /// # Panics
+  ///
+  /// Panics if the font has no characters in the page of `c`. Use `try_font_page()` to get `None`
+  /// instead.
   pub fn font_page(&self, c: char) -> FontPage {
+    match self.try_font_page(c) {
+      Some(page) => page,
+      None => panic!("font_page: the font has no page for {:?}", c),
+    }
+  }
+  /// Returns the FontPage for the character `c`, or `None` if the font has no characters in the
+  /// page of `c`.
+  ///
+  /// See `font_page()`.
+  pub fn try_font_page(&self, c: char) -> Option<FontPage> {
     // getFontPage() takes a mutable pointer but does not write to the data.
     let page_ptr = unsafe { Self::fns().getFontPage.unwrap()(self.cptr() as *mut _, c as u32) };
-    FontPage {
-      page_ptr: unsafe { NonNull::new_unchecked(page_ptr) },
+    Some(FontPage {
+      page_ptr: NonNull::new(page_ptr)?,
       page_test: c as u32 & 0xffffff00,
-    }
+    })
   }
 
   pub(crate) fn cptr(&self) -> *const CFont {
@@ -113,9 +128,10 @@ impl FontPage {
 
   /// Returns the glyph for the character `c`.
   ///
-  /// May return None if the character is not part of this FontPage. Each FontPage contains
-  /// information for 256 characters. All chars with the same high 24 bits share a page;
-  /// specifically, if `(c1 & ~0xff) == (c2 & ~0xff)`, then c1 and c2 belong to the same page.
+  /// Returns None if the character is not part of this FontPage, or if the font has no glyph for
+  /// the character. Each FontPage contains information for 256 characters. All chars with the same
+  /// high 24 bits share a page; specifically, if `(c1 & ~0xff) == (c2 & ~0xff)`, then c1 and c2
+  /// belong to the same page.
   pub fn glyph(&self, c: char) -> Option<FontGlyph> {
     if !self.contains(c) {
       None
@@ -134,10 +150,10 @@ impl FontPage {
         )
       };
       Some(FontGlyph {
-        glyph_ptr: NonNull::new(glyph_ptr).unwrap(),
+        glyph_ptr: NonNull::new(glyph_ptr)?,
         advance,
         glyph_char: c,
-        bitmap: UnownedBitmapRef::<'static>::from_ptr(NonNull::new(bitmap_ptr).unwrap()),
+        bitmap: UnownedBitmapRef::<'static>::from_ptr(NonNull::new(bitmap_ptr)?),
       })
     }
   }
@@ -191,3 +207,51 @@ impl FontGlyph {
     CApiState::get().cgraphics
   }
 }
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::fake_capi;
+
+  #[test]
+  fn from_file_returns_the_load_error() {
+    fake_capi::install();
+    match Font::from_file("missing.pft") {
+      Err(Error::String(s)) => assert_eq!(s, "load_font: no such file"),
+      r => panic!("unexpected result {:?}", r),
+    }
+  }
+
+  #[test]
+  fn from_file_without_a_font_is_not_found() {
+    fake_capi::install();
+    assert!(matches!(Font::from_file("font.pft"), Err(Error::NotFoundError)));
+  }
+
+  #[test]
+  fn try_font_page_without_a_page_is_none() {
+    fake_capi::install();
+    let font = Font::from_ptr(NonNull::dangling());
+    assert!(font.try_font_page('a').is_none());
+  }
+
+  #[test]
+  #[should_panic]
+  fn font_page_without_a_page_panics() {
+    fake_capi::install();
+    let font = Font::from_ptr(NonNull::dangling());
+    font.font_page('a');
+  }
+
+  #[test]
+  fn glyph_without_a_glyph_is_none() {
+    fake_capi::install();
+    let page = FontPage {
+      page_ptr: NonNull::dangling(),
+      page_test: 0,
+    };
+    assert!(page.glyph('a').is_none());
+    // Not part of the page.
+    assert!(page.glyph('\u{100}').is_none());
+  }
+}
@@ -1,17 +1,23 @@
+use alloc::vec::Vec;
 use core::ffi::c_void;
 use core::ptr::NonNull;
 
 use super::active_font::ActiveFont;
 use super::bitmap::{Bitmap, BitmapRef};
 use super::bitmap_collider::BitmapCollider;
+use super::collision::{self, CollisionBody, CollisionResponse, Contact, MoveResult};
 use super::color::Color;
 use super::context_stack::ContextStackId;
 use super::font::Font;
 use super::framebuffer_stencil_bitmap::FramebufferStencilBitmap;
+use super::shifted_bitmap::ShiftedBitmap;
+use super::stroke_font::{self, StrokeTextStyle};
+use super::text_draw_options::TextDrawOptions;
 #[cfg(not(all(target_arch = "arm", target_os = "none")))]
 use super::unowned_bitmap::UnownedBitmapMut;
 use crate::capi_state::CApiState;
 use crate::ctypes::*;
+use crate::error::Error;
 use crate::null_terminated::ToNullTerminatedString;
 use crate::system::System;
 
@@ -51,8 +57,33 @@ impl Graphics {
     }
   }
 
+  /// Tests if two bodies collide, and returns how they overlap if they do.
+  ///
+  /// If both bodies have a mask bitmap, they collide only where their opaque pixels overlap, but
+  /// the returned `Contact` is still computed from their bounds.
+  pub fn collision(&self, a: &CollisionBody, b: &CollisionBody) -> Option<Contact> {
+    collision::contact(self, a, b)
+  }
+
+  /// Moves `body` toward the `goal` position, stopping or changing direction when it collides with
+  /// any of the `others`, like `moveWithCollisions()` in the Playdate Lua sprite API.
+  ///
+  /// The `response` function is called with the index of each body in `others` that is hit, the
+  /// first time it is hit, and chooses how the moving body responds to the collision. Bodies that
+  /// already overlap `body` at its starting position are ignored until they stop overlapping.
+  pub fn move_with_collisions<F: FnMut(usize) -> CollisionResponse>(
+    &self,
+    body: &CollisionBody,
+    goal: euclid::default::Point2D<i32>,
+    others: &[CollisionBody],
+    response: F,
+  ) -> MoveResult {
+    collision::move_with_collisions(self, body, goal, others, response)
+  }
+
   /// Clears the entire display, filling it with `color`.
   pub fn clear<'a, C: Into<Color<'a>>>(&mut self, color: C) {
+    CApiState::get().dirty_rects.borrow_mut().add_screen();
     unsafe {
       Self::fns().clear.unwrap()(color.into().to_c_color());
     }
@@ -110,8 +141,41 @@ impl Graphics {
     unsafe { Self::fns().markUpdatedRows.unwrap()(start, end) }
   }
 
+  /// Enables or disables tracking of the screen areas that are drawn to each frame.
+  ///
+  /// While enabled, the bounding box of each drawing call made to the display framebuffer is
+  /// recorded. The areas drawn so far in the current frame are given by `dirty_rects()`, and the
+  /// areas drawn in the previous frame by `previous_dirty_rects()`. A mostly static scene can use
+  /// them to redraw only what changed, such as drawing the background over just the areas where
+  /// moving objects were drawn last frame. Tracking is disabled by default.
+  ///
+  /// At the end of each frame, the rows covered by the areas drawn in that frame are marked as
+  /// updated with `mark_updated_rows()`. This can save time and battery for scenes that are mostly
+  /// static.
+  pub fn set_dirty_rect_tracking(&mut self, enabled: bool) {
+    CApiState::get().dirty_rects.borrow_mut().set_enabled(enabled)
+  }
+  /// Records `rect` as drawn to this frame, when dirty-rect tracking is enabled.
+  ///
+  /// Drawing calls record themselves, but this is useful after writing to pixels of the
+  /// framebuffer directly. The `rect` is in drawing coordinates, so it is moved by the draw offset.
+  pub fn mark_dirty_rect(&mut self, rect: euclid::default::Rect<i32>) {
+    CApiState::get().dirty_rects.borrow_mut().add_rect(rect)
+  }
+  /// Returns the screen areas that have been drawn to so far this frame, when dirty-rect tracking
+  /// is enabled.
+  pub fn dirty_rects(&self) -> Vec<euclid::default::Rect<i32>> {
+    CApiState::get().dirty_rects.borrow().rects().to_vec()
+  }
+  /// Returns the screen areas that were drawn to during the previous frame, when dirty-rect
+  /// tracking is enabled. Areas that overlap or touch are merged into a single rect.
+  pub fn previous_dirty_rects(&self) -> Vec<euclid::default::Rect<i32>> {
+    CApiState::get().dirty_rects.borrow().previous_rects().to_vec()
+  }
+
   /// Offsets the origin point for all drawing calls to x, y (can be negative).
   pub fn set_draw_offset(&mut self, dx: i32, dy: i32) {
+    CApiState::get().dirty_rects.borrow_mut().set_draw_offset(dx, dy);
     unsafe { Self::fns().setDrawOffset.unwrap()(dx, dy) }
   }
 
@@ -174,6 +238,7 @@ impl Graphics {
   pub fn set_font<'a>(&mut self, font: &'a Font) -> ActiveFont<'a> {
     // setFont() takes a mutable pointer but does not write to the data.
     unsafe { Self::fns().setFont.unwrap()(font.cptr() as *mut _) }
+    CApiState::get().active_font.set(font.cptr() as *mut _);
     ActiveFont::new(font)
   }
 
@@ -208,14 +273,34 @@ impl Graphics {
   /// Sets the mode used for drawing bitmaps. Note that text drawing uses bitmaps, so this
   /// affects how fonts are displayed as well.
   pub fn set_draw_mode(&mut self, mode: BitmapDrawMode) {
+    CApiState::get().draw_mode.set(mode);
     unsafe { Self::fns().setDrawMode.unwrap()(mode) }
   }
+  /// Returns the mode used for drawing bitmaps, as last set by `set_draw_mode()`.
+  pub fn draw_mode(&self) -> BitmapDrawMode {
+    CApiState::get().draw_mode.get()
+  }
+
+  /// Sets the number of pixels of whitespace drawn between each character of text.
+  pub fn set_text_tracking(&mut self, tracking: i32) {
+    CApiState::get().text_tracking.set(tracking);
+    unsafe { Self::fns().setTextTracking.unwrap()(tracking) }
+  }
+  /// Returns the number of pixels of whitespace drawn between each character of text, as last set
+  /// by `set_text_tracking()`.
+  pub fn text_tracking(&self) -> i32 {
+    CApiState::get().text_tracking.get()
+  }
 
   /// Draws the bitmap to the screen.
   ///
   /// The bitmap's upper-left corner is positioned at location (`x`, `y`), and the contents have
   /// the `flip` orientation applied.
   pub fn draw_bitmap(&mut self, bitmap: &BitmapRef, x: i32, y: i32, flip: BitmapFlip) {
+    if dirty_rect_tracking_enabled() {
+      let data = bitmap.data();
+      track_dirty_rect(x, y, data.width(), data.height());
+    }
     // drawBitmap() takes a mutable pointer to a bitmap, but it only reads from the bitmap.
     unsafe { Self::fns().drawBitmap.unwrap()(bitmap.cptr() as *mut _, x, y, flip) }
   }
@@ -232,6 +317,18 @@ impl Graphics {
     xscale: f32,
     yscale: f32,
   ) {
+    if dirty_rect_tracking_enabled() {
+      let data = bitmap.data();
+      let width = (data.width() as f32 * xscale) as i32;
+      let height = (data.height() as f32 * yscale) as i32;
+      // Negative scales flip the bitmap around its position, and truncation may lose a pixel.
+      track_dirty_rect(
+        x.min(x + width) - 1,
+        y.min(y + height) - 1,
+        width.abs() + 2,
+        height.abs() + 2,
+      );
+    }
     // drawScaledBitmap() takes a mutable pointer to a bitmap, but it only reads from the bitmap.
     unsafe { Self::fns().drawScaledBitmap.unwrap()(bitmap.cptr() as *mut _, x, y, xscale, yscale) }
   }
@@ -252,6 +349,16 @@ impl Graphics {
     xscale: f32,
     yscale: f32,
   ) {
+    if dirty_rect_tracking_enabled() {
+      // The bitmap can be rotated any direction around a center point inside it, so the distance
+      // from the center to any pixel is at most the scaled diagonal of the bitmap.
+      let data = bitmap.data();
+      let (w, h) = (data.width() as f32, data.height() as f32);
+      let scale = xscale.abs().max(yscale.abs());
+      let radius = core::intrinsics::sqrtf32(w * w + h * h) * scale;
+      let radius = radius as i32 + 1;
+      track_dirty_rect(x - radius, y - radius, radius * 2, radius * 2);
+    }
     unsafe {
       // drawRotatedBitmap() takes a mutable pointer to a bitmap, but it only reads from the bitmap.
       Self::fns().drawRotatedBitmap.unwrap()(
@@ -278,32 +385,132 @@ impl Graphics {
     height: i32,
     flip: BitmapFlip,
   ) {
+    track_dirty_rect(x, y, width, height);
     // tileBitmap() takes a mutable pointer to a bitmap, but it only reads from the bitmap.
     unsafe { Self::fns().tileBitmap.unwrap()(bitmap.cptr() as *mut _, x, y, width, height, flip) }
   }
 
-  // BUG: Bitmap tables are incomplete in the C Api so we've omitted them. The C Api functions that
-  // do exist and are ommitted are:
-  // - getTableBitmap
-  // - loadBitmapTable
+  /// Draws a `ShiftedBitmap` to the screen with its upper-left corner at location (`x`, `y`).
+  ///
+  /// The precomposed variant for `x` is drawn at a byte-aligned position, so that the bitmap keeps
+  /// a consistent shape as it moves horizontally one pixel at a time. The alignment is relative to
+  /// the drawing offset set by `set_draw_offset()`, which should be a multiple of 8 in `x` to keep
+  /// the draw aligned on the screen.
+  pub fn draw_shifted_bitmap(&mut self, bitmap: &ShiftedBitmap, x: i32, y: i32) {
+    let (variant, aligned_x) = bitmap.variant_for_x(x);
+    self.draw_bitmap(variant, aligned_x, y, BitmapFlip::kBitmapUnflipped)
+  }
+
+  // BUG: Bitmap tables are incomplete in the C Api, so `BitmapTable` only supports loading a table
+  // from a file. The C Api functions that are ommitted are:
   // - loadIntoBitmapTable
   // - newBitmapTable
 
+  /// Draws `text` with lines, which can be scaled and rotated freely, with the top left of the
+  /// first character at `origin`.
+  ///
+  /// The text is drawn in a small built-in stroke font, rather than the font set by `set_font()`.
+  /// See `StrokeTextStyle` for more.
+  pub fn draw_stroke_text<'a>(
+    &mut self,
+    text: &str,
+    origin: euclid::default::Point2D<i32>,
+    style: &StrokeTextStyle,
+    color: Color<'a>,
+  ) {
+    let scale = style.scale();
+    let rotation = euclid::default::Rotation2D::new(euclid::Angle::degrees(style.angle));
+    let to_screen = |(x, y): (f32, f32)| {
+      let p = rotation.transform_point(euclid::default::Point2D::new(x * scale, y * scale));
+      origin + p.round().to_i32().to_vector()
+    };
+    stroke_font::for_each_segment(text, |a, b| {
+      self.draw_line(to_screen(a), to_screen(b), style.line_width, color)
+    });
+  }
+
   /// Draw a text string on the screen at the given (`x`, `y`) coordinates.
   ///
   /// If no font has been set with `Graphics::set_font()`, the default system font "Asheville Sans
   /// 14 Light" is used.
-  pub fn draw_text(&mut self, text: &str, x: i32, y: i32) {
+  ///
+  /// Returns the width and height of the drawn text, measured as a single line, or an error if
+  /// Playdate failed to draw it.
+  pub fn draw_text(
+    &mut self,
+    text: &str,
+    x: i32,
+    y: i32,
+  ) -> Result<euclid::default::Size2D<i32>, Error> {
+    self.draw_text_with_options(text, x, y, TextDrawOptions::default())
+  }
+  /// Draw a text string on the screen at the given (`x`, `y`) coordinates, with `options` that
+  /// override the draw mode or text tracking for this call only.
+  ///
+  /// The draw mode and text tracking set with `set_draw_mode()` and `set_text_tracking()` are
+  /// restored after drawing, so text can be drawn in a different style without changing the state
+  /// used by other drawing calls.
+  ///
+  /// UNCLEAR: The C Api has no way to read the draw mode, text tracking, or font, so the values
+  /// restored (and the font used to measure the text) are the ones last set through `Graphics`.
+  /// If they were changed another way, such as by drawing sprites or in another drawing context
+  /// from `push_context()`, the restored values will be wrong, and the options should not be used.
+  ///
+  /// Returns the width and height of the drawn text, measured as a single line, or an error if
+  /// Playdate failed to draw it.
+  pub fn draw_text_with_options(
+    &mut self,
+    text: &str,
+    x: i32,
+    y: i32,
+    options: TextDrawOptions,
+  ) -> Result<euclid::default::Size2D<i32>, Error> {
+    let state = CApiState::get();
+    let tracking = options.tracking.unwrap_or(state.text_tracking.get());
     let null_term = text.to_null_terminated_utf8();
+    let size = measure_text(&null_term, tracking);
+    if text.is_empty() {
+      // UNCLEAR: drawText() may report drawing an empty string as a failure, so it is not called.
+      return Ok(size);
+    }
+    track_dirty_rect(x, y, size.width, size.height);
+
+    if let Some(mode) = options.draw_mode {
+      unsafe { Self::fns().setDrawMode.unwrap()(mode) }
+    }
+    if options.tracking.is_some() {
+      unsafe { Self::fns().setTextTracking.unwrap()(tracking) }
+    }
     let ptr = null_term.as_ptr() as *const c_void;
     let len = null_term.len() as u64;
     let r =
       unsafe { Self::fns().drawText.unwrap()(ptr, len, CStringEncoding::kUTF8Encoding, x, y) };
-    assert!(r != 0)
+    if options.draw_mode.is_some() {
+      unsafe { Self::fns().setDrawMode.unwrap()(state.draw_mode.get()) }
+    }
+    if options.tracking.is_some() {
+      unsafe { Self::fns().setTextTracking.unwrap()(state.text_tracking.get()) }
+    }
+    match r {
+      0 => Err(Error::String("failed to draw text".into())),
+      _ => Ok(size),
+    }
+  }
+  /// Returns the width and height that `text` would have when drawn with `draw_text()` in the
+  /// active font, measured as a single line.
+  ///
+  /// The `tracking` value is the number of pixels of whitespace between each character, or `None`
+  /// to use the value set with `set_text_tracking()`.
+  pub fn measure_text(&self, text: &str, tracking: Option<i32>) -> euclid::default::Size2D<i32> {
+    let tracking = tracking.unwrap_or(CApiState::get().text_tracking.get());
+    measure_text(&text.to_null_terminated_utf8(), tracking)
   }
 
   /// Draws the current FPS on the screen at the given (`x`, `y`) coordinates.
   pub fn draw_fps(&mut self, x: i32, y: i32) {
+    // UNCLEAR: The size of the FPS counter is not documented, so this is a guess that covers the
+    // two digits it draws.
+    track_dirty_rect(x, y, 32, 16);
     // This function is part of CSystemApi, not CGraphicsApi, but it's a function that draws
     // something to the screen, so its behaviour is more clear when part of the Graphics type.
     unsafe { System::fns().drawFPS.unwrap()(x, y) }
@@ -321,6 +528,7 @@ impl Graphics {
     end_deg: f32,
     color: Color<'a>,
   ) {
+    CApiState::get().dirty_rects.borrow_mut().add_rect(rect);
     unsafe {
       Self::fns().drawEllipse.unwrap()(
         rect.origin.x,
@@ -345,6 +553,7 @@ impl Graphics {
     end_deg: f32,
     color: Color<'a>,
   ) {
+    CApiState::get().dirty_rects.borrow_mut().add_rect(rect);
     unsafe {
       Self::fns().fillEllipse.unwrap()(
         rect.origin.x,
@@ -365,10 +574,13 @@ impl Graphics {
     line_width: i32,
     color: Color<'a>,
   ) {
+    let pad = line_width / 2 + 1;
+    track_dirty_points(&[p1, p2], pad);
     unsafe { Self::fns().drawLine.unwrap()(p1.x, p1.y, p2.x, p2.y, line_width, color.to_c_color()) }
   }
   /// Draws a `rect`.
   pub fn draw_rect<'a>(&mut self, r: euclid::default::Rect<i32>, color: Color<'a>) {
+    CApiState::get().dirty_rects.borrow_mut().add_rect(r);
     unsafe {
       Self::fns().drawRect.unwrap()(
         r.origin.x,
@@ -381,6 +593,7 @@ impl Graphics {
   }
   /// Draws a filled `rect`.
   pub fn fill_rect<'a>(&mut self, r: euclid::default::Rect<i32>, color: Color<'a>) {
+    CApiState::get().dirty_rects.borrow_mut().add_rect(r);
     unsafe {
       Self::fns().fillRect.unwrap()(
         r.origin.x,
@@ -399,6 +612,7 @@ impl Graphics {
     p3: euclid::default::Point2D<i32>,
     color: Color<'a>,
   ) {
+    track_dirty_points(&[p1, p2, p3], 1);
     unsafe {
       Self::fns().fillTriangle.unwrap()(p1.x, p1.y, p2.x, p2.y, p3.x, p3.y, color.to_c_color())
     }
@@ -413,6 +627,7 @@ impl Graphics {
     color: Color<'a>,
     fill_rule: PolygonFillRule,
   ) {
+    track_dirty_points(points, 1);
     // Point2D is a #[repr(C)] struct of x, y. It's alignment will be the same as i32, so an
     // array of Point2D can be treated as an array of i32 with x and y alternating.
     unsafe {
@@ -429,3 +644,46 @@ impl Graphics {
     CApiState::get().cgraphics
   }
 }
+
+/// Measures the null-terminated UTF-8 `text` as drawn with the active font and `tracking`.
+fn measure_text(null_term: &[u8], tracking: i32) -> euclid::default::Size2D<i32> {
+  let font = CApiState::get().active_font.get();
+  // UNCLEAR: Playdate draws with the system font when no font is set. We assume that a null font
+  // also measures with the system font, the same as drawing does.
+  let fns = Graphics::fns();
+  let width = unsafe {
+    fns.getTextWidth.unwrap()(
+      font,
+      null_term.as_ptr() as *const c_void,
+      null_term.len() as u64 - 1, // Don't count the null.
+      CStringEncoding::kUTF8Encoding,
+      tracking,
+    )
+  };
+  let height = unsafe { fns.getFontHeight.unwrap()(font) };
+  euclid::default::Size2D::new(width, height as i32)
+}
+
+/// Whether dirty-rect tracking is enabled, for drawing calls whose bounds are costly to compute.
+fn dirty_rect_tracking_enabled() -> bool {
+  CApiState::get().dirty_rects.borrow().is_enabled()
+}
+
+/// Records the rect at (`x`, `y`) of size `width` by `height` as drawn to, for dirty-rect tracking.
+fn track_dirty_rect(x: i32, y: i32, width: i32, height: i32) {
+  CApiState::get().dirty_rects.borrow_mut().add_rect(euclid::default::Rect::new(
+    euclid::default::Point2D::new(x, y),
+    euclid::default::Size2D::new(width, height),
+  ))
+}
+
+/// Records the bounding box of `points`, grown by `pad` on each side, as drawn to, for dirty-rect
+/// tracking.
+fn track_dirty_points(points: &[euclid::default::Point2D<i32>], pad: i32) {
+  let bounds = euclid::default::Box2D::from_points(points.iter().copied());
+  if !points.is_empty() {
+    let rect = bounds.inflate(pad, pad).to_rect();
+    // Box2D excludes its max edge, but the points themselves are drawn.
+    track_dirty_rect(rect.origin.x, rect.origin.y, rect.size.width + 1, rect.size.height + 1)
+  }
+}
@@ -2,25 +2,41 @@ mod active_font;
 mod bitmap;
 mod bitmap_collider;
 mod bitmap_data;
+mod bitmap_effects;
+mod bitmap_table;
+mod collision;
 mod color;
+mod compiled_image;
 mod context_stack;
+mod dirty_rects;
 mod font;
 mod framebuffer_stencil_bitmap;
 mod graphics;
+mod shifted_bitmap;
+mod stroke_font;
+mod text_draw_options;
 mod unowned_bitmap;
 mod video;
 
 pub(crate) use context_stack::ContextStack;
+pub(crate) use dirty_rects::DirtyRects;
 
 pub use active_font::ActiveFont;
 pub use bitmap::*;
 pub use bitmap_collider::BitmapCollider;
 pub use bitmap_data::BitmapData;
+pub use bitmap_effects::{BitmapEffect, BitmapEffectChain, CombineOp, EffectId};
+pub use bitmap_table::BitmapTable;
+pub use collision::{Collision, CollisionBody, CollisionResponse, Contact, MoveResult};
 pub use color::{Color, Pattern, PixelColor};
+pub use compiled_image::{CompiledImage, CompiledImageTable};
 pub use context_stack::ContextStackId;
 pub use font::{Font, FontGlyph, FontPage};
 pub use framebuffer_stencil_bitmap::FramebufferStencilBitmap;
 pub use graphics::Graphics;
+pub use shifted_bitmap::ShiftedBitmap;
+pub use stroke_font::StrokeTextStyle;
+pub use text_draw_options::TextDrawOptions;
 pub use unowned_bitmap::{UnownedBitmapMut, UnownedBitmapRef};
 pub use video::Video;
 
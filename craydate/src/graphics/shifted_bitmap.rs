@@ -0,0 +1,85 @@
+use alloc::vec::Vec;
+
+use super::bitmap::{Bitmap, BitmapRef};
+use super::color::PixelColor;
+use crate::ctypes::*;
+
+/// The number of horizontal shifts precomposed for every `ShiftedBitmap`, one for each pixel in a
+/// byte of bitmap data.
+const SHIFTS: i32 = 8;
+
+/// A bitmap along with precomposed copies of it shifted horizontally by each of 0 to 7 pixels.
+///
+/// Pixels in a bitmap are packed 8 to a byte, so drawing a bitmap at an `x` position that is not a
+/// multiple of 8 requires the bits of each row to be shifted across byte boundaries as it's drawn.
+/// When an image scrolls slowly, one pixel at a time, the shifting changes each frame, which can
+/// make its motion look uneven. A `ShiftedBitmap` does that shifting once up front, so that drawing
+/// it with `Graphics::draw_shifted_bitmap()` always draws a byte-aligned bitmap, and only the choice
+/// of precomposed variant changes as the position changes.
+///
+/// Font glyphs can be precomposed as well, by constructing a `ShiftedBitmap` from
+/// `FontGlyph::bitmap()`.
+#[derive(Debug)]
+pub struct ShiftedBitmap {
+  variants: Vec<Bitmap>,
+  width: i32,
+  height: i32,
+}
+impl ShiftedBitmap {
+  /// Precomposes the shifted variants of `bitmap`.
+  ///
+  /// Each variant is 7 pixels wider than `bitmap`, with the extra pixels masked out so they are not
+  /// drawn. If `bitmap` has a mask, it is shifted along with the pixels.
+  pub fn new(bitmap: &BitmapRef) -> Self {
+    let data = bitmap.data();
+    let (width, height) = (data.width(), data.height());
+    let src_mask = bitmap.mask_bitmap();
+
+    let variants = (0..SHIFTS)
+      .map(|shift| {
+        let mut variant = Bitmap::new(width + SHIFTS - 1, height, SolidColor::kColorWhite);
+        // A black mask pixel is transparent, so anything outside the shifted source is not drawn.
+        let mut mask = Bitmap::new(width + SHIFTS - 1, height, SolidColor::kColorBlack);
+        {
+          let src_pixels = bitmap.as_pixels();
+          let src_mask_pixels = src_mask.as_ref().map(|m| m.as_pixels());
+          let mut pixels = variant.as_pixels_mut();
+          let mut mask_pixels = mask.as_pixels_mut();
+          for y in 0..height as usize {
+            for x in 0..width as usize {
+              let to_x = x + shift as usize;
+              pixels.set(to_x, y, src_pixels.get(x, y));
+              let opaque = src_mask_pixels.as_ref().map_or(PixelColor::WHITE, |m| m.get(x, y));
+              mask_pixels.set(to_x, y, opaque);
+            }
+          }
+        }
+        // The dimensions match by construction, so this can not fail.
+        variant.set_mask_bitmap(&mask).unwrap();
+        variant
+      })
+      .collect();
+
+    ShiftedBitmap {
+      variants,
+      width,
+      height,
+    }
+  }
+
+  /// The width of the original bitmap, without the space used for shifting.
+  pub fn width(&self) -> i32 {
+    self.width
+  }
+  /// The height of the bitmap.
+  pub fn height(&self) -> i32 {
+    self.height
+  }
+
+  /// Returns the precomposed variant to draw for the bitmap to appear at `x`, along with the
+  /// byte-aligned `x` position where the variant should be drawn.
+  pub(crate) fn variant_for_x(&self, x: i32) -> (&BitmapRef, i32) {
+    let shift = x.rem_euclid(SHIFTS);
+    (&self.variants[shift as usize], x - shift)
+  }
+}
@@ -0,0 +1,191 @@
+/// The width of each glyph in the stroke font's grid, including the space after it.
+const ADVANCE: f32 = 6.0;
+/// The height of capital letters in the stroke font's grid.
+const CAP_HEIGHT: f32 = 6.0;
+/// The distance between lines of text in the stroke font's grid.
+const LINE_HEIGHT: f32 = 10.0;
+
+/// Strokes for the printable ASCII characters, starting from the space character.
+///
+/// The glyphs are in the style of the Hershey vector fonts, on a grid 4 units wide where capital
+/// letters span from `y` = 0 down to the baseline at `y` = 6, and descenders reach `y` = 8. Each
+/// glyph is a list of polylines separated by spaces, and each polyline is a sequence of points
+/// written as two digits, `x` then `y`. A polyline with a single point is drawn as a dot.
+const GLYPHS: [&str; 95] = [
+  "",                                    // ' '
+  "2023 2526",                           // !
+  "1012 3032",                           // "
+  "1016 3036 0242 0444",                 // #
+  "413010010213334445361605 2026",       // $
+  "0640 0010110100 3545463635",          // %
+  "4611102031320405162644",              // &
+  "2022",                                // '
+  "30212536",                            // (
+  "10212516",                            // )
+  "2125 0244 0442",                      // *
+  "2125 0343",                           // +
+  "252617",                              // ,
+  "0343",                                // -
+  "2526",                                // .
+  "0640",                                // /
+  "103041453616050110 0541",             // 0
+  "112026 1636",                         // 1
+  "01103041420646",                      // 2
+  "0110304142334445361605 1333",         // 3
+  "36300444",                            // 4
+  "400003334445361605",                  // 5
+  "30100105163645443303",                // 6
+  "004016",                              // 7
+  "103041423313020110 1304051636454433", // 8
+  "43130201103041453616",                // 9
+  "2122 2526",                           // :
+  "2122 252617",                         // ;
+  "410345",                              // <
+  "0242 0444",                           // =
+  "014305",                              // >
+  "011030414223 2526",                   // ?
+  "32121434 32344441301001051646",       // @
+  "062046 1333",                         // A
+  "06003041423303 3344453606",           // B
+  "4130100105163645",                    // C
+  "00063645413000",                      // D
+  "40000646 0333",                       // E
+  "400006 0333",                         // F
+  "41301001051636454323",                // G
+  "0006 4046 0343",                      // H
+  "1030 2026 1636",                      // I
+  "4045361605",                          // J
+  "0006 4004 1346",                      // K
+  "000646",                              // L
+  "0600234046",                          // M
+  "06004640",                            // N
+  "103041453616050110",                  // O
+  "06003041423303",                      // P
+  "103041453616050110 2446",             // Q
+  "06003041423303 2346",                 // R
+  "413010010213334445361605",            // S
+  "0040 2026",                           // T
+  "000516364540",                        // U
+  "002640",                              // V
+  "0016233640",                          // W
+  "0046 4006",                           // X
+  "002340 2326",                         // Y
+  "00400646",                            // Z
+  "30101636",                            // [
+  "0046",                                // \
+  "10303616",                            // ]
+  "122032",                              // ^
+  "0747",                                // _
+  "1021",                                // `
+  "12324346 441405163645",               // a
+  "0006 0312324345361605",               // b
+  "4332120305163645",                    // c
+  "4046 4332120305163645",               // d
+  "04444332120305163645",                // e
+  "30201116 0232",                       // f
+  "4247381807 4332120304153544",         // g
+  "0006 0312324346",                     // h
+  "2226 20",                             // i
+  "22271808 20",                         // j
+  "0006 4204 2346",                      // k
+  "202536",                              // l
+  "0206 03122326 23324346",              // m
+  "0206 0312324346",                     // n
+  "123243453616050312",                  // o
+  "0208 0312324345361605",               // p
+  "4248 4332120305163645",               // q
+  "0206 04223243",                       // r
+  "43321203143445361605",                // s
+  "10152636 0232",                       // t
+  "0205163645 4246",                     // u
+  "022642",                              // v
+  "0216243642",                          // w
+  "0246 4206",                           // x
+  "0226 421808",                         // y
+  "02420646",                            // z
+  "30212213242536",                      // {
+  "2027",                                // |
+  "10212233242516",                      // }
+  "03123443",                            // ~
+];
+/// Drawn for characters that are not in the font.
+const MISSING_GLYPH: &str = "0040460600";
+
+/// How to draw text with `Graphics::draw_stroke_text()`.
+///
+/// Stroke text is drawn from lines with `Graphics::draw_line()`, using a small built-in font in the
+/// style of the Hershey vector fonts. It can be drawn at any size and rotation, without needing a
+/// bitmap font. Only printable ASCII characters are included, and others are drawn as a box.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct StrokeTextStyle {
+  /// The height of capital letters, in pixels. Lowercase letters are two thirds of this height,
+  /// and descenders go a third of it below the baseline.
+  pub height: f32,
+  /// The clockwise rotation of the text around its origin, in degrees.
+  pub angle: f32,
+  /// The width of the lines the text is drawn with, in pixels.
+  pub line_width: i32,
+}
+impl StrokeTextStyle {
+  /// Constructs a `StrokeTextStyle` for text of the given `height` in pixels, without rotation,
+  /// drawn with 1 pixel wide lines.
+  pub fn new(height: f32) -> Self {
+    StrokeTextStyle {
+      height,
+      angle: 0.0,
+      line_width: 1,
+    }
+  }
+
+  /// The width of the longest line in `text` when drawn in this style, before rotation.
+  pub fn text_width(&self, text: &str) -> f32 {
+    let longest = text.lines().map(|line| line.chars().count()).max().unwrap_or(0);
+    longest as f32 * ADVANCE * self.scale()
+  }
+  /// The height of `text` when drawn in this style, from the top of the first line to the
+  /// baseline of the last line, before rotation.
+  pub fn text_height(&self, text: &str) -> f32 {
+    let lines = text.lines().count().max(1);
+    ((lines - 1) as f32 * LINE_HEIGHT + CAP_HEIGHT) * self.scale()
+  }
+
+  /// The size of a grid unit of the font, in pixels.
+  pub(crate) fn scale(&self) -> f32 {
+    self.height / CAP_HEIGHT
+  }
+}
+
+/// Calls `f` with each line segment of `text`, in the font's grid units, where `(0, 0)` is the top
+/// left of the first character. A dot is given as a segment with the same start and end.
+pub(crate) fn for_each_segment<F: FnMut((f32, f32), (f32, f32))>(text: &str, mut f: F) {
+  for (row, line) in text.lines().enumerate() {
+    for (col, c) in line.chars().enumerate() {
+      let glyph = match c {
+        ' '..='~' => GLYPHS[c as usize - ' ' as usize],
+        _ => MISSING_GLYPH,
+      };
+      let origin = (col as f32 * ADVANCE, row as f32 * LINE_HEIGHT);
+      for polyline in glyph.split(' ').filter(|s| !s.is_empty()) {
+        let mut points = polyline.as_bytes().chunks_exact(2).map(|xy| {
+          (
+            origin.0 + (xy[0] - b'0') as f32,
+            origin.1 + (xy[1] - b'0') as f32,
+          )
+        });
+        let mut last = match points.next() {
+          Some(p) => p,
+          None => continue,
+        };
+        let mut drew = false;
+        for p in points {
+          f(last, p);
+          last = p;
+          drew = true;
+        }
+        if !drew {
+          f(last, last);
+        }
+      }
+    }
+  }
+}
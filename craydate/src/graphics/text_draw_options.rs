@@ -0,0 +1,14 @@
+use crate::ctypes::*;
+
+/// Options for drawing a single string with `Graphics::draw_text_with_options()`, which override
+/// the graphics state for that call only.
+///
+/// Each option left as `None` uses the current state, as set by `Graphics::set_draw_mode()` or
+/// `Graphics::set_text_tracking()`.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub struct TextDrawOptions {
+  /// The mode used to draw the text's glyphs.
+  pub draw_mode: Option<BitmapDrawMode>,
+  /// The number of pixels of whitespace between each character.
+  pub tracking: Option<i32>,
+}
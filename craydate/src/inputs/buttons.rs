@@ -5,7 +5,7 @@ use crate::ctypes::*;
 
 
 /// The state of all buttons, along with changes since the last frame.
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Buttons {
   current: CButtons,
   up_events: [Option<ButtonEvent>; 3],
@@ -13,3 +13,38 @@ pub enum Crank {
     change: f32,
   },
 }
+impl Crank {
+  /// Returns the number of ticks the crank moved across since the last frame, where a full turn
+  /// of the crank is divided into `ticks_per_revolution` ticks. Ticks are counted up when moved
+  /// clockwise, and down when moved counter-clockwise.
+  ///
+  /// The ticks are at fixed positions with one at the top of the crank, so a slow turn still
+  /// crosses them, like the `playdate.getCrankTicks()` function in the Lua Api. This is useful for
+  /// stepping through a menu or turning a gear in fixed steps.
+  ///
+  /// Returns 0 if the crank is docked, or if `ticks_per_revolution` is 0.
+  ///
+  /// # Example
+  /// ```
+  /// // Move the selection by one item for each sixth of a turn.
+  /// selected += inputs.crank().ticks(6);
+  /// ```
+  pub fn ticks(&self, ticks_per_revolution: u32) -> i32 {
+    match *self {
+      Crank::Docked => 0,
+      Crank::Undocked { .. } if ticks_per_revolution == 0 => 0,
+      Crank::Undocked { angle, change } => {
+        let degrees_per_tick = 360.0 / ticks_per_revolution as f32;
+        let previous = angle - change;
+        // Which tick each angle is past, which are compared to find how many were crossed. The
+        // previous angle can be negative, so this rounds down rather than toward zero.
+        let tick = |a: f32| {
+          let t = a / degrees_per_tick;
+          let whole = t as i32;
+          if (whole as f32) > t { whole - 1 } else { whole }
+        };
+        tick(angle) - tick(previous)
+      }
+    }
+  }
+}
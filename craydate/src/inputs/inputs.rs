@@ -6,7 +6,7 @@ use crate::geometry::Vector3;
 use crate::system::System;
 
 /// The set of all input state and/or changes since the last frame.
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Inputs {
   peripherals_enabled: Peripherals,
   buttons: Buttons,
@@ -37,10 +37,31 @@ impl Inputs {
     }
   }
 
+  /// Constructs `Inputs` from states that did not come from the device, such as generated inputs.
+  #[cfg(feature = "unstable-api")]
+  pub(crate) fn from_parts(peripherals_enabled: Peripherals, buttons: Buttons, crank: Crank) -> Self {
+    Inputs {
+      peripherals_enabled,
+      buttons,
+      crank,
+    }
+  }
+
   /// Returns the last read values from the accelerometor.
   ///
-  /// These values are only present if the accelerometer is enabled via `System::enable_devices()`,
-  /// otherwise it returns None.
+  /// These values are only present if the accelerometer is enabled via
+  /// `System::enable_peripherals()`, otherwise it returns None. Each value is in units of earth's
+  /// gravity.
+  ///
+  /// # Example
+  /// ```
+  /// api.system.enable_peripherals(Peripherals::kAccelerometer);
+  /// // Each frame:
+  /// if let Some(tilt) = inputs.accelerometer() {
+  ///   ball.velocity.x += tilt.x * TILT_SPEED;
+  ///   ball.velocity.y += tilt.y * TILT_SPEED;
+  /// }
+  /// ```
   pub fn accelerometer(&self) -> Option<Vector3<f32>> {
     if self.peripherals_enabled & Peripherals::kAccelerometer == Peripherals::kAccelerometer {
       let mut v = Vector3::default();
@@ -0,0 +1,138 @@
+use core::future::Future;
+use core::pin::Pin;
+use core::task::{Context, Poll};
+
+use crate::capi_state::CApiState;
+use crate::executor::Executor;
+use crate::time::{TimeDelta, TimeTicks};
+
+/// Returns a `FrameInterval` which ticks once every `n` frames, starting `n` frames from now.
+///
+/// An `n` of 0 is treated as 1, which ticks every frame.
+///
+/// # Example
+/// ```
+/// let mut autosave = craydate::every_n_frames(30 * 60);
+/// loop {
+///   autosave.next().await;
+///   save_game();
+/// }
+/// ```
+pub fn every_n_frames(n: u64) -> FrameInterval {
+  let n = n.max(1);
+  FrameInterval {
+    n,
+    next_frame: CApiState::get().frame_number.get() + n,
+  }
+}
+
+/// Returns a `TimeInterval` which ticks once every `period` of time, starting one `period` from
+/// now.
+///
+/// A `period` shorter than a millisecond is treated as one millisecond, which ticks on every
+/// system event.
+///
+/// # Example
+/// ```
+/// let mut spawner = craydate::every(TimeDelta::from_seconds(5));
+/// loop {
+///   spawner.next().await;
+///   spawn_enemy();
+/// }
+/// ```
+pub fn every(period: TimeDelta) -> TimeInterval {
+  let period = period.total_whole_milliseconds().max(1);
+  TimeInterval {
+    period,
+    next_tick: TimeTicks::now() + TimeDelta::from_milliseconds(period),
+  }
+}
+
+/// A stream of ticks every few frames, made by `every_n_frames()`.
+///
+/// If a tick is missed, because nothing was waiting on `next()` when it was due, the next call to
+/// `next()` returns right away, and any further missed ticks are skipped so that the ticks stay on
+/// the same schedule.
+#[derive(Debug)]
+pub struct FrameInterval {
+  n: u64,
+  next_frame: u64,
+}
+impl FrameInterval {
+  /// Waits for the next tick, and returns the number of the frame it happened on.
+  pub async fn next(&mut self) -> u64 {
+    let frame = FrameIntervalFuture {
+      frame: self.next_frame,
+    }
+    .await;
+    // Skip ahead to the first tick after `frame`.
+    let missed = (frame - self.next_frame) / self.n;
+    self.next_frame += self.n * (missed + 1);
+    frame
+  }
+}
+
+/// A stream of ticks every `period` of time, made by `every()`.
+///
+/// If a tick is missed, because nothing was waiting on `next()` when it was due, the next call to
+/// `next()` returns right away, and any further missed ticks are skipped so that the ticks stay on
+/// the same schedule.
+#[derive(Debug)]
+pub struct TimeInterval {
+  // In milliseconds.
+  period: i32,
+  next_tick: TimeTicks,
+}
+impl TimeInterval {
+  /// Waits for the next tick, and returns the device time when it was seen.
+  ///
+  /// The tick is seen at the first system event after it is due.
+  pub async fn next(&mut self) -> TimeTicks {
+    let now = TimeIntervalFuture {
+      tick: self.next_tick,
+    }
+    .await;
+    // Skip ahead to the first tick after `now`.
+    let missed = (now - self.next_tick).total_whole_milliseconds() / self.period;
+    self.next_tick += TimeDelta::from_milliseconds(self.period * (missed + 1));
+    now
+  }
+}
+
+/// A future for which poll() waits for the `frame` number to be reached, then returns the current
+/// frame number.
+struct FrameIntervalFuture {
+  frame: u64,
+}
+impl Future for FrameIntervalFuture {
+  type Output = u64;
+
+  fn poll(self: Pin<&mut Self>, ctxt: &mut Context<'_>) -> Poll<u64> {
+    let current = CApiState::get().frame_number.get();
+    if current >= self.frame {
+      Poll::Ready(current)
+    } else {
+      Executor::add_waker_for_system_event(CApiState::get().executor, ctxt.waker());
+      Poll::Pending
+    }
+  }
+}
+
+/// A future for which poll() waits for the device time to reach the `tick`, then returns the
+/// current time.
+struct TimeIntervalFuture {
+  tick: TimeTicks,
+}
+impl Future for TimeIntervalFuture {
+  type Output = TimeTicks;
+
+  fn poll(self: Pin<&mut Self>, ctxt: &mut Context<'_>) -> Poll<TimeTicks> {
+    let now = TimeTicks::now();
+    if now >= self.tick {
+      Poll::Ready(now)
+    } else {
+      Executor::add_waker_for_system_event(CApiState::get().executor, ctxt.waker());
+      Poll::Pending
+    }
+  }
+}
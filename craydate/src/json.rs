@@ -0,0 +1,435 @@
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::ffi::c_void;
+
+use crate::capi_state::CApiState;
+use crate::ctypes::*;
+use crate::error::Error;
+use crate::files::Read;
+use crate::null_terminated::{ToNullTerminatedString, parse_null_terminated_utf8};
+
+/// A value in a JSON document, which is decoded and encoded with Playdate's JSON functions.
+///
+/// # Example
+/// ```
+/// let config: JsonValue = r#"{"volume": 0.5, "name": "crate"}"#.parse()?;
+/// let volume = config.get("volume").and_then(|v| v.as_f32()).unwrap_or(1.0);
+/// api.file.write_file("config.json", config.to_json_string(true).as_bytes())?;
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub enum JsonValue {
+  Null,
+  Bool(bool),
+  Integer(i32),
+  Float(f32),
+  String(String),
+  Array(Vec<JsonValue>),
+  /// The members of a JSON object, in the order they appear in the document.
+  Table(Vec<(String, JsonValue)>),
+}
+impl JsonValue {
+  /// Decodes a JSON document from a `reader`, such as an `OpenFile`.
+  pub fn from_reader<R: Read>(reader: &mut R) -> Result<JsonValue, Error> {
+    let mut builder = TreeBuilder::default();
+    let root = decode_json(reader, &mut builder)?;
+    Ok(builder.root.unwrap_or(root))
+  }
+
+  /// Encodes the value as a JSON document. If `pretty` is true, the document is spread over
+  /// multiple lines with indentation.
+  pub fn to_json_string(&self, pretty: bool) -> String {
+    unsafe extern "C" fn write_string(userdata: *mut c_void, s: *const u8, len: i32) {
+      let out = &mut *(userdata as *mut Vec<u8>);
+      out.extend_from_slice(core::slice::from_raw_parts(s, len as usize));
+    }
+    let mut out = Vec::<u8>::new();
+    let mut encoder = core::mem::MaybeUninit::<CJsonEncoder>::zeroed();
+    unsafe {
+      fns().initEncoder.unwrap()(
+        encoder.as_mut_ptr(),
+        Some(write_string),
+        &mut out as *mut Vec<u8> as *mut c_void,
+        pretty as i32,
+      )
+    };
+    let mut encoder = unsafe { encoder.assume_init() };
+    self.encode(&mut encoder);
+    // The encoder only writes the strings it was given and JSON syntax, which are all UTF-8.
+    String::from_utf8(out).unwrap()
+  }
+  fn encode(&self, e: &mut CJsonEncoder) {
+    unsafe {
+      match self {
+        JsonValue::Null => e.writeNull.unwrap()(e),
+        JsonValue::Bool(true) => e.writeTrue.unwrap()(e),
+        JsonValue::Bool(false) => e.writeFalse.unwrap()(e),
+        JsonValue::Integer(i) => e.writeInt.unwrap()(e, *i),
+        JsonValue::Float(f) => e.writeDouble.unwrap()(e, *f as f64),
+        JsonValue::String(s) => e.writeString.unwrap()(e, s.as_ptr(), s.len() as i32),
+        JsonValue::Array(values) => {
+          e.startArray.unwrap()(e);
+          for v in values {
+            e.addArrayMember.unwrap()(e);
+            v.encode(e);
+          }
+          e.endArray.unwrap()(e);
+        }
+        JsonValue::Table(members) => {
+          e.startTable.unwrap()(e);
+          for (key, v) in members {
+            e.addTableMember.unwrap()(e, key.as_ptr(), key.len() as i32);
+            v.encode(e);
+          }
+          e.endTable.unwrap()(e);
+        }
+      }
+    }
+  }
+
+  /// Returns the value of the member with the `key`, if the value is a table with that member.
+  pub fn get(&self, key: &str) -> Option<&JsonValue> {
+    match self {
+      JsonValue::Table(members) => members.iter().find(|(k, _)| k == key).map(|(_, v)| v),
+      _ => None,
+    }
+  }
+  /// Returns the value at the `index`, if the value is an array that long.
+  pub fn at(&self, index: usize) -> Option<&JsonValue> {
+    self.as_array().and_then(|values| values.get(index))
+  }
+
+  /// Whether the value is `null`.
+  pub fn is_null(&self) -> bool {
+    *self == JsonValue::Null
+  }
+  /// Returns the value if it is a bool.
+  pub fn as_bool(&self) -> Option<bool> {
+    match self {
+      JsonValue::Bool(b) => Some(*b),
+      _ => None,
+    }
+  }
+  /// Returns the value if it is an integer.
+  pub fn as_i32(&self) -> Option<i32> {
+    match self {
+      JsonValue::Integer(i) => Some(*i),
+      _ => None,
+    }
+  }
+  /// Returns the value if it is a number, either an integer or a float.
+  pub fn as_f32(&self) -> Option<f32> {
+    match self {
+      JsonValue::Integer(i) => Some(*i as f32),
+      JsonValue::Float(f) => Some(*f),
+      _ => None,
+    }
+  }
+  /// Returns the value if it is a string.
+  pub fn as_str(&self) -> Option<&str> {
+    match self {
+      JsonValue::String(s) => Some(s),
+      _ => None,
+    }
+  }
+  /// Returns the values if it is an array.
+  pub fn as_array(&self) -> Option<&[JsonValue]> {
+    match self {
+      JsonValue::Array(values) => Some(values),
+      _ => None,
+    }
+  }
+  /// Returns the members if it is a table.
+  pub fn as_table(&self) -> Option<&[(String, JsonValue)]> {
+    match self {
+      JsonValue::Table(members) => Some(members),
+      _ => None,
+    }
+  }
+}
+
+impl core::str::FromStr for JsonValue {
+  type Err = Error;
+
+  /// Decodes a JSON document from a string.
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    let mut builder = TreeBuilder::default();
+    let root = decode_json_str(s, &mut builder)?;
+    Ok(builder.root.unwrap_or(root))
+  }
+}
+
+impl core::fmt::Display for JsonValue {
+  /// Formats the value as a compact JSON document.
+  fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+    f.write_str(&self.to_json_string(false))
+  }
+}
+
+/// The kind of a nested value in a JSON document.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum JsonSublist {
+  Array,
+  Table,
+}
+
+/// Receives the parts of a JSON document as it is decoded by `decode_json()` or
+/// `decode_json_str()`, without building a `JsonValue` for the whole document.
+///
+/// Values are given as `JsonValue`s which are never an `Array` or a `Table`. Those are reported by
+/// `start_sublist()` and `end_sublist()` around the values inside them instead. Every function has
+/// a default which does nothing or decodes everything, so a handler only needs to implement the
+/// ones it uses.
+///
+/// # Example
+/// ```
+/// // Finds the "version" in a large config file, without keeping the rest of it.
+/// struct FindVersion(Option<i32>);
+/// impl JsonHandler for FindVersion {
+///   fn table_value(&mut self, key: &str, value: JsonValue) {
+///     if key == "version" { self.0 = value.as_i32(); }
+///   }
+/// }
+/// ```
+pub trait JsonHandler {
+  /// Called when a nested array or table starts. The `name` is the key of the table member it is
+  /// in, or `_root` for the outermost value.
+  fn start_sublist(&mut self, _name: &str, _kind: JsonSublist) {}
+  /// Called when a nested array or table ends, after its values.
+  fn end_sublist(&mut self, _name: &str, _kind: JsonSublist) {}
+
+  /// Returns whether to decode the value of the table member with the `key`. If false, the value
+  /// is skipped, including anything nested inside it.
+  fn should_decode_table_value(&mut self, _key: &str) -> bool {
+    true
+  }
+  /// Called with the value of a table member.
+  fn table_value(&mut self, _key: &str, _value: JsonValue) {}
+
+  /// Returns whether to decode the value at the `position` in an array. If false, the value is
+  /// skipped, including anything nested inside it.
+  ///
+  /// UNCLEAR: Playdate does not document if positions start at 0 or 1.
+  fn should_decode_array_value(&mut self, _position: i32) -> bool {
+    true
+  }
+  /// Called with a value in an array.
+  fn array_value(&mut self, _position: i32, _value: JsonValue) {}
+}
+
+/// Decodes a JSON document from a `reader`, such as an `OpenFile`, giving each part of it to the
+/// `handler`.
+///
+/// Returns the outermost value if it is not an array or table, or `JsonValue::Null` if it is.
+pub fn decode_json<R: Read, H: JsonHandler>(
+  reader: &mut R,
+  handler: &mut H,
+) -> Result<JsonValue, Error> {
+  struct ReadState<'a, R> {
+    reader: &'a mut R,
+    error: Option<Error>,
+  }
+  unsafe extern "C" fn read<R: Read>(userdata: *mut c_void, buf: *mut u8, bufsize: i32) -> i32 {
+    let state = &mut *(userdata as *mut ReadState<R>);
+    let buf = core::slice::from_raw_parts_mut(buf, bufsize as usize);
+    match state.reader.read(buf) {
+      Ok(n) => n as i32,
+      Err(e) => {
+        state.error = Some(e);
+        0
+      }
+    }
+  }
+  let mut read_state = ReadState {
+    reader,
+    error: None,
+  };
+  let creader = CJsonReader {
+    read: Some(read::<R>),
+    userdata: &mut read_state as *mut ReadState<R> as *mut c_void,
+  };
+  let result = decode_with(handler, |decoder, outval| unsafe {
+    fns().decode.unwrap()(decoder, creader, outval)
+  });
+  match read_state.error {
+    Some(e) => Err(e),
+    None => result,
+  }
+}
+
+/// Decodes a JSON document from a string, giving each part of it to the `handler`.
+///
+/// Returns the outermost value if it is not an array or table, or `JsonValue::Null` if it is.
+pub fn decode_json_str<H: JsonHandler>(s: &str, handler: &mut H) -> Result<JsonValue, Error> {
+  let s = s.to_null_terminated_utf8();
+  decode_with(handler, |decoder, outval| unsafe {
+    fns().decodeString.unwrap()(decoder, s.as_ptr(), outval)
+  })
+}
+
+struct DecodeState<'a, H> {
+  handler: &'a mut H,
+  error: Option<String>,
+}
+
+fn decode_with<H: JsonHandler>(
+  handler: &mut H,
+  decode: impl FnOnce(*mut CJsonDecoder, *mut CJsonValue) -> i32,
+) -> Result<JsonValue, Error> {
+  let mut state = DecodeState {
+    handler,
+    error: None,
+  };
+  let mut decoder = CJsonDecoder {
+    decodeError: Some(decode_error::<H>),
+    willDecodeSublist: Some(will_decode_sublist::<H>),
+    shouldDecodeTableValueForKey: Some(should_decode_table_value::<H>),
+    didDecodeTableValue: Some(did_decode_table_value::<H>),
+    shouldDecodeArrayValueAtIndex: Some(should_decode_array_value::<H>),
+    didDecodeArrayValue: Some(did_decode_array_value::<H>),
+    didDecodeSublist: Some(did_decode_sublist::<H>),
+    userdata: &mut state as *mut DecodeState<H> as *mut c_void,
+    returnString: 0,
+    path: core::ptr::null(),
+  };
+  let mut outval = core::mem::MaybeUninit::<CJsonValue>::zeroed();
+  // UNCLEAR: The meaning of the return value is not documented, so errors are found from the
+  // decodeError callback instead.
+  decode(&mut decoder, outval.as_mut_ptr());
+  match state.error {
+    Some(error) => Err(Error::String(error)),
+    None => Ok(unsafe { scalar_from_c(&outval.assume_init()) }.unwrap_or(JsonValue::Null)),
+  }
+}
+
+fn fns() -> &'static CJsonApi {
+  CApiState::get().cjson
+}
+
+/// SAFETY: The decoder must be one made by `decode_with()` for the handler `H`.
+unsafe fn state<'a, H>(decoder: *mut CJsonDecoder) -> &'a mut DecodeState<'a, H> {
+  &mut *((*decoder).userdata as *mut DecodeState<H>)
+}
+/// SAFETY: The pointer must be null or point to a null-terminated string.
+unsafe fn c_str<'a>(p: *const u8) -> &'a str {
+  if p.is_null() {
+    ""
+  } else {
+    parse_null_terminated_utf8(p).unwrap_or("")
+  }
+}
+fn sublist_kind(kind: i32) -> JsonSublist {
+  match kind {
+    craydate_sys::json_value_type_kJSONArray => JsonSublist::Array,
+    _ => JsonSublist::Table,
+  }
+}
+/// Copies a value from Playdate, or returns `None` if it is an array or table.
+///
+/// SAFETY: The value must be one given by Playdate's decoder.
+unsafe fn scalar_from_c(value: &CJsonValue) -> Option<JsonValue> {
+  match value.type_ as i32 {
+    craydate_sys::json_value_type_kJSONNull => Some(JsonValue::Null),
+    craydate_sys::json_value_type_kJSONTrue => Some(JsonValue::Bool(true)),
+    craydate_sys::json_value_type_kJSONFalse => Some(JsonValue::Bool(false)),
+    craydate_sys::json_value_type_kJSONInteger => Some(JsonValue::Integer(value.data.intval)),
+    craydate_sys::json_value_type_kJSONFloat => Some(JsonValue::Float(value.data.floatval)),
+    craydate_sys::json_value_type_kJSONString => {
+      Some(JsonValue::String(String::from(c_str(value.data.stringval))))
+    }
+    _ => None,
+  }
+}
+
+unsafe extern "C" fn decode_error<H: JsonHandler>(
+  decoder: *mut CJsonDecoder,
+  error: *const u8,
+  linenum: i32,
+) {
+  let state = state::<H>(decoder);
+  state.error = Some(format!("JSON: {} on line {}", c_str(error), linenum));
+}
+unsafe extern "C" fn will_decode_sublist<H: JsonHandler>(
+  decoder: *mut CJsonDecoder,
+  name: *const u8,
+  kind: i32,
+) {
+  state::<H>(decoder).handler.start_sublist(c_str(name), sublist_kind(kind))
+}
+unsafe extern "C" fn did_decode_sublist<H: JsonHandler>(
+  decoder: *mut CJsonDecoder,
+  name: *const u8,
+  kind: i32,
+) -> *mut c_void {
+  state::<H>(decoder).handler.end_sublist(c_str(name), sublist_kind(kind));
+  // The sublist's values were already given to the handler, so nothing is given to Playdate to
+  // pass along as the sublist's value.
+  core::ptr::null_mut()
+}
+unsafe extern "C" fn should_decode_table_value<H: JsonHandler>(
+  decoder: *mut CJsonDecoder,
+  key: *const u8,
+) -> i32 {
+  state::<H>(decoder).handler.should_decode_table_value(c_str(key)) as i32
+}
+unsafe extern "C" fn did_decode_table_value<H: JsonHandler>(
+  decoder: *mut CJsonDecoder,
+  key: *const u8,
+  value: CJsonValue,
+) {
+  if let Some(value) = scalar_from_c(&value) {
+    state::<H>(decoder).handler.table_value(c_str(key), value)
+  }
+}
+unsafe extern "C" fn should_decode_array_value<H: JsonHandler>(
+  decoder: *mut CJsonDecoder,
+  pos: i32,
+) -> i32 {
+  state::<H>(decoder).handler.should_decode_array_value(pos) as i32
+}
+unsafe extern "C" fn did_decode_array_value<H: JsonHandler>(
+  decoder: *mut CJsonDecoder,
+  pos: i32,
+  value: CJsonValue,
+) {
+  if let Some(value) = scalar_from_c(&value) {
+    state::<H>(decoder).handler.array_value(pos, value)
+  }
+}
+
+/// A `JsonHandler` which builds a `JsonValue` of the whole document.
+#[derive(Default)]
+struct TreeBuilder {
+  // The sublists being decoded, with the key they will have in their parent table.
+  stack: Vec<(String, JsonValue)>,
+  root: Option<JsonValue>,
+}
+impl TreeBuilder {
+  fn add(&mut self, key: &str, value: JsonValue) {
+    match self.stack.last_mut() {
+      Some((_, JsonValue::Array(values))) => values.push(value),
+      Some((_, JsonValue::Table(members))) => members.push((String::from(key), value)),
+      _ => self.root = Some(value),
+    }
+  }
+}
+impl JsonHandler for TreeBuilder {
+  fn start_sublist(&mut self, name: &str, kind: JsonSublist) {
+    let value = match kind {
+      JsonSublist::Array => JsonValue::Array(Vec::new()),
+      JsonSublist::Table => JsonValue::Table(Vec::new()),
+    };
+    self.stack.push((String::from(name), value));
+  }
+  fn end_sublist(&mut self, _name: &str, _kind: JsonSublist) {
+    if let Some((key, value)) = self.stack.pop() {
+      self.add(&key, value);
+    }
+  }
+  fn table_value(&mut self, key: &str, value: JsonValue) {
+    self.add(key, value)
+  }
+  fn array_value(&mut self, _position: i32, value: JsonValue) {
+    self.add("", value)
+  }
+}
@@ -0,0 +1,142 @@
+use euclid::default::{Point2D, Rect, SideOffsets2D, Size2D, Vector2D};
+
+use crate::display::Display;
+
+/// A point on the edge or center of a `SafeArea` that a widget is positioned relative to.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum Anchor {
+  TopLeft,
+  Top,
+  TopRight,
+  Left,
+  Center,
+  Right,
+  BottomLeft,
+  Bottom,
+  BottomRight,
+}
+impl Anchor {
+  /// Where the anchor falls along each axis, where 0 is the start (left or top), 1 is the middle,
+  /// and 2 is the end (right or bottom).
+  fn axes(self) -> (i32, i32) {
+    match self {
+      Anchor::TopLeft => (0, 0),
+      Anchor::Top => (1, 0),
+      Anchor::TopRight => (2, 0),
+      Anchor::Left => (0, 1),
+      Anchor::Center => (1, 1),
+      Anchor::Right => (2, 1),
+      Anchor::BottomLeft => (0, 2),
+      Anchor::Bottom => (1, 2),
+      Anchor::BottomRight => (2, 2),
+    }
+  }
+}
+
+/// The area of the screen that HUD and other UI elements should be drawn inside, with widgets
+/// positioned relative to its edges by an `Anchor`.
+///
+/// The area is the screen minus safe margins, such as to keep text away from the edges of the
+/// screen, or TV-style overscan margins for when the game is shown on a display that crops its
+/// edges, such as when streaming or mirroring.
+///
+/// When built from the `Display` with `from_display()`, the area is in drawing coordinates for
+/// the current display scale and flip, so the same UI code keeps working when those change.
+///
+/// # Example
+/// ```
+/// let area = SafeArea::from_display(&api.display, SafeArea::overscan_margins(0.05));
+/// let score_size = api.graphics.measure_text(&score, None);
+/// let pos = area.position(Anchor::TopRight, score_size, Vector2D::new(4, 4));
+/// api.graphics.draw_text(&score, pos.x, pos.y)?;
+/// ```
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct SafeArea {
+  rect: Rect<i32>,
+}
+impl SafeArea {
+  /// Constructs a `SafeArea` for a screen of size `screen`, inset by `margins`.
+  pub fn new(screen: Size2D<i32>, margins: SideOffsets2D<i32>) -> Self {
+    SafeArea {
+      rect: Rect::from_size(screen),
+    }
+    .inset(margins)
+  }
+
+  /// Constructs a `SafeArea` for the display's current configuration, inset by `margins`.
+  ///
+  /// The `margins` are given in full-resolution pixels on the physical screen, so they are divided
+  /// by the display scale (rounding up), and swapped to the opposite sides on any axis where the
+  /// display is flipped. A margin that keeps clear of a particular edge of the device stays with
+  /// that edge.
+  pub fn from_display(display: &Display, margins: SideOffsets2D<i32>) -> Self {
+    let scale = display.scale() as i32;
+    let scaled = |m: i32| (m.max(0) + scale - 1) / scale;
+    let (flip_x, flip_y) = display.flipped();
+    let (left, right) = match flip_x {
+      false => (margins.left, margins.right),
+      true => (margins.right, margins.left),
+    };
+    let (top, bottom) = match flip_y {
+      false => (margins.top, margins.bottom),
+      true => (margins.bottom, margins.top),
+    };
+    let margins = SideOffsets2D::new(scaled(top), scaled(right), scaled(bottom), scaled(left));
+    Self::new(Size2D::new(display.width(), display.height()), margins)
+  }
+
+  /// Returns margins that inset each side of the screen by `fraction` (such as 0.05 for 5%) of the
+  /// screen's full-resolution width or height, to use as TV-style overscan margins.
+  pub fn overscan_margins(fraction: f32) -> SideOffsets2D<i32> {
+    let fraction = fraction.clamp(0.0, 0.5);
+    let x = (crate::LCD_COLUMNS as f32 * fraction) as i32;
+    let y = (crate::LCD_ROWS as f32 * fraction) as i32;
+    SideOffsets2D::new(y, x, y, x)
+  }
+
+  /// The rectangle, in drawing coordinates, inside the safe margins.
+  pub fn rect(&self) -> Rect<i32> {
+    self.rect
+  }
+
+  /// Returns a `SafeArea` inside this one, inset further by `margins`, such as for the inside of a
+  /// panel.
+  ///
+  /// The area shrinks to zero size rather than becoming negative if the margins are too large.
+  pub fn inset(&self, margins: SideOffsets2D<i32>) -> SafeArea {
+    let r = self.rect;
+    let width = (r.size.width - margins.left - margins.right).max(0);
+    let height = (r.size.height - margins.top - margins.bottom).max(0);
+    SafeArea {
+      rect: Rect::new(
+        Point2D::new(r.origin.x + margins.left, r.origin.y + margins.top),
+        Size2D::new(width, height),
+      ),
+    }
+  }
+
+  /// Returns the top-left position, in drawing coordinates, for a widget of `size` placed at
+  /// `anchor` in the area.
+  ///
+  /// The `offset` moves the widget inwards from the anchored edges: rightward from the left edge
+  /// and leftward from the right edge, downward from the top edge and upward from the bottom edge.
+  /// On an axis where the widget is centered, the offset moves it rightward or downward.
+  pub fn position(&self, anchor: Anchor, size: Size2D<i32>, offset: Vector2D<i32>) -> Point2D<i32> {
+    let place = |start: i32, length: i32, widget: i32, axis: i32, offset: i32| match axis {
+      0 => start + offset,
+      1 => start + (length - widget) / 2 + offset,
+      _ => start + length - widget - offset,
+    };
+    let (x_axis, y_axis) = anchor.axes();
+    let r = self.rect;
+    Point2D::new(
+      place(r.origin.x, r.size.width, size.width, x_axis, offset.x),
+      place(r.origin.y, r.size.height, size.height, y_axis, offset.y),
+    )
+  }
+  /// Returns the rectangle, in drawing coordinates, for a widget of `size` placed at `anchor` in
+  /// the area. See `position()`.
+  pub fn place(&self, anchor: Anchor, size: Size2D<i32>, offset: Vector2D<i32>) -> Rect<i32> {
+    Rect::new(self.position(anchor, size, offset), size)
+  }
+}
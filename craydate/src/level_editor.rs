@@ -0,0 +1,373 @@
+use alloc::collections::VecDeque;
+use alloc::vec::Vec;
+
+use euclid::default::{Point2D, Rect, Size2D, Vector2D};
+
+use crate::ctypes::*;
+use crate::error::Error;
+use crate::files::File;
+use crate::graphics::{BitmapTable, Color, Graphics, UnownedBitmapRef};
+use crate::inputs::{ButtonEvent, Crank, Inputs};
+use crate::tile_streamer;
+
+/// **Experimental:** The axis that turning the crank moves a `GridCursor` along.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum CursorAxis {
+  Horizontal,
+  Vertical,
+}
+
+/// **Experimental:** A cursor which moves over the cells of a grid, such as the tiles of a level
+/// being edited.
+///
+/// The cursor moves one cell for each push of the d-pad, and is nudged along an axis by turning the
+/// crank, for moving quickly across a large grid.
+#[derive(Debug, Clone)]
+pub struct GridCursor {
+  position: Point2D<i32>,
+  size: Size2D<i32>,
+  crank_axis: CursorAxis,
+  degrees_per_cell: f32,
+  crank_degrees: f32,
+}
+impl GridCursor {
+  /// Constructs a `GridCursor` at the top left of a grid that is `size` cells large.
+  pub fn new(size: Size2D<i32>) -> Self {
+    GridCursor {
+      position: Point2D::zero(),
+      size,
+      crank_axis: CursorAxis::Horizontal,
+      degrees_per_cell: 30.0,
+      crank_degrees: 0.0,
+    }
+  }
+
+  /// Sets the axis the crank moves the cursor along, and how many degrees the crank must turn to
+  /// move it by one cell. The default is horizontally, by one cell for each 30 degrees. Turning
+  /// clockwise moves right or down.
+  pub fn set_crank_nudge(&mut self, axis: CursorAxis, degrees_per_cell: f32) {
+    self.crank_axis = axis;
+    self.degrees_per_cell = degrees_per_cell.max(1.0);
+    self.crank_degrees = 0.0;
+  }
+
+  /// Moves the cursor for the d-pad and crank input in the frame's `inputs`. Returns whether the
+  /// cursor moved.
+  pub fn update(&mut self, inputs: &Inputs) -> bool {
+    let buttons = inputs.buttons();
+    let pushes = |events: &mut dyn Iterator<Item = ButtonEvent>| {
+      events.filter(|e| *e == ButtonEvent::Push).count() as i32
+    };
+    let mut dx = pushes(&mut buttons.right_events()) - pushes(&mut buttons.left_events());
+    let mut dy = pushes(&mut buttons.down_events()) - pushes(&mut buttons.up_events());
+
+    match *inputs.crank() {
+      Crank::Docked => self.crank_degrees = 0.0,
+      Crank::Undocked { change, .. } => {
+        self.crank_degrees += change;
+        let cells = (self.crank_degrees / self.degrees_per_cell) as i32;
+        self.crank_degrees -= cells as f32 * self.degrees_per_cell;
+        match self.crank_axis {
+          CursorAxis::Horizontal => dx += cells,
+          CursorAxis::Vertical => dy += cells,
+        }
+      }
+    }
+
+    let before = self.position;
+    self.set_position(self.position + Vector2D::new(dx, dy));
+    self.position != before
+  }
+
+  /// The cell the cursor is on.
+  pub fn position(&self) -> Point2D<i32> {
+    self.position
+  }
+  /// Moves the cursor to the cell at `position`, which is clamped to the grid.
+  pub fn set_position(&mut self, position: Point2D<i32>) {
+    self.position = Point2D::new(
+      position.x.clamp(0, (self.size.width - 1).max(0)),
+      position.y.clamp(0, (self.size.height - 1).max(0)),
+    );
+  }
+  /// The rectangle covered by the cursor's cell, in pixels, for cells that are `cell_size` pixels
+  /// square.
+  pub fn rect(&self, cell_size: i32) -> Rect<i32> {
+    Rect::new(self.position * cell_size, Size2D::new(cell_size, cell_size))
+  }
+}
+
+/// **Experimental:** A palette of tiles to place in a level, which are the bitmaps of a
+/// `BitmapTable`, with one of them selected.
+#[derive(Debug)]
+pub struct TilePalette<'a> {
+  table: &'a BitmapTable,
+  selected: usize,
+}
+impl<'a> TilePalette<'a> {
+  /// Constructs a `TilePalette` of the bitmaps in `table`, with the first one selected.
+  pub fn new(table: &'a BitmapTable) -> Self {
+    TilePalette { table, selected: 0 }
+  }
+
+  /// The index of the selected tile in the `BitmapTable`.
+  pub fn selected(&self) -> usize {
+    self.selected
+  }
+  /// Selects the tile at `index`, which is clamped to the size of the table.
+  pub fn select(&mut self, index: usize) {
+    self.selected = index.min(self.table.len().saturating_sub(1));
+  }
+  /// Selects the next tile, wrapping around to the first.
+  pub fn select_next(&mut self) {
+    if !self.table.is_empty() {
+      self.selected = (self.selected + 1) % self.table.len();
+    }
+  }
+  /// Selects the previous tile, wrapping around to the last.
+  pub fn select_previous(&mut self) {
+    if !self.table.is_empty() {
+      self.selected = (self.selected + self.table.len() - 1) % self.table.len();
+    }
+  }
+  /// The bitmap of the selected tile.
+  pub fn selected_bitmap(&self) -> Option<UnownedBitmapRef<'a>> {
+    self.table.bitmap(self.selected)
+  }
+
+  /// Draws the tiles in rows of `columns` tiles, with the top left at `origin`, and a box around
+  /// the selected tile.
+  pub fn draw(&self, graphics: &mut Graphics, origin: Point2D<i32>, columns: usize) {
+    let columns = columns.max(1);
+    let mut selected_rect = None;
+    for index in 0..self.table.len() {
+      let bitmap = match self.table.bitmap(index) {
+        Some(bitmap) => bitmap,
+        None => break,
+      };
+      let data = bitmap.data();
+      let (w, h) = (data.width(), data.height());
+      let x = origin.x + (index % columns) as i32 * (w + 2) + 1;
+      let y = origin.y + (index / columns) as i32 * (h + 2) + 1;
+      graphics.draw_bitmap(&bitmap, x, y, BitmapFlip::kBitmapUnflipped);
+      if index == self.selected {
+        selected_rect = Some(Rect::new(
+          Point2D::new(x - 1, y - 1),
+          Size2D::new(w + 2, h + 2),
+        ));
+      }
+    }
+    if let Some(rect) = selected_rect {
+      graphics.draw_rect(rect, Color::from(SolidColor::kColorXOR));
+    }
+  }
+}
+
+/// **Experimental:** A stack of edits that can be undone and redone, for an editor.
+///
+/// The stack holds values describing each edit, and the editor applies or reverts them as they
+/// are returned from `undo()` and `redo()`. Pushing a new edit clears the edits that could be
+/// redone. Once the stack holds `limit` edits, the oldest are forgotten.
+#[derive(Debug, Clone)]
+pub struct UndoStack<T> {
+  undo: VecDeque<T>,
+  redo: Vec<T>,
+  limit: usize,
+}
+impl<T> UndoStack<T> {
+  /// Constructs an empty `UndoStack` which holds up to `limit` edits.
+  pub fn new(limit: usize) -> Self {
+    UndoStack {
+      undo: VecDeque::new(),
+      redo: Vec::new(),
+      limit: limit.max(1),
+    }
+  }
+
+  /// Records an edit that has been made.
+  pub fn push(&mut self, edit: T) {
+    self.redo.clear();
+    if self.undo.len() == self.limit {
+      self.undo.pop_front();
+    }
+    self.undo.push_back(edit);
+  }
+  /// Returns the last edit to be reverted, moving it to be redone later.
+  pub fn undo(&mut self) -> Option<&T> {
+    let edit = self.undo.pop_back()?;
+    self.redo.push(edit);
+    self.redo.last()
+  }
+  /// Returns the last undone edit to be applied again, moving it back to be undone later.
+  pub fn redo(&mut self) -> Option<&T> {
+    let edit = self.redo.pop()?;
+    self.undo.push_back(edit);
+    self.undo.back()
+  }
+
+  /// Whether there is an edit to undo.
+  pub fn can_undo(&self) -> bool {
+    !self.undo.is_empty()
+  }
+  /// Whether there is an edit to redo.
+  pub fn can_redo(&self) -> bool {
+    !self.redo.is_empty()
+  }
+  /// Forgets all edits, such as after loading a different level.
+  pub fn clear(&mut self) {
+    self.undo.clear();
+    self.redo.clear();
+  }
+}
+
+/// **Experimental:** A change to one tile of an `EditableTileMap`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct TileEdit {
+  /// The tile that was changed.
+  pub position: Point2D<i32>,
+  /// The tile id before the change.
+  pub before: u16,
+  /// The tile id after the change.
+  pub after: u16,
+}
+
+/// **Experimental:** A tile map being edited in the game, with undo and redo, which is saved in the
+/// chunk files that a `TileStreamer` reads.
+///
+/// # Example
+/// ```
+/// let tiles = BitmapTable::from_file("images/tiles")?;
+/// let mut palette = TilePalette::new(&tiles);
+/// let mut map = EditableTileMap::new(Size2D::new(64, 64), 0);
+/// let mut cursor = GridCursor::new(map.size());
+/// // Each frame:
+/// cursor.update(&inputs);
+/// if a_pushed {
+///   map.set_tile(cursor.position(), palette.selected() as u16);
+/// }
+/// if b_pushed {
+///   map.undo();
+/// }
+/// // When done:
+/// map.save(&api.file, "levels/custom", 16)?;
+/// ```
+#[derive(Debug, Clone)]
+pub struct EditableTileMap {
+  size: Size2D<i32>,
+  tiles: Vec<u16>,
+  history: UndoStack<TileEdit>,
+}
+impl EditableTileMap {
+  /// The most edits remembered for `undo()`.
+  const UNDO_LIMIT: usize = 256;
+
+  /// Constructs an `EditableTileMap` of `size` tiles, all set to `fill`.
+  pub fn new(size: Size2D<i32>, fill: u16) -> Self {
+    let size = Size2D::new(size.width.max(0), size.height.max(0));
+    EditableTileMap {
+      size,
+      tiles: alloc::vec![fill; (size.width * size.height) as usize],
+      history: UndoStack::new(Self::UNDO_LIMIT),
+    }
+  }
+  /// Loads an `EditableTileMap` of `size` tiles from the chunk files in `dir`, which are
+  /// `chunk_size` tiles square. Tiles in chunks without a file are set to 0.
+  pub fn load(file: &File, dir: &str, size: Size2D<i32>, chunk_size: u32) -> Result<Self, Error> {
+    let mut map = Self::new(size, 0);
+    let chunk_size = chunk_size.max(1) as i32;
+    for (cx, cy) in map.chunks(chunk_size) {
+      let path = tile_streamer::chunk_path(dir, cx, cy);
+      if file.stat(&path).is_err() {
+        continue;
+      }
+      let bytes = file.read_file(&path)?;
+      for (i, b) in bytes.chunks_exact(2).enumerate() {
+        let x = cx * chunk_size + i as i32 % chunk_size;
+        let y = cy * chunk_size + i as i32 / chunk_size;
+        if let Some(index) = map.index(Point2D::new(x, y)) {
+          map.tiles[index] = u16::from_le_bytes([b[0], b[1]]);
+        }
+      }
+    }
+    Ok(map)
+  }
+  /// Saves the map as chunk files in `dir`, which are `chunk_size` tiles square, in the format read
+  /// by `TileStreamer`. The files are written to the game's data folder.
+  pub fn save(&self, file: &File, dir: &str, chunk_size: u32) -> Result<(), Error> {
+    // UNCLEAR: It's not clear if mkdir fails when the folder already exists, so an error is
+    // ignored here, and writing the files will fail if the folder really is missing.
+    let _ = file.make_folder(dir);
+    let chunk_size = chunk_size.max(1) as i32;
+    let mut bytes = Vec::with_capacity((chunk_size * chunk_size * 2) as usize);
+    for (cx, cy) in self.chunks(chunk_size) {
+      bytes.clear();
+      for y in cy * chunk_size..(cy + 1) * chunk_size {
+        for x in cx * chunk_size..(cx + 1) * chunk_size {
+          let tile = self.tile(Point2D::new(x, y)).unwrap_or(0);
+          bytes.extend_from_slice(&tile.to_le_bytes());
+        }
+      }
+      file.write_file(&tile_streamer::chunk_path(dir, cx, cy), &bytes)?;
+    }
+    Ok(())
+  }
+
+  /// The size of the map, in tiles.
+  pub fn size(&self) -> Size2D<i32> {
+    self.size
+  }
+  /// Returns the tile at `position`, or `None` if it is outside the map.
+  pub fn tile(&self, position: Point2D<i32>) -> Option<u16> {
+    self.index(position).map(|index| self.tiles[index])
+  }
+  /// Sets the tile at `position` to `tile`, which can be undone with `undo()`. Does nothing if the
+  /// position is outside the map.
+  pub fn set_tile(&mut self, position: Point2D<i32>, tile: u16) {
+    if let Some(index) = self.index(position) {
+      let before = self.tiles[index];
+      if before != tile {
+        self.tiles[index] = tile;
+        self.history.push(TileEdit {
+          position,
+          before,
+          after: tile,
+        });
+      }
+    }
+  }
+
+  /// Reverts the last change, and returns it.
+  pub fn undo(&mut self) -> Option<TileEdit> {
+    let edit = *self.history.undo()?;
+    if let Some(index) = self.index(edit.position) {
+      self.tiles[index] = edit.before;
+    }
+    Some(edit)
+  }
+  /// Applies the last undone change again, and returns it.
+  pub fn redo(&mut self) -> Option<TileEdit> {
+    let edit = *self.history.redo()?;
+    if let Some(index) = self.index(edit.position) {
+      self.tiles[index] = edit.after;
+    }
+    Some(edit)
+  }
+  /// The history of changes, such as to show if there is anything to undo.
+  pub fn history(&self) -> &UndoStack<TileEdit> {
+    &self.history
+  }
+
+  fn index(&self, position: Point2D<i32>) -> Option<usize> {
+    let inside = position.x >= 0
+      && position.y >= 0
+      && position.x < self.size.width
+      && position.y < self.size.height;
+    inside.then(|| (position.y * self.size.width + position.x) as usize)
+  }
+
+  fn chunks(&self, chunk_size: i32) -> impl Iterator<Item = (i32, i32)> {
+    let columns = (self.size.width + chunk_size - 1) / chunk_size;
+    let rows = (self.size.height + chunk_size - 1) / chunk_size;
+    (0..rows).flat_map(move |cy| (0..columns).map(move |cx| (cx, cy)))
+  }
+}
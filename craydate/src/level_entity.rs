@@ -0,0 +1,443 @@
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use euclid::default::Point2D;
+
+use crate::error::Error;
+
+/// The value of a field on an entity placed in a level editor.
+#[derive(Debug, Clone, PartialEq)]
+pub enum EntityFieldValue {
+  Bool(bool),
+  Int(i32),
+  Float(f32),
+  String(String),
+}
+impl EntityFieldValue {
+  fn type_name(&self) -> &'static str {
+    match self {
+      EntityFieldValue::Bool(_) => bool::TYPE_NAME,
+      EntityFieldValue::Int(_) => i32::TYPE_NAME,
+      EntityFieldValue::Float(_) => f32::TYPE_NAME,
+      EntityFieldValue::String(_) => String::TYPE_NAME,
+    }
+  }
+}
+
+/// An entity placed in a level, as it was written by the level editor: its kind, its position in
+/// pixels, and its fields by name.
+///
+/// Types that implement `LevelEntity` are built from an `EntityRecord` of their kind.
+#[derive(Debug, Clone, PartialEq)]
+pub struct EntityRecord {
+  /// The kind of entity, which is matched against `LevelEntity::KIND`.
+  pub kind: String,
+  /// The position of the entity in the level, in pixels.
+  pub position: Point2D<i32>,
+  /// The fields of the entity, in the order they were written.
+  pub fields: Vec<(String, EntityFieldValue)>,
+}
+impl EntityRecord {
+  /// Constructs an `EntityRecord` with no fields.
+  pub fn new(kind: &str, position: Point2D<i32>) -> Self {
+    EntityRecord {
+      kind: String::from(kind),
+      position,
+      fields: Vec::new(),
+    }
+  }
+
+  /// Returns the value of the field `name`, if the entity has it.
+  pub fn field(&self, name: &str) -> Option<&EntityFieldValue> {
+    self.fields.iter().find(|(n, _)| n == name).map(|(_, value)| value)
+  }
+  /// Sets the field `name` to `value`, replacing any value it had. A `value` which has no field
+  /// value, such as an `Option` that is `None`, removes the field.
+  pub fn set<T: EntityField>(&mut self, name: &str, value: &T) {
+    let index = self.fields.iter().position(|(n, _)| n == name);
+    match (index, value.to_value()) {
+      (Some(i), Some(value)) => self.fields[i].1 = value,
+      (None, Some(value)) => self.fields.push((String::from(name), value)),
+      (Some(i), None) => {
+        self.fields.remove(i);
+      }
+      (None, None) => (),
+    }
+  }
+
+  /// Returns the field `name` converted to a `T`.
+  ///
+  /// Returns an error if the field is missing (unless `T` is an `Option`), or if it holds a value
+  /// of another type.
+  pub fn get<T: EntityField>(&self, name: &'static str) -> Result<T, EntityError> {
+    match self.field(name) {
+      Some(value) => T::from_value(value).ok_or_else(|| EntityError::WrongType {
+        kind: self.kind.clone(),
+        field: name,
+        expected: T::TYPE_NAME,
+        found: value.type_name(),
+      }),
+      None => T::missing().ok_or_else(|| EntityError::MissingField {
+        kind: self.kind.clone(),
+        field: name,
+      }),
+    }
+  }
+  /// Returns the field `name` converted to a `T`, or `T::default()` if the field is missing.
+  ///
+  /// Returns an error if the field holds a value of another type.
+  pub fn get_or_default<T: EntityField + Default>(
+    &self,
+    name: &'static str,
+  ) -> Result<T, EntityError> {
+    match self.field(name) {
+      Some(_) => self.get(name),
+      None => Ok(T::default()),
+    }
+  }
+
+  /// Returns an error if the record is not of the given `kind`.
+  pub fn expect_kind(&self, kind: &'static str) -> Result<(), EntityError> {
+    if self.kind == kind {
+      Ok(())
+    } else {
+      Err(EntityError::WrongKind {
+        expected: kind,
+        found: self.kind.clone(),
+      })
+    }
+  }
+}
+
+/// A type which can be stored in a field of an `EntityRecord`.
+pub trait EntityField: Sized {
+  /// The name of the type, used in error messages.
+  const TYPE_NAME: &'static str;
+
+  /// Converts the field's value to the type, or returns `None` if the value has another type.
+  fn from_value(value: &EntityFieldValue) -> Option<Self>;
+  /// Converts the type to a field value, or returns `None` if the field should be left out.
+  fn to_value(&self) -> Option<EntityFieldValue>;
+  /// The value to use when the field is missing, or `None` if the field is required.
+  fn missing() -> Option<Self> {
+    None
+  }
+}
+impl EntityField for bool {
+  const TYPE_NAME: &'static str = "bool";
+
+  fn from_value(value: &EntityFieldValue) -> Option<Self> {
+    match value {
+      EntityFieldValue::Bool(b) => Some(*b),
+      _ => None,
+    }
+  }
+  fn to_value(&self) -> Option<EntityFieldValue> {
+    Some(EntityFieldValue::Bool(*self))
+  }
+}
+impl EntityField for i32 {
+  const TYPE_NAME: &'static str = "int";
+
+  fn from_value(value: &EntityFieldValue) -> Option<Self> {
+    match value {
+      EntityFieldValue::Int(i) => Some(*i),
+      _ => None,
+    }
+  }
+  fn to_value(&self) -> Option<EntityFieldValue> {
+    Some(EntityFieldValue::Int(*self))
+  }
+}
+impl EntityField for f32 {
+  const TYPE_NAME: &'static str = "float";
+
+  fn from_value(value: &EntityFieldValue) -> Option<Self> {
+    match value {
+      EntityFieldValue::Float(f) => Some(*f),
+      // Editors may write whole numbers without a fractional part.
+      EntityFieldValue::Int(i) => Some(*i as f32),
+      _ => None,
+    }
+  }
+  fn to_value(&self) -> Option<EntityFieldValue> {
+    Some(EntityFieldValue::Float(*self))
+  }
+}
+impl EntityField for String {
+  const TYPE_NAME: &'static str = "string";
+
+  fn from_value(value: &EntityFieldValue) -> Option<Self> {
+    match value {
+      EntityFieldValue::String(s) => Some(s.clone()),
+      _ => None,
+    }
+  }
+  fn to_value(&self) -> Option<EntityFieldValue> {
+    Some(EntityFieldValue::String(self.clone()))
+  }
+}
+impl<T: EntityField> EntityField for Option<T> {
+  const TYPE_NAME: &'static str = T::TYPE_NAME;
+
+  fn from_value(value: &EntityFieldValue) -> Option<Self> {
+    T::from_value(value).map(Some)
+  }
+  fn to_value(&self) -> Option<EntityFieldValue> {
+    self.as_ref().and_then(T::to_value)
+  }
+  fn missing() -> Option<Self> {
+    Some(None)
+  }
+}
+
+/// A type which is placed in levels by a level editor, and built from the `EntityRecord`s in an
+/// `EntityLayer`.
+///
+/// This is usually implemented with `#[derive(LevelEntity)]`, which reads each field of the struct
+/// from the editor field of the same name. The derive accepts these attributes:
+/// * `#[entity(kind = "Name")]` on the struct sets the `KIND`, which is otherwise the name of the
+///   struct.
+/// * `#[entity(rename = "name")]` on a field reads it from the editor field `name`.
+/// * `#[entity(default)]` on a field uses `Default::default()` when the editor field is missing.
+/// * `#[entity(position)]` on a `Point2D<i32>` field stores the entity's position in it.
+///
+/// # Example
+/// ```
+/// #[derive(LevelEntity)]
+/// #[entity(kind = "Door")]
+/// struct Door {
+///   #[entity(position)]
+///   pos: Point2D<i32>,
+///   #[entity(rename = "targetLevel")]
+///   target_level: String,
+///   #[entity(default)]
+///   locked: bool,
+/// }
+///
+/// let layer = EntityLayer::from_bytes(&api.file.read_file("levels/1.entities")?)?;
+/// let doors: Vec<Door> = layer.entities_of_kind()?;
+/// ```
+pub trait LevelEntity: Sized {
+  /// The kind of entity, as named in the level editor.
+  const KIND: &'static str;
+
+  /// Builds the entity from an `EntityRecord`, or returns an error if the record is not of the
+  /// right kind, or its fields are missing or of the wrong type.
+  fn from_record(record: &EntityRecord) -> Result<Self, EntityError>;
+  /// Writes the entity as an `EntityRecord`, such as for saving an edited level.
+  fn to_record(&self) -> EntityRecord;
+}
+
+/// The entities placed in a level, which can be read from and written to the entity layer format
+/// shared with the level editor importers in the `craydate-build` crate.
+///
+/// The format is:
+/// * The bytes `CDEL`, then a version byte, which is currently 1.
+/// * The number of entities as a `u32`, and then each entity: its kind as a string, its position
+///   as two `i32`, the number of fields as a `u16`, and then each field: its name as a string, a
+///   type byte (0 for bool, 1 for int, 2 for float, 3 for string), and the value.
+///
+/// Numbers are little-endian. A bool is a single byte, an int is an `i32`, a float is an `f32`,
+/// and a string is its length in bytes as a `u16` followed by the UTF-8 bytes.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct EntityLayer {
+  /// The entities in the layer, in the order they were written.
+  pub entities: Vec<EntityRecord>,
+}
+impl EntityLayer {
+  /// The bytes at the start of the entity layer format.
+  pub const MAGIC: [u8; 4] = *b"CDEL";
+  /// The version of the entity layer format that is read and written.
+  pub const VERSION: u8 = 1;
+
+  /// Reads an `EntityLayer` from bytes in the entity layer format.
+  pub fn from_bytes(bytes: &[u8]) -> Result<Self, EntityError> {
+    let mut r = Reader { bytes, pos: 0 };
+    if r.take(4)? != Self::MAGIC {
+      return Err(EntityError::Malformed(String::from("not an entity layer")));
+    }
+    let version = r.take(1)?[0];
+    if version != Self::VERSION {
+      return Err(EntityError::Malformed(format!(
+        "unsupported entity layer version {}",
+        version
+      )));
+    }
+    let count = r.u32()?;
+    let mut entities = Vec::new();
+    for _ in 0..count {
+      let kind = r.string()?;
+      let position = Point2D::new(r.i32()?, r.i32()?);
+      let field_count = r.u16()?;
+      let mut fields = Vec::with_capacity(field_count as usize);
+      for _ in 0..field_count {
+        let name = r.string()?;
+        let value = match r.take(1)?[0] {
+          0 => EntityFieldValue::Bool(r.take(1)?[0] != 0),
+          1 => EntityFieldValue::Int(r.i32()?),
+          2 => EntityFieldValue::Float(f32::from_bits(r.u32()?)),
+          3 => EntityFieldValue::String(r.string()?),
+          t => {
+            return Err(EntityError::Malformed(format!(
+              "unknown type {} for field `{}`",
+              t, name
+            )));
+          }
+        };
+        fields.push((name, value));
+      }
+      entities.push(EntityRecord {
+        kind,
+        position,
+        fields,
+      });
+    }
+    if r.pos != bytes.len() {
+      return Err(EntityError::Malformed(String::from(
+        "unexpected bytes after the last entity",
+      )));
+    }
+    Ok(EntityLayer { entities })
+  }
+  /// Writes the `EntityLayer` in the entity layer format.
+  pub fn to_bytes(&self) -> Vec<u8> {
+    let mut out = Vec::new();
+    out.extend_from_slice(&Self::MAGIC);
+    out.push(Self::VERSION);
+    out.extend_from_slice(&(self.entities.len() as u32).to_le_bytes());
+    for entity in &self.entities {
+      write_string(&mut out, &entity.kind);
+      out.extend_from_slice(&entity.position.x.to_le_bytes());
+      out.extend_from_slice(&entity.position.y.to_le_bytes());
+      out.extend_from_slice(&(entity.fields.len() as u16).to_le_bytes());
+      for (name, value) in &entity.fields {
+        write_string(&mut out, name);
+        match value {
+          EntityFieldValue::Bool(b) => out.extend_from_slice(&[0, *b as u8]),
+          EntityFieldValue::Int(i) => {
+            out.push(1);
+            out.extend_from_slice(&i.to_le_bytes());
+          }
+          EntityFieldValue::Float(f) => {
+            out.push(2);
+            out.extend_from_slice(&f.to_bits().to_le_bytes());
+          }
+          EntityFieldValue::String(s) => {
+            out.push(3);
+            write_string(&mut out, s);
+          }
+        }
+      }
+    }
+    out
+  }
+
+  /// Builds every entity of kind `T::KIND` in the layer, in the order they were written.
+  ///
+  /// Returns the first error from building an entity, so that a level which does not match the
+  /// game's code is found when it's loaded.
+  pub fn entities_of_kind<T: LevelEntity>(&self) -> Result<Vec<T>, EntityError> {
+    self.entities.iter().filter(|e| e.kind == T::KIND).map(T::from_record).collect()
+  }
+  /// Adds the `entity` to the end of the layer.
+  pub fn push<T: LevelEntity>(&mut self, entity: &T) {
+    self.entities.push(entity.to_record())
+  }
+}
+
+fn write_string(out: &mut Vec<u8>, s: &str) {
+  // Strings longer than a u16 can hold are cut short, at a char boundary.
+  let mut len = s.len().min(u16::MAX as usize);
+  while !s.is_char_boundary(len) {
+    len -= 1;
+  }
+  out.extend_from_slice(&(len as u16).to_le_bytes());
+  out.extend_from_slice(&s.as_bytes()[..len]);
+}
+
+struct Reader<'a> {
+  bytes: &'a [u8],
+  pos: usize,
+}
+impl<'a> Reader<'a> {
+  fn take(&mut self, len: usize) -> Result<&'a [u8], EntityError> {
+    let end = self.pos + len;
+    if end > self.bytes.len() {
+      return Err(EntityError::Malformed(String::from(
+        "the entity layer ends early",
+      )));
+    }
+    let bytes = &self.bytes[self.pos..end];
+    self.pos = end;
+    Ok(bytes)
+  }
+  fn u16(&mut self) -> Result<u16, EntityError> {
+    let b = self.take(2)?;
+    Ok(u16::from_le_bytes([b[0], b[1]]))
+  }
+  fn u32(&mut self) -> Result<u32, EntityError> {
+    let b = self.take(4)?;
+    Ok(u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+  }
+  fn i32(&mut self) -> Result<i32, EntityError> {
+    Ok(self.u32()? as i32)
+  }
+  fn string(&mut self) -> Result<String, EntityError> {
+    let len = self.u16()? as usize;
+    let bytes = self.take(len)?;
+    core::str::from_utf8(bytes)
+      .map(String::from)
+      .map_err(|_| EntityError::Malformed(String::from("a string is not valid UTF-8")))
+  }
+}
+
+/// An error from reading an `EntityLayer` or building a `LevelEntity` from it.
+#[derive(Debug, Clone, PartialEq)]
+pub enum EntityError {
+  /// The bytes are not a valid entity layer, as described by the contained string.
+  Malformed(String),
+  /// An entity was built from a record of another kind.
+  WrongKind {
+    expected: &'static str,
+    found: String,
+  },
+  /// A required field is missing from an entity.
+  MissingField { kind: String, field: &'static str },
+  /// A field of an entity holds a value of the wrong type.
+  WrongType {
+    kind: String,
+    field: &'static str,
+    expected: &'static str,
+    found: &'static str,
+  },
+}
+impl core::fmt::Display for EntityError {
+  fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+    match self {
+      EntityError::Malformed(s) => write!(f, "malformed entity layer: {}", s),
+      EntityError::WrongKind { expected, found } => {
+        write!(f, "expected a `{}` entity, found `{}`", expected, found)
+      }
+      EntityError::MissingField { kind, field } => {
+        write!(f, "`{}` entity is missing the field `{}`", kind, field)
+      }
+      EntityError::WrongType {
+        kind,
+        field,
+        expected,
+        found,
+      } => write!(
+        f,
+        "`{}` entity field `{}` should be {} but is {}",
+        kind, field, expected, found
+      ),
+    }
+  }
+}
+impl From<EntityError> for Error {
+  fn from(e: EntityError) -> Self {
+    Error::String(format!("{}", e))
+  }
+}
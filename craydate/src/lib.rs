@@ -16,7 +16,8 @@
 //! 
 //! This crate uses unstable features in order to provide a `#![no_std]` application to run on the
 //! Playdate simulator and hardware device. Thus it requires use of the [Rust nightly
-//! compiler](https://doc.rust-lang.org/1.2.0/book/nightly-rust.html).
+//! compiler](https://doc.rust-lang.org/1.2.0/book/nightly-rust.html). The nightly compiler must be
+//! at least version 1.87, which is the `rust-version` of the crate.
 //! 
 //! # Getting Started
 //! 
@@ -158,6 +159,24 @@
 //! Logging to the Playdate simulator's console, for debugging, is possible through the
 //! `craydate::log()` and `craydate::log_error()` functions.
 //! 
+//! # Stability
+//! 
+//! The core APIs for drawing, sound, input, files and the system are stable: they follow semantic
+//! versioning, and breaking changes to them only happen in a new minor version (while the crate is
+//! below 1.0).
+//! 
+//! Large new subsystems may first ship as experimental APIs, so that they can be tried out and
+//! improved based on feedback before their design is settled. Experimental APIs are only available
+//! when the `unstable-api` feature is enabled, and they can change or be removed in any release:
+//! ```toml
+//! [dependencies]
+//! craydate = { version = "0.1", features = ["unstable-api"] }
+//! ```
+//! 
+//! The experimental APIs are currently `Scoreboards` (and the `Api::scoreboards` field),
+//! `SoakTest`, `ReplayViewer`, `DeterminismVerifier`, `PerformanceGovernor`, `TileStreamer`, and
+//! the level editing types such as `EditableTileMap`.
+//! 
 //! # Platforms
 //! 
 //! **Currently the craydate project only supports development for the Windows simulator.** We will
@@ -216,26 +235,55 @@ extern crate craydate_macro;
 /// }
 /// ```
 pub use craydate_macro::main;
+/// Derives the `LevelEntity` trait for a struct. See `LevelEntity` for the attributes it accepts.
+pub use craydate_macro::LevelEntity;
+/// Embeds a folder of files in the game's binary. See `EmbeddedAssets` for how to unpack them.
+///
+/// # Example
+/// ```
+/// static DEFAULTS: craydate::EmbeddedAssets = craydate::include_assets!("defaults");
+/// ```
+pub use craydate_macro::include_assets;
 
 mod allocator;
 mod api;
+mod asset;
+mod assets;
 mod callback_builder;
 mod callbacks;
 mod capi_state;
+mod cheat_menu;
 mod clamped_float;
+mod combinators;
+mod cooldown;
 mod ctypes;
 mod ctypes_enums;
 mod display;
+mod embedded_assets;
 mod error;
 mod executor;
+#[cfg(test)]
+mod fake_capi;
+mod file_logger;
 mod files;
+mod frame_interpolation;
+mod frame_profiler;
 mod geometry;
 mod graphics;
 mod inputs;
+mod interval;
+mod json;
+mod layout;
+mod level_entity;
+mod localization;
 mod log;
 mod menu;
 mod null_terminated;
+mod save_store;
+mod small_string;
 mod sound;
+mod stability;
+mod string_interner;
 mod system;
 mod system_event;
 mod time;
@@ -248,27 +296,68 @@ pub mod macro_helpers;
 /// `extern crate alloc` elsewhere.
 pub use alloc::{borrow::ToOwned, format, string::String};
 
+pub use allocator::MemoryStats;
 pub use api::*;
+pub use assets::{AssetLocation, AssetOrder, Assets};
 pub use callback_builder::{CallbackBuilder, CallbackBuilderWithArg};
 pub use callbacks::Callbacks;
+#[cfg(feature = "cheats")]
+pub use cheat_menu::CheatMenu;
 pub use clamped_float::*;
+pub use combinators::{join2, join3, select, Either};
+pub use cooldown::{Cooldown, TimedUnlock};
 pub use ctypes_enums::*;
 pub use display::*;
+pub use embedded_assets::{EmbeddedAsset, EmbeddedAssets};
 pub use error::*;
+pub use file_logger::FileLogger;
 pub use files::*;
+pub use frame_interpolation::*;
+pub use frame_profiler::{FrameProfiler, FrameStage, FrameSummary};
 pub use geometry::*;
 pub use graphics::*;
 pub use inputs::*;
+pub use interval::{every, every_n_frames, FrameInterval, TimeInterval};
+pub use json::{decode_json, decode_json_str, JsonHandler, JsonSublist, JsonValue};
+pub use layout::{Anchor, SafeArea};
+pub use level_entity::{
+  EntityError, EntityField, EntityFieldValue, EntityLayer, EntityRecord, LevelEntity,
+};
+pub use localization::StringTables;
 pub use log::{log, log_error};
 pub use menu::*;
+pub use save_store::{SaveData, SaveStore};
+pub use small_string::SmallString;
 pub use sound::*;
+pub use string_interner::{InternedStr, StringInterner};
 pub use system::*;
 pub use system_event::*;
 pub use time::*;
 
+stability::experimental! {
+  mod determinism;
+  mod level_editor;
+  mod performance_governor;
+  mod replay;
+  mod scoreboards;
+  mod soak_test;
+  mod tile_streamer;
+
+  pub use determinism::*;
+  pub use level_editor::{
+    CursorAxis, EditableTileMap, GridCursor, TileEdit, TilePalette, UndoStack,
+  };
+  pub use performance_governor::PerformanceGovernor;
+  pub use replay::*;
+  pub use scoreboards::{Board, Score, Scoreboards, ScoresList};
+  pub use soak_test::{SoakReport, SoakTest};
+  pub use tile_streamer::TileStreamer;
+}
+
 /// The global allocator, which will defer allocation requests to the Playdate system, and deal with
 /// ensuring correct alignment.
-#[global_allocator]
+// Tests run on the host, with the allocator from std.
+#[cfg_attr(not(test), global_allocator)]
 static mut GLOBAL_ALLOCATOR: allocator::Allocator = allocator::Allocator::new();
 
 /// A helper implementation of panic_handler for the toplevel crate to forward to.
@@ -277,6 +366,9 @@ static mut GLOBAL_ALLOCATOR: allocator::Allocator = allocator::Allocator::new();
 /// easy by letting them simply forward over to this function.
 #[cfg(not(all(target_arch = "arm", target_os = "none")))]
 pub fn panic_handler(panic_info: &core::panic::PanicInfo) -> ! {
+  #[cfg(feature = "unstable-api")]
+  crate::soak_test::write_panic_report(panic_info);
+
   crate::log::log_to_stdout("panic!");
   if let Some(loc) = panic_info.location() {
     crate::log::log_to_stdout(" at ");
@@ -309,6 +401,7 @@ pub fn panic_handler(_panic_info: &core::panic::PanicInfo) -> ! {
 }
 
 /// The error handler for when allocations fail. It will simply panic.
+#[cfg(not(test))]
 #[alloc_error_handler]
 fn craydate_alloc_error_handler(layout: core::alloc::Layout) -> ! {
   panic!(
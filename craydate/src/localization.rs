@@ -0,0 +1,79 @@
+use alloc::vec::Vec;
+
+use crate::ctypes_enums::Language;
+use crate::system::System;
+
+/// A set of string tables, one for each language the game is translated to, with one of them
+/// selected to look up the game's text in.
+///
+/// The table type `T` is up to the game, such as a struct with a field for each string, or a map
+/// from keys to strings. The first table given is the fallback, which is selected when the game has
+/// no table for a language.
+///
+/// # Example
+/// ```
+/// struct Strings { start: &'static str, quit: &'static str }
+/// let mut strings = StringTables::new(Language::kPDLanguageEnglish, Strings {
+///   start: "Start",
+///   quit: "Quit",
+/// });
+/// strings.add(Language::kPDLanguageJapanese, Strings { start: "スタート", quit: "やめる" });
+/// strings.select_system_language(&api.system);
+/// api.graphics.draw_text(strings.current().start, 10, 10)?;
+/// ```
+#[derive(Debug)]
+pub struct StringTables<T> {
+  tables: Vec<(Language, T)>,
+  current: usize,
+}
+impl<T> StringTables<T> {
+  /// Constructs a set of string tables with the `fallback` table for the `language`, which is
+  /// selected.
+  pub fn new(language: Language, fallback: T) -> Self {
+    StringTables {
+      tables: alloc::vec![(language, fallback)],
+      current: 0,
+    }
+  }
+
+  /// Adds the `table` for the `language`, replacing any table the language already had.
+  pub fn add(&mut self, language: Language, table: T) {
+    match self.tables.iter_mut().find(|(l, _)| *l == language) {
+      Some((_, existing)) => *existing = table,
+      None => self.tables.push((language, table)),
+    }
+  }
+  /// Returns whether there is a table for the `language`.
+  pub fn contains(&self, language: Language) -> bool {
+    self.tables.iter().any(|(l, _)| *l == language)
+  }
+
+  /// Selects the table for the `language`, or the fallback table if there is none.
+  ///
+  /// Returns whether there was a table for the `language`.
+  pub fn select(&mut self, language: Language) -> bool {
+    let found = self.tables.iter().position(|(l, _)| *l == language);
+    self.current = found.unwrap_or(0);
+    found.is_some()
+  }
+  /// Selects the table for the language the device is set to, as given by
+  /// `System::get_language()`, or the fallback table if there is none.
+  ///
+  /// Returns whether there was a table for the device's language.
+  pub fn select_system_language(&mut self, system: &System) -> bool {
+    self.select(system.get_language())
+  }
+
+  /// Returns the language of the selected table.
+  pub fn language(&self) -> Language {
+    self.tables[self.current].0
+  }
+  /// Returns the selected table.
+  pub fn current(&self) -> &T {
+    &self.tables[self.current].1
+  }
+  /// Returns the table for the `language`, if there is one.
+  pub fn get(&self, language: Language) -> Option<&T> {
+    self.tables.iter().find(|(l, _)| *l == language).map(|(_, t)| t)
+  }
+}
@@ -12,6 +12,7 @@ pub fn log<S: alloc::string::ToString>(s: S) {
       let string = s.to_string();
       let vec = string.to_null_terminated_utf8();
       unsafe { capi.csystem.logToConsole.unwrap()(vec.as_ptr()) };
+      crate::file_logger::mirror("", &string);
       #[cfg(not(all(target_arch = "arm", target_os = "none")))]
       {
         log_to_stdout("LOG: ");
@@ -38,6 +39,8 @@ pub fn log_error<S: alloc::string::ToString>(s: S) {
     Some(capi) => {
       let string = s.to_string();
       let vec = string.to_null_terminated_utf8();
+      // Written to the file first, as Playdate pauses on the error.
+      crate::file_logger::mirror("ERROR: ", &string);
       unsafe { capi.csystem.error.unwrap()(vec.as_ptr()) };
       #[cfg(not(all(target_arch = "arm", target_os = "none")))]
       {
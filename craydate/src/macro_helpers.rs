@@ -83,6 +83,7 @@ pub mod __private {
         Executor::wake_system_wakers(CApiState::get().executor);
       }
       CSystemEvent::kEventLock => {
+        CApiState::get().update_audio_for_event(&SystemEvent::WillLock);
         CApiState::get().add_system_event(SystemEvent::WillLock);
         Executor::wake_system_wakers(CApiState::get().executor);
       }
@@ -91,18 +92,27 @@ pub mod __private {
         Executor::wake_system_wakers(CApiState::get().executor);
       }
       CSystemEvent::kEventPause => {
+        CApiState::get().update_audio_for_event(&SystemEvent::WillPause);
         CApiState::get().add_system_event(SystemEvent::WillPause);
         Executor::wake_system_wakers(CApiState::get().executor);
       }
       CSystemEvent::kEventResume => {
+        CApiState::get().update_audio_for_event(&SystemEvent::WillResume);
         CApiState::get().add_system_event(SystemEvent::WillResume);
         Executor::wake_system_wakers(CApiState::get().executor);
       }
       CSystemEvent::kEventTerminate => {
+        // The handler is taken out to run it, as it may use the `System`, and the game is closing
+        // so it will not be called again.
+        let handler = CApiState::get().terminate_handler.borrow_mut().take();
+        if let Some(mut handler) = handler {
+          handler();
+        }
         CApiState::get().add_system_event(SystemEvent::WillTerminate);
         Executor::wake_system_wakers(CApiState::get().executor);
       }
       CSystemEvent::kEventUnlock => {
+        CApiState::get().update_audio_for_event(&SystemEvent::DidUnlock);
         CApiState::get().add_system_event(SystemEvent::DidUnlock);
         Executor::wake_system_wakers(CApiState::get().executor);
       }
@@ -124,8 +134,19 @@ pub mod __private {
     // without missing the first frame.
     Executor::poll_futures(capi.executor);
 
+    // The game has finished drawing the frame, so mark any rows it drew to as updated, and keep the
+    // areas it drew to for the next frame.
+    capi.dirty_rects.borrow_mut().finish_frame();
+
     capi.frame_number.set(capi.frame_number.get() + 1);
 
+    let now =
+      TimeTicks::from_milliseconds(unsafe { capi.csystem.getCurrentTimeMilliseconds.unwrap()() });
+    let delta = match capi.last_frame_time.replace(Some(now)) {
+      Some(last) => now - last,
+      None => TimeDelta::from_milliseconds(0),
+    };
+
     // Capture input state which will be returned from any futures waiting for the update_callback().
     // So this must happen before we wake those futures.
     let buttons_set = unsafe {
@@ -139,12 +160,32 @@ pub mod __private {
     };
     capi.set_current_frame_button_state(buttons_set);
 
+    // Report a low battery before the frame, so the game can react to it before drawing.
+    if let Some(percentage) = capi.check_low_battery() {
+      capi.add_system_event(SystemEvent::LowBattery { percentage });
+      Executor::wake_system_wakers(capi.executor);
+      // The event is dropped if nothing was waiting for the next system event to take it.
+      capi.system_event_watcher_state.borrow().next_event.take();
+    }
+
+    // Report the crank being docked or undocked before the frame, as Playdate has no event for it.
+    if let Some(docked) = capi.check_crank_docked() {
+      capi.add_system_event(match docked {
+        true => SystemEvent::CrankDocked,
+        false => SystemEvent::CrankUndocked,
+      });
+      Executor::wake_system_wakers(capi.executor);
+      // The event is dropped if nothing was waiting for the next system event to take it.
+      capi.system_event_watcher_state.borrow().next_event.take();
+    }
+
     CApiState::get().add_system_event(SystemEvent::NextFrame {
       frame_number: capi.frame_number.get(),
       inputs: Inputs::new(
         capi.peripherals_enabled.get(),
         &capi.button_state_per_frame.get().map(|b| b.unwrap()),
       ),
+      delta,
     });
     Executor::wake_system_wakers(capi.executor);
 
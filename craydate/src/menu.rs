@@ -1,16 +1,111 @@
+use alloc::collections::BTreeMap;
+use alloc::string::String;
 use alloc::vec::Vec;
 use core::ffi::c_void;
+use core::future::Future;
 use core::marker::PhantomData;
+use core::pin::Pin;
 use core::ptr::NonNull;
+use core::task::{Context, Poll};
 
 use crate::callbacks::RegisteredCallback;
 use crate::callback_builder::*;
 use crate::capi_state::CApiState;
 use crate::ctypes::*;
+use crate::executor::Executor;
 use crate::null_terminated::ToNullTerminatedString;
 
 /// A callback builder for a closure to be called on menu events.
-pub type MenuCallback<'a, T, F, S> = CallbackBuilder<'a, T, F, NoNull, S>;
+///
+/// The callback may be `MenuCallback::none()`, in which case changes to the menu item can be waited
+/// for with `next_change()` instead.
+pub type MenuCallback<'a, T, F, S> = CallbackBuilder<'a, T, F, AllowNull, S>;
+
+/// A C callback function given to Playdate for a `MenuItem`.
+type MenuCallbackFn = unsafe extern "C" fn(*mut c_void);
+
+#[derive(Debug, Default)]
+struct MenuEventState {
+  // How many times the menu item's callback has been called.
+  count: u64,
+  // The C function for the user's callback, registered in their `Callbacks`, if any.
+  forward: Option<MenuCallbackFn>,
+}
+
+/// Counts the callbacks of each `MenuItem`, so that futures can wait for them.
+///
+/// The callback given to Playdate is always `on_menu_item_changed()`, which forwards the event on
+/// to the user's callback in their `Callbacks` when they have given one.
+#[derive(Debug, Default)]
+pub(crate) struct MenuEvents {
+  states: BTreeMap<usize, MenuEventState>,
+}
+impl MenuEvents {
+  pub fn new() -> Self {
+    MenuEvents::default()
+  }
+
+  fn watch(key: usize, forward: Option<MenuCallbackFn>) {
+    let mut events = CApiState::get().menu_events.borrow_mut();
+    events.states.insert(key, MenuEventState { count: 0, forward });
+  }
+  fn unwatch(key: usize) {
+    let mut events = CApiState::get().menu_events.borrow_mut();
+    events.states.remove(&key);
+  }
+  fn count(key: usize) -> Option<u64> {
+    let events = CApiState::get().menu_events.borrow();
+    events.states.get(&key).map(|state| state.count)
+  }
+  fn on_event(key: usize) -> Option<Option<MenuCallbackFn>> {
+    let mut events = CApiState::get().menu_events.borrow_mut();
+    let state = events.states.get_mut(&key)?;
+    state.count += 1;
+    Some(state.forward)
+  }
+}
+
+extern "C" fn on_menu_item_changed(userdata: *mut c_void) {
+  // The borrow on the `MenuEvents` is released before forwarding, as the user's callback may add or
+  // remove menu items.
+  match MenuEvents::on_event(userdata as usize) {
+    Some(Some(forward)) => unsafe { forward(userdata) },
+    // Without a user callback, the executor is woken so that futures waiting on the menu item see
+    // the change.
+    Some(None) => Executor::wake_system_wakers(CApiState::get().executor),
+    None => (),
+  }
+}
+
+/// A future for which poll() waits for a `MenuItem`'s callback to be called after the future was
+/// constructed, then returns Complete.
+///
+/// The future is also complete if the `MenuItem` is destroyed, since it can then never change.
+struct MenuChangeFuture {
+  key: usize,
+  count: Option<u64>,
+}
+impl MenuChangeFuture {
+  fn new(key: usize) -> Self {
+    MenuChangeFuture {
+      key,
+      count: MenuEvents::count(key),
+    }
+  }
+}
+impl Future for MenuChangeFuture {
+  type Output = ();
+
+  fn poll(self: Pin<&mut Self>, ctxt: &mut Context<'_>) -> Poll<Self::Output> {
+    match (self.count, MenuEvents::count(self.key)) {
+      (Some(before), Some(now)) if now == before => {
+        Executor::add_waker_for_system_event(CApiState::get().executor, ctxt.waker());
+        Poll::Pending
+      }
+      _ => Poll::Ready(()),
+    }
+  }
+}
 
 static mut MENU_KEY: usize = 0;
 /// Makes a unique id to pass as a "userdata" key to determine which callback is being called.
@@ -21,6 +116,22 @@ fn make_callback_key() -> usize {
   }
 }
 
+/// Registers the `callback`, if any, under a new key, and starts counting the menu item's events.
+fn register_callback<'a, T, F: Fn(T) + 'static>(
+  callback: MenuCallback<'a, T, F, Constructed>,
+) -> (usize, Option<RegisteredCallback>) {
+  let key = make_callback_key();
+  let (forward, reg) = match callback.into_inner() {
+    Some((callbacks, cb)) => {
+      let (func, reg) = callbacks.add_menu_item(key, cb);
+      (Some(func), Some(reg))
+    }
+    None => (None, None),
+  };
+  MenuEvents::watch(key, forward);
+  (key, reg)
+}
+
 pub enum Action {}
 pub enum Checkmark {}
 pub enum Options {}
@@ -29,7 +140,9 @@ pub enum AnyType {}
 /// A system menu item. The game can specify up to 3 custom menu items in the system menu.
 pub struct MenuItem<Type = AnyType> {
   ptr: NonNull<CMenuItem>,
-  _callback: RegisteredCallback, // Holds ownership of the closure.
+  _callback: Option<RegisteredCallback>, // Holds ownership of the closure.
+  // The userdata key that the menu item was added with, which finds its callback and events.
+  key: usize,
   _marker: PhantomData<Type>,
 }
 
@@ -61,19 +174,18 @@ impl MenuItem {
     title: &str,
     callback: MenuCallback<'a, T, F, Constructed>,
   ) -> MenuItem<Action> {
-    let key = make_callback_key();
-    let (callbacks, cb) = callback.into_inner().unwrap();
-    let (func, reg) = callbacks.add_menu_item(key, cb);
+    let (key, reg) = register_callback(callback);
     let ptr = unsafe {
       Self::fns().addMenuItem.unwrap()(
         title.to_null_terminated_utf8().as_ptr(),
-        Some(func),
+        Some(on_menu_item_changed),
         key as *mut c_void,
       )
     };
     MenuItem {
       ptr: NonNull::new(ptr).unwrap(),
       _callback: reg,
+      key,
       _marker: PhantomData,
     }
   }
@@ -108,20 +220,19 @@ impl MenuItem {
     intially_checked: bool,
     callback: MenuCallback<'a, T, F, Constructed>,
   ) -> MenuItem<Checkmark> {
-    let key = make_callback_key();
-    let (callbacks, cb) = callback.into_inner().unwrap();
-    let (func, reg) = callbacks.add_menu_item(key, cb);
+    let (key, reg) = register_callback(callback);
     let ptr = unsafe {
       Self::fns().addCheckmarkMenuItem.unwrap()(
         title.to_null_terminated_utf8().as_ptr(),
         intially_checked as i32,
-        Some(func),
+        Some(on_menu_item_changed),
         key as *mut c_void,
       )
     };
     MenuItem {
       ptr: NonNull::new(ptr).unwrap(),
       _callback: reg,
+      key,
       _marker: PhantomData,
     }
   }
@@ -156,24 +267,12 @@ impl MenuItem {
     options: impl IntoIterator<Item = &'a str>,
     callback: MenuCallback<'a, T, F, Constructed>,
   ) -> MenuItem<Options> {
-    let key = make_callback_key();
-    let (callbacks, cb) = callback.into_inner().unwrap();
-    let (func, reg) = callbacks.add_menu_item(key, cb);
-    let options_null_terminated: Vec<_> =
-      options.into_iter().map(|o| o.to_null_terminated_utf8()).collect();
-    let options_pointers: Vec<_> = options_null_terminated.iter().map(|o| o.as_ptr()).collect();
-    let ptr = unsafe {
-      Self::fns().addOptionsMenuItem.unwrap()(
-        title.to_null_terminated_utf8().as_ptr(),
-        options_pointers.as_ptr() as *mut *const u8,
-        options_pointers.len() as i32,
-        Some(func),
-        key as *mut c_void,
-      )
-    };
+    let (key, reg) = register_callback(callback);
+    let ptr = add_options_menu_item(title, options, key);
     MenuItem {
       ptr: NonNull::new(ptr).unwrap(),
       _callback: reg,
+      key,
       _marker: PhantomData,
     }
   }
@@ -205,7 +304,31 @@ impl<T> MenuItem<T> {
   }
 }
 
+impl MenuItem<Action> {
+  /// Waits until the action menu item is chosen.
+  ///
+  /// This works whether or not a callback was given when constructing the menu item, and the
+  /// callback is still called. It also completes if the menu item is destroyed first.
+  ///
+  /// # Example
+  /// ```
+  /// let restart = MenuItem::new_action("restart", MenuCallback::none());
+  /// restart.next_change().await;
+  /// ```
+  pub async fn next_change(&self) {
+    MenuChangeFuture::new(self.key).await
+  }
+}
+
 impl MenuItem<Checkmark> {
+  /// Waits until the checkmark menu item is changed, and returns if it is now checked.
+  ///
+  /// This works whether or not a callback was given when constructing the menu item, and the
+  /// callback is still called.
+  pub async fn next_change(&self) -> bool {
+    MenuChangeFuture::new(self.key).await;
+    self.checked()
+  }
   /// Returns if the checkmark menu item was checked when the menu was closed.
   pub fn checked(&self) -> bool {
     // getMenuItemValue takes a mutable pointer but doesn't write to its data.
@@ -218,6 +341,15 @@ impl MenuItem<Checkmark> {
 }
 
 impl MenuItem<Options> {
+  /// Waits until a different option is chosen in the menu item, and returns the index of the option
+  /// that is now selected.
+  ///
+  /// This works whether or not a callback was given when constructing the menu item, and the
+  /// callback is still called.
+  pub async fn next_change(&self) -> i32 {
+    MenuChangeFuture::new(self.key).await;
+    self.value()
+  }
   /// Returns the index of the option that was selected when the menu was closed.
   pub fn value(&self) -> i32 {
     // getMenuItemValue takes a mutable pointer but doesn't write to its data.
@@ -227,10 +359,127 @@ impl MenuItem<Options> {
   pub fn set_value(&mut self, value: i32) {
     unsafe { Self::fns().setMenuItemValue.unwrap()(self.cptr_mut(), value) }
   }
+
+  /// Replaces the options that can be chosen in the menu item.
+  ///
+  /// The selected index is kept if it is still one of the options, otherwise the last option is
+  /// selected. The callback given when constructing the menu item remains registered.
+  ///
+  /// BUG: Playdate has no function to change the options of a menu item, so the menu item is
+  /// removed and added again with the new options. This can move it to the end of the system menu.
+  pub fn set_options<'a>(&mut self, options: impl IntoIterator<Item = &'a str>) {
+    let title = String::from(self.title());
+    let value = self.value();
+    unsafe { Self::fns().removeMenuItem.unwrap()(self.cptr_mut()) };
+    let options: Vec<&str> = options.into_iter().collect();
+    let ptr = add_options_menu_item(&title, options.iter().copied(), self.key);
+    self.ptr = NonNull::new(ptr).unwrap();
+    self.set_value(value.clamp(0, (options.len() as i32 - 1).max(0)));
+  }
+}
+
+/// An options menu item where each option maps to a value of type `T`.
+///
+/// The options are given as pairs of a title to show in the menu and the value it stands for. The
+/// selected value is found with `selected()`, and the options can be rebuilt while the game runs
+/// with `set_choices()`.
+///
+/// # Example
+/// ```
+/// #[derive(Clone)]
+/// enum Difficulty { Easy, Normal, Hard }
+/// let difficulty = OptionsMenuItem::new(
+///   "difficulty",
+///   [("easy", Difficulty::Easy), ("normal", Difficulty::Normal), ("hard", Difficulty::Hard)],
+///   MenuCallback::with(&mut callbacks).call(|_| {}),
+/// );
+/// let chosen: Option<Difficulty> = difficulty.selected();
+/// ```
+pub struct OptionsMenuItem<T: Clone> {
+  item: MenuItem<Options>,
+  values: Vec<T>,
+}
+impl<T: Clone> OptionsMenuItem<T> {
+  /// Construct a new options menu item from pairs of option titles and values, and add it to the
+  /// system menu as long as the `OptionsMenuItem` stays alive.
+  ///
+  /// The callback is registered in the same way as for `MenuItem::new_options()`.
+  pub fn new<'a, U, F: Fn(U) + 'static>(
+    title: &str,
+    choices: impl IntoIterator<Item = (&'a str, T)>,
+    callback: MenuCallback<'a, U, F, Constructed>,
+  ) -> Self {
+    let (names, values): (Vec<&str>, Vec<T>) = choices.into_iter().unzip();
+    OptionsMenuItem {
+      item: MenuItem::new_options(title, names, callback),
+      values,
+    }
+  }
+
+  /// Returns the value of the option that was selected when the menu was closed, or `None` if there
+  /// are no options.
+  pub fn selected(&self) -> Option<T> {
+    usize::try_from(self.item.value()).ok().and_then(|i| self.values.get(i)).cloned()
+  }
+  /// Returns the index of the option that was selected when the menu was closed.
+  pub fn selected_index(&self) -> usize {
+    self.item.value().max(0) as usize
+  }
+  /// Sets the index of the option to be selected when the menu is next opened.
+  pub fn set_selected_index(&mut self, index: usize) {
+    self.item.set_value(index as i32)
+  }
+  /// Waits until a different option is chosen in the menu item, and returns the value of the option
+  /// that is now selected.
+  pub async fn next_change(&self) -> Option<T> {
+    self.item.next_change().await;
+    self.selected()
+  }
+  /// Returns the values of the options, in the order they appear in the menu.
+  pub fn choices(&self) -> &[T] {
+    &self.values
+  }
+  /// Replaces the options that can be chosen with pairs of option titles and values.
+  ///
+  /// This rebuilds the menu item as with `MenuItem::set_options()`.
+  pub fn set_choices<'a>(&mut self, choices: impl IntoIterator<Item = (&'a str, T)>) {
+    let (names, values): (Vec<&str>, Vec<T>) = choices.into_iter().unzip();
+    self.item.set_options(names);
+    self.values = values;
+  }
+
+  /// The options menu item, such as to change its title.
+  pub fn menu_item(&self) -> &MenuItem<Options> {
+    &self.item
+  }
+  /// The options menu item, such as to change its title.
+  pub fn menu_item_mut(&mut self) -> &mut MenuItem<Options> {
+    &mut self.item
+  }
+}
+
+fn add_options_menu_item<'a>(
+  title: &str,
+  options: impl IntoIterator<Item = &'a str>,
+  key: usize,
+) -> *mut CMenuItem {
+  let options_null_terminated: Vec<_> =
+    options.into_iter().map(|o| o.to_null_terminated_utf8()).collect();
+  let options_pointers: Vec<_> = options_null_terminated.iter().map(|o| o.as_ptr()).collect();
+  unsafe {
+    MenuItem::<Options>::fns().addOptionsMenuItem.unwrap()(
+      title.to_null_terminated_utf8().as_ptr(),
+      options_pointers.as_ptr() as *mut *const u8,
+      options_pointers.len() as i32,
+      Some(on_menu_item_changed),
+      key as *mut c_void,
+    )
+  }
 }
 
 impl<Type> Drop for MenuItem<Type> {
   fn drop(&mut self) {
     unsafe { Self::fns().removeMenuItem.unwrap()(self.cptr_mut()) };
+    MenuEvents::unwatch(self.key);
   }
 }
@@ -0,0 +1,157 @@
+use alloc::boxed::Box;
+use alloc::collections::VecDeque;
+use alloc::string::String;
+use alloc::vec::Vec;
+
+struct DegradationStep {
+  name: String,
+  degrade: Box<dyn FnMut()>,
+  restore: Box<dyn FnMut()>,
+}
+
+/// **Experimental:** Keeps the frame rate stable by turning off expensive work when frames take too
+/// long, and turning it back on when there is time to spare.
+///
+/// The game registers degradation steps with `add_step()`, such as disabling particles or playing
+/// music with fewer voices, and reports how long each frame took with `record_frame()`. When the
+/// average frame time goes over budget, the next step is degraded. When the average drops well
+/// under budget, the most recently degraded step is restored. Steps are degraded in the order they
+/// were added, so the first step added should be the one the player would miss least.
+///
+/// After each change, the governor waits for a full window of new frame times before deciding
+/// again, so that the effect of the change is measured before another is made.
+///
+/// # Example
+/// ```
+/// let mut governor = PerformanceGovernor::for_refresh_rate(30.0);
+/// governor.add_step("particles", || particles.disable(), || particles.enable());
+/// loop {
+///   let timer = api.system.start_timer();
+///   update_and_draw();
+///   governor.record_frame(timer.elapsed_microseconds());
+/// }
+/// ```
+pub struct PerformanceGovernor {
+  budget_micros: u32,
+  headroom: f32,
+  window: usize,
+  frame_times: VecDeque<u32>,
+  steps: Vec<DegradationStep>,
+  degraded: usize,
+}
+impl PerformanceGovernor {
+  /// Constructs a `PerformanceGovernor` where each frame has `budget_micros` microseconds to run.
+  pub fn new(budget_micros: u32) -> Self {
+    PerformanceGovernor {
+      budget_micros,
+      headroom: 0.75,
+      window: 30,
+      frame_times: VecDeque::new(),
+      steps: Vec::new(),
+      degraded: 0,
+    }
+  }
+  /// Constructs a `PerformanceGovernor` whose budget is the time between frames at `refresh_rate`
+  /// frames per second, as set with `Display::set_refresh_rate()`.
+  pub fn for_refresh_rate(refresh_rate: f32) -> Self {
+    assert!(refresh_rate > 0.0);
+    Self::new((1_000_000.0 / refresh_rate) as u32)
+  }
+
+  /// Sets the number of frames whose times are averaged to decide if the game is over budget. The
+  /// default is 30 frames.
+  pub fn set_window(&mut self, frames: usize) {
+    assert!(frames > 0);
+    self.window = frames;
+    while self.frame_times.len() > frames {
+      self.frame_times.pop_front();
+    }
+  }
+  /// Sets the fraction of the budget that the average frame time must fall under before a step is
+  /// restored. The default is 0.75.
+  ///
+  /// A step is only restored when it's likely to fit in the budget again, so that the governor does
+  /// not flip back and forth between degrading and restoring the same step.
+  pub fn set_headroom(&mut self, fraction: f32) {
+    self.headroom = fraction.clamp(0.0, 1.0);
+  }
+
+  /// Registers a degradation step, identified by `name`. The `degrade` function is called to turn
+  /// off work when over budget, and `restore` is called to turn it back on.
+  pub fn add_step<D: FnMut() + 'static, R: FnMut() + 'static>(
+    &mut self,
+    name: &str,
+    degrade: D,
+    restore: R,
+  ) {
+    self.steps.push(DegradationStep {
+      name: String::from(name),
+      degrade: Box::new(degrade),
+      restore: Box::new(restore),
+    })
+  }
+
+  /// Reports how long the last frame took to run, in microseconds, and degrades or restores a
+  /// step if needed.
+  pub fn record_frame(&mut self, frame_micros: u32) {
+    if self.frame_times.len() == self.window {
+      self.frame_times.pop_front();
+    }
+    self.frame_times.push_back(frame_micros);
+    if self.frame_times.len() < self.window {
+      return;
+    }
+
+    let average = self.average_frame_micros().unwrap();
+    if average > self.budget_micros && self.degraded < self.steps.len() {
+      (self.steps[self.degraded].degrade)();
+      self.degraded += 1;
+      self.frame_times.clear();
+    } else if (average as f32) < self.budget_micros as f32 * self.headroom && self.degraded > 0 {
+      self.degraded -= 1;
+      (self.steps[self.degraded].restore)();
+      self.frame_times.clear();
+    }
+  }
+
+  /// Restores all degraded steps, such as when starting a new level where the load may be
+  /// different.
+  pub fn restore_all(&mut self) {
+    while self.degraded > 0 {
+      self.degraded -= 1;
+      (self.steps[self.degraded].restore)();
+    }
+    self.frame_times.clear();
+  }
+
+  /// The average time of the recorded frames in the window, in microseconds, or `None` if no
+  /// frames have been recorded since the last change.
+  pub fn average_frame_micros(&self) -> Option<u32> {
+    match self.frame_times.len() {
+      0 => None,
+      len => Some((self.frame_times.iter().map(|t| *t as u64).sum::<u64>() / len as u64) as u32),
+    }
+  }
+  /// The per-frame budget, in microseconds.
+  pub fn budget_micros(&self) -> u32 {
+    self.budget_micros
+  }
+  /// The names of the steps which are currently degraded, in the order they were degraded.
+  pub fn degraded_steps(&self) -> impl Iterator<Item = &str> {
+    self.steps[..self.degraded].iter().map(|s| s.name.as_str())
+  }
+  /// Whether any step is currently degraded.
+  pub fn is_degraded(&self) -> bool {
+    self.degraded > 0
+  }
+}
+
+impl core::fmt::Debug for PerformanceGovernor {
+  fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+    f.debug_struct("PerformanceGovernor")
+      .field("budget_micros", &self.budget_micros)
+      .field("average_frame_micros", &self.average_frame_micros())
+      .field("degraded", &self.degraded_steps().collect::<Vec<_>>())
+      .finish()
+  }
+}
@@ -0,0 +1,183 @@
+use alloc::collections::VecDeque;
+
+use crate::inputs::Crank;
+
+/// **Experimental:** Records the last few seconds of gameplay, and plays them back with rewind and
+/// fast-forward.
+///
+/// The game's state is recorded as a snapshot of type `S` every few frames, along with the game's
+/// input of type `I` for every frame. Any frame in the recording can then be rebuilt by starting
+/// from the nearest earlier snapshot and stepping the game forward with the recorded inputs. This
+/// keeps memory use low while still allowing any frame to be viewed.
+///
+/// The game must be deterministic for a replay to be accurate: stepping the game from a snapshot
+/// with the same inputs must always produce the same state.
+///
+/// # Example
+/// ```
+/// let mut replay = ReplayViewer::new(30 * 10, 30);
+/// // Each frame, during gameplay.
+/// replay.record(&state, input);
+/// step_game(&mut state, &input);
+///
+/// // To view the replay, seek with the crank and draw the rebuilt state.
+/// replay.start_viewing();
+/// replay.scrub_with_crank(inputs.crank(), 90);
+/// let viewed_state = replay.state_at_cursor(step_game);
+/// ```
+#[derive(Debug)]
+pub struct ReplayViewer<S, I> {
+  capacity_frames: usize,
+  snapshot_interval: usize,
+  /// The index of the frame at the front of `inputs`. Frame indices count from the first frame
+  /// ever recorded.
+  first_frame: u64,
+  inputs: VecDeque<I>,
+  /// Snapshots along with the frame index they were taken at, oldest first. The state is the one
+  /// from before that frame's input was applied.
+  snapshots: VecDeque<(u64, S)>,
+  /// The frame being viewed, when viewing the replay.
+  cursor: Option<u64>,
+  /// Crank movement that has not yet added up to a whole frame of scrubbing.
+  crank_remainder: f32,
+}
+impl<S: Clone, I: Clone> ReplayViewer<S, I> {
+  /// Constructs a `ReplayViewer` that keeps the last `capacity_frames` frames of gameplay, and
+  /// snapshots the game's state every `snapshot_interval` frames.
+  ///
+  /// A smaller `snapshot_interval` uses more memory but makes seeking faster, as fewer frames need
+  /// to be stepped from the snapshot.
+  pub fn new(capacity_frames: usize, snapshot_interval: usize) -> Self {
+    assert!(snapshot_interval > 0);
+    ReplayViewer {
+      capacity_frames: capacity_frames.max(snapshot_interval),
+      snapshot_interval,
+      first_frame: 0,
+      inputs: VecDeque::new(),
+      snapshots: VecDeque::new(),
+      cursor: None,
+      crank_remainder: 0.0,
+    }
+  }
+
+  /// Records a frame of gameplay, where `state` is the game's state before the frame's `input` is
+  /// applied to it.
+  ///
+  /// Frames that are older than the capacity of the `ReplayViewer` are dropped, a whole snapshot
+  /// interval at a time. Recording is ignored while viewing the replay.
+  pub fn record(&mut self, state: &S, input: I) {
+    if self.is_viewing() {
+      return;
+    }
+    let frame = self.end_frame();
+    if self.inputs.len().is_multiple_of(self.snapshot_interval) {
+      self.snapshots.push_back((frame, state.clone()));
+    }
+    self.inputs.push_back(input);
+
+    // Drop a whole snapshot interval at a time, so that the oldest frame always has a snapshot.
+    while self.inputs.len() > self.capacity_frames + self.snapshot_interval {
+      self.snapshots.pop_front();
+      self.inputs.drain(..self.snapshot_interval);
+      self.first_frame += self.snapshot_interval as u64;
+    }
+  }
+
+  /// Drops all recorded frames, and stops viewing.
+  pub fn clear(&mut self) {
+    self.first_frame = self.end_frame();
+    self.inputs.clear();
+    self.snapshots.clear();
+    self.cursor = None;
+  }
+
+  /// The number of frames that are recorded and can be viewed.
+  pub fn len(&self) -> usize {
+    self.inputs.len()
+  }
+  /// Whether there are no recorded frames.
+  pub fn is_empty(&self) -> bool {
+    self.inputs.is_empty()
+  }
+
+  /// Starts viewing the replay, at the most recently recorded frame.
+  pub fn start_viewing(&mut self) {
+    if !self.is_empty() {
+      self.cursor = Some(self.end_frame() - 1);
+      self.crank_remainder = 0.0;
+    }
+  }
+  /// Stops viewing the replay, leaving the recording as it was.
+  pub fn stop_viewing(&mut self) {
+    self.cursor = None;
+  }
+  /// Whether the replay is being viewed.
+  pub fn is_viewing(&self) -> bool {
+    self.cursor.is_some()
+  }
+
+  /// The position of the frame being viewed in the recording, where 0 is the oldest recorded
+  /// frame, or `None` if the replay is not being viewed.
+  pub fn cursor(&self) -> Option<usize> {
+    self.cursor.map(|c| (c - self.first_frame) as usize)
+  }
+
+  /// Moves the frame being viewed by `frames`, which rewinds when negative and fast-forwards when
+  /// positive. The movement stops at the ends of the recording.
+  pub fn seek(&mut self, frames: i64) {
+    if let Some(cursor) = self.cursor {
+      let last = self.end_frame() as i64 - 1;
+      let to = (cursor as i64 + frames).clamp(self.first_frame as i64, last);
+      self.cursor = Some(to as u64);
+    }
+  }
+
+  /// Moves the frame being viewed with the crank, where turning the crank clockwise fast-forwards
+  /// and counter-clockwise rewinds by `frames_per_revolution` frames for each full turn.
+  pub fn scrub_with_crank(&mut self, crank: &Crank, frames_per_revolution: u32) {
+    if let Crank::Undocked { change, .. } = crank {
+      let frames = self.crank_remainder + change / 360.0 * frames_per_revolution as f32;
+      let whole = frames as i64;
+      self.crank_remainder = frames - whole as f32;
+      self.seek(whole);
+    }
+  }
+
+  /// Rebuilds the game's state at the frame being viewed, by stepping the nearest earlier snapshot
+  /// forward with `step` and the recorded inputs.
+  ///
+  /// The returned state is from before the viewed frame's input was applied. Returns `None` if the
+  /// replay is not being viewed.
+  pub fn state_at_cursor<F: FnMut(&mut S, &I)>(&self, mut step: F) -> Option<S> {
+    let cursor = self.cursor?;
+    let (snapshot_frame, snapshot) = self.snapshots.iter().rev().find(|(f, _)| *f <= cursor)?;
+    let mut state = snapshot.clone();
+    let from = (snapshot_frame - self.first_frame) as usize;
+    let to = (cursor - self.first_frame) as usize;
+    for input in self.inputs.range(from..to) {
+      step(&mut state, input);
+    }
+    Some(state)
+  }
+
+  /// Stops viewing and returns the game's state at the frame being viewed, so that gameplay can
+  /// continue from there.
+  ///
+  /// All frames recorded after the viewed frame are dropped. Returns `None` if the replay is not
+  /// being viewed.
+  pub fn resume_from_cursor<F: FnMut(&mut S, &I)>(&mut self, step: F) -> Option<S> {
+    let state = self.state_at_cursor(step)?;
+    let cursor = self.cursor.take()?;
+    self.inputs.truncate((cursor - self.first_frame) as usize);
+    // Snapshots from the cursor's frame on are of the dropped frames. Snapshots stay every
+    // `snapshot_interval` frames from the oldest frame, so `record()` takes the next one when it
+    // reaches the next multiple, and until then frames are rebuilt from the last kept snapshot.
+    self.snapshots.retain(|(f, _)| *f < cursor);
+    Some(state)
+  }
+
+  /// The frame index that the next recorded frame will have.
+  fn end_frame(&self) -> u64 {
+    self.first_frame + self.inputs.len() as u64
+  }
+}
@@ -0,0 +1,191 @@
+use alloc::boxed::Box;
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::marker::PhantomData;
+
+use crate::error::Error;
+use crate::files::File;
+
+/// A type which can be saved by a `SaveStore`.
+///
+/// The game chooses how its save data is encoded, such as by writing each field in order, or by
+/// calling an encoder from another crate.
+pub trait SaveData: Sized {
+  /// The version of the encoding made by `to_save_bytes()`.
+  ///
+  /// When the encoding changes in an update to the game, increase the version, and add a migration
+  /// to the `SaveStore` with `SaveStore::add_migration()` from the previous version, so that the
+  /// saves made before the update can still be loaded.
+  const VERSION: u32 = 1;
+
+  /// Encodes the data to bytes to be saved.
+  fn to_save_bytes(&self) -> Vec<u8>;
+  /// Decodes the data from bytes made by `to_save_bytes()`, or returns an error if they are not
+  /// valid.
+  fn from_save_bytes(bytes: &[u8]) -> Result<Self, Error>;
+}
+impl SaveData for Vec<u8> {
+  fn to_save_bytes(&self) -> Vec<u8> {
+    self.clone()
+  }
+  fn from_save_bytes(bytes: &[u8]) -> Result<Self, Error> {
+    Ok(bytes.into())
+  }
+}
+impl SaveData for String {
+  fn to_save_bytes(&self) -> Vec<u8> {
+    self.as_bytes().into()
+  }
+  fn from_save_bytes(bytes: &[u8]) -> Result<Self, Error> {
+    Ok(core::str::from_utf8(bytes).map_err(|_| "SaveStore: the save is not UTF-8")?.into())
+  }
+}
+
+/// Converts the bytes of a save from one version to the next.
+type Migration = Box<dyn Fn(&[u8]) -> Result<Vec<u8>, Error>>;
+
+/// Saves and loads a game's save data of type `T` in named slots, in a folder in the game's data
+/// folder.
+///
+/// Each slot is saved to its own file with `File::write_atomic()`, so that if the device loses
+/// power while saving, the slot still holds the previous save.
+///
+/// Saves are written with the `SaveData::VERSION` they were encoded with. When a save from an older
+/// version is loaded, it is passed through each migration from its version up to the current
+/// version before being decoded. Saves written before versions were recorded are treated as
+/// version 1.
+///
+/// # Example
+/// ```
+/// let saves = SaveStore::<MySave>::new(&api.file, "saves")?;
+/// saves.save(&api.file, "slot1", &my_save)?;
+/// for slot in saves.list_slots(&api.file)? {
+///   let save = saves.load(&api.file, &slot)?;
+/// }
+/// ```
+///
+/// When `MySave::VERSION` is 3, migrations are needed from versions 1 and 2:
+/// ```
+/// let mut saves = SaveStore::<MySave>::new(&api.file, "saves")?;
+/// saves.add_migration(1, |v1| Ok(add_coins_field(v1)));
+/// saves.add_migration(2, |v2| Ok(rename_level_field(v2)));
+/// ```
+pub struct SaveStore<T: SaveData> {
+  folder: String,
+  migrations: Vec<(u32, Migration)>,
+  _marker: PhantomData<T>,
+}
+impl<T: SaveData> SaveStore<T> {
+  const EXTENSION: &'static str = ".sav";
+  // Marks a save that begins with its version.
+  const MAGIC: &'static [u8; 4] = b"CDSV";
+
+  /// Constructs a `SaveStore` which keeps its slots in `folder` in the game's data folder, making
+  /// the folder if it does not exist.
+  pub fn new(file: &File, folder: &str) -> Result<Self, Error> {
+    let folder = String::from(folder.trim_end_matches('/'));
+    file.make_folders(&folder)?;
+    Ok(SaveStore {
+      folder,
+      migrations: Vec::new(),
+      _marker: PhantomData,
+    })
+  }
+
+  /// Adds a migration which converts the bytes of a save at `from_version` to the bytes of a save
+  /// at `from_version + 1`.
+  ///
+  /// Adding a second migration from the same version replaces the first.
+  pub fn add_migration<F>(&mut self, from_version: u32, migrate: F)
+  where
+    F: Fn(&[u8]) -> Result<Vec<u8>, Error> + 'static,
+  {
+    self.migrations.retain(|(version, _)| *version != from_version);
+    self.migrations.push((from_version, Box::new(migrate)));
+  }
+
+  /// Saves the `data` to the `slot`, replacing any save already in it.
+  ///
+  /// Slot names are used as file names, so they should not contain a '/'.
+  pub fn save(&self, file: &File, slot: &str, data: &T) -> Result<(), Error> {
+    let mut bytes = Vec::from(&Self::MAGIC[..]);
+    bytes.extend_from_slice(&T::VERSION.to_le_bytes());
+    bytes.extend_from_slice(&data.to_save_bytes());
+    Ok(file.write_atomic(&self.path(slot), &bytes)?)
+  }
+  /// Loads the save in the `slot`, migrating it first if it was saved with an older version.
+  ///
+  /// Returns an error if there is no save in the slot, if the save is from a newer version, if a
+  /// migration is missing or fails, or if `SaveData::from_save_bytes()` fails.
+  ///
+  /// The migrated save is not written back to the slot until it is saved again.
+  pub fn load(&self, file: &File, slot: &str) -> Result<T, Error> {
+    let bytes = file.read_file(&self.path(slot))?;
+    let (mut version, payload) = Self::split_version(&bytes);
+    if version > T::VERSION {
+      return Err(format!("SaveStore: the save is from a newer version {}", version).into());
+    }
+    let mut migrated = None;
+    while version < T::VERSION {
+      let migrate = match self.migrations.iter().find(|(from, _)| *from == version) {
+        Some((_, migrate)) => migrate,
+        None => return Err(format!("SaveStore: no migration from version {}", version).into()),
+      };
+      migrated = Some(migrate(migrated.as_deref().unwrap_or(payload))?);
+      version += 1;
+    }
+    T::from_save_bytes(migrated.as_deref().unwrap_or(payload))
+  }
+  /// Returns the version that the save in the `slot` was saved with.
+  pub fn saved_version(&self, file: &File, slot: &str) -> Result<u32, Error> {
+    Ok(Self::split_version(&file.read_file(&self.path(slot))?).0)
+  }
+  /// Deletes the save in the `slot`.
+  pub fn delete(&self, file: &File, slot: &str) -> Result<(), Error> {
+    Ok(file.delete(&self.path(slot))?)
+  }
+  /// Returns whether there is a save in the `slot`.
+  pub fn contains(&self, file: &File, slot: &str) -> bool {
+    file.exists(&self.path(slot))
+  }
+  /// Returns the names of the slots which have a save in them, in sorted order.
+  pub fn list_slots(&self, file: &File) -> Result<Vec<String>, Error> {
+    let mut slots: Vec<String> = file
+      .list(&self.folder)?
+      .filter(|entry| !entry.is_folder)
+      .filter_map(|entry| entry.name.strip_suffix(Self::EXTENSION).map(String::from))
+      .collect();
+    slots.sort();
+    Ok(slots)
+  }
+
+  /// The folder in the game's data folder where the slots are saved.
+  pub fn folder(&self) -> &str {
+    &self.folder
+  }
+
+  fn path(&self, slot: &str) -> String {
+    format!("{}/{}{}", self.folder, slot, Self::EXTENSION)
+  }
+  /// Splits a save into its version and the bytes from `SaveData::to_save_bytes()`.
+  fn split_version(bytes: &[u8]) -> (u32, &[u8]) {
+    match bytes.strip_prefix(&Self::MAGIC[..]) {
+      Some(rest) if rest.len() >= 4 => {
+        let (version, payload) = rest.split_at(4);
+        (u32::from_le_bytes(version.try_into().unwrap()), payload)
+      }
+      // Saves from before versions were recorded.
+      _ => (1, bytes),
+    }
+  }
+}
+impl<T: SaveData> core::fmt::Debug for SaveStore<T> {
+  fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+    let versions: Vec<u32> = self.migrations.iter().map(|(version, _)| *version).collect();
+    f.debug_struct("SaveStore")
+      .field("folder", &self.folder)
+      .field("migrations", &versions)
+      .finish()
+  }
+}
@@ -0,0 +1,449 @@
+use alloc::collections::BTreeMap;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::future::Future;
+use core::pin::Pin;
+use core::task::{Context, Poll};
+
+use crate::capi_state::CApiState;
+use crate::ctypes::*;
+use crate::error::Error;
+use crate::executor::Executor;
+use crate::null_terminated::{ToNullTerminatedString, parse_null_terminated_utf8};
+use crate::time::WallClockTime;
+
+/// **Experimental:** A score on a scoreboard.
+#[derive(Debug, Clone)]
+pub struct Score {
+  /// The score's position on the scoreboard, starting at 1.
+  pub rank: u32,
+  /// The score's value.
+  pub value: u32,
+  /// The name of the player who made the score.
+  pub player: String,
+}
+impl Score {
+  /// SAFETY: The `score` must be a valid pointer to a score from Playdate.
+  unsafe fn from_c(score: &CScore) -> Self {
+    Score {
+      rank: score.rank,
+      value: score.value,
+      player: c_string(score.player),
+    }
+  }
+}
+
+/// **Experimental:** The top scores on a scoreboard.
+#[derive(Debug, Clone)]
+pub struct ScoresList {
+  /// The id of the scoreboard.
+  pub board_id: String,
+  /// When the scores were last updated on the server.
+  pub last_updated: WallClockTime,
+  /// Whether the player's own score is included in `scores`.
+  pub player_included: bool,
+  /// The most scores that can be returned.
+  pub limit: u32,
+  /// The scores, in order of their rank.
+  pub scores: Vec<Score>,
+}
+
+/// **Experimental:** A scoreboard that the game has in the Catalog.
+#[derive(Debug, Clone)]
+pub struct Board {
+  /// The id of the scoreboard, which is given to the other `Scoreboards` functions.
+  pub board_id: String,
+  /// The name of the scoreboard, to show to the player.
+  pub name: String,
+}
+
+/// A scoreboard request which is waiting to be sent to Playdate, or for its response.
+#[derive(Debug)]
+struct Waiting {
+  ticket: u64,
+  board_id: String,
+  /// Whether the request was sent to Playdate.
+  sent: bool,
+  /// Whether the future for the request was dropped, so its response should be thrown away.
+  dropped: bool,
+}
+
+/// The requests and results of one type of scoreboard request.
+///
+/// Playdate's scoreboard callbacks are not given any userdata to tell which request they are for.
+/// Only the scores callback says which scoreboard it is for, so only one scores request is sent at
+/// a time for each scoreboard, and only one request of each other type is sent at a time. Other
+/// requests wait to be sent until the response for the one before them arrives, which makes it
+/// clear which request each response is for.
+#[derive(Debug)]
+struct Requests<T> {
+  /// Whether responses say which scoreboard they are for, so that requests for different
+  /// scoreboards can be sent at the same time.
+  keyed_by_board: bool,
+  next_ticket: u64,
+  waiting: Vec<Waiting>,
+  results: BTreeMap<u64, Result<T, String>>,
+}
+impl<T> Requests<T> {
+  fn new(keyed_by_board: bool) -> Self {
+    Requests {
+      keyed_by_board,
+      next_ticket: 0,
+      waiting: Vec::new(),
+      results: BTreeMap::new(),
+    }
+  }
+
+  fn issue(&mut self, board_id: &str) -> u64 {
+    self.next_ticket += 1;
+    self.waiting.push(Waiting {
+      ticket: self.next_ticket,
+      board_id: String::from(board_id),
+      sent: false,
+      dropped: false,
+    });
+    self.next_ticket
+  }
+  /// Returns true if the request for `ticket` should be sent now, and marks it as sent. Returns
+  /// false if it was already sent, or if it has to wait for an earlier request to get a response.
+  fn start_sending(&mut self, ticket: u64) -> bool {
+    let i = match self.waiting.iter().position(|w| w.ticket == ticket) {
+      Some(i) => i,
+      None => return false,
+    };
+    let keyed_by_board = self.keyed_by_board;
+    let board_id = &self.waiting[i].board_id;
+    let blocked =
+      self.waiting.iter().any(|w| w.sent && (!keyed_by_board || w.board_id == *board_id));
+    if self.waiting[i].sent || blocked {
+      return false;
+    }
+    self.waiting[i].sent = true;
+    true
+  }
+  /// Stores the `result` for the request that was sent for `board_id`. The `board_id` is `None`
+  /// for responses that don't say which scoreboard they are for.
+  fn complete(&mut self, board_id: Option<&str>, result: Result<T, String>) {
+    let found = self
+      .waiting
+      .iter()
+      .position(|w| w.sent && board_id.is_none_or(|board_id| w.board_id == board_id));
+    if let Some(i) = found {
+      let waiting = self.waiting.remove(i);
+      if !waiting.dropped {
+        self.results.insert(waiting.ticket, result);
+      }
+    }
+  }
+  /// Forgets the request for `ticket`, whose future was dropped. If the request was sent, its
+  /// response is thrown away when it arrives.
+  fn drop_ticket(&mut self, ticket: u64) {
+    self.results.remove(&ticket);
+    if let Some(i) = self.waiting.iter().position(|w| w.ticket == ticket) {
+      if self.waiting[i].sent {
+        self.waiting[i].dropped = true;
+      } else {
+        self.waiting.remove(i);
+      }
+    }
+  }
+}
+
+/// The scoreboard requests waiting for a response from the server.
+#[derive(Debug)]
+pub(crate) struct ScoreboardRequests {
+  add_score: Requests<Score>,
+  personal_best: Requests<Score>,
+  boards: Requests<Vec<Board>>,
+  scores: Requests<ScoresList>,
+}
+impl ScoreboardRequests {
+  pub fn new() -> Self {
+    ScoreboardRequests {
+      add_score: Requests::new(false),
+      personal_best: Requests::new(false),
+      boards: Requests::new(false),
+      scores: Requests::new(true),
+    }
+  }
+}
+
+/// **Experimental:** Access to the online scoreboards of games in the Catalog.
+///
+/// Each function sends a request to the server and returns a future which completes when the
+/// response arrives, which can take some time. The scoreboards must be set up for the game in the
+/// Catalog for the requests to succeed.
+///
+/// # Example
+/// ```
+/// match api.scoreboards.add_score("highscores", score).await {
+///   Ok(score) => log(format!("ranked {}", score.rank)),
+///   Err(_) => log("score will be sent later"),
+/// }
+/// ```
+#[derive(Debug)]
+pub struct Scoreboards;
+impl Scoreboards {
+  pub(crate) fn new() -> Self {
+    Scoreboards
+  }
+
+  /// Adds the `value` as a score for the player to the scoreboard with the id `board_id`, and
+  /// returns the score as added to the scoreboard.
+  ///
+  /// Returns an error with the message from Playdate if the score could not be added.
+  pub async fn add_score(&self, board_id: &str, value: u32) -> Result<Score, Error> {
+    ScoreboardFuture::new(
+      board_id,
+      |r| &mut r.add_score,
+      move |board_id| unsafe {
+        Self::fns().addScore.unwrap()(board_id.as_ptr(), value, Some(on_add_score));
+      },
+    )
+    .await
+  }
+
+  /// Returns the player's best score on the scoreboard with the id `board_id`.
+  ///
+  /// Returns an error with the message from Playdate if the score could not be found, such as when
+  /// the player has no score on the scoreboard.
+  pub async fn personal_best(&self, board_id: &str) -> Result<Score, Error> {
+    ScoreboardFuture::new(
+      board_id,
+      |r| &mut r.personal_best,
+      |board_id| unsafe {
+        Self::fns().getPersonalBest.unwrap()(board_id.as_ptr(), Some(on_personal_best));
+      },
+    )
+    .await
+  }
+
+  /// Returns the scoreboards of the game.
+  pub async fn boards(&self) -> Result<Vec<Board>, Error> {
+    ScoreboardFuture::new(
+      "",
+      |r| &mut r.boards,
+      |_| unsafe {
+        Self::fns().getScoreboards.unwrap()(Some(on_boards));
+      },
+    )
+    .await
+  }
+
+  /// Returns the top scores on the scoreboard with the id `board_id`.
+  pub async fn scores(&self, board_id: &str) -> Result<ScoresList, Error> {
+    ScoreboardFuture::new(
+      board_id,
+      |r| &mut r.scores,
+      |board_id| unsafe {
+        Self::fns().getScores.unwrap()(board_id.as_ptr(), Some(on_scores));
+      },
+    )
+    .await
+  }
+
+  pub(crate) fn fns() -> &'static CScoreboardsApi {
+    CApiState::get().cscoreboards
+  }
+}
+
+fn requests() -> core::cell::RefMut<'static, ScoreboardRequests> {
+  CApiState::get().scoreboard_requests.borrow_mut()
+}
+
+/// Copies a string from Playdate, which is empty if the pointer is null.
+///
+/// SAFETY: The pointer must be null or point to a null-terminated string.
+unsafe fn c_string(p: *const u8) -> String {
+  if p.is_null() {
+    String::new()
+  } else {
+    String::from(parse_null_terminated_utf8(p).unwrap_or(""))
+  }
+}
+
+/// Returns the error message given to a callback, if any.
+///
+/// SAFETY: The pointer must be null or point to a null-terminated string.
+unsafe fn c_error(message: *const u8) -> Option<String> {
+  match message.is_null() {
+    true => None,
+    false => Some(c_string(message)),
+  }
+}
+
+/// Makes the result of a callback which gives a single score, and frees the score.
+///
+/// SAFETY: The pointers must be null or valid pointers given to the callback by Playdate.
+unsafe fn score_result(score: *mut CScore, message: *const u8) -> Result<Score, String> {
+  let result = match (c_error(message), score.as_ref()) {
+    (Some(error), _) => Err(error),
+    (None, Some(s)) => Ok(Score::from_c(s)),
+    (None, None) => Err(String::from("No score was returned")),
+  };
+  if !score.is_null() {
+    Scoreboards::fns().freeScore.unwrap()(score);
+  }
+  result
+}
+
+unsafe extern "C" fn on_add_score(score: *mut CScore, message: *const u8) {
+  let result = score_result(score, message);
+  requests().add_score.complete(None, result);
+}
+unsafe extern "C" fn on_personal_best(score: *mut CScore, message: *const u8) {
+  let result = score_result(score, message);
+  requests().personal_best.complete(None, result);
+}
+unsafe extern "C" fn on_boards(boards: *mut CBoardsList, message: *const u8) {
+  let result = match (c_error(message), boards.as_ref()) {
+    (Some(error), _) => Err(error),
+    (None, Some(list)) => {
+      let boards = match list.boards.is_null() {
+        true => &[][..],
+        false => core::slice::from_raw_parts(list.boards, list.count as usize),
+      };
+      Ok(
+        boards
+          .iter()
+          .map(|b| Board {
+            board_id: c_string(b.boardID),
+            name: c_string(b.name),
+          })
+          .collect(),
+      )
+    }
+    (None, None) => Ok(Vec::new()),
+  };
+  if !boards.is_null() {
+    Scoreboards::fns().freeBoardsList.unwrap()(boards);
+  }
+  requests().boards.complete(None, result);
+}
+unsafe extern "C" fn on_scores(scores: *mut CScoresList, message: *const u8) {
+  let board_id = scores.as_ref().map(|list| c_string(list.boardID));
+  let result = match (c_error(message), scores.as_ref()) {
+    (Some(error), _) => Err(error),
+    (None, Some(list)) => {
+      let scores = match list.scores.is_null() {
+        true => &[][..],
+        false => core::slice::from_raw_parts(list.scores, list.count as usize),
+      };
+      Ok(ScoresList {
+        board_id: c_string(list.boardID),
+        last_updated: WallClockTime::from_seconds_since_epoch(list.lastUpdated),
+        player_included: list.playerIncluded != 0,
+        limit: list.limit,
+        scores: scores.iter().map(|s| Score::from_c(s)).collect(),
+      })
+    }
+    (None, None) => Err(String::from("No scores were returned")),
+  };
+  if !scores.is_null() {
+    Scoreboards::fns().freeScoresList.unwrap()(scores);
+  }
+  requests().scores.complete(board_id.as_deref(), result);
+}
+
+/// A future for which poll() sends a scoreboard request once no earlier request is in the way, and
+/// waits for its response, then returns its result.
+struct ScoreboardFuture<T: 'static, F: Fn(&[u8])> {
+  ticket: u64,
+  /// The board id, null-terminated for passing to `send`.
+  board_id: Vec<u8>,
+  select: fn(&mut ScoreboardRequests) -> &mut Requests<T>,
+  /// Sends the request to Playdate.
+  ///
+  /// UNCLEAR: The meaning of the return value of Playdate's request functions is not documented,
+  /// so it's ignored and the result comes from the callback.
+  send: F,
+}
+impl<T, F: Fn(&[u8])> ScoreboardFuture<T, F> {
+  fn new(board_id: &str, select: fn(&mut ScoreboardRequests) -> &mut Requests<T>, send: F) -> Self {
+    ScoreboardFuture {
+      ticket: select(&mut requests()).issue(board_id),
+      board_id: board_id.to_null_terminated_utf8(),
+      select,
+      send,
+    }
+  }
+}
+impl<T, F: Fn(&[u8])> Future for ScoreboardFuture<T, F> {
+  type Output = Result<T, Error>;
+
+  fn poll(self: Pin<&mut Self>, ctxt: &mut Context<'_>) -> Poll<Self::Output> {
+    let (result, send) = {
+      let mut requests = requests();
+      let requests = (self.select)(&mut requests);
+      match requests.results.remove(&self.ticket) {
+        Some(result) => (Some(result), false),
+        None => (None, requests.start_sending(self.ticket)),
+      }
+    };
+    match result {
+      Some(result) => Poll::Ready(result.map_err(Error::String)),
+      None => {
+        // The requests are not borrowed while sending, in case Playdate runs the callback right
+        // away.
+        if send {
+          (self.send)(&self.board_id);
+        }
+        Executor::add_waker_for_system_event(CApiState::get().executor, ctxt.waker());
+        Poll::Pending
+      }
+    }
+  }
+}
+impl<T, F: Fn(&[u8])> Drop for ScoreboardFuture<T, F> {
+  fn drop(&mut self) {
+    (self.select)(&mut requests()).drop_ticket(self.ticket);
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn scores_go_to_the_request_for_their_board() {
+    let mut requests = Requests::<u32>::new(true);
+    let a = requests.issue("a");
+    let b = requests.issue("b");
+    assert!(requests.start_sending(a));
+    assert!(requests.start_sending(b));
+    requests.complete(Some("b"), Ok(2));
+    requests.complete(Some("a"), Ok(1));
+    assert_eq!(requests.results.remove(&a), Some(Ok(1)));
+    assert_eq!(requests.results.remove(&b), Some(Ok(2)));
+  }
+
+  #[test]
+  fn requests_without_a_board_in_the_response_are_sent_one_at_a_time() {
+    let mut requests = Requests::<u32>::new(false);
+    let a = requests.issue("a");
+    let b = requests.issue("b");
+    assert!(requests.start_sending(a));
+    assert!(!requests.start_sending(b));
+    requests.complete(None, Ok(1));
+    assert!(requests.start_sending(b));
+    assert_eq!(requests.results.remove(&a), Some(Ok(1)));
+  }
+
+  #[test]
+  fn responses_for_dropped_requests_are_thrown_away() {
+    let mut requests = Requests::<u32>::new(false);
+    let a = requests.issue("a");
+    let b = requests.issue("a");
+    assert!(requests.start_sending(a));
+    requests.drop_ticket(a);
+    // The response for `a` is still on its way, so `b` waits for it.
+    assert!(!requests.start_sending(b));
+    requests.complete(None, Ok(1));
+    assert!(requests.results.is_empty());
+    assert!(requests.start_sending(b));
+    requests.drop_ticket(b);
+    requests.complete(None, Ok(2));
+    assert!(requests.results.is_empty());
+    assert!(requests.waiting.is_empty());
+  }
+}
@@ -0,0 +1,214 @@
+use alloc::string::String;
+use core::borrow::Borrow;
+use core::hash::{Hash, Hasher};
+
+use static_assertions::const_assert_eq;
+
+/// The number of bytes a `SmallString` can hold without allocating. With the length and the tag
+/// of `Repr`, this makes a `SmallString` 24 bytes on the Playdate's 32-bit CPU, which is twice the
+/// size of a `String` there. On 64-bit targets, such as the simulator, it is 32 bytes.
+const INLINE_CAPACITY: usize = 22;
+
+enum Repr {
+  Inline {
+    len: u8,
+    bytes: [u8; INLINE_CAPACITY],
+  },
+  Heap(String),
+}
+
+/// A string which stores short text inline, without a heap allocation.
+///
+/// Strings of up to 22 bytes, such as most labels, names, and short UI text, are stored inside the
+/// `SmallString` itself, so creating or cloning them each frame does not touch the allocator.
+/// Longer strings are stored in a heap-allocated `String`.
+pub struct SmallString {
+  repr: Repr,
+}
+const_assert_eq!(
+  core::mem::size_of::<SmallString>(),
+  match core::mem::size_of::<usize>() {
+    4 => 24,
+    _ => 32,
+  }
+);
+impl SmallString {
+  /// Constructs an empty `SmallString`.
+  pub const fn new() -> Self {
+    SmallString {
+      repr: Repr::Inline {
+        len: 0,
+        bytes: [0; INLINE_CAPACITY],
+      },
+    }
+  }
+
+  /// Returns the string's contents.
+  pub fn as_str(&self) -> &str {
+    match &self.repr {
+      // SAFETY: The inline bytes are only ever written from a `&str`, and `len` always falls on a
+      // character boundary.
+      Repr::Inline { len, bytes } => unsafe {
+        core::str::from_utf8_unchecked(&bytes[..*len as usize])
+      },
+      Repr::Heap(s) => s.as_str(),
+    }
+  }
+
+  /// Appends `s` to the end of the string, moving it to the heap if it no longer fits inline.
+  pub fn push_str(&mut self, s: &str) {
+    match &mut self.repr {
+      Repr::Inline { len, bytes } => {
+        let start = *len as usize;
+        let end = start + s.len();
+        if end <= INLINE_CAPACITY {
+          bytes[start..end].copy_from_slice(s.as_bytes());
+          *len = end as u8;
+        } else {
+          let mut heap = String::with_capacity(end);
+          // SAFETY: The inline bytes up to `len` were written from a `&str`.
+          heap.push_str(unsafe { core::str::from_utf8_unchecked(&bytes[..start]) });
+          heap.push_str(s);
+          self.repr = Repr::Heap(heap);
+        }
+      }
+      Repr::Heap(heap) => heap.push_str(s),
+    }
+  }
+  /// Appends the character `c` to the end of the string.
+  pub fn push(&mut self, c: char) {
+    self.push_str(c.encode_utf8(&mut [0; 4]))
+  }
+  /// Removes all of the string's contents. A heap-allocated string keeps its allocation.
+  pub fn clear(&mut self) {
+    match &mut self.repr {
+      Repr::Inline { len, .. } => *len = 0,
+      Repr::Heap(heap) => heap.clear(),
+    }
+  }
+
+  /// Whether the string's contents are stored inline, without a heap allocation.
+  pub fn is_inline(&self) -> bool {
+    match self.repr {
+      Repr::Inline { .. } => true,
+      Repr::Heap(_) => false,
+    }
+  }
+}
+
+impl Default for SmallString {
+  fn default() -> Self {
+    Self::new()
+  }
+}
+
+impl Clone for SmallString {
+  fn clone(&self) -> Self {
+    match &self.repr {
+      Repr::Inline { len, bytes } => SmallString {
+        repr: Repr::Inline {
+          len: *len,
+          bytes: *bytes,
+        },
+      },
+      Repr::Heap(s) => SmallString::from(s.as_str()),
+    }
+  }
+}
+
+impl From<&str> for SmallString {
+  fn from(s: &str) -> Self {
+    let mut small = SmallString::new();
+    small.push_str(s);
+    small
+  }
+}
+impl From<String> for SmallString {
+  /// Converts a `String` into a `SmallString`. Short strings are moved inline, and long strings
+  /// keep using the `String`'s allocation.
+  fn from(s: String) -> Self {
+    if s.len() <= INLINE_CAPACITY {
+      SmallString::from(s.as_str())
+    } else {
+      SmallString {
+        repr: Repr::Heap(s),
+      }
+    }
+  }
+}
+impl From<SmallString> for String {
+  fn from(s: SmallString) -> Self {
+    match s.repr {
+      Repr::Inline { .. } => String::from(s.as_str()),
+      Repr::Heap(heap) => heap,
+    }
+  }
+}
+
+impl core::ops::Deref for SmallString {
+  type Target = str;
+
+  fn deref(&self) -> &str {
+    self.as_str()
+  }
+}
+impl AsRef<str> for SmallString {
+  fn as_ref(&self) -> &str {
+    self.as_str()
+  }
+}
+impl Borrow<str> for SmallString {
+  fn borrow(&self) -> &str {
+    self.as_str()
+  }
+}
+
+impl core::fmt::Write for SmallString {
+  fn write_str(&mut self, s: &str) -> core::fmt::Result {
+    self.push_str(s);
+    Ok(())
+  }
+}
+impl core::fmt::Display for SmallString {
+  fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+    core::fmt::Display::fmt(self.as_str(), f)
+  }
+}
+impl core::fmt::Debug for SmallString {
+  fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+    core::fmt::Debug::fmt(self.as_str(), f)
+  }
+}
+
+impl PartialEq for SmallString {
+  fn eq(&self, other: &Self) -> bool {
+    self.as_str() == other.as_str()
+  }
+}
+impl Eq for SmallString {}
+impl PartialEq<str> for SmallString {
+  fn eq(&self, other: &str) -> bool {
+    self.as_str() == other
+  }
+}
+impl PartialEq<&str> for SmallString {
+  fn eq(&self, other: &&str) -> bool {
+    self.as_str() == *other
+  }
+}
+impl PartialOrd for SmallString {
+  fn partial_cmp(&self, other: &Self) -> Option<core::cmp::Ordering> {
+    Some(self.cmp(other))
+  }
+}
+impl Ord for SmallString {
+  fn cmp(&self, other: &Self) -> core::cmp::Ordering {
+    self.as_str().cmp(other.as_str())
+  }
+}
+impl Hash for SmallString {
+  fn hash<H: Hasher>(&self, state: &mut H) {
+    // Hash the same as a `str`, to agree with the `Borrow<str>` implementation.
+    self.as_str().hash(state)
+  }
+}
@@ -0,0 +1,352 @@
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use crate::FilePathError;
+use crate::allocator::MemoryStats;
+use crate::capi_state::CApiState;
+use crate::ctypes::*;
+use crate::files::File;
+use crate::inputs::{Buttons, Crank, Inputs};
+use crate::time::{TimeDelta, TimeTicks};
+
+const ALL_BUTTONS: [CButtons; 6] = [
+  CButtons::kButtonLeft,
+  CButtons::kButtonRight,
+  CButtons::kButtonUp,
+  CButtons::kButtonDown,
+  CButtons::kButtonB,
+  CButtons::kButtonA,
+];
+
+/// **Experimental:** A summary of a `SoakTest` run.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SoakReport {
+  /// The number of frames of random input that were generated.
+  pub frames: u64,
+  /// How long the test has been running.
+  pub elapsed: TimeDelta,
+  /// Memory allocated when the test started.
+  pub start_memory: MemoryStats,
+  /// Memory allocated at the time of the report.
+  pub current_memory: MemoryStats,
+  /// The lowest number of allocated bytes seen after the warm-up time. Memory that stays above
+  /// this level as the test runs is a sign of a leak.
+  pub settled_bytes: Option<usize>,
+}
+impl SoakReport {
+  /// The growth in allocated bytes since the warm-up time ended, in bytes per hour.
+  pub fn growth_per_hour(&self, warm_up: TimeDelta) -> Option<f32> {
+    let settled = self.settled_bytes?;
+    let hours = (self.elapsed.to_seconds() - warm_up.to_seconds()) / (60.0 * 60.0);
+    if hours <= 0.0 {
+      return None;
+    }
+    Some((self.current_memory.allocated_bytes as f32 - settled as f32) / hours)
+  }
+}
+impl core::fmt::Display for SoakReport {
+  fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+    writeln!(f, "Soak test report")?;
+    writeln!(f, "frames: {}", self.frames)?;
+    writeln!(f, "elapsed: {}", self.elapsed)?;
+    writeln!(
+      f,
+      "allocated bytes: {} at start, {} now, {} peak",
+      self.start_memory.allocated_bytes,
+      self.current_memory.allocated_bytes,
+      self.current_memory.peak_allocated_bytes
+    )?;
+    if let Some(settled) = self.settled_bytes {
+      writeln!(f, "allocated bytes after warm-up: {} lowest", settled)?;
+    }
+    writeln!(
+      f,
+      "live allocations: {} at start, {} now",
+      self.start_memory.live_allocations, self.current_memory.live_allocations
+    )
+  }
+}
+
+/// The number of bytes set aside for describing a panic, which is enough for the file path of the
+/// panic location and the numbers that follow it.
+const PANIC_MESSAGE_CAPACITY: usize = 512;
+
+/// What the panic handler needs to describe a panic during the running `SoakTest`.
+///
+/// The message buffer is allocated when the test starts, so that describing a panic does not need
+/// to allocate or touch any files while the game is panicking.
+#[derive(Debug)]
+pub(crate) struct SoakTestState {
+  /// Which `SoakTest` the state belongs to, so that dropping an older test does not clear the
+  /// state of a newer one.
+  id: usize,
+  frames: u64,
+  allocated_bytes: usize,
+  message: Vec<u8>,
+}
+
+/// **Experimental:** A driver for long running "monkey" tests, which feeds random inputs to the
+/// game in place of the player's, and watches for panics and growing memory use.
+///
+/// Each frame, the game calls `next_inputs()` and uses the returned `Inputs` instead of the ones
+/// from the `SystemEvent::NextFrame` event. A summary report is written to a file in the game's
+/// data folder every few minutes, and if the game panics in the simulator, where it panicked is
+/// printed to the console. The test can be left running in the simulator for hours to find leaks
+/// and crashes that are rarely hit during play.
+///
+/// # Example
+/// ```
+/// let mut soak = SoakTest::new(1234, TimeDelta::from_hours(4));
+/// loop {
+///   match events.next().await {
+///     SystemEvent::NextFrame { inputs, .. } => {
+///       let inputs = if soak.is_running() {
+///         soak.next_inputs(api.system.current_time())
+///       } else {
+///         inputs
+///       };
+///       // Update the game with `inputs`.
+///     }
+///     _ => (),
+///   }
+/// }
+/// ```
+#[derive(Debug)]
+pub struct SoakTest {
+  id: usize,
+  report: SoakReport,
+  report_path: String,
+  rng: u32,
+  duration: TimeDelta,
+  warm_up: TimeDelta,
+  report_interval: TimeDelta,
+  start_time: Option<TimeTicks>,
+  last_report_time: Option<TimeTicks>,
+  buttons: CButtons,
+  crank_angle: Option<f32>,
+  finished: bool,
+}
+impl SoakTest {
+  /// Constructs a `SoakTest` which generates inputs from the random `seed`, for `duration` of time.
+  ///
+  /// The report is written to "soak_report.txt" by default.
+  pub fn new(seed: u32, duration: TimeDelta) -> Self {
+    let capi = CApiState::get();
+    let id = capi.soak_test_generation.get() + 1;
+    capi.soak_test_generation.set(id);
+    let start_memory = MemoryStats::get();
+    *capi.soak_test.borrow_mut() = Some(SoakTestState {
+      id,
+      frames: 0,
+      allocated_bytes: start_memory.allocated_bytes,
+      message: Vec::with_capacity(PANIC_MESSAGE_CAPACITY),
+    });
+    SoakTest {
+      id,
+      report: SoakReport {
+        frames: 0,
+        elapsed: TimeDelta::from_milliseconds(0),
+        start_memory,
+        current_memory: start_memory,
+        settled_bytes: None,
+      },
+      report_path: String::from("soak_report.txt"),
+      // Xorshift can not leave the all-zero state.
+      rng: seed.max(1),
+      duration,
+      warm_up: TimeDelta::from_minutes(1),
+      report_interval: TimeDelta::from_minutes(5),
+      start_time: None,
+      last_report_time: None,
+      buttons: CButtons(0),
+      crank_angle: Some(0.0),
+      finished: false,
+    }
+  }
+
+  /// Sets the path of the report file, in the game's data folder.
+  pub fn set_report_path(&mut self, path: &str) {
+    self.report_path = String::from(path);
+  }
+  /// Sets how long after starting to wait before watching memory growth, so that caches and
+  /// lazily loaded assets are not mistaken for leaks. The default is 1 minute.
+  pub fn set_warm_up(&mut self, warm_up: TimeDelta) {
+    self.warm_up = warm_up;
+  }
+  /// Sets how often the report file is written. The default is every 5 minutes.
+  pub fn set_report_interval(&mut self, interval: TimeDelta) {
+    self.report_interval = interval;
+  }
+
+  /// Whether the test is still generating inputs. Once the test's duration has passed, a final
+  /// report is written and this returns false.
+  pub fn is_running(&self) -> bool {
+    !self.finished
+  }
+
+  /// Generates random inputs for the next frame, and records the game's memory use.
+  pub fn next_inputs(&mut self, now: TimeTicks) -> Inputs {
+    let start = *self.start_time.get_or_insert(now);
+    let elapsed = now - start;
+    self.update_report(elapsed);
+
+    let due = match self.last_report_time {
+      Some(last) => now - last >= self.report_interval,
+      None => false,
+    };
+    if self.last_report_time.is_none() || due {
+      self.last_report_time = Some(now);
+      // Failing to write the report should not stop the test.
+      let _ = self.write_report();
+    }
+    if !self.finished && elapsed >= self.duration {
+      self.finished = true;
+      let _ = self.write_report();
+    }
+
+    // Each button changes state on about one frame in 8, so they are held for a while.
+    let previous = self.buttons;
+    for button in ALL_BUTTONS {
+      if self.next_random().is_multiple_of(8) {
+        self.buttons = CButtons(self.buttons.0 ^ button.0);
+      }
+    }
+    let pushed = CButtons(self.buttons.0 & !previous.0);
+    let released = CButtons(previous.0 & !self.buttons.0);
+    let frames = [
+      PDButtonsSet {
+        current: self.buttons,
+        pushed,
+        released,
+      },
+      PDButtonsSet {
+        current: previous,
+        pushed: CButtons(0),
+        released: CButtons(0),
+      },
+    ];
+
+    // The crank is docked or undocked rarely, and turned by up to 30 degrees each frame.
+    if self.next_random().is_multiple_of(600) {
+      self.crank_angle = match self.crank_angle {
+        Some(_) => None,
+        None => Some(0.0),
+      };
+    }
+    let change = (self.next_random() % 61) as f32 - 30.0;
+    let crank = match &mut self.crank_angle {
+      None => Crank::Docked,
+      Some(angle) => {
+        // The change is never more than a full turn, so one wrap is enough.
+        *angle += change;
+        if *angle < 0.0 {
+          *angle += 360.0;
+        } else if *angle >= 360.0 {
+          *angle -= 360.0;
+        }
+        Crank::Undocked {
+          angle: *angle,
+          change,
+        }
+      }
+    };
+    Inputs::from_parts(
+      CApiState::get().peripherals_enabled.get(),
+      Buttons::new(&frames),
+      crank,
+    )
+  }
+
+  /// Returns a summary of the test so far.
+  pub fn report(&self) -> SoakReport {
+    self.report.clone()
+  }
+
+  /// Writes the report to the report file.
+  pub fn write_report(&self) -> Result<(), FilePathError> {
+    File::new().write_file(&self.report_path, format!("{}", self.report).as_bytes())
+  }
+
+  fn update_report(&mut self, elapsed: TimeDelta) {
+    let warm_up = self.warm_up;
+    let report = &mut self.report;
+    report.frames += 1;
+    report.elapsed = elapsed;
+    report.current_memory = MemoryStats::get();
+    if elapsed >= warm_up {
+      let bytes = report.current_memory.allocated_bytes;
+      report.settled_bytes = Some(report.settled_bytes.map_or(bytes, |s| s.min(bytes)));
+    }
+    if let Some(state) = CApiState::get().soak_test.borrow_mut().as_mut() {
+      if state.id == self.id {
+        state.frames = report.frames;
+        state.allocated_bytes = report.current_memory.allocated_bytes;
+      }
+    }
+  }
+
+  fn next_random(&mut self) -> u32 {
+    self.rng ^= self.rng << 13;
+    self.rng ^= self.rng >> 17;
+    self.rng ^= self.rng << 5;
+    self.rng
+  }
+}
+impl Drop for SoakTest {
+  fn drop(&mut self) {
+    let mut state = CApiState::get().soak_test.borrow_mut();
+    if state.as_ref().is_some_and(|state| state.id == self.id) {
+      *state = None;
+    }
+  }
+}
+
+/// Prints where the game panicked, if a `SoakTest` is running.
+///
+/// Called from the panic handler, so it avoids panicking again if the state is in use, and only
+/// writes into the message buffer that was allocated when the test started.
+#[cfg(not(all(target_arch = "arm", target_os = "none")))]
+pub(crate) fn write_panic_report(panic_info: &core::panic::PanicInfo) {
+  use core::fmt::Write;
+
+  let capi = match CApiState::try_get() {
+    Some(capi) => capi,
+    None => return,
+  };
+  let mut state = match capi.soak_test.try_borrow_mut() {
+    Ok(state) => state,
+    Err(_) => return,
+  };
+  if let Some(state) = state.as_mut() {
+    let mut message = FixedBuffer(&mut state.message);
+    message.0.clear();
+    let _ = match panic_info.location() {
+      Some(loc) => write!(
+        message,
+        "soak test: PANICKED at {}:{}:{}",
+        loc.file(),
+        loc.line(),
+        loc.column()
+      ),
+      None => write!(message, "soak test: PANICKED at an unknown location"),
+    };
+    let _ = writeln!(
+      message,
+      " after {} frames, with {} bytes allocated",
+      state.frames, state.allocated_bytes
+    );
+    crate::log::log_bytes_to_stdout(&state.message);
+  }
+}
+
+/// Writes into a `Vec` without growing it, dropping whatever does not fit in its capacity.
+struct FixedBuffer<'a>(&'a mut Vec<u8>);
+impl core::fmt::Write for FixedBuffer<'_> {
+  fn write_str(&mut self, s: &str) -> core::fmt::Result {
+    let room = self.0.capacity() - self.0.len();
+    let bytes = &s.as_bytes()[..s.len().min(room)];
+    self.0.extend_from_slice(bytes);
+    Ok(())
+  }
+}
@@ -0,0 +1,161 @@
+use alloc::vec::Vec;
+
+/// The step sizes of IMA-ADPCM, indexed by the step index.
+const STEP_TABLE: [i32; 89] = [
+  7, 8, 9, 10, 11, 12, 13, 14, 16, 17, 19, 21, 23, 25, 28, 31, 34, 37, 41, 45, 50, 55, 60, 66, 73,
+  80, 88, 97, 107, 118, 130, 143, 157, 173, 190, 209, 230, 253, 279, 307, 337, 371, 408, 449, 494,
+  544, 598, 658, 724, 796, 876, 963, 1060, 1166, 1282, 1411, 1552, 1707, 1878, 2066, 2272, 2499,
+  2749, 3024, 3327, 3660, 4026, 4428, 4871, 5358, 5894, 6484, 7132, 7845, 8630, 9493, 10442, 11487,
+  12635, 13899, 15289, 16818, 18500, 20350, 22385, 24623, 27086, 29794, 32767,
+];
+/// How much the step index changes after each 4 bit code.
+const INDEX_TABLE: [i32; 16] = [-1, -1, -1, -1, 2, 4, 6, 8, -1, -1, -1, -1, 2, 4, 6, 8];
+
+/// The number of bytes in each block that `encode_blocks()` makes, per channel.
+pub(crate) const BLOCK_BYTES_PER_CHANNEL: usize = 256;
+
+/// The IMA-ADPCM state of one channel, which the encoder and decoder keep in step.
+#[derive(Debug, Default, Copy, Clone)]
+struct ChannelState {
+  predictor: i32,
+  index: i32,
+}
+impl ChannelState {
+  fn decode(&mut self, code: u8) -> i16 {
+    let step = STEP_TABLE[self.index as usize];
+    let mut diff = step >> 3;
+    if code & 4 != 0 {
+      diff += step;
+    }
+    if code & 2 != 0 {
+      diff += step >> 1;
+    }
+    if code & 1 != 0 {
+      diff += step >> 2;
+    }
+    self.advance(code, diff)
+  }
+
+  fn encode(&mut self, sample: i16) -> u8 {
+    let mut step = STEP_TABLE[self.index as usize];
+    let mut remaining = sample as i32 - self.predictor;
+    let mut code = 0;
+    if remaining < 0 {
+      code = 8;
+      remaining = -remaining;
+    }
+    // Build the same difference that the decoder will compute from the code.
+    let mut diff = step >> 3;
+    for bit in [4, 2, 1] {
+      if remaining >= step {
+        code |= bit;
+        remaining -= step;
+        diff += step;
+      }
+      step >>= 1;
+    }
+    self.advance(code, diff);
+    code
+  }
+
+  fn advance(&mut self, code: u8, diff: i32) -> i16 {
+    let predictor = if code & 8 != 0 {
+      self.predictor - diff
+    } else {
+      self.predictor + diff
+    };
+    self.predictor = predictor.clamp(i16::MIN as i32, i16::MAX as i32);
+    self.index = (self.index + INDEX_TABLE[code as usize]).clamp(0, 88);
+    self.predictor as i16
+  }
+}
+
+/// Returns the number of sample frames in each block of `block_align` bytes.
+///
+/// Each block starts with a 4 byte header per channel holding the first sample, followed by 4 bit
+/// codes for the rest.
+pub(crate) fn frames_per_block(block_align: usize, channels: usize) -> usize {
+  (block_align.saturating_sub(4 * channels)) * 2 / channels + 1
+}
+
+/// Compresses 16 bit samples, interleaved if there is more than one channel, to IMA-ADPCM blocks
+/// laid out as in an IMA-ADPCM WAV file, with `BLOCK_BYTES_PER_CHANNEL` bytes per channel in each
+/// block.
+///
+/// The last block is padded with silence, so the number of frames should be kept alongside the
+/// data to know where the audio ends.
+pub(crate) fn encode_blocks(pcm: &[i16], channels: usize) -> Vec<u8> {
+  let block_align = BLOCK_BYTES_PER_CHANNEL * channels;
+  let frames_per_block = frames_per_block(block_align, channels);
+  let frames = pcm.len() / channels;
+  let blocks = frames.div_ceil(frames_per_block);
+  let sample = |frame: usize, c: usize| pcm.get(frame * channels + c).copied().unwrap_or(0);
+
+  let mut states = alloc::vec![ChannelState::default(); channels];
+  let mut out = Vec::with_capacity(blocks * block_align);
+  for block in 0..blocks {
+    let first = block * frames_per_block;
+    for (c, state) in states.iter_mut().enumerate() {
+      // The first sample is stored whole, and the step index carries on from the last block.
+      state.predictor = sample(first, c) as i32;
+      out.extend_from_slice(&(state.predictor as i16).to_le_bytes());
+      out.push(state.index as u8);
+      out.push(0);
+    }
+    // The rest are in groups of 8 frames, with 4 bytes of codes for each channel in turn. Each
+    // byte holds two codes, with the earlier sample in the low bits.
+    for group in (first + 1..first + frames_per_block).step_by(8) {
+      for (c, state) in states.iter_mut().enumerate() {
+        for pair in 0..4 {
+          let low = state.encode(sample(group + pair * 2, c));
+          let high = state.encode(sample(group + pair * 2 + 1, c));
+          out.push(low | (high << 4));
+        }
+      }
+    }
+  }
+  out
+}
+
+/// Decompresses IMA-ADPCM blocks of `block_align` bytes, laid out as in an IMA-ADPCM WAV file, to
+/// 16 bit samples which are interleaved if there is more than one channel.
+///
+/// If `frames` is given, the output is cut to that many frames, dropping the padding at the end of
+/// the last block.
+pub(crate) fn decode_blocks(
+  data: &[u8],
+  channels: usize,
+  block_align: usize,
+  frames: Option<usize>,
+) -> Vec<i16> {
+  let header_bytes = 4 * channels;
+  if channels == 0 || block_align <= header_bytes {
+    return Vec::new();
+  }
+  let mut out = Vec::with_capacity(
+    data.len() / block_align * frames_per_block(block_align, channels) * channels,
+  );
+  let mut group_samples = alloc::vec![0i16; 8 * channels];
+  for block in data.chunks(block_align).filter(|block| block.len() >= header_bytes) {
+    let mut states = alloc::vec![ChannelState::default(); channels];
+    for (c, state) in states.iter_mut().enumerate() {
+      let header = &block[c * 4..c * 4 + 4];
+      state.predictor = i16::from_le_bytes([header[0], header[1]]) as i32;
+      state.index = (header[2] as i32).clamp(0, 88);
+      out.push(state.predictor as i16);
+    }
+    for group in block[header_bytes..].chunks_exact(4 * channels) {
+      for (c, state) in states.iter_mut().enumerate() {
+        for (i, byte) in group[c * 4..c * 4 + 4].iter().enumerate() {
+          group_samples[(i * 2) * channels + c] = state.decode(byte & 0xf);
+          group_samples[(i * 2 + 1) * channels + c] = state.decode(byte >> 4);
+        }
+      }
+      out.extend_from_slice(&group_samples);
+    }
+  }
+  if let Some(frames) = frames {
+    out.truncate(frames * channels);
+  }
+  out
+}
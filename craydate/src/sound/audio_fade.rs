@@ -0,0 +1,161 @@
+use alloc::sync::Arc;
+use core::sync::atomic::{AtomicU32, Ordering};
+
+use super::signals::lfo::Lfo;
+use super::sound_channel::SoundChannel;
+use super::Sound;
+use crate::system_event::SystemEvent;
+use crate::time::TimeDelta;
+
+/// How the `Sound` system fades its volume when the game is paused or the device is locked.
+///
+/// The fades are run by the audio engine, so they continue while the game is paused and not
+/// receiving frame updates.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct SystemFades {
+  /// The volume (0.0 - 1.0) to fade to while the game is paused or the device is locked.
+  pub volume: f32,
+  /// How long it takes to fade down when the game is paused or the device is locked.
+  pub fade_out: TimeDelta,
+  /// How long it takes to fade back up when the game resumes or the device is unlocked.
+  pub fade_in: TimeDelta,
+}
+impl Default for SystemFades {
+  fn default() -> Self {
+    SystemFades {
+      volume: 0.0,
+      fade_out: TimeDelta::from_milliseconds(250),
+      fade_in: TimeDelta::from_milliseconds(500),
+    }
+  }
+}
+
+/// A fade of the volume, shared between the game thread and the audio thread.
+///
+/// The fields are written by the game while `generation` is odd. The audio thread can interrupt
+/// the game part way through writing, so when it sees an odd or changed `generation` it keeps
+/// using the gain that it last computed instead of waiting.
+struct FadeState {
+  generation: AtomicU32,
+  from: AtomicU32,
+  to: AtomicU32,
+  start_frame: AtomicU32,
+  length_frames: AtomicU32,
+}
+impl FadeState {
+  fn gain_at(&self, now_frame: u32) -> Option<f32> {
+    let generation = self.generation.load(Ordering::Acquire);
+    if generation % 2 == 1 {
+      return None;
+    }
+    let from = f32::from_bits(self.from.load(Ordering::Relaxed));
+    let to = f32::from_bits(self.to.load(Ordering::Relaxed));
+    let start = self.start_frame.load(Ordering::Relaxed);
+    let length = self.length_frames.load(Ordering::Relaxed);
+    if self.generation.load(Ordering::Acquire) != generation {
+      return None;
+    }
+    let elapsed = now_frame.wrapping_sub(start);
+    if elapsed >= length {
+      Some(to)
+    } else {
+      Some(from + (to - from) * (elapsed as f32 / length as f32))
+    }
+  }
+}
+
+/// Fades the volume of a `SoundChannel` smoothly over time, from the audio engine.
+///
+/// The fade is applied as the channel's volume modulator, so it scales the channel's volume
+/// without changing it.
+pub(crate) struct AudioFade {
+  lfo: Lfo,
+  state: Arc<FadeState>,
+  system_fades: Option<SystemFades>,
+  /// The volume to fade back to after a system fade, which is the volume the game last asked for.
+  game_volume: f32,
+}
+impl AudioFade {
+  pub(crate) fn new() -> Self {
+    let state = Arc::new(FadeState {
+      generation: AtomicU32::new(0),
+      from: AtomicU32::new(1f32.to_bits()),
+      to: AtomicU32::new(1f32.to_bits()),
+      start_frame: AtomicU32::new(0),
+      length_frames: AtomicU32::new(0),
+    });
+    let current_time = Sound::fns().getCurrentTime.unwrap();
+    let shared = state.clone();
+    let mut last_gain = 1.0;
+    let mut lfo = Lfo::new_with_user_function(false, move || {
+      // SAFETY: getCurrentTime() only reads the audio clock, which is safe from the audio thread.
+      if let Some(gain) = shared.gain_at(unsafe { current_time() }) {
+        last_gain = gain;
+      }
+      last_gain
+    });
+    // Keep the fade moving even if nothing is playing on the channel.
+    lfo.set_global(true);
+    AudioFade {
+      lfo,
+      state,
+      system_fades: Some(SystemFades::default()),
+      game_volume: 1.0,
+    }
+  }
+
+  /// Makes the fade control the volume of `channel`.
+  pub(crate) fn attach_to_channel(&self, channel: &mut SoundChannel) {
+    channel.set_volume_modulator(Some(&self.lfo));
+  }
+
+  pub(crate) fn set_system_fades(&mut self, fades: Option<SystemFades>) {
+    self.system_fades = fades;
+  }
+
+  /// The current gain being applied by the fade.
+  pub(crate) fn gain(&self) -> f32 {
+    self.state.gain_at(Self::now()).unwrap_or(self.game_volume)
+  }
+
+  /// Fades to `volume` over `duration`, as requested by the game.
+  pub(crate) fn fade_to(&mut self, volume: f32, duration: TimeDelta) {
+    self.game_volume = volume;
+    self.start_fade(volume, duration);
+  }
+
+  /// Fades the volume down or back up for system events where the game is paused or locked.
+  pub(crate) fn on_system_event(&mut self, event: &SystemEvent) {
+    let fades = match self.system_fades {
+      Some(fades) => fades,
+      None => return,
+    };
+    match event {
+      SystemEvent::WillPause | SystemEvent::WillLock => {
+        self.start_fade(fades.volume.min(self.game_volume), fades.fade_out)
+      }
+      SystemEvent::WillResume | SystemEvent::DidUnlock => {
+        self.start_fade(self.game_volume, fades.fade_in)
+      }
+      _ => (),
+    }
+  }
+
+  fn start_fade(&mut self, to: f32, duration: TimeDelta) {
+    let now = Self::now();
+    // Start from the current gain so that a fade which interrupts another one is continuous.
+    let from = self.state.gain_at(now).unwrap_or(to);
+    let length = duration.to_sample_frames().max(0) as u32;
+    let s = &self.state;
+    s.generation.fetch_add(1, Ordering::AcqRel);
+    s.from.store(from.to_bits(), Ordering::Relaxed);
+    s.to.store(to.clamp(0.0, 1.0).to_bits(), Ordering::Relaxed);
+    s.start_frame.store(now, Ordering::Relaxed);
+    s.length_frames.store(length, Ordering::Relaxed);
+    s.generation.fetch_add(1, Ordering::AcqRel);
+  }
+
+  fn now() -> u32 {
+    unsafe { Sound::fns().getCurrentTime.unwrap()() }
+  }
+}
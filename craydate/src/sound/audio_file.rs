@@ -0,0 +1,310 @@
+use alloc::format;
+use alloc::vec::Vec;
+
+use super::adpcm;
+use crate::error::Error;
+
+/// The sample rates that audio files are resampled to, when they are not already at one of them.
+///
+/// UNCLEAR: Playdate accepts any sample rate for a sample, but these are the rates that `pdc`
+/// produces, and that play without the sound engine having to resample them by uneven steps.
+pub(crate) const SUPPORTED_SAMPLE_RATES: [u32; 3] = [11_025, 22_050, 44_100];
+
+/// Audio decoded from a WAV or AIFF file, as 16 bit samples.
+#[derive(Debug)]
+pub(crate) struct DecodedAudio {
+  /// The samples, with left and right samples interleaved if `stereo`.
+  pub samples: Vec<i16>,
+  pub stereo: bool,
+  pub sample_rate: u32,
+}
+impl DecodedAudio {
+  /// Resamples the audio to `sample_rate` with linear interpolation.
+  pub fn resample(self, sample_rate: u32) -> DecodedAudio {
+    if sample_rate == self.sample_rate || sample_rate == 0 {
+      return self;
+    }
+    let channels = if self.stereo { 2 } else { 1 };
+    let in_frames = self.samples.len() / channels;
+    if in_frames == 0 {
+      return DecodedAudio {
+        samples: Vec::new(),
+        stereo: self.stereo,
+        sample_rate,
+      };
+    }
+    let out_frames = (in_frames as u64 * sample_rate as u64 / self.sample_rate as u64) as usize;
+    let mut samples = Vec::with_capacity(out_frames * channels);
+    for i in 0..out_frames {
+      // The position in the input, in 1/65536ths of a frame.
+      let pos = ((i as u64) << 16) * self.sample_rate as u64 / sample_rate as u64;
+      let frame = (pos >> 16) as usize;
+      let next = (frame + 1).min(in_frames - 1);
+      let fraction = (pos & 0xffff) as i64;
+      for c in 0..channels {
+        // The difference between samples times the fraction can need more than 32 bits.
+        let a = self.samples[frame * channels + c] as i64;
+        let b = self.samples[next * channels + c] as i64;
+        samples.push((a + (((b - a) * fraction) >> 16)) as i16);
+      }
+    }
+    DecodedAudio {
+      samples,
+      stereo: self.stereo,
+      sample_rate,
+    }
+  }
+
+  /// Resamples the audio to the lowest of the `SUPPORTED_SAMPLE_RATES` which is at least its
+  /// current rate, or the highest one if it's above them all. Does nothing if it is already at one
+  /// of them.
+  pub fn resample_to_supported_rate(self) -> DecodedAudio {
+    let rate = SUPPORTED_SAMPLE_RATES
+      .iter()
+      .copied()
+      .find(|rate| *rate >= self.sample_rate)
+      .unwrap_or(SUPPORTED_SAMPLE_RATES[SUPPORTED_SAMPLE_RATES.len() - 1]);
+    self.resample(rate)
+  }
+}
+
+/// How each sample is stored in a file.
+#[derive(Debug, Copy, Clone)]
+enum Encoding {
+  /// Signed integers of the given number of bits.
+  Int(u16),
+  /// 8 bit unsigned integers, as used by WAV files.
+  Unsigned8,
+  /// IEEE floating point numbers of the given number of bits.
+  Float(u16),
+  /// IMA-ADPCM, in blocks of the given number of bytes.
+  ImaAdpcm(u16),
+}
+
+/// Decodes a WAV or AIFF file holding uncompressed or IMA-ADPCM audio.
+///
+/// WAV files can have integer samples of 8, 16, 24 or 32 bits, floating point samples of 32 or 64
+/// bits, or be compressed with IMA-ADPCM. AIFF and AIFF-C files can have integer samples of 8, 16,
+/// 24 or 32 bits, or 32 or 64 bit floating point samples in AIFF-C. Files with more than two
+/// channels are played in stereo from their first two channels.
+pub(crate) fn decode_audio_file(bytes: &[u8]) -> Result<DecodedAudio, Error> {
+  if bytes.len() >= 12 && &bytes[0..4] == b"RIFF" && &bytes[8..12] == b"WAVE" {
+    decode_wav(bytes)
+  } else if bytes.len() >= 12
+    && &bytes[0..4] == b"FORM"
+    && (&bytes[8..12] == b"AIFF" || &bytes[8..12] == b"AIFC")
+  {
+    decode_aiff(bytes)
+  } else {
+    Err("decode_audio_file: not a WAV or AIFF file".into())
+  }
+}
+
+/// Calls `f` with the id and contents of each chunk in a RIFF or IFF file, after the 12 byte file
+/// header, until it returns `Some`.
+fn for_each_chunk<'a, T>(
+  bytes: &'a [u8],
+  big_endian: bool,
+  mut f: impl FnMut(&'a [u8], &'a [u8]) -> Result<Option<T>, Error>,
+) -> Result<Option<T>, Error> {
+  let mut pos = 12;
+  while pos + 8 <= bytes.len() {
+    let id = &bytes[pos..pos + 4];
+    let len_bytes = [
+      bytes[pos + 4],
+      bytes[pos + 5],
+      bytes[pos + 6],
+      bytes[pos + 7],
+    ];
+    let len = if big_endian {
+      u32::from_be_bytes(len_bytes)
+    } else {
+      u32::from_le_bytes(len_bytes)
+    };
+    let start = pos + 8;
+    let end = start.saturating_add(len as usize).min(bytes.len());
+    if let Some(t) = f(id, &bytes[start..end])? {
+      return Ok(Some(t));
+    }
+    // Chunks are padded to an even length.
+    pos = end + (len as usize & 1);
+  }
+  Ok(None)
+}
+
+fn decode_wav(bytes: &[u8]) -> Result<DecodedAudio, Error> {
+  let mut fmt = None;
+  let mut fact = None;
+  let decoded = for_each_chunk(bytes, false, |id, chunk| {
+    match id {
+      b"fmt " if chunk.len() >= 16 => {
+        let u16_at = |i: usize| u16::from_le_bytes([chunk[i], chunk[i + 1]]);
+        let mut tag = u16_at(0);
+        // WAVE_FORMAT_EXTENSIBLE keeps the real format in the first two bytes of its sub-format.
+        if tag == 0xfffe && chunk.len() >= 26 {
+          tag = u16_at(24);
+        }
+        let channels = u16_at(2);
+        let rate = u32::from_le_bytes([chunk[4], chunk[5], chunk[6], chunk[7]]);
+        let block_align = u16_at(12);
+        let bits = u16_at(14);
+        let encoding = match (tag, bits) {
+          (0x11, 4) => Encoding::ImaAdpcm(block_align),
+          (1, 8) => Encoding::Unsigned8,
+          (1, 16 | 24 | 32) => Encoding::Int(bits),
+          (3, 32 | 64) => Encoding::Float(bits),
+          _ => {
+            return Err(Error::String(format!(
+              "decode_audio_file: unsupported WAV format (type {}, {} bits)",
+              tag, bits
+            )));
+          }
+        };
+        fmt = Some((encoding, channels, rate));
+        Ok(None)
+      }
+      b"data" => {
+        let (encoding, channels, rate) =
+          fmt.ok_or("decode_audio_file: the data comes before the format")?;
+        Ok(Some(convert(chunk, encoding, false, channels, rate, fact)?))
+      }
+      // The number of frames, which compressed files have as their last block can be padded.
+      b"fact" if chunk.len() >= 4 => {
+        fact = Some(u32::from_le_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]) as usize);
+        Ok(None)
+      }
+      _ => Ok(None),
+    }
+  })?;
+  decoded.ok_or_else(|| "decode_audio_file: the file has no audio data".into())
+}
+
+fn decode_aiff(bytes: &[u8]) -> Result<DecodedAudio, Error> {
+  let is_aifc = &bytes[8..12] == b"AIFC";
+  let mut comm = None;
+  let mut ssnd = None;
+  for_each_chunk(bytes, true, |id, chunk| {
+    match id {
+      b"COMM" if chunk.len() >= 18 => {
+        let channels = u16::from_be_bytes([chunk[0], chunk[1]]);
+        let bits = u16::from_be_bytes([chunk[6], chunk[7]]);
+        let rate = extended_to_u32(&chunk[8..18]);
+        let compression = if is_aifc && chunk.len() >= 22 {
+          &chunk[18..22]
+        } else {
+          b"NONE"
+        };
+        let (encoding, big_endian) = match (compression, bits) {
+          (b"NONE", 8 | 16 | 24 | 32) => (Encoding::Int(bits), true),
+          (b"sowt", 16) => (Encoding::Int(bits), false),
+          (b"fl32" | b"FL32", _) => (Encoding::Float(32), true),
+          (b"fl64" | b"FL64", _) => (Encoding::Float(64), true),
+          _ => {
+            return Err(Error::String(format!(
+              "decode_audio_file: unsupported AIFF format ({}, {} bits)",
+              core::str::from_utf8(compression).unwrap_or("?"),
+              bits
+            )));
+          }
+        };
+        comm = Some((encoding, big_endian, channels, rate));
+      }
+      b"SSND" if chunk.len() >= 8 => {
+        // The sound data starts after an offset, which is used to align blocks.
+        let offset = u32::from_be_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]) as usize;
+        ssnd = Some(chunk.get(8 + offset..).unwrap_or(&[]));
+      }
+      _ => (),
+    }
+    Ok(None::<()>)
+  })?;
+  let (encoding, big_endian, channels, rate) =
+    comm.ok_or("decode_audio_file: the file has no COMM chunk")?;
+  let data = ssnd.ok_or("decode_audio_file: the file has no audio data")?;
+  convert(data, encoding, big_endian, channels, rate, None)
+}
+
+/// Converts samples stored with the `encoding` into 16 bit mono or stereo samples.
+///
+/// The number of `frames` is given for compressed data, where it can not be found from the length
+/// of the data.
+fn convert(
+  data: &[u8],
+  encoding: Encoding,
+  big_endian: bool,
+  channels: u16,
+  sample_rate: u32,
+  frames: Option<usize>,
+) -> Result<DecodedAudio, Error> {
+  if channels == 0 || sample_rate == 0 {
+    return Err("decode_audio_file: the file has no channels or no sample rate".into());
+  }
+  let stereo = channels >= 2;
+  let kept = if stereo { 2 } else { 1 };
+
+  let bytes_per_sample = match encoding {
+    Encoding::Int(bits) | Encoding::Float(bits) => bits as usize / 8,
+    Encoding::Unsigned8 => 1,
+    Encoding::ImaAdpcm(block_align) => {
+      let all = adpcm::decode_blocks(data, channels as usize, block_align as usize, frames);
+      let samples = all.chunks_exact(channels as usize).flat_map(|f| f[..kept].iter().copied());
+      return Ok(DecodedAudio {
+        samples: samples.collect(),
+        stereo,
+        sample_rate,
+      });
+    }
+  };
+  let convert_one = |s: &[u8]| -> i16 {
+    // The bytes from most to least significant.
+    let mut be = [0u8; 8];
+    for (i, b) in s.iter().enumerate() {
+      be[if big_endian { i } else { s.len() - 1 - i }] = *b;
+    }
+    match encoding {
+      Encoding::Unsigned8 => ((s[0] as i16) - 128) << 8,
+      Encoding::Int(8) => (s[0] as i8 as i16) << 8,
+      // Integer samples keep their most significant 16 bits.
+      Encoding::Int(_) => i16::from_be_bytes([be[0], be[1]]),
+      Encoding::Float(32) => float_to_i16(f32::from_be_bytes([be[0], be[1], be[2], be[3]]) as f64),
+      Encoding::Float(_) => float_to_i16(f64::from_be_bytes(be)),
+      Encoding::ImaAdpcm(_) => unreachable!(),
+    }
+  };
+
+  let frame_bytes = bytes_per_sample * channels as usize;
+  let mut samples = Vec::with_capacity(data.len() / frame_bytes * kept);
+  for frame in data.chunks_exact(frame_bytes) {
+    for c in 0..kept {
+      samples.push(convert_one(
+        &frame[c * bytes_per_sample..(c + 1) * bytes_per_sample],
+      ));
+    }
+  }
+  Ok(DecodedAudio {
+    samples,
+    stereo,
+    sample_rate,
+  })
+}
+
+fn float_to_i16(f: f64) -> i16 {
+  (f.clamp(-1.0, 1.0) * i16::MAX as f64) as i16
+}
+
+/// Converts the 80 bit extended precision float used for AIFF sample rates to an integer.
+fn extended_to_u32(bytes: &[u8]) -> u32 {
+  let exponent = (u16::from_be_bytes([bytes[0], bytes[1]]) & 0x7fff) as i32;
+  let mut mantissa = [0u8; 8];
+  mantissa.copy_from_slice(&bytes[2..10]);
+  let mantissa = u64::from_be_bytes(mantissa);
+  // The mantissa has its binary point after the first bit, and the exponent is biased by 16383.
+  let shift = exponent - 16383 - 63;
+  if exponent == 0 || shift <= -64 {
+    0
+  } else if shift < 0 {
+    (mantissa >> -shift) as u32
+  } else {
+    u32::MAX
+  }
+}
@@ -2,10 +2,15 @@ use alloc::vec::Vec;
 use core::mem::MaybeUninit;
 use core::ptr::NonNull;
 
+use super::audio_file;
+use super::sound_format::{sound_format_bytes_per_frame, sound_format_is_stereo};
+use super::wav;
 use crate::capi_state::CApiState;
 use crate::ctypes::*;
+use crate::error::Error;
+use crate::files::File;
 use crate::null_terminated::ToNullTerminatedString;
-use crate::time::TimeTicks;
+use crate::time::{TimeDelta, TimeTicks};
 
 /// A buffer of audio data which can be played with a `SamplePlayer` or as part of a MIDI
 /// `Instrument` in a `Synth`.
@@ -50,7 +55,7 @@ impl AudioSample {
         || format == SoundFormat::kSound16bitMono
         || format == SoundFormat::kSound16bitStereo
         || format == SoundFormat::kSoundADPCMMono
-        || format == SoundFormat::kSound16bitStereo
+        || format == SoundFormat::kSoundADPCMStereo
     );
     let ptr = unsafe {
       Self::fns().newSampleFromData.unwrap()(
@@ -75,7 +80,7 @@ impl AudioSample {
         || format == SoundFormat::kSound16bitMono
         || format == SoundFormat::kSound16bitStereo
         || format == SoundFormat::kSoundADPCMMono
-        || format == SoundFormat::kSound16bitStereo
+        || format == SoundFormat::kSoundADPCMStereo
     );
     let ptr = unsafe {
       Self::fns().newSampleFromData.unwrap()(
@@ -90,6 +95,105 @@ impl AudioSample {
     sample.data.extend(data.iter());
     sample
   }
+  /// Creates a new AudioSample of silence which is `length` long, in the given uncompressed
+  /// `format`, such as to be filled in with `pcm_mut()`.
+  ///
+  /// # Panics
+  ///
+  /// Panics if the `format` is ADPCM, which can not be created this way.
+  pub fn with_length(length: TimeDelta, format: SoundFormat, sample_rate: u32) -> AudioSample {
+    assert!(!is_adpcm(format));
+    let frames = (length.to_seconds().max(0.0) * sample_rate as f32) as usize;
+    // UNCLEAR: This assumes 8 bit samples are signed like 16 bit samples, so that zero is silence.
+    let data = alloc::vec![0; frames * sound_format_bytes_per_frame(format)];
+    Self::from_vec(data, format, sample_rate)
+  }
+
+  /// Creates a new AudioSample holding a copy of the 16 bit samples in `pcm`, which are
+  /// interleaved left and right samples if the `format` is stereo.
+  ///
+  /// # Panics
+  ///
+  /// Panics if the `format` is not `kSound16bitMono` or `kSound16bitStereo`.
+  pub fn from_pcm_buffer(pcm: &[i16], format: SoundFormat, sample_rate: u32) -> AudioSample {
+    assert!(is_pcm16(format));
+    let data = pcm.iter().flat_map(|s| s.to_ne_bytes()).collect();
+    Self::from_vec(data, format, sample_rate)
+  }
+
+  /// Creates a new AudioSample from the PCM WAV file at `path`, such as one written by
+  /// `save_wav()`.
+  ///
+  /// Unlike `from_file()`, this reads the file as written, rather than a file compiled by `pdc`, so
+  /// it can read files made by the game in its data folder.
+  pub fn from_wav_file(file: &File, path: &str) -> Result<AudioSample, Error> {
+    let (data, format, sample_rate) = wav::decode_wav(&file.read_file(path)?)?;
+    Ok(Self::from_vec(data, format, sample_rate))
+  }
+  /// Creates a new AudioSample from the WAV or AIFF file at `path`, which is read as written rather
+  /// than compiled by `pdc`.
+  ///
+  /// Integer samples of 8 to 32 bits, floating point samples, and IMA-ADPCM WAV files such as those
+  /// written by `save_adpcm_wav()` are converted to 16 bit, so they can be played by a `Synth`. If
+  /// the file's sample rate is not 11025, 22050 or 44100Hz, the audio is resampled to the next of
+  /// those rates above it. Files with more than two channels play their first two channels.
+  pub fn from_audio_file(file: &File, path: &str) -> Result<AudioSample, Error> {
+    let audio = audio_file::decode_audio_file(&file.read_file(path)?)?;
+    Ok(Self::from_decoded_audio(audio.resample_to_supported_rate()))
+  }
+  /// Creates a new AudioSample from the WAV or AIFF file at `path`, like
+  /// `from_audio_file()`, but resampled to `sample_rate`.
+  pub fn from_audio_file_with_rate(
+    file: &File,
+    path: &str,
+    sample_rate: u32,
+  ) -> Result<AudioSample, Error> {
+    let audio = audio_file::decode_audio_file(&file.read_file(path)?)?;
+    Ok(Self::from_decoded_audio(audio.resample(sample_rate)))
+  }
+  fn from_decoded_audio(audio: audio_file::DecodedAudio) -> AudioSample {
+    let format = if audio.stereo {
+      SoundFormat::kSound16bitStereo
+    } else {
+      SoundFormat::kSound16bitMono
+    };
+    Self::from_pcm_buffer(&audio.samples, format, audio.sample_rate)
+  }
+
+  /// Writes the sample to `path` in the game's data folder as a PCM WAV file, such as to keep a
+  /// recording from the microphone, or to inspect generated audio on a computer.
+  ///
+  /// Returns an error if the sample is ADPCM, which can not be written as PCM.
+  pub fn save_wav(&self, file: &File, path: &str) -> Result<(), Error> {
+    let format = self.sound_format();
+    if is_adpcm(format) {
+      return Err("save_wav: ADPCM samples can not be saved as WAV".into());
+    }
+    let wav = wav::encode_wav(self.data(), format, self.sample_rate());
+    file.write_file(path, &wav)?;
+    Ok(())
+  }
+
+  /// Writes the sample to `path` in the game's data folder as an IMA-ADPCM WAV file, which is about
+  /// a quarter of the size of 16 bit PCM, such as to keep a recording from the microphone.
+  ///
+  /// The file can be loaded back as 16 bit PCM with `from_audio_file()`, which can be played by a
+  /// `Synth`. Returns an error if the sample is already ADPCM, as its data can't be read back.
+  pub fn save_adpcm_wav(&self, file: &File, path: &str) -> Result<(), Error> {
+    let format = self.sound_format();
+    if is_adpcm(format) {
+      return Err("save_adpcm_wav: the sample is already ADPCM".into());
+    }
+    let pcm: Vec<i16> = match self.pcm() {
+      Some(pcm) => pcm.into(),
+      // UNCLEAR: This assumes 8 bit samples are signed, as in `with_length()`.
+      None => self.data().iter().map(|b| (*b as i8 as i16) << 8).collect(),
+    };
+    let wav = wav::encode_adpcm_wav(&pcm, sound_format_is_stereo(format), self.sample_rate());
+    file.write_file(path, &wav)?;
+    Ok(())
+  }
+
   /// Loads the sound data from the file at `path` into the existing AudioSample.
   pub fn load_file(&mut self, path: &str) {
     unsafe {
@@ -129,19 +233,117 @@ impl AudioSample {
   }
 
   /// Retrieves the sample’s data.
-  // Note: No mutable access to the buffer is provided because audio runs on a different thread, so
-  // changing data in the AudioSample is probably not intended and would be a data race.
   pub fn data(&self) -> &[u8] {
     let (ptr, _, _, bytes) = self.all_data();
     unsafe { core::slice::from_raw_parts(ptr, bytes as usize) }
   }
 
+  /// Retrieves the sample’s data as 16 bit samples, which are interleaved left and right samples if
+  /// the sample is stereo.
+  ///
+  /// Returns `None` if the sample's format is not `kSound16bitMono` or `kSound16bitStereo`.
+  pub fn pcm(&self) -> Option<&[i16]> {
+    if !is_pcm16(self.sound_format()) {
+      return None;
+    }
+    // SAFETY: Any bit pattern is a valid i16, and `align_to()` handles the alignment.
+    match unsafe { self.data().align_to::<i16>() } {
+      (&[], pcm, &[]) => Some(pcm),
+      _ => None,
+    }
+  }
+  /// Gives mutable access to the sample’s data as 16 bit samples, such as for applying an effect to
+  /// the sample or generating audio into it. The samples are interleaved left and right samples if
+  /// the sample is stereo.
+  ///
+  /// The `&mut self` borrow ensures no other reference to the data exists in the game. It does not
+  /// stop the audio thread, which reads the data directly while a `SamplePlayer` plays the sample,
+  /// and changing samples that are being read at the same time is a data race. Stop any
+  /// `SamplePlayer` that is playing the sample before writing to it.
+  ///
+  /// Returns `None` if the sample's format is not `kSound16bitMono` or `kSound16bitStereo`.
+  pub fn pcm_mut(&mut self) -> Option<&mut [i16]> {
+    let (ptr, format, _, bytes) = self.all_data();
+    if !is_pcm16(format) {
+      return None;
+    }
+    let data = unsafe { core::slice::from_raw_parts_mut(ptr, bytes as usize) };
+    // SAFETY: Any bit pattern is a valid i16, and `align_to_mut()` handles the alignment.
+    match unsafe { data.align_to_mut::<i16>() } {
+      (&mut [], pcm, &mut []) => Some(pcm),
+      _ => None,
+    }
+  }
+
+  /// Multiplies every sample by `gain`, clamping them to the range of a 16 bit sample.
+  ///
+  /// Returns an error if the sample's format is not `kSound16bitMono` or `kSound16bitStereo`.
+  pub fn apply_gain(&mut self, gain: f32) -> Result<(), Error> {
+    let pcm = self.pcm_mut().ok_or("apply_gain: the sample is not 16 bit PCM")?;
+    for s in pcm {
+      *s = (*s as f32 * gain).clamp(i16::MIN as f32, i16::MAX as f32) as i16;
+    }
+    Ok(())
+  }
+  /// Scales the sample so its loudest sample is at full volume, such as to even out the levels of
+  /// recordings from the microphone. Returns the gain that was applied.
+  ///
+  /// A silent sample is left unchanged, with a gain of 1. Returns an error if the sample's format
+  /// is not `kSound16bitMono` or `kSound16bitStereo`.
+  pub fn normalize(&mut self) -> Result<f32, Error> {
+    let pcm = self.pcm().ok_or("normalize: the sample is not 16 bit PCM")?;
+    let peak = pcm.iter().map(|s| s.unsigned_abs()).max().unwrap_or(0);
+    if peak == 0 {
+      return Ok(1.0);
+    }
+    let gain = i16::MAX as f32 / peak as f32;
+    self.apply_gain(gain)?;
+    Ok(gain)
+  }
+  /// Removes any constant offset from the samples of each channel, so that they are centered on
+  /// zero. An offset is often present in recordings from a microphone, and wastes headroom and can
+  /// click when the sound starts or stops.
+  ///
+  /// Returns an error if the sample's format is not `kSound16bitMono` or `kSound16bitStereo`.
+  pub fn remove_dc_offset(&mut self) -> Result<(), Error> {
+    let channels = if sound_format_is_stereo(self.sound_format()) {
+      2
+    } else {
+      1
+    };
+    let pcm = self.pcm_mut().ok_or("remove_dc_offset: the sample is not 16 bit PCM")?;
+    let frames = pcm.len() / channels;
+    if frames == 0 {
+      return Ok(());
+    }
+    for c in 0..channels {
+      let sum: i64 = pcm.iter().skip(c).step_by(channels).map(|s| *s as i64).sum();
+      let offset = sum / frames as i64;
+      for s in pcm.iter_mut().skip(c).step_by(channels) {
+        *s = (*s as i64 - offset).clamp(i16::MIN as i64, i16::MAX as i64) as i16;
+      }
+    }
+    Ok(())
+  }
+
+  /// The number of sample frames in the sample, where a frame holds one sample for each channel.
+  ///
+  /// Returns 0 for ADPCM samples, where the number of frames depends on the encoding.
+  pub fn frame_count(&self) -> usize {
+    let (_, format, _, bytes) = self.all_data();
+    if is_adpcm(format) {
+      0
+    } else {
+      bytes as usize / sound_format_bytes_per_frame(format)
+    }
+  }
+
   /// Retrieves the sample’s SoundFormat.
   pub fn sound_format(&self) -> SoundFormat {
     let (_, format, _, _) = self.all_data();
     format
   }
-  /// Retrieves the sample’s SoundFormat.
+  /// Retrieves the sample’s sample rate, in frames per second.
   pub fn sample_rate(&self) -> u32 {
     let (_, _, sample_rate, _) = self.all_data();
     sample_rate
@@ -164,3 +366,10 @@ impl Drop for AudioSample {
     unsafe { Self::fns().freeSample.unwrap()(self.cptr_mut()) }
   }
 }
+
+fn is_adpcm(format: SoundFormat) -> bool {
+  format == SoundFormat::kSoundADPCMMono || format == SoundFormat::kSoundADPCMStereo
+}
+fn is_pcm16(format: SoundFormat) -> bool {
+  format == SoundFormat::kSound16bitMono || format == SoundFormat::kSound16bitStereo
+}
@@ -0,0 +1,142 @@
+use alloc::collections::BTreeMap;
+
+use super::sources::file_player::FilePlayer;
+use super::sources::sample_player::SamplePlayer;
+use super::sources::sound_source::SoundSource;
+use crate::ctypes::*;
+use crate::system_event::SystemEvent;
+
+#[derive(Debug, Copy, Clone)]
+pub(crate) enum PausablePlayer {
+  File(*mut CFilePlayer),
+  Sample(*mut CSamplePlayer),
+}
+impl PausablePlayer {
+  fn is_playing(&self) -> bool {
+    let source = match *self {
+      PausablePlayer::File(ptr) => ptr as *mut CSoundSource,
+      PausablePlayer::Sample(ptr) => ptr as *mut CSoundSource,
+    };
+    unsafe { SoundSource::fns().isPlaying.unwrap()(source) != 0 }
+  }
+  fn pause(&self) {
+    match *self {
+      PausablePlayer::File(ptr) => unsafe { FilePlayer::fns().pause.unwrap()(ptr) },
+      PausablePlayer::Sample(ptr) => unsafe { SamplePlayer::fns().setPaused.unwrap()(ptr, 1) },
+    }
+  }
+  fn resume(&self, repeat: i32) {
+    match *self {
+      // Playing a paused FilePlayer resumes it from where it was paused.
+      PausablePlayer::File(ptr) => unsafe {
+        FilePlayer::fns().play.unwrap()(ptr, repeat);
+      },
+      PausablePlayer::Sample(ptr) => unsafe { SamplePlayer::fns().setPaused.unwrap()(ptr, 0) },
+    }
+  }
+}
+
+#[derive(Debug)]
+struct PlayerState {
+  player: PausablePlayer,
+  // The `repeat` value last passed to play(), which is needed to resume a FilePlayer.
+  repeat: i32,
+  // Whether the player was paused by `suspend_all()`, and should be resumed by `resume_all()`.
+  suspended: bool,
+}
+
+/// Tracks the players which can be paused, so that they can all be suspended together, such as
+/// while the device is locked.
+#[derive(Debug)]
+pub(crate) struct AudioSuspend {
+  players: BTreeMap<usize, PlayerState>,
+  suspended: bool,
+  suspend_on_lock: bool,
+}
+impl AudioSuspend {
+  pub(crate) fn new() -> Self {
+    AudioSuspend {
+      players: BTreeMap::new(),
+      suspended: false,
+      suspend_on_lock: true,
+    }
+  }
+
+  pub(crate) fn add_player(&mut self, player: PausablePlayer) {
+    let state = PlayerState {
+      player,
+      repeat: 1,
+      suspended: false,
+    };
+    self.players.insert(Self::key(player), state);
+  }
+  pub(crate) fn remove_player(&mut self, player: PausablePlayer) {
+    self.players.remove(&Self::key(player));
+  }
+  pub(crate) fn repeat(&self, player: PausablePlayer) -> i32 {
+    self.players.get(&Self::key(player)).map_or(1, |state| state.repeat)
+  }
+  pub(crate) fn set_repeat(&mut self, player: PausablePlayer, repeat: i32) {
+    if let Some(state) = self.players.get_mut(&Self::key(player)) {
+      state.repeat = repeat;
+    }
+  }
+  /// Called when the game pauses or resumes a player itself, which takes precedence over any
+  /// suspend in progress.
+  pub(crate) fn clear_suspended(&mut self, player: PausablePlayer) {
+    if let Some(state) = self.players.get_mut(&Self::key(player)) {
+      state.suspended = false;
+    }
+  }
+
+  pub(crate) fn is_suspended(&self) -> bool {
+    self.suspended
+  }
+  pub(crate) fn set_suspend_on_lock(&mut self, suspend: bool) {
+    self.suspend_on_lock = suspend;
+  }
+
+  pub(crate) fn suspend_all(&mut self) {
+    if self.suspended {
+      return;
+    }
+    self.suspended = true;
+    for state in self.players.values_mut() {
+      if state.player.is_playing() {
+        state.player.pause();
+        state.suspended = true;
+      }
+    }
+  }
+  pub(crate) fn resume_all(&mut self) {
+    if !self.suspended {
+      return;
+    }
+    self.suspended = false;
+    for state in self.players.values_mut() {
+      if state.suspended {
+        state.player.resume(state.repeat);
+        state.suspended = false;
+      }
+    }
+  }
+
+  /// Suspends audio while the device is locked.
+  pub(crate) fn on_system_event(&mut self, event: &SystemEvent) {
+    if !self.suspend_on_lock {
+      return;
+    }
+    match event {
+      SystemEvent::WillLock => self.suspend_all(),
+      SystemEvent::DidUnlock => self.resume_all(),
+      _ => (),
+    }
+  }
+
+  fn key(player: PausablePlayer) -> usize {
+    match player {
+      PausablePlayer::File(ptr) => ptr as usize,
+      PausablePlayer::Sample(ptr) => ptr as usize,
+    }
+  }
+}
@@ -6,8 +6,11 @@ use super::sound_effect::SoundEffect;
 use crate::capi_state::CApiState;
 use crate::ctypes::*;
 
-// A `BitCrusher` effect. A `BitCrusher` acts as a `SoundEffect` which can be added to a
-// `SoundChannel`.
+/// A `BitCrusher` effect. A `BitCrusher` acts as a `SoundEffect` which can be added to a
+/// `SoundChannel`.
+///
+/// It reduces the bit depth and the sample rate of the sound passing through it, for a lo-fi,
+/// retro sound.
 pub struct BitCrusher {
   effect: ManuallyDrop<SoundEffect>,
   ptr: NonNull<CBitCrusher>,
@@ -43,7 +46,7 @@ impl BitCrusher {
     self.amount_modulator = signal.map(|signal| signal.as_ref().clone());
   }
   /// Gets the current signal modulating the crushing amount.
-  pub fn amount_modulator(&mut self) -> Option<&SynthSignal> {
+  pub fn amount_modulator(&self) -> Option<&SynthSignal> {
     self.amount_modulator.as_ref()
   }
 
@@ -63,7 +66,7 @@ impl BitCrusher {
     self.undersampling_modulator = signal.map(|signal| signal.as_ref().clone());
   }
   /// Gets the current signal modulating the undersampling amount.
-  pub fn undersampling_modulator(&mut self) -> Option<&SynthSignal> {
+  pub fn undersampling_modulator(&self) -> Option<&SynthSignal> {
     self.undersampling_modulator.as_ref()
   }
 
@@ -75,6 +78,12 @@ impl BitCrusher {
   }
 }
 
+impl Default for BitCrusher {
+  fn default() -> Self {
+    Self::new()
+  }
+}
+
 impl Drop for BitCrusher {
   fn drop(&mut self) {
     // Ensure the SoundEffect has a chance to clean up before it is freed.
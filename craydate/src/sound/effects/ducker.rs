@@ -0,0 +1,234 @@
+use alloc::sync::Arc;
+use core::ffi::c_void;
+use core::mem::ManuallyDrop;
+use core::sync::atomic::{AtomicU32, Ordering};
+
+use super::super::Sound;
+use super::super::signals::lfo::Lfo;
+use super::super::signals::scaled_signal::AtomicF32;
+use super::super::signals::synth_signal::SynthSignal;
+use super::super::sound_channel::SoundChannel;
+use super::super::volume::Volume;
+use super::sound_effect::SoundEffect;
+use crate::ctypes::*;
+use crate::time::TimeDelta;
+
+/// The value of a full scale sample, as samples given to an effect are in Q8.24 fixed point.
+const FULL_SCALE: f32 = (1 << 24) as f32;
+
+/// The settings and the measured level, shared with the audio thread.
+#[derive(Debug)]
+struct DuckState {
+  threshold: AtomicF32,
+  ducked_volume: AtomicF32,
+  // Times are in sample frames.
+  attack: AtomicU32,
+  release: AtomicU32,
+  hold: AtomicU32,
+
+  // The sound time when the level on the key channel was last over the threshold.
+  last_loud_frame: AtomicU32,
+  // Whether the level has ever been over the threshold, as `last_loud_frame` starts out at 0.
+  heard: AtomicU32,
+  // The gain applied to the ducked channel.
+  gain: AtomicF32,
+}
+impl DuckState {
+  /// Moves the gain toward the ducked or full volume, given that `elapsed` frames passed since the
+  /// last step.
+  fn step_gain(&self, now: u32, elapsed: u32) -> f32 {
+    let ducking = self.heard.load(Ordering::Relaxed) != 0
+      && now.wrapping_sub(self.last_loud_frame.load(Ordering::Relaxed))
+        <= self.hold.load(Ordering::Relaxed);
+    let ducked = self.ducked_volume.get();
+    let (target, time) = if ducking {
+      (ducked, self.attack.load(Ordering::Relaxed))
+    } else {
+      (1.0, self.release.load(Ordering::Relaxed))
+    };
+    let gain = self.gain.get();
+    // Move across the full range, from the ducked volume to 1, over `time`.
+    let range = (1.0 - ducked).max(f32::EPSILON);
+    let step = if time == 0 {
+      range
+    } else {
+      range * elapsed as f32 / time as f32
+    };
+    let gain = if gain < target {
+      (gain + step).min(target)
+    } else {
+      (gain - step).max(target)
+    };
+    self.gain.set(gain);
+    gain
+  }
+}
+
+/// Lowers the volume of one `SoundChannel` while sound is playing loudly on another, such as to
+/// duck music under dialogue.
+///
+/// The `Ducker` is a `SoundEffect` which is added to the key channel, the one whose sound causes
+/// the ducking. It passes the sound through unchanged while measuring its level. The channel to be
+/// ducked is given to `duck()`, which sets the ducker's signal as that channel's volume modulator.
+///
+/// When the key channel's level goes over the threshold, the ducked channel fades down to the
+/// ducked volume over the attack time. Once the level has stayed under the threshold for the hold
+/// time, it fades back up over the release time.
+///
+/// The default channel's volume modulator is used by `Sound` for fades, so the ducked channel
+/// should be another channel, such as a bus in a `Mixer`.
+///
+/// # Example
+/// ```
+/// let mut ducker = Ducker::new();
+/// ducker.set_ducked_volume(Volume::new(0.3));
+/// mixer.bus_mut("dialogue").unwrap().add_sound_effect(&mut ducker)?;
+/// ducker.duck(mixer.bus_mut("music").unwrap());
+/// ```
+pub struct Ducker {
+  effect: ManuallyDrop<SoundEffect>,
+  ptr: *mut CSoundEffect,
+  lfo: Lfo,
+  state: Arc<DuckState>,
+  // The reference given to Playdate as the effect's userdata.
+  userdata: *const DuckState,
+}
+impl Ducker {
+  /// Creates a new `Ducker`, which ducks to half volume with a threshold of 0.1, an attack of 50ms,
+  /// a hold of 200ms and a release of 500ms.
+  pub fn new() -> Self {
+    let state = Arc::new(DuckState {
+      threshold: AtomicF32::new(0.1),
+      ducked_volume: AtomicF32::new(0.5),
+      attack: AtomicU32::new(TimeDelta::from_milliseconds(50).to_sample_frames() as u32),
+      release: AtomicU32::new(TimeDelta::from_milliseconds(500).to_sample_frames() as u32),
+      hold: AtomicU32::new(TimeDelta::from_milliseconds(200).to_sample_frames() as u32),
+      last_loud_frame: AtomicU32::new(0),
+      heard: AtomicU32::new(0),
+      gain: AtomicF32::new(1.0),
+    });
+
+    unsafe extern "C" fn c_effect_func(
+      effect: *mut CSoundEffect,
+      left: *mut i32,
+      right: *mut i32,
+      nsamples: i32,
+      bufactive: i32,
+    ) -> i32 {
+      if bufactive == 0 {
+        return 0;
+      }
+      let state = &*(SoundEffect::fns().getUserdata.unwrap()(effect) as *const DuckState);
+      let len = nsamples.max(0) as usize;
+      let left = core::slice::from_raw_parts(left, len);
+      // UNCLEAR: Whether the right buffer is valid for a mono channel, so it is not read unless it
+      // is non-null.
+      let peak = if right.is_null() {
+        left.iter().map(|s| s.unsigned_abs()).max().unwrap_or(0)
+      } else {
+        let right = core::slice::from_raw_parts(right, len);
+        left.iter().chain(right).map(|s| s.unsigned_abs()).max().unwrap_or(0)
+      };
+      if peak as f32 / FULL_SCALE > state.threshold.get() {
+        state.last_loud_frame.store(Sound::fns().getCurrentTime.unwrap()(), Ordering::Relaxed);
+        state.heard.store(1, Ordering::Relaxed);
+      }
+      // The sound is passed through unchanged.
+      bufactive
+    }
+
+    let userdata = Arc::into_raw(state.clone());
+    let ptr = unsafe {
+      SoundEffect::fns().newEffect.unwrap()(Some(c_effect_func), userdata as *mut c_void)
+    };
+
+    let current_time = Sound::fns().getCurrentTime.unwrap();
+    let shared = state.clone();
+    let mut last_frame = None;
+    let mut lfo = Lfo::new_with_user_function(false, move || {
+      // SAFETY: getCurrentTime() only reads the audio clock, which is safe from the audio thread.
+      let now = unsafe { current_time() };
+      let elapsed = last_frame.map_or(0, |last: u32| now.wrapping_sub(last));
+      last_frame = Some(now);
+      shared.step_gain(now, elapsed)
+    });
+    // Keep the gain moving even if nothing is playing on the ducked channel.
+    lfo.set_global(true);
+
+    Ducker {
+      effect: ManuallyDrop::new(SoundEffect::from_ptr(ptr)),
+      ptr,
+      lfo,
+      state,
+      userdata,
+    }
+  }
+
+  /// Makes the ducker control the volume of `channel`, by setting its volume modulator.
+  pub fn duck(&self, channel: &mut SoundChannel) {
+    channel.set_volume_modulator(Some(&self.lfo));
+  }
+  /// The signal which scales the volume of the ducked channel, between the ducked volume and 1.
+  ///
+  /// This can be used as a modulator elsewhere, such as to duck a single `Synth`.
+  pub fn ducking_signal(&self) -> &SynthSignal {
+    self.lfo.as_ref()
+  }
+
+  /// Sets the level (0.0 - 1.0) which the key channel's sound must go over to duck.
+  pub fn set_threshold(&mut self, threshold: f32) {
+    self.state.threshold.set(threshold)
+  }
+  /// Sets the volume that the ducked channel is lowered to, as a proportion of its volume.
+  pub fn set_ducked_volume(&mut self, volume: Volume) {
+    self.state.ducked_volume.set(volume.into())
+  }
+  /// Sets how long it takes to lower the ducked channel's volume.
+  pub fn set_attack(&mut self, attack: TimeDelta) {
+    self.state.attack.store(attack.to_sample_frames().max(0) as u32, Ordering::Relaxed)
+  }
+  /// Sets how long the key channel must be quiet before the ducked channel's volume is raised.
+  pub fn set_hold(&mut self, hold: TimeDelta) {
+    self.state.hold.store(hold.to_sample_frames().max(0) as u32, Ordering::Relaxed)
+  }
+  /// Sets how long it takes to raise the ducked channel's volume back up.
+  pub fn set_release(&mut self, release: TimeDelta) {
+    self.state.release.store(release.to_sample_frames().max(0) as u32, Ordering::Relaxed)
+  }
+
+  /// Returns the gain (0.0 - 1.0) currently applied to the ducked channel.
+  pub fn gain(&self) -> f32 {
+    self.state.gain.get()
+  }
+  /// Returns whether the ducked channel is lowered, or moving down or back up.
+  pub fn is_ducking(&self) -> bool {
+    self.gain() < 1.0
+  }
+}
+
+impl Default for Ducker {
+  fn default() -> Self {
+    Self::new()
+  }
+}
+
+impl Drop for Ducker {
+  fn drop(&mut self) {
+    // Ensure the SoundEffect has a chance to clean up before it is freed.
+    unsafe { ManuallyDrop::drop(&mut self.effect) };
+    unsafe { SoundEffect::fns().freeEffect.unwrap()(self.ptr) };
+    // The effect is gone, so Playdate no longer holds the userdata.
+    unsafe { Arc::from_raw(self.userdata) };
+  }
+}
+
+impl AsRef<SoundEffect> for Ducker {
+  fn as_ref(&self) -> &SoundEffect {
+    &self.effect
+  }
+}
+impl AsMut<SoundEffect> for Ducker {
+  fn as_mut(&mut self) -> &mut SoundEffect {
+    &mut self.effect
+  }
+}
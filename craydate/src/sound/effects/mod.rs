@@ -1,5 +1,6 @@
 pub mod bit_crusher;
 pub mod delay_line;
+pub mod ducker;
 pub mod one_pole_filter;
 pub mod overdrive;
 pub mod ring_modulator;
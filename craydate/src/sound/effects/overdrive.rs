@@ -6,8 +6,11 @@ use super::sound_effect::SoundEffect;
 use crate::capi_state::CApiState;
 use crate::ctypes::*;
 
-// An `Overdrive` effect. An `Overdrive` acts as a `SoundEffect` which can be added to a
-// `SoundChannel`.
+/// An overdrive effect. An `Overdrive` acts as a `SoundEffect` which can be added to a
+/// `SoundChannel`.
+///
+/// It amplifies the sound passing through it by the gain, and then clips it at the limit, which
+/// distorts the sound. An offset makes the clipping asymmetric.
 pub struct Overdrive {
   effect: ManuallyDrop<SoundEffect>,
   ptr: NonNull<COverdrive>,
@@ -26,7 +29,7 @@ impl Overdrive {
     }
   }
 
-  /// Sets the gain of the overdrive effect.
+  /// Sets the gain of the overdrive effect, which the input is multiplied by before it is clipped.
   pub fn set_gain(&mut self, gain: f32) {
     unsafe { Self::fns().setGain.unwrap()(self.cptr_mut(), gain) }
   }
@@ -45,7 +48,7 @@ impl Overdrive {
     self.limit_modulator = signal.map(|signal| signal.as_ref().clone());
   }
   /// Gets the current signal modulating the limit parameter.
-  pub fn limit_modulator(&mut self) -> Option<&SynthSignal> {
+  pub fn limit_modulator(&self) -> Option<&SynthSignal> {
     self.limit_modulator.as_ref()
   }
 
@@ -63,7 +66,7 @@ impl Overdrive {
     self.offset_modulator = signal.map(|signal| signal.as_ref().clone());
   }
   /// Gets the current signal modulating the offset parameter.
-  pub fn offset_modulator(&mut self) -> Option<&SynthSignal> {
+  pub fn offset_modulator(&self) -> Option<&SynthSignal> {
     self.offset_modulator.as_ref()
   }
 
@@ -75,6 +78,12 @@ impl Overdrive {
   }
 }
 
+impl Default for Overdrive {
+  fn default() -> Self {
+    Self::new()
+  }
+}
+
 impl Drop for Overdrive {
   fn drop(&mut self) {
     // Ensure the SoundEffect has a chance to clean up before it is freed.
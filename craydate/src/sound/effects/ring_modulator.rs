@@ -6,8 +6,11 @@ use super::sound_effect::SoundEffect;
 use crate::capi_state::CApiState;
 use crate::ctypes::*;
 
-// A ring modulator effect. A `RingModulator` acts as a `SoundEffect` which can be added to a
-// `SoundChannel`.
+/// A ring modulator effect. A `RingModulator` acts as a `SoundEffect` which can be added to a
+/// `SoundChannel`.
+///
+/// It multiplies the sound passing through it with a sine wave, which gives a metallic or robotic
+/// sound when the frequency is in the audible range, and a tremolo when it is very low.
 pub struct RingModulator {
   effect: ManuallyDrop<SoundEffect>,
   ptr: NonNull<CRingModulator>,
@@ -24,7 +27,7 @@ impl RingModulator {
     }
   }
 
-  /// Sets the frequency of the modulation signal.
+  /// Sets the frequency, in Hz, of the modulation signal.
   pub fn set_frequency(&mut self, frequency: f32) {
     unsafe { Self::fns().setFrequency.unwrap()(self.cptr_mut(), frequency) }
   }
@@ -33,12 +36,12 @@ impl RingModulator {
     let modulator_ptr = signal.map_or_else(core::ptr::null_mut, |signal|
       // setFrequencyModulator() takes a mutable pointer to the modulator but there is no visible
       // state on the modulator.
-      signal.as_ref().cptr()as *mut _);
+      signal.as_ref().cptr() as *mut _);
     unsafe { Self::fns().setFrequencyModulator.unwrap()(self.cptr_mut(), modulator_ptr) }
     self.frequency_modulator = signal.map(|signal| signal.as_ref().clone());
   }
   /// Gets the current signal modulating the frequency of the ring modulator.
-  pub fn frequency_modulator(&mut self) -> Option<&SynthSignal> {
+  pub fn frequency_modulator(&self) -> Option<&SynthSignal> {
     self.frequency_modulator.as_ref()
   }
 
@@ -50,6 +53,12 @@ impl RingModulator {
   }
 }
 
+impl Default for RingModulator {
+  fn default() -> Self {
+    Self::new()
+  }
+}
+
 impl Drop for RingModulator {
   fn drop(&mut self) {
     // Ensure the SoundEffect has a chance to clean up before it is freed.
@@ -55,7 +55,7 @@ impl SoundEffect {
     self.mix_modulator = signal.map(|signal| signal.as_ref().clone());
   }
   /// Gets the current signal modulating the effect’s mix level.
-  pub fn mix_modulator(&mut self) -> Option<&SynthSignal> {
+  pub fn mix_modulator(&self) -> Option<&SynthSignal> {
     self.mix_modulator.as_ref()
   }
 
@@ -115,6 +115,11 @@ impl Drop for SoundEffect {
   }
 }
 
+/// Provides access to the `SoundEffect` methods of any sound effect type, such as `BitCrusher`.
+///
+/// Each sound effect type implements `AsRef<SoundEffect>` and `AsMut<SoundEffect>`, which
+/// gives it this trait, so code can work with any sound effect through generics like
+/// `T: AsSoundEffect`.
 pub trait AsSoundEffect: AsRef<SoundEffect> + AsMut<SoundEffect> {
   fn as_sound_effect(&self) -> &SoundEffect {
     self.as_ref()
@@ -6,8 +6,12 @@ use super::sound_effect::SoundEffect;
 use crate::capi_state::CApiState;
 use crate::ctypes::*;
 
-// A two pole IIR filter, which is one of the `TwoPoleFilterType` types. A `TwoPoleFilter` acts as a
-// `SoundEffect` which can be added to a `SoundChannel`.
+/// A two pole IIR filter, which is one of the `TwoPoleFilterType` types. A `TwoPoleFilter` acts as
+/// a `SoundEffect` which can be added to a `SoundChannel`.
+///
+/// The low-pass, high-pass, band-pass and notch types cut frequencies around the filter's
+/// frequency, and are useful for muffling and wah effects. The peaking (PEQ) and shelf types boost
+/// or cut frequencies by the filter's gain, and are useful for equalizing a channel.
 pub struct TwoPoleFilter {
   effect: ManuallyDrop<SoundEffect>,
   ptr: NonNull<CTwoPoleFilter>,
@@ -15,7 +19,7 @@ pub struct TwoPoleFilter {
   resonance_modulator: Option<SynthSignal>,
 }
 impl TwoPoleFilter {
-  /// Creates a new `TwoPoleFilter`.
+  /// Creates a new `TwoPoleFilter` of type `filter_type`.
   pub fn new(filter_type: TwoPoleFilterType) -> Self {
     let ptr = unsafe { Self::fns().newFilter.unwrap()() };
     let mut f = TwoPoleFilter {
@@ -42,23 +46,25 @@ impl TwoPoleFilter {
   /// The signal is scaled so that a value of 1.0 corresponds to half the sample rate.
   pub fn set_frequency_modulator<T: AsRef<SynthSignal>>(&mut self, signal: Option<&T>) {
     let modulator_ptr = signal.map_or_else(core::ptr::null_mut, |signal|
-      // setFrequencyModulator() takes a mutable pointer to the modulator but there is no visible state on
-      // the modulator.
+      // setFrequencyModulator() takes a mutable pointer to the modulator but there is no visible
+      // state on the modulator.
       signal.as_ref().cptr() as *mut _);
     unsafe { Self::fns().setFrequencyModulator.unwrap()(self.cptr_mut(), modulator_ptr) }
     self.frequency_modulator = signal.map(|signal| signal.as_ref().clone());
   }
   /// Gets the current signal modulating the effect’s frequency.
-  pub fn frequency_modulator(&mut self) -> Option<&SynthSignal> {
+  pub fn frequency_modulator(&self) -> Option<&SynthSignal> {
     self.frequency_modulator.as_ref()
   }
 
-  /// Sets the filter gain.
+  /// Sets the filter gain, in dB, which is used by the `kFilterTypePEQ`, `kFilterTypeLowShelf`
+  /// and `kFilterTypeHighShelf` types.
   pub fn set_gain(&mut self, gain: f32) {
     unsafe { Self::fns().setGain.unwrap()(self.cptr_mut(), gain) }
   }
 
-  /// Sets the center/corner resonance of the filter. Value is in Hz.
+  /// Sets the resonance of the filter, from 0 to 1, which boosts the frequencies near the
+  /// center/corner frequency.
   pub fn set_resonance(&mut self, resonance: f32) {
     unsafe { Self::fns().setResonance.unwrap()(self.cptr_mut(), resonance) }
   }
@@ -72,7 +78,7 @@ impl TwoPoleFilter {
     self.resonance_modulator = signal.map(|signal| signal.as_ref().clone());
   }
   /// Gets the current signal modulating the effect’s filter resonance.
-  pub fn resonance_modulator(&mut self) -> Option<&SynthSignal> {
+  pub fn resonance_modulator(&self) -> Option<&SynthSignal> {
     self.resonance_modulator.as_ref()
   }
 
@@ -53,13 +53,18 @@ impl ActiveMicrophoneCallback {
       _c_function_data: boxed_c_function_data,
     }
   }
+
+  /// Whether this is still the callback set in Playdate, and has not been replaced by another.
+  pub(crate) fn is_active(&self) -> bool {
+    self.generation == CApiState::get().headphone_change_generation.get()
+  }
 }
 
 impl Drop for ActiveMicrophoneCallback {
   fn drop(&mut self) {
     // Use a generation tag to avoid unsetting the headphone callback if another callback was set
     // before this object was dropped.
-    if self.generation == CApiState::get().headphone_change_generation.get() {
+    if self.is_active() {
       unsafe { Sound::fns().setMicCallback.unwrap()(None, core::ptr::null_mut(), false as i32) }
     }
   }
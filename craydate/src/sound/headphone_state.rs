@@ -1,3 +1,11 @@
+use core::future::Future;
+use core::pin::Pin;
+use core::task::{Context, Poll};
+
+use super::Sound;
+use crate::capi_state::CApiState;
+use crate::executor::Executor;
+
 /// The state of the headphone jack.
 #[derive(Copy, Clone, Debug, PartialEq, Eq)]
 pub enum HeadphoneState {
@@ -14,4 +22,50 @@ impl HeadphoneState {
       }
     }
   }
+
+  /// Reads the current state from Playdate.
+  pub(crate) fn current() -> Self {
+    // Grab the function pointer last passed to getHeadphoneState() in
+    // `set_headphone_change_callback()`, so that we don't change that here.
+    let func = CApiState::get().headphone_change_func.borrow().clone();
+
+    let mut headphone = 0;
+    let mut mic = 0;
+    unsafe { Sound::fns().getHeadphoneState.unwrap()(&mut headphone, &mut mic, func) };
+    HeadphoneState::new(headphone != 0, mic != 0)
+  }
+
+  /// Whether headphones are plugged in.
+  pub fn is_connected(&self) -> bool {
+    *self != HeadphoneState::HeadphoneNotConnected
+  }
+  /// Whether headphones with a microphone are plugged in.
+  pub fn has_microphone(&self) -> bool {
+    matches!(
+      self,
+      HeadphoneState::HeadphoneConnected {
+        has_microphone: true
+      }
+    )
+  }
+}
+
+/// A future for which poll() waits for the `HeadphoneState` to change from `from`, then returns
+/// Complete with the new state.
+pub(crate) struct HeadphoneChangedFuture {
+  pub from: HeadphoneState,
+}
+
+impl Future for HeadphoneChangedFuture {
+  type Output = HeadphoneState;
+
+  fn poll(self: Pin<&mut Self>, ctxt: &mut Context<'_>) -> Poll<Self::Output> {
+    let state = HeadphoneState::current();
+    if state == self.from {
+      Executor::add_waker_for_system_event(CApiState::get().executor, ctxt.waker());
+      Poll::Pending
+    } else {
+      Poll::Ready(state)
+    }
+  }
 }
@@ -1,6 +1,7 @@
 use crate::time::{TimeSpan, TimeTicks};
 
 /// A span of time for sound to loop over.
+#[derive(Debug)]
 pub enum LoopTimeSpan {
   /// A bounded time span that specifies a start and end time.
   Bounded(TimeSpan),
@@ -0,0 +1,203 @@
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+use core::future::Future;
+use core::pin::Pin;
+use core::sync::atomic::{AtomicBool, AtomicI16, AtomicUsize, Ordering};
+use core::task::{Context, Poll};
+
+use super::SAMPLE_FRAMES_PER_SEC;
+use super::audio_sample::AudioSample;
+use super::headphone::{ActiveMicrophoneCallback, MicrophoneCallbackOutput};
+use crate::capi_state::CApiState;
+use crate::ctypes::*;
+use crate::executor::Executor;
+use crate::time::TimeDelta;
+
+/// Which microphone to record from.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum MicrophoneSource {
+  /// The microphone on the plugged in headset if it has one, and the device's internal microphone
+  /// otherwise.
+  PreferHeadset,
+  /// The device's internal microphone, even when a headset with a microphone is plugged in.
+  Device,
+}
+impl MicrophoneSource {
+  pub(crate) fn force_device_microphone(self) -> bool {
+    self == MicrophoneSource::Device
+  }
+}
+
+/// The recorded samples, shared between the game thread and the audio thread.
+///
+/// The audio thread writes samples past `len` and then moves `len` forward, so the game only reads
+/// samples that have been fully written. The buffer is allocated up front so that the audio thread
+/// never allocates.
+struct RecordBuffer {
+  samples: Vec<AtomicI16>,
+  len: AtomicUsize,
+  stopped: AtomicBool,
+}
+impl RecordBuffer {
+  fn is_full(&self) -> bool {
+    self.len.load(Ordering::Acquire) >= self.samples.len()
+  }
+}
+
+/// A recording from the microphone into memory, started by `Sound::record_microphone()`.
+///
+/// The recording stops when it reaches its maximum length, when `stop()` is called, or when it is
+/// dropped. The samples recorded so far can be read while it is recording, such as to show a
+/// level meter or to process the audio in chunks, and the finished recording can be turned into
+/// an `AudioSample` with `into_sample()` to be played back.
+///
+/// Recording uses the microphone callback, so it replaces any callback set by
+/// `Sound::set_microphone_callback()`, and the same caveats apply.
+///
+/// # Example
+/// ```
+/// let recording =
+///   api.sound.record_microphone(MicrophoneSource::PreferHeadset, TimeDelta::from_seconds(5));
+/// recording.finished().await;
+/// let player = SamplePlayer::new(&recording.into_sample());
+/// ```
+pub struct MicrophoneRecording {
+  buffer: Arc<RecordBuffer>,
+  callback: Option<ActiveMicrophoneCallback>,
+}
+impl MicrophoneRecording {
+  pub(crate) fn start(source: MicrophoneSource, max_length: TimeDelta) -> Self {
+    let frames = max_length.to_sample_frames().max(0) as usize;
+    let buffer = Arc::new(RecordBuffer {
+      samples: (0..frames).map(|_| AtomicI16::new(0)).collect(),
+      len: AtomicUsize::new(0),
+      stopped: AtomicBool::new(false),
+    });
+
+    let audio_buffer = buffer.clone();
+    let callback = ActiveMicrophoneCallback::set_active_callback(
+      move |input: &[i16]| {
+        let buffer = &audio_buffer;
+        if buffer.stopped.load(Ordering::Relaxed) {
+          return MicrophoneCallbackOutput::StopRecording;
+        }
+        let start = buffer.len.load(Ordering::Relaxed);
+        let end = (start + input.len()).min(buffer.samples.len());
+        for (slot, sample) in buffer.samples[start..end].iter().zip(input) {
+          slot.store(*sample, Ordering::Relaxed);
+        }
+        buffer.len.store(end, Ordering::Release);
+        if end < buffer.samples.len() {
+          MicrophoneCallbackOutput::ContinueRecording
+        } else {
+          MicrophoneCallbackOutput::StopRecording
+        }
+      },
+      source.force_device_microphone(),
+    );
+
+    MicrophoneRecording {
+      buffer,
+      callback: Some(callback),
+    }
+  }
+
+  /// Whether the microphone is still recording.
+  ///
+  /// The recording stops when it is full, when `stop()` is called, or when another microphone
+  /// callback or recording replaces it.
+  pub fn is_recording(&self) -> bool {
+    let active = self.callback.as_ref().is_some_and(|callback| callback.is_active());
+    active && !self.buffer.stopped.load(Ordering::Relaxed) && !self.buffer.is_full()
+  }
+  /// Stops recording. The samples recorded so far are kept.
+  pub fn stop(&mut self) {
+    self.buffer.stopped.store(true, Ordering::Relaxed);
+    self.callback = None;
+  }
+
+  /// The number of samples recorded so far.
+  pub fn samples_len(&self) -> usize {
+    self.buffer.len.load(Ordering::Acquire)
+  }
+  /// The length of audio recorded so far.
+  pub fn recorded_length(&self) -> TimeDelta {
+    TimeDelta::from_sample_frames(self.samples_len() as i32)
+  }
+  /// The length of audio that can be recorded before the recording stops.
+  pub fn max_length(&self) -> TimeDelta {
+    TimeDelta::from_sample_frames(self.buffer.samples.len() as i32)
+  }
+
+  /// Returns a copy of the samples recorded after the first `start` samples. The samples are mono,
+  /// 16 bit, at 44.1kHz.
+  pub fn samples_from(&self, start: usize) -> Vec<i16> {
+    let len = self.samples_len();
+    let start = start.min(len);
+    self.buffer.samples[start..len].iter().map(|s| s.load(Ordering::Relaxed)).collect()
+  }
+  /// Waits until samples are recorded after the first `start` samples, and returns a copy of
+  /// them, such as to process the recording in chunks while it is recording.
+  ///
+  /// Returns an empty chunk if the recording stops without any more samples.
+  ///
+  /// # Example
+  /// ```
+  /// let mut processed = 0;
+  /// loop {
+  ///   let chunk = recording.next_chunk(processed).await;
+  ///   if chunk.is_empty() { break; }
+  ///   processed += chunk.len();
+  ///   show_level_meter(&chunk);
+  /// }
+  /// ```
+  pub async fn next_chunk(&self, start: usize) -> Vec<i16> {
+    MicrophoneRecordingFuture {
+      recording: self,
+      more_than: start,
+    }
+    .await;
+    self.samples_from(start)
+  }
+
+  /// Waits until the recording stops, either because it reached its maximum length, or it was
+  /// stopped or replaced.
+  pub async fn finished(&self) {
+    MicrophoneRecordingFuture {
+      recording: self,
+      more_than: usize::MAX,
+    }
+    .await
+  }
+
+  /// Stops recording, and returns the recorded audio as an `AudioSample`.
+  pub fn into_sample(mut self) -> AudioSample {
+    self.stop();
+    let data = self.samples_from(0).iter().flat_map(|s| s.to_ne_bytes()).collect();
+    AudioSample::from_vec(
+      data,
+      SoundFormat::kSound16bitMono,
+      SAMPLE_FRAMES_PER_SEC as u32,
+    )
+  }
+}
+
+/// A future for which poll() waits for a `MicrophoneRecording` to have more than `more_than`
+/// samples, or to stop, then returns Complete.
+struct MicrophoneRecordingFuture<'a> {
+  recording: &'a MicrophoneRecording,
+  more_than: usize,
+}
+
+impl Future for MicrophoneRecordingFuture<'_> {
+  type Output = ();
+
+  fn poll(self: Pin<&mut Self>, ctxt: &mut Context<'_>) -> Poll<Self::Output> {
+    if self.recording.is_recording() && self.recording.samples_len() <= self.more_than {
+      Executor::add_waker_for_system_event(CApiState::get().executor, ctxt.waker());
+      Poll::Pending
+    } else {
+      Poll::Ready(())
+    }
+  }
+}
@@ -1,4 +1,5 @@
 pub mod midi_note_range;
+pub mod note;
 pub mod sequence;
 pub mod sequence_track;
 pub mod track_note;
@@ -0,0 +1,316 @@
+use alloc::format;
+use alloc::vec::Vec;
+
+use crate::error::Error;
+
+/// The name of a note within an octave, without the octave.
+///
+/// Notes are named with sharps. `Note::from_str()` also accepts flats, such as "Db3" for `CSharp`
+/// in octave 3.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum PitchClass {
+  C,
+  CSharp,
+  D,
+  DSharp,
+  E,
+  F,
+  FSharp,
+  G,
+  GSharp,
+  A,
+  ASharp,
+  B,
+}
+impl PitchClass {
+  const ALL: [PitchClass; 12] = [
+    Self::C,
+    Self::CSharp,
+    Self::D,
+    Self::DSharp,
+    Self::E,
+    Self::F,
+    Self::FSharp,
+    Self::G,
+    Self::GSharp,
+    Self::A,
+    Self::ASharp,
+    Self::B,
+  ];
+
+  /// Returns the pitch class that is `semitones` above C, wrapping around each octave.
+  pub fn from_semitones(semitones: i32) -> Self {
+    Self::ALL[semitones.rem_euclid(12) as usize]
+  }
+  /// Returns the number of semitones above C, between 0 and 11.
+  pub fn semitones(self) -> u8 {
+    self as u8
+  }
+
+  fn name(self) -> &'static str {
+    match self {
+      Self::C => "C",
+      Self::CSharp => "C#",
+      Self::D => "D",
+      Self::DSharp => "D#",
+      Self::E => "E",
+      Self::F => "F",
+      Self::FSharp => "F#",
+      Self::G => "G",
+      Self::GSharp => "G#",
+      Self::A => "A",
+      Self::ASharp => "A#",
+      Self::B => "B",
+    }
+  }
+}
+
+/// A musical note, which is one of the 128 MIDI notes.
+///
+/// Middle C is `C4`, which is MIDI note 60, and `A4` is the 440Hz note that instruments are tuned
+/// to. The lowest note is `C-1`, and the highest is `G9`.
+///
+/// See: <https://syntheway.com/MIDI_Keyboards_Middle_C_MIDI_Note_Number_60_C4.htm>
+///
+/// # Example
+/// ```
+/// let note: Note = "Db3".parse()?;
+/// assert_eq!(note.midi_number(), 49);
+/// for n in Note::C4.chord(Chord::Major) {
+///   synth.play_midi_note(TrackNote { note: n, ..Default::default() }, length, None);
+/// }
+/// ```
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Note(u8);
+impl Note {
+  /// Middle C, which is MIDI note 60.
+  pub const C4: Note = Note(60);
+  /// The A above middle C, at 440Hz, which is MIDI note 69.
+  pub const A4: Note = Note(69);
+  /// The lowest MIDI note, `C-1`.
+  pub const MIN: Note = Note(0);
+  /// The highest MIDI note, `G9`.
+  pub const MAX: Note = Note(127);
+
+  /// Constructs the note of the `pitch` in the `octave`, or `None` if it is not a MIDI note.
+  ///
+  /// Octaves start at C, so `Note::new(PitchClass::B, 3)` is the note below `C4`.
+  pub fn new(pitch: PitchClass, octave: i32) -> Option<Note> {
+    let number = (octave + 1) * 12 + pitch.semitones() as i32;
+    Self::from_midi_number(u8::try_from(number).ok()?)
+  }
+  /// Constructs the note with the MIDI note number, or `None` if it is not between 0 and 127.
+  pub const fn from_midi_number(number: u8) -> Option<Note> {
+    if number <= Self::MAX.0 {
+      Some(Note(number))
+    } else {
+      None
+    }
+  }
+  /// Returns the note nearest to the `frequency` in Hz, or `None` if that is not a MIDI note.
+  pub fn from_frequency(frequency: f32) -> Option<Note> {
+    if !frequency.is_finite() || frequency <= 0.0 {
+      return None;
+    }
+    // Find the equivalent frequency in the octave starting at C4, and count the octaves moved.
+    let c4 = Self::C4.frequency();
+    let mut f = frequency;
+    let mut octaves = 0;
+    while f < c4 {
+      f *= 2.0;
+      octaves -= 1;
+    }
+    while f >= c4 * 2.0 {
+      f /= 2.0;
+      octaves += 1;
+    }
+    // The nearest semitone, where a note is nearest if it's within a quarter tone (half a semitone)
+    // of the frequency. The 13th entry is the C above, for frequencies just below it.
+    let ratio = f / c4;
+    let semitones = (0..=12).min_by(|a, b| {
+      let da = (SEMITONE_RATIOS[*a] - ratio).abs();
+      let db = (SEMITONE_RATIOS[*b] - ratio).abs();
+      da.total_cmp(&db)
+    });
+    let number = Self::C4.0 as i32 + octaves * 12 + semitones.unwrap() as i32;
+    Self::from_midi_number(u8::try_from(number).ok()?)
+  }
+
+  /// Returns the MIDI note number, which is between 0 and 127.
+  pub const fn midi_number(self) -> u8 {
+    self.0
+  }
+  /// Returns the frequency of the note in Hz, in equal temperament tuned to 440Hz for `A4`.
+  pub fn frequency(self) -> f32 {
+    let semitones_from_a4 = self.0 as i32 - Self::A4.0 as i32;
+    let octaves = semitones_from_a4.div_euclid(12);
+    let semitones = semitones_from_a4.rem_euclid(12) as usize;
+    let mut f = 440.0 * SEMITONE_RATIOS[semitones];
+    for _ in 0..octaves.abs() {
+      if octaves > 0 { f *= 2.0 } else { f /= 2.0 }
+    }
+    f
+  }
+  /// Returns the name of the note within its octave.
+  pub fn pitch_class(self) -> PitchClass {
+    PitchClass::from_semitones(self.0 as i32)
+  }
+  /// Returns the octave of the note, where middle C is in octave 4. Octaves start at C.
+  pub fn octave(self) -> i32 {
+    self.0 as i32 / 12 - 1
+  }
+
+  /// Returns the note `semitones` above this one, or below it if negative, or `None` if that is
+  /// not a MIDI note.
+  pub fn transpose(self, semitones: i32) -> Option<Note> {
+    Self::from_midi_number(u8::try_from(self.0 as i32 + semitones).ok()?)
+  }
+  /// Returns the notes of the `chord` with this note as its root, leaving out any that are not
+  /// MIDI notes.
+  pub fn chord(self, chord: Chord) -> Vec<Note> {
+    chord.intervals().iter().filter_map(|i| self.transpose(*i as i32)).collect()
+  }
+  /// Returns the notes of the `scale` over one octave, starting with this note as its root, and
+  /// leaving out any that are not MIDI notes.
+  pub fn scale(self, scale: Scale) -> Vec<Note> {
+    scale.intervals().iter().filter_map(|i| self.transpose(*i as i32)).collect()
+  }
+  /// Returns the note at the `degree` of the `scale` with this note as its root, or `None` if that
+  /// is not a MIDI note.
+  ///
+  /// The root is degree 0, and degrees past the end of the scale continue into higher octaves, so
+  /// in a major scale degree 7 is the root an octave up. Negative degrees go down from the root.
+  pub fn scale_degree(self, scale: Scale, degree: i32) -> Option<Note> {
+    let intervals = scale.intervals();
+    let len = intervals.len() as i32;
+    let octave = degree.div_euclid(len);
+    let step = intervals[degree.rem_euclid(len) as usize] as i32;
+    self.transpose(octave * 12 + step)
+  }
+}
+
+/// The frequency ratio of each semitone above a note, up to an octave.
+const SEMITONE_RATIOS: [f32; 13] = [
+  1.0,
+  1.059_463_1,
+  1.122_462,
+  1.189_207_1,
+  1.259_921,
+  1.334_839_9,
+  core::f32::consts::SQRT_2,
+  1.498_307_1,
+  1.587_401,
+  1.681_792_9,
+  1.781_797_4,
+  1.887_748_6,
+  2.0,
+];
+
+impl core::str::FromStr for Note {
+  type Err = Error;
+
+  /// Parses a note name such as "C4", "A#3", "Db3" or "C-1".
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    let err = || Error::String(format!("invalid note name \"{}\"", s));
+    let mut chars = s.chars();
+    let letter = match chars.next().map(|c| c.to_ascii_uppercase()) {
+      Some('C') => 0,
+      Some('D') => 2,
+      Some('E') => 4,
+      Some('F') => 5,
+      Some('G') => 7,
+      Some('A') => 9,
+      Some('B') => 11,
+      _ => return Err(err()),
+    };
+    let rest = chars.as_str();
+    let (accidental, octave) = if let Some(octave) = rest.strip_prefix('#') {
+      (1, octave)
+    } else if let Some(octave) = rest.strip_prefix('b') {
+      (-1, octave)
+    } else {
+      (0, rest)
+    };
+    let octave: i32 = octave.parse().map_err(|_| err())?;
+    let number = (octave + 1) * 12 + letter + accidental;
+    u8::try_from(number).ok().and_then(Note::from_midi_number).ok_or_else(err)
+  }
+}
+
+impl core::fmt::Display for Note {
+  fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+    write!(f, "{}{}", self.pitch_class().name(), self.octave())
+  }
+}
+
+impl From<Note> for u8 {
+  fn from(note: Note) -> Self {
+    note.midi_number()
+  }
+}
+impl From<Note> for f32 {
+  fn from(note: Note) -> Self {
+    note.midi_number() as f32
+  }
+}
+
+/// A set of notes played together, built on a root note with `Note::chord()`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Chord {
+  Major,
+  Minor,
+  Diminished,
+  Augmented,
+  Sus2,
+  Sus4,
+  Major7,
+  Minor7,
+  Dominant7,
+}
+impl Chord {
+  /// Returns the semitones above the root of each note in the chord, including the root.
+  pub fn intervals(self) -> &'static [u8] {
+    match self {
+      Self::Major => &[0, 4, 7],
+      Self::Minor => &[0, 3, 7],
+      Self::Diminished => &[0, 3, 6],
+      Self::Augmented => &[0, 4, 8],
+      Self::Sus2 => &[0, 2, 7],
+      Self::Sus4 => &[0, 5, 7],
+      Self::Major7 => &[0, 4, 7, 11],
+      Self::Minor7 => &[0, 3, 7, 10],
+      Self::Dominant7 => &[0, 4, 7, 10],
+    }
+  }
+}
+
+/// A set of notes over an octave which melodies are built from, with a root note given to
+/// `Note::scale()` or `Note::scale_degree()`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Scale {
+  Major,
+  NaturalMinor,
+  HarmonicMinor,
+  MajorPentatonic,
+  MinorPentatonic,
+  Blues,
+  Dorian,
+  Mixolydian,
+  Chromatic,
+}
+impl Scale {
+  /// Returns the semitones above the root of each note in the scale, starting with the root.
+  pub fn intervals(self) -> &'static [u8] {
+    match self {
+      Self::Major => &[0, 2, 4, 5, 7, 9, 11],
+      Self::NaturalMinor => &[0, 2, 3, 5, 7, 8, 10],
+      Self::HarmonicMinor => &[0, 2, 3, 5, 7, 8, 11],
+      Self::MajorPentatonic => &[0, 2, 4, 7, 9],
+      Self::MinorPentatonic => &[0, 3, 5, 7, 10],
+      Self::Blues => &[0, 3, 5, 6, 7, 10],
+      Self::Dorian => &[0, 2, 3, 5, 7, 9, 10],
+      Self::Mixolydian => &[0, 2, 4, 5, 7, 9, 10],
+      Self::Chromatic => &[0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11],
+    }
+  }
+}
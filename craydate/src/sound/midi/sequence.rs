@@ -1,7 +1,10 @@
 use alloc::collections::BTreeMap;
 use alloc::vec::Vec;
+use core::future::Future;
 use core::marker::PhantomData;
+use core::pin::Pin;
 use core::ptr::NonNull;
+use core::task::{Context, Poll};
 
 use super::super::sources::instrument::Instrument;
 use super::super::SoundCompletionCallback;
@@ -11,6 +14,7 @@ use crate::callbacks::RegisteredCallback;
 use crate::capi_state::CApiState;
 use crate::ctypes::*;
 use crate::error::Error;
+use crate::executor::Executor;
 use crate::null_terminated::ToNullTerminatedString;
 
 /// Represents a MIDI music file, as a collection of `SequenceTrack`s that can be played together.
@@ -24,6 +28,8 @@ pub struct Sequence {
   // The set of instruments attached to tracks. Some of the tracks are owned by Playdate, and some
   // are owned by the this Sequence type. But all instruments are owned by this Sequence.
   instruments: BTreeMap<u32, Instrument>,
+  // The loop last set with `set_loops()`, as Playdate has no function to read it back.
+  loops: Option<SequenceLoops>,
 }
 impl Sequence {
   fn from_ptr(ptr: *mut CSoundSequence) -> Self {
@@ -32,11 +38,16 @@ impl Sequence {
       finished_callback: None,
       user_created_tracks: Vec::new(),
       instruments: BTreeMap::new(),
+      loops: None,
     }
   }
 
-  /// Constructs a new `Sequence`, which is a set of `SequenceTrack`s that can be played together.
-  pub(crate) fn new() -> Self {
+  /// Constructs a new, empty `Sequence`, which is a set of `SequenceTrack`s that can be played
+  /// together.
+  ///
+  /// Tracks can be added with `create_track_at_index()` and filled with notes, to generate music at
+  /// runtime.
+  pub fn new() -> Self {
     let ptr = unsafe { Self::fns().newSequence.unwrap()() };
     Self::from_ptr(ptr)
   }
@@ -217,6 +228,7 @@ impl Sequence {
     let track_ptr = unsafe { SequenceTrack::fns().newTrack.unwrap()() };
     assert!(!track_ptr.is_null());
     unsafe { Sequence::fns().setTrackAtIndex.unwrap()(self.cptr_mut(), track_ptr, index) };
+    self.user_created_tracks.push(NonNull::new(track_ptr).unwrap());
     let mut instrument = Instrument::new();
     unsafe { SequenceTrack::fns().setInstrument.unwrap()(track_ptr, instrument.cptr_mut()) };
     self.instruments.insert(index, instrument);
@@ -256,8 +268,15 @@ impl Sequence {
 
   /// Sets the looping range of the sequence.
   ///
-  /// If loops is 0, the loop repeats endlessly.
+  /// When the sequence reaches `end_step`, it jumps back to `start_step`, `count` times. If `count`
+  /// is 0, the loop repeats endlessly. Use `looped()` to wait for the end of each loop, such as to
+  /// change the music at the end of a phrase.
   pub fn set_loops(&mut self, start_step: u32, end_step: u32, count: i32) {
+    self.loops = Some(SequenceLoops {
+      start_step,
+      end_step,
+      count,
+    });
     // BUG: The step numbers should be u32 but the Playdate C Api has them as `int`:
     // <https://devforum.play.date/t/playdate-sound-sequence-setloops-takes-int-but-should-take-uint32-t/4980>
     unsafe {
@@ -265,6 +284,38 @@ impl Sequence {
     }
   }
 
+  /// Returns the looping range last set with `set_loops()`, if any.
+  pub fn loops(&self) -> Option<SequenceLoops> {
+    self.loops
+  }
+
+  /// Waits until the sequence jumps back to the start of its loop, and returns true. Returns false
+  /// if the sequence stops playing first.
+  ///
+  /// This completes up to a frame after the loop happens. The jump back is noticed as the current
+  /// step going backward, so `set_current_step()` to an earlier step while waiting also completes
+  /// it.
+  ///
+  /// # Example
+  /// ```
+  /// sequence.set_loops(0, 64, 0);
+  /// sequence.play(SoundCompletionCallback::none());
+  /// while sequence.looped().await {
+  ///   if boss_appeared {
+  ///     // Move on to the next section at the end of the phrase.
+  ///     sequence.set_loops(64, 128, 0);
+  ///     break;
+  ///   }
+  /// }
+  /// ```
+  pub async fn looped(&self) -> bool {
+    SequenceLoopedFuture {
+      sequence: self,
+      last_step: self.current_step(),
+    }
+    .await
+  }
+
   pub(crate) fn cptr(&self) -> *const CSoundSequence {
     self.ptr.as_ptr()
   }
@@ -276,6 +327,47 @@ impl Sequence {
   }
 }
 
+/// A range of steps that a `Sequence` loops over, as given to `Sequence::set_loops()`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct SequenceLoops {
+  /// The step that the sequence jumps back to at the end of the loop.
+  pub start_step: u32,
+  /// The step at which the sequence jumps back to `start_step`.
+  pub end_step: u32,
+  /// The number of times to loop, where 0 loops endlessly.
+  pub count: i32,
+}
+
+/// A future for which poll() waits for a `Sequence` to jump back to the start of its loop.
+struct SequenceLoopedFuture<'a> {
+  sequence: &'a Sequence,
+  last_step: u32,
+}
+
+impl Future for SequenceLoopedFuture<'_> {
+  type Output = bool;
+
+  fn poll(mut self: Pin<&mut Self>, ctxt: &mut Context<'_>) -> Poll<Self::Output> {
+    if !self.sequence.is_playing() {
+      return Poll::Ready(false);
+    }
+    let step = self.sequence.current_step();
+    if step < self.last_step {
+      Poll::Ready(true)
+    } else {
+      self.last_step = step;
+      Executor::add_waker_for_system_event(CApiState::get().executor, ctxt.waker());
+      Poll::Pending
+    }
+  }
+}
+
+impl Default for Sequence {
+  fn default() -> Self {
+    Self::new()
+  }
+}
+
 impl Drop for Sequence {
   fn drop(&mut self) {
     // The instruments will be dropped after the sequence-owned tracks that refer to them.
@@ -3,6 +3,7 @@ use core::marker::PhantomData;
 use core::ptr::NonNull;
 
 use super::super::sources::instrument::Instrument;
+use super::note::Note;
 use super::sequence::Sequence;
 use super::sequence_track_control::SequenceTrackControl;
 use super::track_note::{ResolvedTrackNote, TrackNote};
@@ -99,14 +100,20 @@ impl<'a> SequenceTrack<'a> {
         break;
       }
       v.push(ResolvedTrackNote {
+        step: out_step,
         length,
-        midi_note: midi_note as u8,
+        note: Note::from_midi_number(midi_note as u8).unwrap_or(Note::MAX),
         velocity: velocity.into(),
       });
     }
     v.into_iter()
   }
 
+  /// Returns the note playing `note` that starts at `step`, if there is one.
+  pub fn note_at(&self, step: u32, note: Note) -> Option<ResolvedTrackNote> {
+    self.notes_in_step_range(step, step).find(|n| n.note == note)
+  }
+
   /// Returns an iterator over all notes, as `ResolvedTrackNote`, in the track.
   pub fn notes(&self) -> impl Iterator<Item = ResolvedTrackNote> {
     let mut v = Vec::new();
@@ -130,8 +137,9 @@ impl<'a> SequenceTrack<'a> {
         break;
       }
       v.push(ResolvedTrackNote {
+        step: out_step,
         length,
-        midi_note: midi_note as u8,
+        note: Note::from_midi_number(midi_note as u8).unwrap_or(Note::MAX),
         velocity: velocity.into(),
       });
     }
@@ -225,11 +233,15 @@ impl<'a> SequenceTrackMut<'a> {
         self.cptr_mut(),
         step,
         length,
-        note.midi_note as f32,
+        note.note.into(),
         note.velocity.into(),
       )
     }
   }
+  /// Removes the note at `step` playing `note`.
+  pub fn remove_note(&mut self, step: u32, note: Note) {
+    self.remove_note_event(step, note.into())
+  }
   /// Removes the event at `step` playing `midi_note`.
   pub fn remove_note_event(&mut self, step: u32, midi_note: f32) {
     unsafe { SequenceTrack::fns().removeNoteEvent.unwrap()(self.cptr_mut(), step, midi_note) }
@@ -248,7 +260,7 @@ impl<'a> SequenceTrackMut<'a> {
     let seq = unsafe { self.sequence() };
     seq.set_track_instrument(self.index, instrument);
     let instrument: &mut Instrument = seq.track_instrument_mut(self.index);
-    self.track.instrument = unsafe { NonNull::new_unchecked(instrument as *mut _) };
+    self.track.instrument = NonNull::from(instrument);
   }
 
   /// Mutes the track.
@@ -1,14 +1,11 @@
 use super::super::volume::Volume;
+use super::note::Note;
 
 /// A MIDI note which is played as part of a `SequenceTrack` in a `SequenceTrack`.
 #[derive(Debug)]
 pub struct TrackNote {
-  /// The MIDI note number, which is between 0 and 127.
-  ///
-  /// See: <https://syntheway.com/MIDI_Keyboards_Middle_C_MIDI_Note_Number_60_C4.htm>
-  ///
-  /// TODO: Support MIDI string notation (e.g. "Db3").
-  pub midi_note: u8,
+  /// The MIDI note, which can be parsed from a string such as "Db3".
+  pub note: Note,
   /// Velocity indicates how hard the key was struck when the note was played, which usually
   /// corresponds to the note's loudness.
   pub velocity: Volume,
@@ -16,7 +13,7 @@ pub struct TrackNote {
 impl Default for TrackNote {
   fn default() -> Self {
     Self {
-      midi_note: 60,
+      note: Note::C4,
       velocity: Volume::one(),
     }
   }
@@ -26,10 +23,10 @@ impl Default for TrackNote {
 /// in a `SequenceTrack`.
 #[derive(Debug)]
 pub struct ResolvedTrackNote {
-  /// The MIDI note number, which is between 0 and 127.
-  ///
-  /// See: <https://syntheway.com/MIDI_Keyboards_Middle_C_MIDI_Note_Number_60_C4.htm>
-  pub midi_note: u8,
+  /// The step in the `SequenceTrack` where the note starts.
+  pub step: u32,
+  /// The MIDI note.
+  pub note: Note,
   /// Velocity indicates how hard the key was struck when the note was played, which usually
   /// corresponds to the note's loudness.
   pub velocity: Volume,
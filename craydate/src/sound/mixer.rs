@@ -0,0 +1,234 @@
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use super::Sound;
+use super::sound_channel::SoundChannel;
+use super::volume::Volume;
+use crate::error::Error;
+use crate::files::File;
+
+/// A named group of sounds in a `Mixer`, which plays through its own `SoundChannel`.
+#[derive(Debug)]
+struct MixerBus {
+  name: String,
+  channel: SoundChannel,
+  volume: f32,
+  muted: bool,
+  soloed: bool,
+}
+
+/// Mixes groups of sounds, such as "music", "sfx" and "ui", each with its own volume.
+///
+/// Each bus in the mixer is a `SoundChannel` which sources are added to, and the mixer sets the
+/// volume of each channel from the master volume, the bus volume, and whether the bus is muted or
+/// soloed. When any bus is soloed, only the soloed buses can be heard.
+///
+/// The user's volume settings can be kept across launches with `save()` and `load()`, such as from
+/// an options screen.
+///
+/// # Example
+/// ```
+/// let mut mixer = Mixer::new(&mut api.sound, &["music", "sfx", "ui"]);
+/// mixer.bus_mut("music").unwrap().add_source(&mut music_player)?;
+/// mixer.load(&api.file, "mixer.txt").ok();
+/// mixer.set_bus_volume_db("music", -6.0);
+/// mixer.save(&api.file, "mixer.txt")?;
+/// ```
+#[derive(Debug)]
+pub struct Mixer {
+  buses: Vec<MixerBus>,
+  master_volume: f32,
+}
+impl Mixer {
+  /// Constructs a mixer with a bus for each of the `names`, which are added to the sound system to
+  /// play from the device.
+  ///
+  /// Any duplicate names are ignored.
+  pub fn new(sound: &mut Sound, names: &[&str]) -> Self {
+    let mut mixer = Mixer {
+      buses: Vec::new(),
+      master_volume: 1.0,
+    };
+    for name in names {
+      let _ = mixer.add_bus(sound, name);
+    }
+    mixer
+  }
+
+  /// Adds a bus with the `name`, which is added to the sound system to play from the device.
+  ///
+  /// Returns an error if there is already a bus with the `name`, or if the `name` contains a line
+  /// break, as it could not be saved.
+  pub fn add_bus(&mut self, sound: &mut Sound, name: &str) -> Result<(), Error> {
+    if self.find(name).is_some() {
+      return Err(format!("Mixer: there is already a bus named \"{}\"", name).into());
+    }
+    if name.contains('\n') {
+      return Err("Mixer: bus names can not contain line breaks".into());
+    }
+    let mut channel = SoundChannel::new();
+    sound.add_channel(&mut channel);
+    self.buses.push(MixerBus {
+      name: name.into(),
+      channel,
+      volume: 1.0,
+      muted: false,
+      soloed: false,
+    });
+    self.apply_volumes();
+    Ok(())
+  }
+  /// Returns the names of the buses, in the order they were added.
+  pub fn bus_names(&self) -> impl Iterator<Item = &str> {
+    self.buses.iter().map(|bus| bus.name.as_str())
+  }
+  /// Returns the `SoundChannel` of the bus with the `name`, or `None` if there is no such bus.
+  pub fn bus(&self, name: &str) -> Option<&SoundChannel> {
+    self.find(name).map(|i| &self.buses[i].channel)
+  }
+  /// Returns the `SoundChannel` of the bus with the `name`, or `None` if there is no such bus.
+  ///
+  /// Sources and effects are added to the channel to play through the bus. Its volume is set by the
+  /// mixer, so setting the channel's volume directly will be undone by the mixer.
+  pub fn bus_mut(&mut self, name: &str) -> Option<&mut SoundChannel> {
+    self.find(name).map(|i| &mut self.buses[i].channel)
+  }
+
+  /// Sets the volume that every bus is scaled by.
+  pub fn set_master_volume(&mut self, volume: Volume) {
+    self.master_volume = volume.into();
+    self.apply_volumes()
+  }
+  /// Returns the volume that every bus is scaled by.
+  pub fn master_volume(&self) -> Volume {
+    self.master_volume.into()
+  }
+  /// Sets the volume that every bus is scaled by, as a gain in decibels.
+  pub fn set_master_volume_db(&mut self, db: f32) {
+    self.set_master_volume(Volume::from_decibels(db))
+  }
+  /// Returns the volume that every bus is scaled by, as a gain in decibels.
+  pub fn master_volume_db(&self) -> f32 {
+    self.master_volume().to_decibels()
+  }
+
+  /// Sets the volume of the bus with the `name`. Does nothing if there is no such bus.
+  pub fn set_bus_volume(&mut self, name: &str, volume: Volume) {
+    if let Some(i) = self.find(name) {
+      self.buses[i].volume = volume.into();
+      self.apply_volumes()
+    }
+  }
+  /// Returns the volume of the bus with the `name`, before the master volume is applied, or `None`
+  /// if there is no such bus.
+  pub fn bus_volume(&self, name: &str) -> Option<Volume> {
+    self.find(name).map(|i| self.buses[i].volume.into())
+  }
+  /// Sets the volume of the bus with the `name`, as a gain in decibels. Does nothing if there is no
+  /// such bus.
+  pub fn set_bus_volume_db(&mut self, name: &str, db: f32) {
+    self.set_bus_volume(name, Volume::from_decibels(db))
+  }
+  /// Returns the volume of the bus with the `name`, as a gain in decibels, or `None` if there is no
+  /// such bus.
+  pub fn bus_volume_db(&self, name: &str) -> Option<f32> {
+    self.bus_volume(name).map(|v| v.to_decibels())
+  }
+
+  /// Sets whether the bus with the `name` is muted. Does nothing if there is no such bus.
+  pub fn set_muted(&mut self, name: &str, muted: bool) {
+    if let Some(i) = self.find(name) {
+      self.buses[i].muted = muted;
+      self.apply_volumes()
+    }
+  }
+  /// Returns whether the bus with the `name` is muted. Returns false if there is no such bus.
+  pub fn is_muted(&self, name: &str) -> bool {
+    self.find(name).is_some_and(|i| self.buses[i].muted)
+  }
+  /// Sets whether the bus with the `name` is soloed. While any bus is soloed, the buses that are
+  /// not soloed are silent. Does nothing if there is no such bus.
+  pub fn set_soloed(&mut self, name: &str, soloed: bool) {
+    if let Some(i) = self.find(name) {
+      self.buses[i].soloed = soloed;
+      self.apply_volumes()
+    }
+  }
+  /// Returns whether the bus with the `name` is soloed. Returns false if there is no such bus.
+  pub fn is_soloed(&self, name: &str) -> bool {
+    self.find(name).is_some_and(|i| self.buses[i].soloed)
+  }
+
+  /// Saves the master volume, and the volume and mute setting of each bus, to the file at `path`.
+  ///
+  /// Solo is not saved, since it is meant for listening to a bus while working on a game.
+  pub fn save(&self, file: &File, path: &str) -> Result<(), Error> {
+    let mut text = format!("master {}\n", self.master_volume);
+    for bus in &self.buses {
+      text += &format!("bus {} {} {}\n", bus.volume, bus.muted as u8, bus.name);
+    }
+    Ok(file.write_file(path, text.as_bytes())?)
+  }
+  /// Loads the settings saved by `save()` from the file at `path`.
+  ///
+  /// Buses in the file which are not in the mixer are ignored, and buses in the mixer which are not
+  /// in the file keep their current settings, so that a save from an older version of a game can
+  /// still be loaded. Returns an error if the file can not be read or is not in the expected
+  /// format, in which case no settings are changed.
+  pub fn load(&mut self, file: &File, path: &str) -> Result<(), Error> {
+    let bytes = file.read_file(path)?;
+    let text = core::str::from_utf8(&bytes).map_err(|_| "Mixer: the settings are not UTF-8")?;
+    let bad_line = |line: &str| Error::String(format!("Mixer: bad settings line \"{}\"", line));
+
+    let mut master = None;
+    let mut buses = Vec::new();
+    for line in text.lines().filter(|line| !line.is_empty()) {
+      let mut parts = line.splitn(4, ' ');
+      match parts.next() {
+        Some("master") => {
+          let v = parts.next().and_then(|v| v.parse::<f32>().ok());
+          master = Some(v.ok_or_else(|| bad_line(line))?);
+        }
+        Some("bus") => {
+          let volume = parts.next().and_then(|v| v.parse::<f32>().ok());
+          let muted = parts.next().and_then(|m| m.parse::<u8>().ok());
+          let name = parts.next();
+          match (volume, muted, name) {
+            (Some(volume), Some(muted), Some(name)) => buses.push((name, volume, muted != 0)),
+            _ => return Err(bad_line(line)),
+          }
+        }
+        _ => return Err(bad_line(line)),
+      }
+    }
+
+    if let Some(master) = master {
+      self.master_volume = Volume::new(master).into();
+    }
+    for (name, volume, muted) in buses {
+      if let Some(i) = self.find(name) {
+        self.buses[i].volume = Volume::new(volume).into();
+        self.buses[i].muted = muted;
+      }
+    }
+    self.apply_volumes();
+    Ok(())
+  }
+
+  fn find(&self, name: &str) -> Option<usize> {
+    self.buses.iter().position(|bus| bus.name == name)
+  }
+  fn apply_volumes(&mut self) {
+    let any_soloed = self.buses.iter().any(|bus| bus.soloed);
+    for bus in &mut self.buses {
+      let audible = !bus.muted && (bus.soloed || !any_soloed);
+      let volume = if audible {
+        self.master_volume * bus.volume
+      } else {
+        0.0
+      };
+      bus.channel.set_volume(volume.into());
+    }
+  }
+}
@@ -1,48 +1,77 @@
+pub(crate) mod adpcm;
+pub(crate) mod audio_fade;
+pub(crate) mod audio_file;
 pub(crate) mod audio_sample;
+pub(crate) mod audio_suspend;
 pub(crate) mod effects;
 pub(crate) mod headphone;
 pub(crate) mod headphone_state;
 pub(crate) mod loop_sound_span;
+pub(crate) mod microphone;
 pub(crate) mod midi;
+pub(crate) mod mixer;
+pub(crate) mod music_manager;
+pub(crate) mod polyphony;
+pub(crate) mod sfx_pool;
 pub(crate) mod signals;
 pub(crate) mod sound_channel;
+pub(crate) mod sound_clock;
 pub(crate) mod sound_format;
+pub(crate) mod source_events;
 pub(crate) mod sources;
 pub(crate) mod volume;
+pub(crate) mod wav;
 
+pub use audio_fade::SystemFades;
 pub use audio_sample::AudioSample;
 pub use effects::bit_crusher::BitCrusher;
 pub use effects::delay_line::DelayLine;
+pub use effects::ducker::Ducker;
 pub use effects::one_pole_filter::OnePoleFilter;
 pub use effects::overdrive::Overdrive;
 pub use effects::ring_modulator::RingModulator;
-pub use effects::sound_effect::SoundEffect;
+pub use effects::sound_effect::{AsSoundEffect, SoundEffect};
 pub use effects::two_pole_filter::TwoPoleFilter;
 pub use headphone::{ActiveMicrophoneCallback, MicrophoneCallbackOutput};
 pub use headphone_state::HeadphoneState;
 pub use loop_sound_span::LoopTimeSpan;
+pub use microphone::{MicrophoneRecording, MicrophoneSource};
 pub use midi::midi_note_range::MidiNoteRange;
-pub use midi::sequence::Sequence;
-pub use midi::sequence_track::SequenceTrack;
-pub use midi::track_note::TrackNote;
-pub use signals::control::Control;
+pub use mixer::Mixer;
+pub use music_manager::{MusicManager, MusicTrack};
+pub use midi::note::{Chord, Note, PitchClass, Scale};
+pub use midi::sequence::{Sequence, SequenceLoops};
+pub use midi::sequence_track::{CreateSignalResult, SequenceTrack, SequenceTrackMut};
+pub use midi::sequence_track_control::SequenceTrackControl;
+pub use midi::track_note::{ResolvedTrackNote, TrackNote};
+pub use polyphony::Polyphony;
+pub use sfx_pool::SfxPool;
+pub use signals::control::{Control, ControlEvent, ControlRef};
 pub use signals::envelope::Envelope;
 pub use signals::lfo::Lfo;
+pub use signals::scaled_signal::ScaledSignal;
 pub use signals::synth_signal::{AsSynthSignal, SynthSignal};
+pub use signals::triggered_envelope::TriggeredEnvelope;
 pub use sound_channel::SoundChannel;
+pub use sound_clock::SoundClock;
 pub use sound_format::*;
+pub use source_events::PlayedNote;
 pub use sources::callback_source::CallbackSource;
 pub use sources::delay_line_tap::DelayLineTap;
 pub use sources::file_player::FilePlayer;
 pub use sources::instrument::{Instrument, VoiceId};
 pub use sources::sample_player::SamplePlayer;
 pub use sources::sound_source::{AsSoundSource, SoundSource};
-pub use sources::synth::{Synth, SynthGenerator, SynthGeneratorVTable, SynthRender};
+pub use sources::synth::{
+  NoiseColor, ParameterCache, Synth, SynthGenerator, SynthGeneratorVTable, SynthRender,
+};
 pub use volume::{StereoVolume, Volume};
 
+use audio_fade::AudioFade;
+use headphone_state::HeadphoneChangedFuture;
 use crate::callback_builder::{AllowNull, CallbackBuilder, CallbackBuilderWithArg, Constructed};
 use crate::capi_state::CApiState;
-use crate::time::TimeTicks;
+use crate::time::{TimeDelta, TimeTicks};
 
 pub(crate) const SAMPLE_FRAMES_PER_SEC: i32 = 44_100;
 
@@ -60,11 +89,12 @@ pub struct Sound {
 }
 impl Sound {
   pub(crate) fn new() -> Self {
-    Sound {
-      default_channel: SoundChannel::new_system_channel(unsafe {
-        Self::fns().getDefaultChannel.unwrap()()
-      }),
-    }
+    let mut default_channel =
+      SoundChannel::new_system_channel(unsafe { Self::fns().getDefaultChannel.unwrap()() });
+    let fade = AudioFade::new();
+    fade.attach_to_channel(&mut default_channel);
+    *CApiState::get().audio_fade.borrow_mut() = Some(fade);
+    Sound { default_channel }
   }
 
   /// The default `SoundChannel`. Attaching a `SoundSource` to it will play from the device.
@@ -76,9 +106,69 @@ impl Sound {
     &mut self.default_channel
   }
 
+  /// Fades the volume of the default `SoundChannel` to `volume` over `duration`, such as for a
+  /// transition between scenes.
+  ///
+  /// The fade is run by the audio engine, so it continues while the game is paused. It scales the
+  /// channel's volume through its volume modulator, so it will stop having an effect if the game
+  /// sets a different volume modulator on the default channel.
+  pub fn fade_to(&mut self, volume: Volume, duration: TimeDelta) {
+    Self::with_fade(|fade| fade.fade_to(volume.to_f32(), duration))
+  }
+  /// Fades the volume of the default `SoundChannel` down to silence over `duration`. See
+  /// `fade_to()`.
+  pub fn fade_out(&mut self, duration: TimeDelta) {
+    self.fade_to(Volume::zero(), duration)
+  }
+  /// Fades the volume of the default `SoundChannel` up to full over `duration`. See `fade_to()`.
+  pub fn fade_in(&mut self, duration: TimeDelta) {
+    self.fade_to(Volume::one(), duration)
+  }
+  /// Returns the volume currently being applied by fades to the default `SoundChannel`.
+  pub fn fade_volume(&self) -> Volume {
+    Self::with_fade(|fade| fade.gain()).into()
+  }
+  /// Sets how the default `SoundChannel` fades when the game is paused or the device is locked, and
+  /// back when the game resumes. Pass `None` to not fade for these events.
+  ///
+  /// By default, sound fades out with the values from `SystemFades::default()`, instead of
+  /// stopping abruptly when the system menu opens.
+  pub fn set_system_fades(&mut self, fades: Option<SystemFades>) {
+    Self::with_fade(|fade| fade.set_system_fades(fades))
+  }
+  fn with_fade<R>(f: impl FnOnce(&mut AudioFade) -> R) -> R {
+    f(CApiState::get().audio_fade.borrow_mut().as_mut().unwrap())
+  }
+
+  /// Pauses every `FilePlayer` and `SamplePlayer` that is playing, until `resume_all()` is called.
+  ///
+  /// This happens automatically while the device is locked, unless turned off with
+  /// `set_suspend_on_lock()`. Other sound sources, such as a `Synth`, can not be paused and keep
+  /// playing, though the default channel is faded out while locked (see `set_system_fades()`).
+  pub fn suspend_all(&mut self) {
+    CApiState::get().audio_suspend.borrow_mut().suspend_all()
+  }
+  /// Resumes the players that were paused by `suspend_all()`.
+  ///
+  /// Players that the game paused, played or stopped itself while suspended are left alone.
+  pub fn resume_all(&mut self) {
+    CApiState::get().audio_suspend.borrow_mut().resume_all()
+  }
+  /// Whether audio is suspended by `suspend_all()`, or because the device is locked.
+  pub fn is_suspended(&self) -> bool {
+    CApiState::get().audio_suspend.borrow().is_suspended()
+  }
+  /// Sets whether audio is suspended with `suspend_all()` when the device is locked, and resumed
+  /// when it's unlocked. It is on by default.
+  pub fn set_suspend_on_lock(&mut self, suspend: bool) {
+    CApiState::get().audio_suspend.borrow_mut().set_suspend_on_lock(suspend)
+  }
+
   /// Add a user-created `SoundChannel` to have it play from the device.
+  ///
+  /// Does nothing if the `SoundChannel` was already added.
   pub fn add_channel(&mut self, channel: &mut SoundChannel) {
-    if !channel.is_system_channel() {
+    if !channel.is_added() {
       channel.set_added(true);
       unsafe { Self::fns().addChannel.unwrap()(channel.cptr_mut()) };
     }
@@ -87,13 +177,15 @@ impl Sound {
   ///
   /// Does nothing if the `SoundChannel` was not already added with `add_channel()`.
   pub fn remove_channel(&mut self, channel: &mut SoundChannel) {
-    if !channel.is_system_channel() {
+    if !channel.is_system_channel() && channel.is_added() {
       channel.set_added(false);
       unsafe { Self::fns().removeChannel.unwrap()(channel.cptr_mut()) }
     }
   }
 
   /// Returns the sound engine’s current time value.
+  ///
+  /// See `SoundClock` to schedule notes in time with a tempo.
   pub fn current_sound_time(&self) -> TimeTicks {
     TimeTicks::from_sample_frames(unsafe { Self::fns().getCurrentTime.unwrap()() })
   }
@@ -103,9 +195,20 @@ impl Sound {
     unsafe { Self::fns().setOutputsActive.unwrap()(headphone as i32, speaker as i32) };
   }
 
-  // TODO: Consider adding a record_to_sample() like Lua, which would produce an AudioSample:
-  // https://sdk.play.date/1.10.0/Inside%20Playdate.html#f-sound.micinput.recordToSample. That might
-  // replace set_microphone_callback() entirely.
+  /// Starts recording from the microphone into memory, for up to `max_length`.
+  ///
+  /// This replaces any callback set with `set_microphone_callback()`. Dropping the returned
+  /// `MicrophoneRecording` stops the recording.
+  ///
+  /// BUG: The recording currently stays empty, for the same reason as noted on
+  /// `set_microphone_callback()`.
+  pub fn record_microphone(
+    &mut self,
+    source: MicrophoneSource,
+    max_length: TimeDelta,
+  ) -> MicrophoneRecording {
+    MicrophoneRecording::start(source, max_length)
+  }
 
   /// Set a callback that is called each sound frame with the microphone's input sound.
   /// 
@@ -187,14 +290,28 @@ impl Sound {
   /// Returns the current headphones state, which includes if they are plugged in and if they have a
   /// microphone.
   pub fn headphone_state(&self) -> HeadphoneState {
-    // Grab the function pointer last passed to getHeadphoneState() in
-    // `set_headphone_change_callback()`, so that we don't change that here.
-    let func = CApiState::get().headphone_change_func.borrow().clone();
-
-    let mut headphone = 0;
-    let mut mic = 0;
-    unsafe { Self::fns().getHeadphoneState.unwrap()(&mut headphone, &mut mic, func) };
-    HeadphoneState::new(headphone != 0, mic != 0)
+    HeadphoneState::current()
+  }
+  /// Waits until headphones are plugged in or removed, or the microphone on them changes, and
+  /// returns the new state.
+  ///
+  /// Unlike `set_headphone_change_callback()`, waiting for a change does not stop audio from
+  /// automatically switching to and from the headphones.
+  ///
+  /// # Example
+  /// ```
+  /// loop {
+  ///   match api.sound.headphone_changed().await {
+  ///     HeadphoneState::HeadphoneNotConnected => music.pause(),
+  ///     HeadphoneState::HeadphoneConnected { .. } => (),
+  ///   }
+  /// }
+  /// ```
+  pub async fn headphone_changed(&self) -> HeadphoneState {
+    HeadphoneChangedFuture {
+      from: HeadphoneState::current(),
+    }
+    .await
   }
 
   // BUG: Microphone monitoring functions are missing:
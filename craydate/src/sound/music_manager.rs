@@ -0,0 +1,272 @@
+use alloc::collections::VecDeque;
+use alloc::string::String;
+
+use super::loop_sound_span::LoopTimeSpan;
+use super::sources::file_player::FilePlayer;
+use super::sources::sound_source::AsSoundSource;
+use super::volume::{StereoVolume, Volume};
+use super::{Sound, SoundCompletionCallback};
+use crate::error::Error;
+use crate::time::{TimeDelta, TimeTicks};
+
+/// A music file to be played by a `MusicManager`.
+#[derive(Debug)]
+pub struct MusicTrack {
+  /// The path of the file to stream, as given to `FilePlayer::from_file()`.
+  pub path: String,
+  /// The part of the file to loop over, or `None` to loop over the whole file.
+  pub loop_range: Option<LoopTimeSpan>,
+  /// How many times to play the track, where 0 loops it until another track is played.
+  pub times: i32,
+}
+impl MusicTrack {
+  /// A track which plays the file at `path` once.
+  pub fn new(path: &str) -> Self {
+    MusicTrack {
+      path: path.into(),
+      loop_range: None,
+      times: 1,
+    }
+  }
+  /// A track which loops the file at `path` until another track is played.
+  ///
+  /// If `loop_range` is given, the file plays from the start and then loops over just that range,
+  /// such as to play an intro once before the looping part of a song. The loop is done by the
+  /// `FilePlayer`, so it is gapless.
+  pub fn looping(path: &str, loop_range: Option<LoopTimeSpan>) -> Self {
+    MusicTrack {
+      path: path.into(),
+      loop_range,
+      times: 0,
+    }
+  }
+}
+
+#[derive(Debug)]
+struct QueuedTrack {
+  track: MusicTrack,
+  crossfade: TimeDelta,
+}
+
+struct PlayingTrack {
+  player: FilePlayer,
+  path: String,
+  times: i32,
+}
+
+struct FadingTrack {
+  player: FilePlayer,
+  // The sound time when the fade out is done and the player can be stopped.
+  ends: TimeTicks,
+}
+
+/// Plays music from files, with a queue of tracks to play next and crossfades between them.
+///
+/// The manager streams each track with a `FilePlayer`. While crossfading, the track that was
+/// playing fades out on one player while the new track fades in on another, so at most two files
+/// are streamed at once.
+///
+/// The manager must be given a chance to move through its queue by calling `update()` once per
+/// frame. A queued track starts when the current track finishes, or for a track that plays once,
+/// early enough to crossfade into the queued track as the current one ends. A track that loops
+/// endlessly plays until `play()` or `skip()` is called.
+///
+/// The players are not added to a `SoundChannel`, so they play through the default channel.
+///
+/// # Example
+/// ```
+/// let mut music = MusicManager::new();
+/// music.play(MusicTrack::looping("music/title", None), TimeDelta::from_seconds(0))?;
+/// // On a scene change.
+/// music.play(MusicTrack::new("music/level1"), TimeDelta::from_seconds(2))?;
+/// music.queue(MusicTrack::looping("music/level1-loop", None), TimeDelta::from_seconds(1));
+/// loop {
+///   music.update()?;
+///   // ...
+/// }
+/// ```
+pub struct MusicManager {
+  current: Option<PlayingTrack>,
+  fading: Option<FadingTrack>,
+  queue: VecDeque<QueuedTrack>,
+  volume: f32,
+}
+impl MusicManager {
+  /// Constructs a `MusicManager` with nothing playing.
+  pub fn new() -> Self {
+    MusicManager {
+      current: None,
+      fading: None,
+      queue: VecDeque::new(),
+      volume: 1.0,
+    }
+  }
+
+  /// Plays the `track` now, crossfading from the current track over `crossfade`.
+  ///
+  /// The queue is kept, and plays after the `track`. Returns an error if the track's file can not
+  /// be played, in which case the current track keeps playing.
+  pub fn play(&mut self, track: MusicTrack, crossfade: TimeDelta) -> Result<(), Error> {
+    let mut player = FilePlayer::from_file(&track.path)?;
+    if let Some(loop_range) = track.loop_range {
+      player.set_loop_range(loop_range);
+    }
+    let fades = crossfade > TimeDelta::from_seconds(0);
+    let v = self.volume;
+    if fades {
+      player.as_source_mut().set_volume(StereoVolume::zero());
+    } else {
+      player.as_source_mut().set_volume(StereoVolume::new(v, v));
+    }
+    player.play(track.times)?;
+    if fades {
+      player.fade_volume(
+        StereoVolume::new(v, v),
+        crossfade,
+        SoundCompletionCallback::none(),
+      );
+    }
+
+    // A track still fading out from an earlier crossfade is cut off, so that only two players
+    // stream at once.
+    if let Some(mut fading) = self.fading.take() {
+      fading.player.stop();
+    }
+    if let Some(mut old) = self.current.take() {
+      if fades {
+        old.player.fade_out(crossfade);
+        self.fading = Some(FadingTrack {
+          player: old.player,
+          ends: Self::now() + crossfade,
+        });
+      } else {
+        old.player.stop();
+      }
+    }
+    self.current = Some(PlayingTrack {
+      player,
+      path: track.path,
+      times: track.times,
+    });
+    Ok(())
+  }
+
+  /// Adds the `track` to the end of the queue, to crossfade into over `crossfade` when the tracks
+  /// before it are done.
+  pub fn queue(&mut self, track: MusicTrack, crossfade: TimeDelta) {
+    self.queue.push_back(QueuedTrack { track, crossfade })
+  }
+  /// Removes every track from the queue, without changing the current track.
+  pub fn clear_queue(&mut self) {
+    self.queue.clear()
+  }
+  /// Returns the number of tracks waiting in the queue.
+  pub fn queue_len(&self) -> usize {
+    self.queue.len()
+  }
+
+  /// Plays the next track in the queue now, with the crossfade it was queued with. If the queue is
+  /// empty, the current track is stopped.
+  ///
+  /// If the next track can not be played, it is removed from the queue and the error is returned.
+  pub fn skip(&mut self) -> Result<(), Error> {
+    match self.queue.pop_front() {
+      Some(next) => self.play(next.track, next.crossfade),
+      None => {
+        self.stop(TimeDelta::from_seconds(0));
+        Ok(())
+      }
+    }
+  }
+
+  /// Fades out the current track over `fade`, and empties the queue.
+  pub fn stop(&mut self, fade: TimeDelta) {
+    self.queue.clear();
+    if let Some(mut fading) = self.fading.take() {
+      fading.player.stop();
+    }
+    if let Some(mut old) = self.current.take() {
+      if fade > TimeDelta::from_seconds(0) {
+        old.player.fade_out(fade);
+        self.fading = Some(FadingTrack {
+          player: old.player,
+          ends: Self::now() + fade,
+        });
+      } else {
+        old.player.stop();
+      }
+    }
+  }
+
+  /// Moves through the queue, starting the next track when the current one is finishing, and
+  /// stops tracks that have faded out. This should be called once per frame.
+  ///
+  /// If the next track can not be played, it is removed from the queue and the error is returned.
+  pub fn update(&mut self) -> Result<(), Error> {
+    let now = Self::now();
+    if self.fading.as_ref().is_some_and(|f| f.ends <= now) {
+      let mut fading = self.fading.take().unwrap();
+      fading.player.stop();
+    }
+
+    let start_next = match (&self.current, self.queue.front()) {
+      (_, None) => false,
+      (None, Some(_)) => true,
+      (Some(current), Some(next)) => {
+        let player = &current.player;
+        if !player.as_source().is_playing() {
+          true
+        } else if current.times == 1 {
+          // Start the crossfade so that it finishes as the track ends.
+          let remaining = player.file_len() - player.offset();
+          remaining <= next.crossfade
+        } else {
+          false
+        }
+      }
+    };
+    if start_next {
+      self.skip()?;
+    } else if self.current.as_ref().is_some_and(|c| !c.player.as_source().is_playing()) {
+      self.current = None;
+    }
+    Ok(())
+  }
+
+  /// Returns whether a track is playing, not counting a track which is fading out after `stop()`.
+  pub fn is_playing(&self) -> bool {
+    self.current.as_ref().is_some_and(|c| c.player.as_source().is_playing())
+  }
+  /// Returns the path of the track that is playing, if any.
+  pub fn current_path(&self) -> Option<&str> {
+    self.current.as_ref().map(|c| c.path.as_str())
+  }
+  /// Returns the `FilePlayer` of the track that is playing, if any, such as to change its rate.
+  pub fn current_player_mut(&mut self) -> Option<&mut FilePlayer> {
+    self.current.as_mut().map(|c| &mut c.player)
+  }
+
+  /// Sets the volume that tracks play at. This changes the current track's volume immediately, and
+  /// tracks fade in to this volume.
+  pub fn set_volume(&mut self, volume: Volume) {
+    self.volume = volume.into();
+    if let Some(current) = &mut self.current {
+      let v = self.volume;
+      current.player.as_source_mut().set_volume(StereoVolume::new(v, v));
+    }
+  }
+  /// Returns the volume that tracks play at.
+  pub fn volume(&self) -> Volume {
+    self.volume.into()
+  }
+
+  fn now() -> TimeTicks {
+    TimeTicks::from_sample_frames(unsafe { Sound::fns().getCurrentTime.unwrap()() })
+  }
+}
+
+impl Default for MusicManager {
+  fn default() -> Self {
+    Self::new()
+  }
+}
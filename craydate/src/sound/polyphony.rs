@@ -0,0 +1,166 @@
+use super::Sound;
+use super::midi::note::Note;
+use super::midi::track_note::TrackNote;
+use super::sound_channel::SoundChannel;
+use super::sources::sound_source::AsSoundSource;
+use super::sources::synth::Synth;
+use super::volume::Volume;
+use crate::error::Error;
+use crate::time::{TimeDelta, TimeTicks};
+
+#[derive(Debug, Default, Copy, Clone)]
+struct VoiceState {
+  // The note the voice was last asked to play, if it was played as a MIDI note and not stopped.
+  note: Option<Note>,
+  // When the voice was last given a note, counting up for each note, to find the oldest voice.
+  age: u64,
+  // When the note is scheduled to start, in sound time. The voice is busy until then, even though
+  // it is not yet playing.
+  starts_at: Option<TimeTicks>,
+}
+
+/// Plays notes across `N` `Synth` voices, so that up to `N` notes can sound at once.
+///
+/// Each note is given to a voice which is not playing, or if all voices are playing, the voice
+/// that was given a note the longest time ago stops to play the new note. This is similar to an
+/// `Instrument`, but owns its voices and doesn't need a `Sequence`, with voices that are all set
+/// up the same way.
+///
+/// The voices need to be added to a `SoundChannel`, with `add_to_channel()`, to be heard.
+///
+/// # Example
+/// ```
+/// let mut poly: Polyphony<4> = Polyphony::new(|| {
+///   let mut synth = Synth::new_with_waveform(SoundWaveform::kWaveformSquare);
+///   synth.set_release_time(TimeDelta::from_milliseconds(200));
+///   synth
+/// });
+/// poly.add_to_channel(api.sound.default_channel_mut())?;
+/// for note in Note::C4.chord(Chord::Minor) {
+///   poly.play_midi_note(TrackNote { note, ..Default::default() }, None, None);
+/// }
+/// ```
+#[derive(Debug)]
+pub struct Polyphony<const N: usize> {
+  voices: [Synth; N],
+  states: [VoiceState; N],
+  next_age: u64,
+}
+impl<const N: usize> Polyphony<N> {
+  /// Constructs the voices by calling `make_voice` once for each of them, so that they all start
+  /// out with the same settings.
+  pub fn new(make_voice: impl FnMut() -> Synth) -> Self {
+    let mut make_voice = make_voice;
+    Polyphony {
+      voices: core::array::from_fn(|_| make_voice()),
+      states: [VoiceState::default(); N],
+      next_age: 0,
+    }
+  }
+
+  /// Adds every voice to the `channel`, so that they play to it.
+  pub fn add_to_channel(&mut self, channel: &mut SoundChannel) -> Result<(), Error> {
+    self.voices.iter_mut().try_for_each(|voice| channel.add_source(voice))
+  }
+  /// Removes every voice from the `channel`.
+  pub fn remove_from_channel(&mut self, channel: &mut SoundChannel) -> Result<(), Error> {
+    self.voices.iter_mut().try_for_each(|voice| channel.remove_source(voice))
+  }
+
+  /// Returns the voices, such as to look at their settings.
+  pub fn voices(&self) -> &[Synth; N] {
+    &self.voices
+  }
+  /// Returns the voices, such as to change their settings. Changes should be made to every voice
+  /// so that notes sound the same regardless of which voice plays them.
+  pub fn voices_mut(&mut self) -> &mut [Synth; N] {
+    &mut self.voices
+  }
+  /// Changes the settings of every voice with `f`.
+  pub fn configure(&mut self, f: impl FnMut(&mut Synth)) {
+    self.voices.iter_mut().for_each(f)
+  }
+
+  /// Returns the number of voices which are playing a note, or have a note scheduled to play.
+  pub fn active_voices_count(&self) -> usize {
+    let now = Self::now();
+    (0..N).filter(|i| self.is_busy(*i, now)).count()
+  }
+
+  /// Plays a note with the `frequency` on a free voice, or the oldest voice if none are free.
+  ///
+  /// The parameters are the same as for `Synth::play_frequency_note()`. Returns the index of the
+  /// voice in `voices()` which plays the note. Notes played this way can only be stopped by
+  /// `stop_all_notes()`, or by stopping the voice directly.
+  pub fn play_frequency_note(
+    &mut self,
+    frequency: f32,
+    volume: Volume,
+    length: Option<TimeDelta>,
+    when: Option<TimeTicks>,
+  ) -> usize {
+    let i = self.take_voice(None, when);
+    let _ = self.voices[i].play_frequency_note(frequency, volume, length, when);
+    i
+  }
+  /// Plays a MIDI note on a free voice, or the oldest voice if none are free.
+  ///
+  /// The parameters are the same as for `Synth::play_midi_note()`. Returns the index of the voice
+  /// in `voices()` which plays the note.
+  pub fn play_midi_note(
+    &mut self,
+    note: TrackNote,
+    length: Option<TimeDelta>,
+    when: Option<TimeTicks>,
+  ) -> usize {
+    let i = self.take_voice(Some(note.note), when);
+    let _ = self.voices[i].play_midi_note(note, length, when);
+    i
+  }
+
+  /// Stops each voice that is playing the MIDI `note`, letting it go into its release.
+  ///
+  /// If `when` is `None`, the note is stopped immediately. Otherwise it is scheduled to be stopped
+  /// at the given absolute time. Use `Sound::current_sound_time()` to get the current time.
+  pub fn stop_note(&mut self, note: Note, when: Option<TimeTicks>) {
+    for (voice, state) in self.voices.iter_mut().zip(self.states.iter_mut()) {
+      if state.note == Some(note) {
+        voice.stop(when);
+        state.note = None;
+      }
+    }
+  }
+  /// Stops every voice.
+  ///
+  /// If `when` is `None`, the notes are stopped immediately. Otherwise they are scheduled to be
+  /// stopped at the given absolute time. Use `Sound::current_sound_time()` to get the current time.
+  pub fn stop_all_notes(&mut self, when: Option<TimeTicks>) {
+    for (voice, state) in self.voices.iter_mut().zip(self.states.iter_mut()) {
+      voice.stop(when);
+      state.note = None;
+    }
+  }
+
+  /// Picks the voice for a new note, preferring a voice that is free, and otherwise the voice that
+  /// was given a note the longest time ago.
+  fn take_voice(&mut self, note: Option<Note>, when: Option<TimeTicks>) -> usize {
+    assert!(N > 0, "a Polyphony needs at least one voice");
+    let now = Self::now();
+    let free = (0..N).filter(|i| !self.is_busy(*i, now)).min_by_key(|i| self.states[*i].age);
+    let i = free.unwrap_or_else(|| (0..N).min_by_key(|i| self.states[*i].age).unwrap());
+    self.states[i] = VoiceState {
+      note,
+      age: self.next_age,
+      starts_at: when,
+    };
+    self.next_age += 1;
+    i
+  }
+  fn is_busy(&self, i: usize, now: TimeTicks) -> bool {
+    let scheduled = self.states[i].starts_at.is_some_and(|starts_at| starts_at > now);
+    scheduled || self.voices[i].as_source().is_playing()
+  }
+  fn now() -> TimeTicks {
+    TimeTicks::from_sample_frames(unsafe { Sound::fns().getCurrentTime.unwrap()() })
+  }
+}
@@ -0,0 +1,190 @@
+use alloc::collections::BTreeMap;
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use super::Sound;
+use super::audio_sample::AudioSample;
+use super::sound_channel::SoundChannel;
+use super::sources::sample_player::SamplePlayer;
+use super::sources::sound_source::AsSoundSource;
+use super::volume::{StereoVolume, Volume};
+use crate::error::Error;
+
+#[derive(Debug)]
+struct SfxVoice {
+  // The player is made when the voice is first used, as it needs a sample. It is not bound to the
+  // sample's lifetime, as the `SfxPool` keeps its samples alive longer than the players that use
+  // them.
+  player: Option<SamplePlayer<'static>>,
+  // The name of the sample the voice last played.
+  sample: Option<String>,
+  priority: i32,
+  // When the voice was last given a sound, counting up for each sound, to find the oldest voice.
+  age: u64,
+}
+
+/// Plays short sound effects by name, on a fixed number of `SamplePlayer` voices.
+///
+/// Samples are loaded into the pool once, and then played with `play()`, which doesn't need the
+/// game to keep a `SamplePlayer` alive for the sound to play out. Each sound is given to a voice
+/// which is not playing. If all voices are playing, the voice with the lowest priority, and the
+/// oldest among those, is stopped to play the new sound, as long as its priority is not higher than
+/// the new sound's.
+///
+/// The pool plays through its own `SoundChannel`, which is added to the sound system, and can be
+/// used to set the volume of every sound effect or to add effects to them.
+///
+/// # Example
+/// ```
+/// let mut sfx = SfxPool::new(&mut api.sound, 8);
+/// sfx.load_file("jump", "sfx/jump")?;
+/// sfx.load_file("explode", "sfx/explode")?;
+/// sfx.play("jump")?;
+/// // An explosion is more important than footsteps, so it can take a voice from them.
+/// sfx.play_with_priority("explode", 10, Volume::one(), 1.0)?;
+/// ```
+#[derive(Debug)]
+pub struct SfxPool {
+  // The voices are declared before the samples so they are dropped first, as they play from them.
+  voices: Vec<SfxVoice>,
+  samples: BTreeMap<String, AudioSample>,
+  channel: SoundChannel,
+  next_age: u64,
+}
+impl SfxPool {
+  /// Constructs a pool which can play up to `voices` sounds at once, through a new `SoundChannel`
+  /// which is added to the sound system.
+  pub fn new(sound: &mut Sound, voices: usize) -> Self {
+    let mut channel = SoundChannel::new();
+    sound.add_channel(&mut channel);
+    SfxPool {
+      voices: (0..voices)
+        .map(|_| SfxVoice {
+          player: None,
+          sample: None,
+          priority: 0,
+          age: 0,
+        })
+        .collect(),
+      samples: BTreeMap::new(),
+      channel,
+      next_age: 0,
+    }
+  }
+
+  /// Adds the `sample` to the pool, to be played by `name`. If a sample already has the `name`, it
+  /// is replaced, and any voices playing it are stopped.
+  pub fn insert(&mut self, name: &str, sample: AudioSample) {
+    self.stop_sample(name);
+    self.samples.insert(name.into(), sample);
+  }
+  /// Loads the sound file at `path`, as with `AudioSample::from_file()`, to be played by `name`.
+  ///
+  /// Returns `Error::NotFoundError` if the file could not be loaded.
+  pub fn load_file(&mut self, name: &str, path: &str) -> Result<(), Error> {
+    let sample = AudioSample::from_file(path).ok_or(Error::NotFoundError)?;
+    self.insert(name, sample);
+    Ok(())
+  }
+  /// Removes the sample with the `name` from the pool, stopping any voices playing it, and returns
+  /// it.
+  pub fn remove(&mut self, name: &str) -> Option<AudioSample> {
+    self.stop_sample(name);
+    self.samples.remove(name)
+  }
+  /// Returns whether there is a sample with the `name` in the pool.
+  pub fn contains(&self, name: &str) -> bool {
+    self.samples.contains_key(name)
+  }
+
+  /// Plays the sample with the `name` at full volume and normal speed, with a priority of 0.
+  ///
+  /// Returns whether a voice was found to play the sound, or `Error::NotFoundError` if there is no
+  /// sample with the `name`.
+  pub fn play(&mut self, name: &str) -> Result<bool, Error> {
+    self.play_with_priority(name, 0, Volume::one(), 1.0)
+  }
+  /// Plays the sample with the `name` at the `volume` and playback `rate`, where 1.0 is normal
+  /// speed and 2.0 is up an octave.
+  ///
+  /// If every voice is busy, the sound takes the voice playing the lowest priority sound, if its
+  /// priority is not higher than `priority`. Returns whether a voice was found to play the sound,
+  /// or `Error::NotFoundError` if there is no sample with the `name`.
+  pub fn play_with_priority(
+    &mut self,
+    name: &str,
+    priority: i32,
+    volume: Volume,
+    rate: f32,
+  ) -> Result<bool, Error> {
+    let sample = self.samples.get(name).ok_or(Error::NotFoundError)?;
+    let Some(i) = self.find_voice(priority) else {
+      return Ok(false);
+    };
+    let voice = &mut self.voices[i];
+    let player = match &mut voice.player {
+      Some(player) => {
+        player.stop();
+        player.set_sample(sample);
+        player
+      }
+      None => {
+        let mut player = SamplePlayer::new(sample);
+        self.channel.add_source(&mut player)?;
+        voice.player.insert(player)
+      }
+    };
+    let v = volume.to_f32();
+    player.as_source_mut().set_volume(StereoVolume::new(v, v));
+    player.play(1, rate);
+    voice.sample = Some(name.into());
+    voice.priority = priority;
+    voice.age = self.next_age;
+    self.next_age += 1;
+    Ok(true)
+  }
+
+  /// Stops every sound that is playing.
+  pub fn stop_all(&mut self) {
+    for player in self.voices.iter_mut().filter_map(|v| v.player.as_mut()) {
+      player.stop();
+    }
+  }
+  /// Returns the number of voices which are playing a sound.
+  pub fn playing_count(&self) -> usize {
+    self.voices.iter().filter(|v| Self::is_playing(v)).count()
+  }
+
+  /// The channel that the sounds play through.
+  pub fn channel(&self) -> &SoundChannel {
+    &self.channel
+  }
+  /// The channel that the sounds play through, such as to change its volume or add effects.
+  pub fn channel_mut(&mut self) -> &mut SoundChannel {
+    &mut self.channel
+  }
+
+  fn find_voice(&self, priority: i32) -> Option<usize> {
+    let free = self.voices.iter().position(|v| !Self::is_playing(v));
+    free.or_else(|| {
+      let (i, voice) = self.voices.iter().enumerate().min_by_key(|(_, v)| (v.priority, v.age))?;
+      if voice.priority <= priority {
+        Some(i)
+      } else {
+        None
+      }
+    })
+  }
+  fn stop_sample(&mut self, name: &str) {
+    for voice in &mut self.voices {
+      if voice.sample.as_deref() == Some(name) {
+        // The player is dropped since it still refers to the sample.
+        voice.player = None;
+        voice.sample = None;
+      }
+    }
+  }
+  fn is_playing(voice: &SfxVoice) -> bool {
+    voice.player.as_ref().is_some_and(|p| p.is_playing())
+  }
+}
@@ -5,6 +5,18 @@ use super::synth_signal::{SynthSignal, SynthSignalSubclass};
 use crate::capi_state::CApiState;
 use crate::ctypes::*;
 
+/// A value at a step in the timeline of a `Control` signal.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct ControlEvent {
+  /// The step in the `Sequence` at which the signal takes the value.
+  pub step: i32,
+  /// The value of the signal at the step.
+  pub value: f32,
+  /// If true, the signal moves smoothly from the previous event's value to this one. Otherwise it
+  /// jumps to this value at the step.
+  pub interpolate: bool,
+}
+
 /// A borrow of a `Control`.
 pub struct ControlRef {
   ptr: NonNull<CControlSignal>,
@@ -27,6 +39,13 @@ impl ControlRef {
     unsafe { Control::fns().addEvent.unwrap()(self.cptr_mut(), step, value, interpolate as i32) }
   }
 
+  /// Adds each of the `events` to the signal's timeline. See `add_event()`.
+  pub fn add_events(&mut self, events: impl IntoIterator<Item = ControlEvent>) {
+    for e in events {
+      self.add_event(e.step, e.value, e.interpolate)
+    }
+  }
+
   /// Removes the control event at the given step.
   pub fn remove_event(&mut self, step: i32) {
     unsafe { Control::fns().removeEvent.unwrap()(self.cptr_mut(), step) }
@@ -86,11 +105,24 @@ impl Control {
     Self::from_ptr(ptr)
   }
 
+  /// Constructs a new control signal with the given `events` in its timeline.
+  pub fn with_events(events: impl IntoIterator<Item = ControlEvent>) -> Self {
+    let mut control = Self::new();
+    control.add_events(events);
+    control
+  }
+
   pub(crate) fn fns() -> &'static craydate_sys::playdate_control_signal {
     unsafe { &*CApiState::get().csound.controlsignal }
   }
 }
 
+impl Default for Control {
+  fn default() -> Self {
+    Self::new()
+  }
+}
+
 impl AsRef<SynthSignal> for Control {
   fn as_ref(&self) -> &SynthSignal {
     &self.signal
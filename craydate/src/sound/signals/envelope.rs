@@ -24,6 +24,9 @@ impl SynthSignalSubclass for EnvelopeSubclass {}
 /// - setOffset
 /// - trigger
 /// - setGlobal
+///
+/// A `ScaledSignal` can scale and offset an `Envelope`, and a `TriggeredEnvelope` can be triggered
+/// without playing a note.
 pub struct Envelope {
   signal: SynthSignal,
   subclass: Rc<EnvelopeSubclass>,
@@ -90,7 +90,6 @@ impl Lfo {
   }
   /// Constructs a new LFO with a custom function. See `set_user_function()`.
   pub fn new_with_user_function(
-    &mut self,
     interpolate: bool,
     f: impl FnMut() -> f32 + Send + 'static,
   ) -> Self {
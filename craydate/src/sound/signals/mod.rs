@@ -1,4 +1,6 @@
 pub mod control;
 pub mod envelope;
 pub mod lfo;
+pub mod scaled_signal;
 pub mod synth_signal;
+pub mod triggered_envelope;
@@ -0,0 +1,147 @@
+use alloc::sync::Arc;
+use core::sync::atomic::{AtomicU32, Ordering};
+
+use super::envelope::Envelope;
+use super::lfo::Lfo;
+use super::synth_signal::SynthSignal;
+use crate::ctypes::*;
+
+/// An `f32` which can be shared with the audio thread.
+#[derive(Debug)]
+pub(crate) struct AtomicF32(AtomicU32);
+impl AtomicF32 {
+  pub fn new(f: f32) -> Self {
+    AtomicF32(AtomicU32::new(f.to_bits()))
+  }
+  pub fn get(&self) -> f32 {
+    f32::from_bits(self.0.load(Ordering::Relaxed))
+  }
+  pub fn set(&self, f: f32) {
+    self.0.store(f.to_bits(), Ordering::Relaxed)
+  }
+}
+
+#[derive(Debug)]
+struct ScaleState {
+  scale: AtomicF32,
+  offset: AtomicF32,
+}
+
+/// The signal that a `ScaledSignal` reads from.
+enum Input {
+  Envelope(*mut CSynthEnvelope),
+  Lfo(*mut CSynthLfo),
+}
+
+/// The data used by the `ScaledSignal`'s function, which runs on the audio thread.
+struct ScaledFunction {
+  input: Input,
+  // Holds `input` alive for as long as the function can run. It is only cloned and dropped on the
+  // game thread, along with the LFO that holds the function.
+  _input_signal: SynthSignal,
+  state: Arc<ScaleState>,
+}
+// SAFETY: The function only reads the value of the `input` signal, through Playdate's getValue
+// functions, on the audio thread. The `SynthSignal` is not touched there.
+unsafe impl Send for ScaledFunction {}
+impl ScaledFunction {
+  fn value(&self) -> f32 {
+    let v = match self.input {
+      Input::Envelope(ptr) => unsafe { Envelope::fns().getValue.unwrap()(ptr) },
+      Input::Lfo(ptr) => unsafe { Lfo::fns().getValue.unwrap()(ptr) },
+    };
+    v * self.state.scale.get() + self.state.offset.get()
+  }
+}
+
+/// A `SynthSignal` which scales and offsets the value of another signal.
+///
+/// The C Api is missing `setScale()` and `setOffset()` for an `Envelope`, which are available in
+/// the Lua Api. A `ScaledSignal` provides them, for an `Envelope` or an `Lfo`, by running the
+/// signal through an `Lfo` with a user function. Its value is `value * scale + offset`, where
+/// `value` is the value of the input signal.
+///
+/// The input signal only changes while it is in use, such as when an `Envelope` is attached to a
+/// `Synth` that is playing a note. For an `Envelope` that is started without a note, use a
+/// `TriggeredEnvelope`.
+///
+/// # Example
+/// ```
+/// let envelope = Envelope::new(attack, decay, 0.5, release);
+/// synth.set_amplitude_modulator(Some(&envelope));
+/// // The same envelope, used to sweep the filter frequency up to 2000Hz above 200Hz.
+/// let sweep = ScaledSignal::from_envelope(&envelope, 2000.0, 200.0);
+/// filter.set_frequency_modulator(Some(&sweep));
+/// ```
+pub struct ScaledSignal {
+  lfo: Lfo,
+  state: Arc<ScaleState>,
+}
+impl ScaledSignal {
+  /// Constructs a `ScaledSignal` that follows the value of the `envelope`.
+  pub fn from_envelope(envelope: &Envelope, scale: f32, offset: f32) -> Self {
+    Self::new(
+      Input::Envelope(envelope.cptr() as *mut _),
+      envelope.as_ref().clone(),
+      scale,
+      offset,
+    )
+  }
+  /// Constructs a `ScaledSignal` that follows the value of the `lfo`.
+  pub fn from_lfo(lfo: &Lfo, scale: f32, offset: f32) -> Self {
+    Self::new(
+      Input::Lfo(lfo.cptr() as *mut _),
+      lfo.as_ref().clone(),
+      scale,
+      offset,
+    )
+  }
+  fn new(input: Input, input_signal: SynthSignal, scale: f32, offset: f32) -> Self {
+    let state = Arc::new(ScaleState {
+      scale: AtomicF32::new(scale),
+      offset: AtomicF32::new(offset),
+    });
+    let function = ScaledFunction {
+      input,
+      _input_signal: input_signal,
+      state: state.clone(),
+    };
+    // UNCLEAR: How often Playdate calls the LFO function. Interpolating smooths the steps between
+    // calls.
+    let lfo = Lfo::new_with_user_function(true, move || function.value());
+    ScaledSignal { lfo, state }
+  }
+
+  /// Sets the amount that the input signal's value is multiplied by.
+  pub fn set_scale(&mut self, scale: f32) {
+    self.state.scale.set(scale)
+  }
+  /// Returns the amount that the input signal's value is multiplied by.
+  pub fn scale(&self) -> f32 {
+    self.state.scale.get()
+  }
+  /// Sets the amount that is added to the input signal's value after scaling it.
+  pub fn set_offset(&mut self, offset: f32) {
+    self.state.offset.set(offset)
+  }
+  /// Returns the amount that is added to the input signal's value after scaling it.
+  pub fn offset(&self) -> f32 {
+    self.state.offset.get()
+  }
+
+  /// Return the current output value of the `ScaledSignal`.
+  pub fn get_value(&self) -> f32 {
+    self.lfo.get_value()
+  }
+}
+
+impl AsRef<SynthSignal> for ScaledSignal {
+  fn as_ref(&self) -> &SynthSignal {
+    self.lfo.as_ref()
+  }
+}
+impl AsMut<SynthSignal> for ScaledSignal {
+  fn as_mut(&mut self) -> &mut SynthSignal {
+    self.lfo.as_mut()
+  }
+}
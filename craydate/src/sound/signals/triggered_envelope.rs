@@ -0,0 +1,201 @@
+use alloc::sync::Arc;
+use core::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+
+use super::lfo::Lfo;
+use super::scaled_signal::AtomicF32;
+use super::synth_signal::SynthSignal;
+use crate::capi_state::CApiState;
+use crate::time::{TimeDelta, TimeTicks};
+
+/// The envelope settings and the current trigger, shared with the audio thread.
+#[derive(Debug)]
+struct TriggerState {
+  // Times are in sample frames.
+  attack: AtomicU32,
+  decay: AtomicU32,
+  sustain: AtomicF32,
+  release: AtomicU32,
+  scale: AtomicF32,
+  offset: AtomicF32,
+
+  triggered: AtomicBool,
+  velocity: AtomicF32,
+  start: AtomicU32,
+  // The time the release starts, or `NOT_RELEASED`.
+  released_at: AtomicU32,
+}
+const NOT_RELEASED: u32 = u32::MAX;
+
+impl TriggerState {
+  /// The envelope's value, before velocity, scale and offset, at `t` frames after it was triggered.
+  fn level_at(&self, t: u32) -> f32 {
+    let attack = self.attack.load(Ordering::Relaxed);
+    let decay = self.decay.load(Ordering::Relaxed);
+    let sustain = self.sustain.get();
+    if t < attack {
+      t as f32 / attack as f32
+    } else if t - attack < decay {
+      1.0 - (1.0 - sustain) * (t - attack) as f32 / decay as f32
+    } else {
+      sustain
+    }
+  }
+
+  fn value(&self, now: u32) -> f32 {
+    let level = if self.triggered.load(Ordering::Acquire) {
+      let start = self.start.load(Ordering::Relaxed);
+      let released_at = self.released_at.load(Ordering::Relaxed);
+      let t = now.wrapping_sub(start);
+      let released_t = released_at.wrapping_sub(start);
+      if released_at == NOT_RELEASED || t < released_t {
+        self.level_at(t)
+      } else {
+        let release = self.release.load(Ordering::Relaxed);
+        let since_release = t - released_t;
+        if since_release < release {
+          self.level_at(released_t) * (1.0 - since_release as f32 / release as f32)
+        } else {
+          0.0
+        }
+      }
+    } else {
+      0.0
+    };
+    level * self.velocity.get() * self.scale.get() + self.offset.get()
+  }
+}
+
+/// An envelope which is started by calling `trigger()`, instead of by a `Synth` playing a note.
+///
+/// The C Api is missing `trigger()`, `setScale()` and `setOffset()` for an `Envelope`, which are
+/// available in the Lua Api. A `TriggeredEnvelope` computes the envelope itself, in time with the
+/// sound engine, and provides its value through an `Lfo` so it can be used as a modulator anywhere
+/// a `SynthSignal` is accepted.
+///
+/// The envelope always starts from 0 when triggered, like an `Envelope` with retrigger set. Its
+/// value is the envelope's level times the `velocity` given to `trigger()`, then multiplied by the
+/// scale and added to the offset.
+///
+/// # Example
+/// ```
+/// let mut sweep = TriggeredEnvelope::new(attack, decay, 0.0, release);
+/// sweep.set_scale(1000.0);
+/// filter.set_frequency_modulator(Some(&sweep));
+/// // On each hit, sweep the filter for half a second.
+/// sweep.trigger(1.0, Some(TimeDelta::from_milliseconds(500)));
+/// ```
+pub struct TriggeredEnvelope {
+  lfo: Lfo,
+  state: Arc<TriggerState>,
+}
+impl TriggeredEnvelope {
+  /// Constructs a new `TriggeredEnvelope`, which is at 0 until it is triggered.
+  ///
+  /// See `set_attack()`, `set_decay()`, `set_sustain_level()`, and `set_release()` for more details
+  /// on the parameters.
+  pub fn new(attack: TimeTicks, decay: TimeTicks, sustain: f32, release: TimeTicks) -> Self {
+    let state = Arc::new(TriggerState {
+      attack: AtomicU32::new(attack.to_sample_frames()),
+      decay: AtomicU32::new(decay.to_sample_frames()),
+      sustain: AtomicF32::new(sustain),
+      release: AtomicU32::new(release.to_sample_frames()),
+      scale: AtomicF32::new(1.0),
+      offset: AtomicF32::new(0.0),
+      triggered: AtomicBool::new(false),
+      velocity: AtomicF32::new(1.0),
+      start: AtomicU32::new(0),
+      released_at: AtomicU32::new(NOT_RELEASED),
+    });
+    let current_time = Self::current_time_fn();
+    let audio_state = state.clone();
+    // UNCLEAR: How often Playdate calls the LFO function. Interpolating smooths the steps between
+    // calls.
+    let lfo =
+      Lfo::new_with_user_function(true, move || audio_state.value(unsafe { current_time() }));
+    TriggeredEnvelope { lfo, state }
+  }
+
+  /// Starts the envelope from 0 at the current sound time.
+  ///
+  /// The envelope's level is multiplied by `velocity`. If `length` is given, the envelope is
+  /// released after that long, otherwise it holds at the sustain level until `release()` is called.
+  pub fn trigger(&mut self, velocity: f32, length: Option<TimeDelta>) {
+    let now = unsafe { Self::current_time_fn()() };
+    let released_at = match length {
+      Some(length) => now.wrapping_add(length.to_sample_frames().max(0) as u32),
+      None => NOT_RELEASED,
+    };
+    // Stop the envelope while changing the trigger, so the audio thread doesn't see half of it.
+    self.state.triggered.store(false, Ordering::Release);
+    self.state.velocity.set(velocity);
+    self.state.start.store(now, Ordering::Relaxed);
+    self.state.released_at.store(released_at, Ordering::Relaxed);
+    self.state.triggered.store(true, Ordering::Release);
+  }
+  /// Starts the release phase of the envelope now, if it has been triggered and not yet released.
+  pub fn release(&mut self) {
+    let now = unsafe { Self::current_time_fn()() };
+    let released_at = self.state.released_at.load(Ordering::Relaxed);
+    let start = self.state.start.load(Ordering::Relaxed);
+    if released_at == NOT_RELEASED || released_at.wrapping_sub(start) > now.wrapping_sub(start) {
+      self.state.released_at.store(now, Ordering::Relaxed);
+    }
+  }
+  /// Returns whether the envelope has been triggered and has not finished its release phase.
+  pub fn is_active(&self) -> bool {
+    if !self.state.triggered.load(Ordering::Acquire) {
+      return false;
+    }
+    let now = unsafe { Self::current_time_fn()() };
+    let start = self.state.start.load(Ordering::Relaxed);
+    let released_at = self.state.released_at.load(Ordering::Relaxed);
+    let release = self.state.release.load(Ordering::Relaxed);
+    released_at == NOT_RELEASED
+      || now.wrapping_sub(start) < released_at.wrapping_sub(start).saturating_add(release)
+  }
+
+  /// Sets the envelope attack time to `attack`.
+  pub fn set_attack(&mut self, attack: TimeTicks) {
+    self.state.attack.store(attack.to_sample_frames(), Ordering::Relaxed)
+  }
+  /// Sets the envelope decay time to `decay`.
+  pub fn set_decay(&mut self, decay: TimeTicks) {
+    self.state.decay.store(decay.to_sample_frames(), Ordering::Relaxed)
+  }
+  /// Sets the envelope sustain level to `sustain`, as a proportion of the maximum.
+  pub fn set_sustain_level(&mut self, sustain: f32) {
+    self.state.sustain.set(sustain)
+  }
+  /// Sets the envelope release time to `release`.
+  pub fn set_release(&mut self, release: TimeTicks) {
+    self.state.release.store(release.to_sample_frames(), Ordering::Relaxed)
+  }
+  /// Sets the amount that the envelope's value is multiplied by.
+  pub fn set_scale(&mut self, scale: f32) {
+    self.state.scale.set(scale)
+  }
+  /// Sets the amount that is added to the envelope's value after scaling it.
+  pub fn set_offset(&mut self, offset: f32) {
+    self.state.offset.set(offset)
+  }
+
+  /// Return the current output value of the `TriggeredEnvelope`.
+  pub fn get_value(&self) -> f32 {
+    self.state.value(unsafe { Self::current_time_fn()() })
+  }
+
+  fn current_time_fn() -> unsafe extern "C" fn() -> u32 {
+    CApiState::get().csound.getCurrentTime.unwrap()
+  }
+}
+
+impl AsRef<SynthSignal> for TriggeredEnvelope {
+  fn as_ref(&self) -> &SynthSignal {
+    self.lfo.as_ref()
+  }
+}
+impl AsMut<SynthSignal> for TriggeredEnvelope {
+  fn as_mut(&mut self) -> &mut SynthSignal {
+    self.lfo.as_mut()
+  }
+}
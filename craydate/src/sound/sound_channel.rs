@@ -25,6 +25,8 @@ pub struct SoundChannel {
   // `Sound::add_channel()`.
   added: bool,
   volume_modulator: Option<SynthSignal>,
+  // The pan last set with `set_pan()`, as Playdate has no function to read it back.
+  pan: f32,
   pan_modulator: Option<SynthSignal>,
   dry_level_signal: SynthSignal,
   wet_level_signal: SynthSignal,
@@ -44,12 +46,18 @@ impl SoundChannel {
       owned,
       added: false,
       volume_modulator: None,
+      pan: 0.0,
       pan_modulator: None,
       dry_level_signal,
       wet_level_signal,
     }
   }
 
+  /// Constructs a new `SoundChannel`, which must be added to the sound system with
+  /// `Sound::add_channel()` before anything played into it can be heard.
+  ///
+  /// Each channel has its own volume, pan, and effects, so separate channels can be used to mix
+  /// groups of sounds, such as music and sound effects, independently.
   pub fn new() -> SoundChannel {
     Self::from_ptr(unsafe { Self::fns().newChannel.unwrap()() }, true)
   }
@@ -65,6 +73,11 @@ impl SoundChannel {
     assert!(self.owned);
     self.added = added
   }
+  /// Whether the channel is playing from the device, either because it was added with
+  /// `Sound::add_channel()` or because it is the default channel.
+  pub fn is_added(&self) -> bool {
+    self.added || self.is_system_channel()
+  }
 
   /// Adds the `source` to this channel, so it plays into the channel.
   ///
@@ -123,7 +136,7 @@ impl SoundChannel {
     self.volume_modulator = signal.map(|signal| signal.as_ref().clone());
   }
   /// Gets the current signal modulating the channel volume.
-  pub fn volume_modulator(&mut self) -> Option<&SynthSignal> {
+  pub fn volume_modulator(&self) -> Option<&SynthSignal> {
     self.volume_modulator.as_ref()
   }
 
@@ -131,9 +144,27 @@ impl SoundChannel {
   ///
   /// The pan value is between -1 which is left and 1 which is right. 0 is center.
   pub fn set_pan(&mut self, pan: ClampedFloatInclusive<-1, 1>) {
-    unsafe { Self::fns().setPan.unwrap()(self.cptr_mut(), pan.into()) }
+    self.pan = pan.to_f32();
+    unsafe { Self::fns().setPan.unwrap()(self.cptr_mut(), self.pan) }
+  }
+  /// Gets the pan parameter for the channel, as last set by `set_pan()`.
+  ///
+  /// The pan value is between -1 which is left and 1 which is right. 0 is center. Any pan
+  /// modulator is not included.
+  pub fn pan(&self) -> ClampedFloatInclusive<-1, 1> {
+    self.pan.into()
   }
   /// Sets a signal to modulate the channel pan.
+  ///
+  /// The channel keeps a reference to the signal, so it does not need to be kept alive by the
+  /// caller.
+  ///
+  /// # Example
+  /// ```
+  /// // Auto-pan, sweeping the channel from side to side once every two seconds.
+  /// let lfo = Lfo::new_with_fixed_function(LfoFixedFunction::Sine, 0.5, 0.0, 0.0, 1.0);
+  /// channel.set_pan_modulator(Some(&lfo));
+  /// ```
   pub fn set_pan_modulator<T: AsRef<SynthSignal>>(&mut self, signal: Option<&T>) {
     let modulator_ptr = signal.map_or_else(core::ptr::null_mut, |signal|
       // setPanModulator() takes a mutable pointer to the modulator but there is no visible state on
@@ -143,16 +174,16 @@ impl SoundChannel {
     self.pan_modulator = signal.map(|signal| signal.as_ref().clone());
   }
   /// Gets the current signal modulating the channel pan.
-  pub fn pan_modulator(&mut self) -> Option<&SynthSignal> {
+  pub fn pan_modulator(&self) -> Option<&SynthSignal> {
     self.pan_modulator.as_ref()
   }
 
   /// Returns a signal that follows the volume of the channel before effects are applied.
-  pub fn dry_level_signal(&mut self) -> &SynthSignal {
+  pub fn dry_level_signal(&self) -> &SynthSignal {
     &self.dry_level_signal
   }
   /// Returns a signal that follows the volume of the channel after effects are applied.
-  pub fn wet_level_signal(&mut self) -> &SynthSignal {
+  pub fn wet_level_signal(&self) -> &SynthSignal {
     &self.wet_level_signal
   }
 
@@ -167,6 +198,12 @@ impl SoundChannel {
   }
 }
 
+impl Default for SoundChannel {
+  fn default() -> Self {
+    Self::new()
+  }
+}
+
 impl Drop for SoundChannel {
   fn drop(&mut self) {
     if self.added {
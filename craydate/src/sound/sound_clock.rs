@@ -0,0 +1,170 @@
+use super::{SAMPLE_FRAMES_PER_SEC, Sound};
+use crate::time::{TimeDelta, TimeTicks};
+
+/// A musical clock that runs on the sound engine's time, for scheduling notes in time with a tempo.
+///
+/// The sound engine's time, from `Sound::current_sound_time()`, is what notes are scheduled
+/// against, so scheduling with a `SoundClock` keeps notes in time with each other regardless of
+/// how long each frame takes. The clock counts beats from its origin, which is the time it was
+/// created at unless given otherwise, and each beat is computed from the origin so that rounding
+/// never builds up over a long song.
+///
+/// Note that `TimeTicks` has millisecond resolution, so a scheduled beat may be up to a millisecond
+/// early when the beat length is not a whole number of milliseconds.
+///
+/// # Example
+/// ```
+/// let clock = SoundClock::new(128.0, 4);
+/// // Play the next note on the downbeat of the next bar.
+/// synth.play_midi_note(note, Some(clock.beat_length()), Some(clock.next_bar()));
+/// ```
+#[derive(Debug, Clone)]
+pub struct SoundClock {
+  // The sound time of beat 0, in sample frames.
+  origin: u64,
+  beats_per_minute: f32,
+  beats_per_bar: u32,
+}
+impl SoundClock {
+  /// Constructs a clock with its first beat at the current sound time.
+  ///
+  /// The `beats_per_minute` must be positive, and `beats_per_bar` must be at least 1.
+  pub fn new(beats_per_minute: f32, beats_per_bar: u32) -> Self {
+    Self::with_origin(Self::now(), beats_per_minute, beats_per_bar)
+  }
+  /// Constructs a clock with its first beat, and the first beat of its first bar, at `origin`.
+  ///
+  /// The `beats_per_minute` must be positive, and `beats_per_bar` must be at least 1.
+  pub fn with_origin(origin: TimeTicks, beats_per_minute: f32, beats_per_bar: u32) -> Self {
+    assert!(beats_per_minute > 0.0);
+    assert!(beats_per_bar > 0);
+    SoundClock {
+      origin: origin.to_sample_frames() as u64,
+      beats_per_minute,
+      beats_per_bar,
+    }
+  }
+
+  /// Returns the sound engine's current time, which is the time that notes are scheduled against.
+  ///
+  /// This is the same as `Sound::current_sound_time()`.
+  pub fn now() -> TimeTicks {
+    TimeTicks::from_sample_frames(Self::now_frames() as u32)
+  }
+  /// Converts a device time, as from `System::current_time()`, into sound time. The `device_now`
+  /// is the current device time.
+  ///
+  /// The two clocks run at the same rate but from different starting points, so this is only as
+  /// accurate as the time between reading each of them, which is well under a millisecond.
+  pub fn sound_time_from_device_time(device_time: TimeTicks, device_now: TimeTicks) -> TimeTicks {
+    Self::now() + (device_time - device_now)
+  }
+  /// Converts a sound time into device time, as from `System::current_time()`. The `device_now` is
+  /// the current device time.
+  ///
+  /// The two clocks run at the same rate but from different starting points, so this is only as
+  /// accurate as the time between reading each of them, which is well under a millisecond.
+  pub fn device_time_from_sound_time(sound_time: TimeTicks, device_now: TimeTicks) -> TimeTicks {
+    device_now + (sound_time - Self::now())
+  }
+
+  /// Returns the time of the first beat.
+  pub fn origin(&self) -> TimeTicks {
+    Self::ticks_from_frames(self.origin)
+  }
+  /// Returns the tempo in beats per minute.
+  pub fn tempo(&self) -> f32 {
+    self.beats_per_minute
+  }
+  /// Changes the tempo to `beats_per_minute`, which must be positive.
+  ///
+  /// The current beat stays the same, with the beats from here on being closer together or further
+  /// apart, so that a song can change tempo without skipping.
+  pub fn set_tempo(&mut self, beats_per_minute: f32) {
+    assert!(beats_per_minute > 0.0);
+    let now = Self::now_frames();
+    let beat = self.beat_at_frames(now);
+    self.beats_per_minute = beats_per_minute;
+    // Move the origin so that `beat` is still at `now`.
+    let since_origin = (beat * self.frames_per_beat()) as u64;
+    self.origin = now.saturating_sub(since_origin);
+  }
+  /// Returns the number of beats in each bar.
+  pub fn beats_per_bar(&self) -> u32 {
+    self.beats_per_bar
+  }
+
+  /// Returns the length of a beat.
+  pub fn beat_length(&self) -> TimeDelta {
+    TimeDelta::from_seconds_lossy(60.0 / self.beats_per_minute)
+  }
+  /// Returns the length of a bar.
+  pub fn bar_length(&self) -> TimeDelta {
+    TimeDelta::from_seconds_lossy(60.0 / self.beats_per_minute * self.beats_per_bar as f32)
+  }
+
+  /// Returns the number of beats, including the fraction of the current beat, from the origin to
+  /// `time`. Times before the origin are at beat 0.
+  pub fn beat_at(&self, time: TimeTicks) -> f64 {
+    self.beat_at_frames(time.to_sample_frames() as u64)
+  }
+  /// Returns the number of beats, including the fraction of the current beat, from the origin to
+  /// now.
+  pub fn current_beat(&self) -> f64 {
+    self.beat_at_frames(Self::now_frames())
+  }
+  /// Returns the whole number of bars from the origin to now.
+  pub fn current_bar(&self) -> u64 {
+    self.current_beat() as u64 / self.beats_per_bar as u64
+  }
+  /// Returns the time of the `beat`, counting from 0 at the origin.
+  pub fn time_of_beat(&self, beat: f64) -> TimeTicks {
+    Self::ticks_from_frames(self.frames_of_beat(beat))
+  }
+  /// Returns the time of the first beat of the `bar`, counting from 0 at the origin.
+  pub fn time_of_bar(&self, bar: u64) -> TimeTicks {
+    self.time_of_beat((bar * self.beats_per_bar as u64) as f64)
+  }
+
+  /// Returns the time of the next whole beat after now.
+  pub fn next_beat(&self) -> TimeTicks {
+    self.next_subdivision(1)
+  }
+  /// Returns the time of the first beat of the next bar after now.
+  pub fn next_bar(&self) -> TimeTicks {
+    let bar = self.current_beat() as u64 / self.beats_per_bar as u64 + 1;
+    self.time_of_bar(bar)
+  }
+  /// Returns the time of the next division after now, when each beat is divided into
+  /// `per_beat` parts. For example, 2 gives eighth notes when a beat is a quarter note.
+  pub fn next_subdivision(&self, per_beat: u32) -> TimeTicks {
+    assert!(per_beat > 0);
+    let parts = self.current_beat() * per_beat as f64;
+    let next = parts as u64 + 1;
+    self.time_of_beat(next as f64 / per_beat as f64)
+  }
+  /// Returns the division of a beat nearest to `time`, when each beat is divided into `per_beat`
+  /// parts, such as to judge how close a button press was to the beat in a rhythm game.
+  pub fn quantize(&self, time: TimeTicks, per_beat: u32) -> TimeTicks {
+    assert!(per_beat > 0);
+    let parts = self.beat_at(time) * per_beat as f64;
+    let nearest = (parts + 0.5) as u64;
+    self.time_of_beat(nearest as f64 / per_beat as f64)
+  }
+
+  fn frames_per_beat(&self) -> f64 {
+    SAMPLE_FRAMES_PER_SEC as f64 * 60.0 / self.beats_per_minute as f64
+  }
+  fn beat_at_frames(&self, frames: u64) -> f64 {
+    frames.saturating_sub(self.origin) as f64 / self.frames_per_beat()
+  }
+  fn frames_of_beat(&self, beat: f64) -> u64 {
+    self.origin + (beat.max(0.0) * self.frames_per_beat()) as u64
+  }
+  fn ticks_from_frames(frames: u64) -> TimeTicks {
+    TimeTicks::from_sample_frames(frames as u32)
+  }
+  fn now_frames() -> u64 {
+    unsafe { Sound::fns().getCurrentTime.unwrap()() as u64 }
+  }
+}
@@ -0,0 +1,159 @@
+use alloc::collections::BTreeMap;
+use core::future::Future;
+use core::pin::Pin;
+use core::task::{Context, Poll};
+
+use crate::capi_state::CApiState;
+use crate::ctypes::*;
+use crate::executor::Executor;
+
+/// A C callback function given to Playdate for a `SoundSource`.
+pub(crate) type SourceCallbackFn = unsafe extern "C" fn(*mut CSoundSource);
+
+/// The events that Playdate reports through a callback on a `SoundSource`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub(crate) enum SourceEvent {
+  Finished,
+  Looped,
+}
+
+#[derive(Debug, Default)]
+struct SourceEventState {
+  // How many times the event has happened.
+  count: u64,
+  // The C function for the user's callback, registered in their `Callbacks`, if any.
+  forward: Option<SourceCallbackFn>,
+}
+
+/// Counts the finish and loop events of each `SoundSource`, so that futures can wait for them.
+///
+/// Playdate only holds a single callback function for each event, so the callback given to
+/// Playdate is always one of ours, and it forwards the event on to the user's callback in their
+/// `Callbacks` when they have set one.
+#[derive(Debug, Default)]
+pub(crate) struct SourceEvents {
+  states: BTreeMap<(usize, SourceEvent), SourceEventState>,
+}
+impl SourceEvents {
+  pub fn new() -> Self {
+    SourceEvents::default()
+  }
+
+  /// Starts counting the `event` for the `source`, returning the C function to give to Playdate.
+  pub fn watch(source: *mut CSoundSource, event: SourceEvent) -> SourceCallbackFn {
+    let mut events = CApiState::get().source_events.borrow_mut();
+    events.states.entry((source as usize, event)).or_default();
+    match event {
+      SourceEvent::Finished => on_finished,
+      SourceEvent::Looped => on_looped,
+    }
+  }
+  /// Stops counting the `event` for the `source`. Any futures waiting for it will complete.
+  pub fn unwatch(source: *mut CSoundSource, event: SourceEvent) {
+    let mut events = CApiState::get().source_events.borrow_mut();
+    events.states.remove(&(source as usize, event));
+  }
+  /// Sets the C function for the user's callback which the `event` will be forwarded to.
+  pub fn set_forward(source: *mut CSoundSource, event: SourceEvent, f: Option<SourceCallbackFn>) {
+    let mut events = CApiState::get().source_events.borrow_mut();
+    if let Some(state) = events.states.get_mut(&(source as usize, event)) {
+      state.forward = f;
+    }
+  }
+
+  /// Returns how many times the `event` has happened for the `source`, or `None` if it is not
+  /// being counted, such as after the source is destroyed.
+  pub fn count(source: *const CSoundSource, event: SourceEvent) -> Option<u64> {
+    let events = CApiState::get().source_events.borrow();
+    events.states.get(&(source as usize, event)).map(|state| state.count)
+  }
+
+  /// Returns a future that completes the next time the `event` happens for the `source`.
+  pub fn next(source: *const CSoundSource, event: SourceEvent) -> SourceEventFuture {
+    SourceEventFuture {
+      source: source as usize,
+      event,
+      count: Self::count(source, event),
+    }
+  }
+
+  fn on_event(source: *mut CSoundSource, event: SourceEvent) -> Option<SourceCallbackFn> {
+    let mut events = CApiState::get().source_events.borrow_mut();
+    let state = events.states.get_mut(&(source as usize, event))?;
+    state.count += 1;
+    state.forward
+  }
+}
+
+extern "C" fn on_finished(source: *mut CSoundSource) {
+  // The borrow on the `SourceEvents` is released before forwarding, as the user's callback may
+  // change the callbacks of any `SoundSource`.
+  if let Some(forward) = SourceEvents::on_event(source, SourceEvent::Finished) {
+    unsafe { forward(source) }
+  }
+}
+extern "C" fn on_looped(source: *mut CSoundSource) {
+  if let Some(forward) = SourceEvents::on_event(source, SourceEvent::Looped) {
+    // Loop callbacks are registered in `Callbacks` with a key one past the source pointer, to keep
+    // them apart from its completion callback. This pointer is not aligned, but it is only used as
+    // a map key.
+    unsafe { forward((source as *mut u8).add(1) as *mut CSoundSource) }
+  }
+}
+
+/// A note played on a `Synth`, which can be waited on until it finishes playing.
+///
+/// # Example
+/// ```
+/// let length = Some(TimeDelta::from_seconds(1));
+/// synth.play_frequency_note(440.0, Volume::one(), length, None).finished().await;
+/// ```
+#[derive(Debug)]
+pub struct PlayedNote {
+  future: SourceEventFuture,
+}
+impl PlayedNote {
+  pub(crate) fn new(source: *const CSoundSource) -> Self {
+    PlayedNote {
+      future: SourceEvents::next(source, SourceEvent::Finished),
+    }
+  }
+
+  /// Waits until the `Synth` finishes playing, including its release time.
+  ///
+  /// If the note is scheduled to play later, this waits for it to play and then finish. It also
+  /// completes if the `Synth` is destroyed first.
+  ///
+  /// UNCLEAR: Playing another note on the `Synth` before this one ends may finish the note early,
+  /// or may extend it, depending on whether Playdate reports the first note finishing.
+  pub async fn finished(self) {
+    self.future.await
+  }
+}
+
+/// A future for which poll() waits for a `SoundSource` event to happen after the future was
+/// constructed, then returns Complete.
+///
+/// The future is also complete if the `SoundSource` is destroyed, since the event can then never
+/// happen.
+#[derive(Debug)]
+pub(crate) struct SourceEventFuture {
+  source: usize,
+  event: SourceEvent,
+  count: Option<u64>,
+}
+
+impl Future for SourceEventFuture {
+  type Output = ();
+
+  fn poll(self: Pin<&mut Self>, ctxt: &mut Context<'_>) -> Poll<Self::Output> {
+    let now = SourceEvents::count(self.source as *const CSoundSource, self.event);
+    match (self.count, now) {
+      (Some(before), Some(now)) if now == before => {
+        Executor::add_waker_for_system_event(CApiState::get().executor, ctxt.waker());
+        Poll::Pending
+      }
+      _ => Poll::Ready(()),
+    }
+  }
+}
@@ -6,13 +6,29 @@ use core::mem::ManuallyDrop;
 use core::ptr::NonNull;
 
 use super::super::sound_channel::SoundChannel;
+use super::super::Sound;
 use super::sound_source::SoundSource;
 use crate::ctypes::*;
 use crate::system::System;
 
 /// A `SoundSource` that is a user-defined function that writes to the audio buffer directly.
 ///
+/// This can be used for procedural audio written in Rust, without going through a `Synth`.
+///
 /// Destroying the `CallbackSource` will remove it from the channel if it's attached.
+///
+/// # Example
+/// ```
+/// let mut phase = 0u32;
+/// let tone = CallbackSource::new_stereo(&mut api.sound, move |left, right| {
+///   for (l, r) in left.iter_mut().zip(right.iter_mut()) {
+///     phase = phase.wrapping_add(1);
+///     *l = if phase & 64 == 0 { 2000 } else { -2000 };
+///     *r = *l;
+///   }
+///   true
+/// });
+/// ```
 pub struct CallbackSource {
   source: ManuallyDrop<SoundSource>,
   ptr: NonNull<CSoundSource>,
@@ -20,6 +36,73 @@ pub struct CallbackSource {
   _mono_data: Option<Box<MonoData>>,
 }
 impl CallbackSource {
+  /// Constructs a new stereo `CallbackSource` that runs `callback` each sound frame to fill the
+  /// stereo sound buffers.
+  ///
+  /// The `CallbackSource` starts out being attached to the `Sound`'s default channel.
+  ///
+  /// The `callback` closure should fill the passed-in left and right slices with samples and return
+  /// true, or return false if the source is silent through the cycle.
+  pub fn new_stereo<F>(sound: &mut Sound, callback: F) -> Self
+  where
+    F: FnMut(&mut [i16], &mut [i16]) -> bool + Sync + 'static,
+  {
+    let stereo_ptr = Box::into_raw(Box::new(StereoData {
+      callback: Box::new(callback),
+    }));
+    let stereo_data = unsafe { Box::from_raw(stereo_ptr) };
+    let ptr = unsafe {
+      Sound::fns().addSource.unwrap()(
+        Some(c_stereo_function),
+        stereo_ptr as *mut c_void,
+        /*stereo=*/ true as i32,
+      )
+    };
+    let mut s = CallbackSource {
+      source: ManuallyDrop::new(SoundSource::from_ptr(ptr)),
+      ptr: NonNull::new(ptr).unwrap(),
+      _stereo_data: Some(stereo_data),
+      _mono_data: None,
+    };
+    // A CallbackSource is already attached to the default channel when created, but we add it
+    // anyway so that the `SoundSource` knows which channel it is attached to.
+    sound.default_channel_mut().add_source(&mut s).unwrap();
+    s
+  }
+  /// Constructs a new mono `CallbackSource` that runs `callback` each sound frame to fill the mono
+  /// sound buffer.
+  ///
+  /// The `CallbackSource` starts out being attached to the `Sound`'s default channel.
+  ///
+  /// The `callback` closure should fill the passed-in slice with samples and return true, or return
+  /// false if the source is silent through the cycle.
+  pub fn new_mono<F>(sound: &mut Sound, callback: F) -> Self
+  where
+    F: FnMut(&mut [i16]) -> bool + Sync + 'static,
+  {
+    let mono_ptr = Box::into_raw(Box::new(MonoData {
+      callback: Box::new(callback),
+    }));
+    let mono_data = unsafe { Box::from_raw(mono_ptr) };
+    let ptr = unsafe {
+      Sound::fns().addSource.unwrap()(
+        Some(c_mono_function),
+        mono_ptr as *mut c_void,
+        /*stereo=*/ false as i32,
+      )
+    };
+    let mut s = CallbackSource {
+      source: ManuallyDrop::new(SoundSource::from_ptr(ptr)),
+      ptr: NonNull::new(ptr).unwrap(),
+      _stereo_data: None,
+      _mono_data: Some(mono_data),
+    };
+    // A CallbackSource is already attached to the default channel when created, but we add it
+    // anyway so that the `SoundSource` knows which channel it is attached to.
+    sound.default_channel_mut().add_source(&mut s).unwrap();
+    s
+  }
+
   /// Constructs a new stereo `CallbackSource` that runs `callback` each sound frame to fill the
   /// stereo sound buffers.
   ///
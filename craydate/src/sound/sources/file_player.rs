@@ -1,6 +1,10 @@
+use core::future::Future;
 use core::mem::ManuallyDrop;
+use core::pin::Pin;
 use core::ptr::NonNull;
+use core::task::{Context, Poll};
 
+use super::super::audio_suspend::PausablePlayer;
 use super::super::loop_sound_span::LoopTimeSpan;
 use super::super::{SoundCompletionCallback, StereoVolume};
 use super::sound_source::{AsSoundSource, SoundSource};
@@ -9,6 +13,7 @@ use crate::callbacks::RegisteredCallback;
 use crate::capi_state::CApiState;
 use crate::ctypes::*;
 use crate::error::Error;
+use crate::executor::Executor;
 use crate::null_terminated::ToNullTerminatedString;
 use crate::time::{TimeDelta, TimeTicks};
 
@@ -40,8 +45,10 @@ impl FilePlayer {
     let r =
       unsafe { Self::fns().loadIntoPlayer.unwrap()(ptr, path.to_null_terminated_utf8().as_ptr()) };
     if r == 0 {
+      unsafe { Self::fns().freePlayer.unwrap()(ptr) };
       Err(Error::NotFoundError)
     } else {
+      CApiState::get().audio_suspend.borrow_mut().add_player(PausablePlayer::File(ptr));
       Ok(FilePlayer {
         source: ManuallyDrop::new(SoundSource::from_ptr(ptr as *mut CSoundSource)),
         ptr: NonNull::new(ptr).unwrap(),
@@ -62,10 +69,17 @@ impl FilePlayer {
     unsafe { Self::fns().setBufferLength.unwrap()(self.cptr_mut(), length.to_seconds()) };
   }
 
-  /// Pauses the file player.
+  /// Pauses the file player. It can continue playing from the same place with `resume()`.
   pub fn pause(&mut self) {
+    CApiState::get().audio_suspend.borrow_mut().clear_suspended(self.pausable());
     unsafe { Self::fns().pause.unwrap()(self.cptr_mut()) }
   }
+  /// Continues playing the file player from where it was paused with `pause()`.
+  ///
+  /// The player loops the number of times that was last given to `play()`.
+  pub fn resume(&mut self) -> Result<(), Error> {
+    self.play(CApiState::get().audio_suspend.borrow().repeat(self.pausable()))
+  }
   /// Starts playing the file player.
   ///
   /// If `times` is greater than one, it loops the given number of times. If zero, it loops
@@ -75,6 +89,10 @@ impl FilePlayer {
   /// `FilePlayer` to be constructed successfully from a file, but then fail to `play()` when it
   /// tries to open and read from the file. In that case, an error is returned.
   pub fn play(&mut self, times: i32) -> Result<(), Error> {
+    let mut suspend = CApiState::get().audio_suspend.borrow_mut();
+    suspend.clear_suspended(self.pausable());
+    suspend.set_repeat(self.pausable(), times);
+    drop(suspend);
     match unsafe { Self::fns().play.unwrap()(self.cptr_mut(), times) } {
       0 => Err(Error::PlayFileError),
       _ => Ok(()),
@@ -177,6 +195,30 @@ impl FilePlayer {
     }
   }
 
+  /// Fades the volume of the fileplayer down to silence over a length of `duration`.
+  ///
+  /// The player keeps playing silently after the fade, until it is stopped with `stop()`.
+  pub fn fade_out(&mut self, duration: TimeDelta) {
+    self.fade_volume(StereoVolume::zero(), duration, SoundCompletionCallback::none())
+  }
+
+  /// Waits until the player is no longer playing, because it reached the end of the file, ran out
+  /// of loops, or was stopped.
+  ///
+  /// # Example
+  /// ```
+  /// music.play(1)?;
+  /// music.finished().await;
+  /// println("music done");
+  /// ```
+  pub async fn finished(&self) {
+    FilePlayerFinishedFuture { player: self }.await
+  }
+
+  fn pausable(&self) -> PausablePlayer {
+    PausablePlayer::File(self.ptr.as_ptr())
+  }
+
   pub(crate) fn cptr(&self) -> *const CFilePlayer {
     self.ptr.as_ptr()
   }
@@ -189,12 +231,31 @@ impl FilePlayer {
 }
 impl Drop for FilePlayer {
   fn drop(&mut self) {
+    CApiState::get().audio_suspend.borrow_mut().remove_player(self.pausable());
     // Ensure the SoundSource has a chance to clean up before it is freed.
     unsafe { ManuallyDrop::drop(&mut self.source) };
     unsafe { Self::fns().freePlayer.unwrap()(self.cptr_mut()) };
   }
 }
 
+/// A future for which poll() waits for a `FilePlayer` to stop playing, then returns Complete.
+struct FilePlayerFinishedFuture<'a> {
+  player: &'a FilePlayer,
+}
+
+impl Future for FilePlayerFinishedFuture<'_> {
+  type Output = ();
+
+  fn poll(self: Pin<&mut Self>, ctxt: &mut Context<'_>) -> Poll<Self::Output> {
+    if self.player.as_source().is_playing() {
+      Executor::add_waker_for_system_event(CApiState::get().executor, ctxt.waker());
+      Poll::Pending
+    } else {
+      Poll::Ready(())
+    }
+  }
+}
+
 impl AsRef<SoundSource> for FilePlayer {
   fn as_ref(&self) -> &SoundSource {
     &self.source
@@ -3,6 +3,7 @@ use core::mem::ManuallyDrop;
 use core::ptr::NonNull;
 
 use super::super::midi::midi_note_range::MidiNoteRange;
+use super::super::midi::note::Note;
 use super::super::midi::track_note::TrackNote;
 use super::super::volume::{StereoVolume, Volume};
 use super::sound_source::SoundSource;
@@ -12,6 +13,8 @@ use crate::ctypes::*;
 use crate::error::Error;
 use crate::time::{TimeDelta, TimeTicks};
 
+/// Identifies a `Synth` voice that was added to an `Instrument`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
 pub struct VoiceId(usize);
 
 /// `Instrument` collects a number of `Synth` objects together to provide polyphony.
@@ -86,6 +89,20 @@ impl<'data> Instrument {
     self.synths.get_mut(voice.0)
   }
 
+  /// Returns the number of voices that have been added to the instrument.
+  pub fn voices_count(&self) -> usize {
+    self.synths.len()
+  }
+  /// Returns an iterator over the voices that have been added to the instrument, along with the
+  /// id of each.
+  pub fn voices(&self) -> impl Iterator<Item = (VoiceId, &Synth)> {
+    self.synths.iter().enumerate().map(|(i, synth)| (VoiceId(i), synth))
+  }
+
+  fn voice_for_ptr(&self, synth_ptr: *const CSynth) -> Option<VoiceId> {
+    self.synths.iter().position(|synth| synth.cptr() == synth_ptr).map(VoiceId)
+  }
+
   /// Plays a note on the Instrument, using the `frequency`.
   ///
   /// The instrument passes the play event to the `Synth` in its collection that has been off for
@@ -95,15 +112,16 @@ impl<'data> Instrument {
   /// `when` is None, the note is played immediately, otherwise the note is scheduled for the given
   /// absolute time. Use `Sound::current_sound_time()` to get the current time.
   ///
-  /// An id for the `Synth` that received the play event is returned. The id matches the one
-  /// returned from add_voice() for the `Synth`.
+  /// The id of the `Synth` that received the play event is returned, which matches the one
+  /// returned from `add_voice()` for the `Synth`. Returns `None` if no voice received the event,
+  /// such as when the instrument has no voices.
   pub fn play_frequency_note(
     &mut self,
     frequency: f32,
     volume: Volume,
     length: Option<TimeDelta>,
     when: Option<TimeTicks>,
-  ) -> usize {
+  ) -> Option<VoiceId> {
     let synth_ptr = unsafe {
       Instrument::fns().playNote.unwrap()(
         self.cptr_mut(),
@@ -113,10 +131,10 @@ impl<'data> Instrument {
         when.map_or(0, |w| w.to_sample_frames()),
       )
     };
-    synth_ptr as usize
+    self.voice_for_ptr(synth_ptr)
   }
 
-  /// Plays a MIDI note on the Instrument, where 'C4' is `60.0` for the `note`.
+  /// Plays a MIDI note on the Instrument.
   ///
   /// The instrument passes the play event to the `Synth` in its collection that has been off for
   /// the longest, or has been playing longest if all synths are currently playing.
@@ -125,24 +143,25 @@ impl<'data> Instrument {
   /// `when` is None, the note is played immediately, otherwise the note is scheduled for the given
   /// absolute time. Use `Sound::current_sound_time()` to get the current time.
   ///
-  /// An id for the `Synth` that received the play event is returned. The id matches the one
-  /// returned from add_voice() for the `Synth`.
+  /// The id of the `Synth` that received the play event is returned, which matches the one
+  /// returned from `add_voice()` for the `Synth`. Returns `None` if no voice received the event,
+  /// such as when the instrument has no voices.
   pub fn play_midi_note(
     &mut self,
     note: TrackNote,
     length: Option<TimeDelta>,
     when: Option<TimeTicks>,
-  ) -> usize {
+  ) -> Option<VoiceId> {
     let synth_ptr = unsafe {
       Instrument::fns().playMIDINote.unwrap()(
         self.cptr_mut(),
-        note.midi_note.into(),
+        note.note.into(),
         note.velocity.into(),
         length.map_or(-1.0, |l| l.to_seconds()),
         when.map_or(0, |w| w.to_sample_frames()),
       )
     };
-    synth_ptr as usize
+    self.voice_for_ptr(synth_ptr)
   }
 
   /// Forwards a stop event to the `Synth` currently playing the given note.
@@ -151,11 +170,11 @@ impl<'data> Instrument {
   ///
   /// If `when` is `None`, the note is stopped immediately. Otherwise it is scheduled to be stopped
   /// at the given absolute time. Use `Sound::current_sound_time()` to get the current time.
-  pub fn stop_note(&mut self, midi_note: f32, when: Option<TimeTicks>) {
+  pub fn stop_note(&mut self, note: Note, when: Option<TimeTicks>) {
     unsafe {
       Instrument::fns().noteOff.unwrap()(
         self.cptr_mut(),
-        midi_note,
+        note.into(),
         when.map_or(0, |w| w.to_sample_frames()),
       )
     }
@@ -223,6 +242,12 @@ impl<'data> Instrument {
   }
 }
 
+impl Default for Instrument {
+  fn default() -> Self {
+    Self::new()
+  }
+}
+
 impl Drop for Instrument {
   fn drop(&mut self) {
     // Ensure the SoundSource has a chance to clean up before it is freed.
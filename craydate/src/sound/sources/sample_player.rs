@@ -3,6 +3,8 @@ use core::mem::ManuallyDrop;
 use core::ptr::NonNull;
 
 use super::super::audio_sample::AudioSample;
+use super::super::audio_suspend::PausablePlayer;
+use super::super::source_events::{SourceEvent, SourceEvents};
 use super::super::SoundCompletionCallback;
 use super::sound_source::{AsSoundSource, SoundSource};
 use crate::callback_builder::Constructed;
@@ -29,6 +31,9 @@ impl SamplePlayer<'_> {
     let ptr = unsafe { Self::fns().newPlayer.unwrap()() };
     // setSample() takes a mutable sample pointer but doesn't mutate any visible state.
     unsafe { Self::fns().setSample.unwrap()(ptr, sample.cptr() as *mut _) }
+    CApiState::get().audio_suspend.borrow_mut().add_player(PausablePlayer::Sample(ptr));
+    let func = SourceEvents::watch(ptr as *mut CSoundSource, SourceEvent::Looped);
+    unsafe { Self::fns().setLoopCallback.unwrap()(ptr, Some(func)) }
     SamplePlayer {
       source: ManuallyDrop::new(SoundSource::from_ptr(ptr as *mut CSoundSource)),
       ptr: NonNull::new(ptr).unwrap(),
@@ -37,6 +42,15 @@ impl SamplePlayer<'_> {
     }
   }
 
+  /// Changes the AudioSample that the player plays.
+  ///
+  /// This is not public as the player's lifetime is not tied to the new `sample`, so the caller
+  /// must ensure the `sample` outlives its use by the player.
+  pub(crate) fn set_sample(&mut self, sample: &AudioSample) {
+    // setSample() takes a mutable sample pointer but doesn't mutate any visible state.
+    unsafe { Self::fns().setSample.unwrap()(self.cptr_mut(), sample.cptr() as *mut _) }
+  }
+
   /// Returns the length of AudioSample assigned to the player.
   pub fn len(&self) -> TimeDelta {
     // getLength() takes a mutable pointer it changes no visible state.
@@ -51,6 +65,7 @@ impl SamplePlayer<'_> {
   /// Sets the playback rate for the player. 1.0 is normal speed, 0.5 is down an octave, 2.0 is up
   /// an octave, etc.
   pub fn play(&mut self, repeat: i32, rate: f32) {
+    CApiState::get().audio_suspend.borrow_mut().clear_suspended(self.pausable());
     // TODO: What does the return value of play() mean here?
     let r = unsafe { Self::fns().play.unwrap()(self.cptr_mut(), repeat, rate) };
     assert!(r != 0)
@@ -58,13 +73,21 @@ impl SamplePlayer<'_> {
   pub fn stop(&mut self) {
     unsafe { Self::fns().stop.unwrap()(self.cptr_mut()) };
   }
-  /// Pauses playback of the SamplePlayer.
+  /// Pauses playback of the SamplePlayer. It can continue playing from the same place with
+  /// `resume()`.
   pub fn pause(&mut self) {
+    CApiState::get().audio_suspend.borrow_mut().clear_suspended(self.pausable());
     unsafe { Self::fns().setPaused.unwrap()(self.cptr_mut(), 1) }
   }
+  /// Resumes playback of the SamplePlayer after it was paused with `pause()`.
+  pub fn resume(&mut self) {
+    CApiState::get().audio_suspend.borrow_mut().clear_suspended(self.pausable());
+    unsafe { Self::fns().setPaused.unwrap()(self.cptr_mut(), 0) }
+  }
   /// Resumes playback of the SamplePlayer.
+  #[deprecated(since = "0.1.8", note = "use `resume()` instead")]
   pub fn unpause(&mut self) {
-    unsafe { Self::fns().setPaused.unwrap()(self.cptr_mut(), 1) }
+    self.resume()
   }
   /// Returns if the player is playing a sample.
   pub fn is_playing(&self) -> bool {
@@ -132,13 +155,32 @@ impl SamplePlayer<'_> {
   ) {
     self.loop_callback = None;
     let func = loop_callback.into_inner().and_then(|(callbacks, cb)| {
-      // This pointer is not aligned, but we will not deref it. It's only used as a map key.
-      let key = unsafe { self.as_source_mut().cptr().add(1) } as usize;
+      // The key is one past the source pointer to keep it apart from the key of the source's
+      // completion callback. It's only used as a map key.
+      let key = self.as_source_mut().cptr() as usize + 1;
       let (func, reg) = callbacks.add_sound_source_completion(key, cb);
       self.loop_callback = Some(reg);
       Some(func)
     });
-    unsafe { Self::fns().setLoopCallback.unwrap()(self.cptr_mut(), func) }
+    // Playdate calls our own callback, which forwards to the user's callback.
+    SourceEvents::set_forward(self.as_source_mut().cptr_mut(), SourceEvent::Looped, func);
+  }
+
+  /// Waits until the sample next loops, such as to do something in time with each repeat.
+  ///
+  /// If the player is destroyed or replaced first, this completes without the sample looping.
+  /// This does not replace a callback set with `set_loop_callback()`, which is still called.
+  ///
+  /// # Example
+  /// ```
+  /// player.play(0, 1.0);
+  /// loop {
+  ///   player.looped().await;
+  ///   beats += 1;
+  /// }
+  /// ```
+  pub async fn looped(&self) {
+    SourceEvents::next(self.as_source().cptr(), SourceEvent::Looped).await
   }
 
   pub(crate) fn cptr(&self) -> *const CSamplePlayer {
@@ -147,6 +189,10 @@ impl SamplePlayer<'_> {
   pub(crate) fn cptr_mut(&mut self) -> *mut CSamplePlayer {
     self.ptr.as_ptr()
   }
+  fn pausable(&self) -> PausablePlayer {
+    PausablePlayer::Sample(self.ptr.as_ptr())
+  }
+
   pub(crate) fn fns() -> &'static craydate_sys::playdate_sound_sampleplayer {
     unsafe { &*CApiState::get().csound.sampleplayer }
   }
@@ -154,7 +200,10 @@ impl SamplePlayer<'_> {
 
 impl Drop for SamplePlayer<'_> {
   fn drop(&mut self) {
+    CApiState::get().audio_suspend.borrow_mut().remove_player(self.pausable());
     self.set_loop_callback(SoundCompletionCallback::none());
+    unsafe { Self::fns().setLoopCallback.unwrap()(self.cptr_mut(), None) }
+    SourceEvents::unwatch(self.as_source_mut().cptr_mut(), SourceEvent::Looped);
     // Ensure the SoundSource has a chance to clean up before it is freed.
     unsafe { ManuallyDrop::drop(&mut self.source) };
     unsafe { Self::fns().freePlayer.unwrap()(self.cptr_mut()) }
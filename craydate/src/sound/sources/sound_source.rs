@@ -1,12 +1,18 @@
 use alloc::rc::{Rc, Weak};
+use core::future::Future;
+use core::pin::Pin;
 use core::ptr::NonNull;
+use core::task::{Context, Poll};
 
+use super::super::source_events::{SourceEvent, SourceEvents};
 use super::super::{SoundCompletionCallback, StereoVolume};
 use crate::callback_builder::Constructed;
 use crate::callbacks::RegisteredCallback;
 use crate::capi_state::CApiState;
 use crate::ctypes::*;
 use crate::error::Error;
+use crate::executor::Executor;
+use crate::time::TimeDelta;
 
 /// Represents a weak connection to whatever is playing the SoundSource.
 ///
@@ -43,6 +49,8 @@ pub struct SoundSource {
 }
 impl SoundSource {
   pub(crate) fn from_ptr(ptr: *mut CSoundSource) -> Self {
+    let func = SourceEvents::watch(ptr, SourceEvent::Finished);
+    unsafe { Self::fns().setFinishCallback.unwrap()(ptr, Some(func)) }
     SoundSource {
       ptr: NonNull::new(ptr).unwrap(),
       attachment: Attachment::None,
@@ -117,6 +125,32 @@ impl SoundSource {
   pub fn set_volume(&mut self, v: StereoVolume) {
     unsafe { Self::fns().setVolume.unwrap()(self.cptr_mut(), v.left.into(), v.right.into()) }
   }
+  /// Ramps the playback volume from its current level to `volume` over `duration`, completing
+  /// when the fade is done.
+  ///
+  /// The volume is changed each time the future is polled, which happens at each system event, so
+  /// at least once per frame, and is based on the sound time so it keeps to the `duration` even if
+  /// frames are late. If the future is dropped before it completes, the fade stops at the volume it
+  /// had reached. To fade a whole `SoundChannel` smoothly from the audio engine, use an `Envelope`
+  /// or `Lfo` as its volume modulator instead.
+  ///
+  /// # Example
+  /// ```
+  /// player.as_source_mut().fade_to(StereoVolume::zero(), TimeDelta::from_seconds(2)).await;
+  /// player.stop();
+  /// ```
+  pub async fn fade_to(&mut self, volume: StereoVolume, duration: TimeDelta) {
+    let from = self.volume();
+    let start = unsafe { CApiState::get().csound.getCurrentTime.unwrap()() };
+    FadeFuture {
+      source: self,
+      from: (from.left.to_f32(), from.right.to_f32()),
+      to: (volume.left.to_f32(), volume.right.to_f32()),
+      start,
+      length: duration.to_sample_frames().max(0) as u32,
+    }
+    .await
+  }
   /// Returns whether the source is currently playing.
   pub fn is_playing(&self) -> bool {
     // isPlaying() takes a mutable pointer it changes no visible state.
@@ -155,7 +189,26 @@ impl SoundSource {
       self.completion_callback = Some(reg);
       Some(func)
     });
-    unsafe { Self::fns().setFinishCallback.unwrap()(self.cptr_mut(), func) }
+    // Playdate calls our own callback, which forwards to the user's callback.
+    SourceEvents::set_forward(self.cptr_mut(), SourceEvent::Finished, func);
+  }
+
+  /// Waits until the `SoundSource` finishes playing.
+  ///
+  /// If the source is not playing, this completes right away. For a `Synth`, the futures returned
+  /// from playing a note also wait for any note that is scheduled to play later.
+  ///
+  /// This does not replace a callback set with `set_completion_callback()`, which is still called.
+  ///
+  /// # Example
+  /// ```
+  /// player.play(1, 1.0);
+  /// player.as_source().finished().await;
+  /// ```
+  pub async fn finished(&self) {
+    if self.is_playing() {
+      SourceEvents::next(self.cptr(), SourceEvent::Finished).await
+    }
   }
 
   pub(crate) fn cptr(&self) -> *const CSoundSource {
@@ -172,6 +225,8 @@ impl SoundSource {
 impl Drop for SoundSource {
   fn drop(&mut self) {
     self.set_completion_callback(SoundCompletionCallback::none());
+    unsafe { Self::fns().setFinishCallback.unwrap()(self.cptr_mut(), None) }
+    SourceEvents::unwatch(self.cptr_mut(), SourceEvent::Finished);
 
     match &self.attachment {
       Attachment::None => (),
@@ -185,6 +240,39 @@ impl Drop for SoundSource {
   }
 }
 
+struct FadeFuture<'a> {
+  source: &'a mut SoundSource,
+  from: (f32, f32),
+  to: (f32, f32),
+  // The sound time when the fade started, and its length, in sample frames.
+  start: u32,
+  length: u32,
+}
+impl Future for FadeFuture<'_> {
+  type Output = ();
+
+  fn poll(mut self: Pin<&mut Self>, ctxt: &mut Context<'_>) -> Poll<Self::Output> {
+    let now = unsafe { CApiState::get().csound.getCurrentTime.unwrap()() };
+    let elapsed = now.wrapping_sub(self.start);
+    let (done, t) = if elapsed >= self.length {
+      (true, 1.0)
+    } else {
+      (false, elapsed as f32 / self.length as f32)
+    };
+    let (from, to) = (self.from, self.to);
+    self.source.set_volume(StereoVolume::new(
+      from.0 + (to.0 - from.0) * t,
+      from.1 + (to.1 - from.1) * t,
+    ));
+    if done {
+      Poll::Ready(())
+    } else {
+      Executor::add_waker_for_system_event(CApiState::get().executor, ctxt.waker());
+      Poll::Pending
+    }
+  }
+}
+
 /// Provides explicit access to a type's `SoundSource` methods when it can act as a `SoundSource`.
 pub trait AsSoundSource: AsRef<SoundSource> + AsMut<SoundSource> {
   fn as_source(&self) -> &SoundSource {
@@ -1,14 +1,18 @@
 use alloc::boxed::Box;
 use alloc::collections::BTreeMap;
+use alloc::string::String;
+use alloc::vec::Vec;
 use core::ffi::c_void;
 use core::mem::ManuallyDrop;
 use core::ptr::NonNull;
 
 use super::super::audio_sample::AudioSample;
+use super::super::midi::note::Note;
 use super::super::midi::track_note::TrackNote;
 use super::super::signals::synth_signal::SynthSignal;
+use super::super::source_events::PlayedNote;
 use super::super::volume::Volume;
-use super::sound_source::SoundSource;
+use super::sound_source::{AsSoundSource, SoundSource};
 use crate::capi_state::CApiState;
 use crate::ctypes::*;
 use crate::ctypes_enums::SoundWaveform;
@@ -28,6 +32,7 @@ pub struct Synth {
   frequency_modulator: Option<SynthSignal>,
   amplitude_modulator: Option<SynthSignal>,
   parameter_modulators: BTreeMap<i32, SynthSignal>,
+  parameters: ParameterCache,
   sample: Option<AudioSample>, // Set if constructed from an AudioSample.
 }
 impl Synth {
@@ -40,6 +45,7 @@ impl Synth {
       frequency_modulator: None,
       amplitude_modulator: None,
       parameter_modulators: BTreeMap::new(),
+      parameters: ParameterCache::new(),
       sample: None,
     }
   }
@@ -70,16 +76,108 @@ impl Synth {
     synth
   }
 
+  /// Creates a new Synth that plays noise of the given `color`.
+  ///
+  /// White noise uses the built in `kWaveformNoise` waveform. Pink and brown noise are generated
+  /// into a short looping sample, which uses about 88KB of memory. The note played changes the rate
+  /// that the noise is played at, so lower notes sound darker.
+  pub fn new_noise(color: NoiseColor) -> Synth {
+    if color == NoiseColor::White {
+      return Self::new_with_waveform(SoundWaveform::kWaveformNoise);
+    }
+    let mut seed = 0x2545_f491u32;
+    let mut white = move || {
+      // A xorshift random number generator, as the noise only needs to sound random.
+      seed ^= seed << 13;
+      seed ^= seed >> 17;
+      seed ^= seed << 5;
+      seed as i32 as f32 / i32::MAX as f32
+    };
+    let mut state = [0f32; 3];
+    let pcm: Vec<i16> = (0..NOISE_SAMPLE_RATE)
+      .map(|_| {
+        let w = white();
+        let v = match color {
+          NoiseColor::White => w,
+          // Paul Kellet's economy filter, which is close to -3dB per octave.
+          NoiseColor::Pink => {
+            state[0] = 0.99765 * state[0] + w * 0.0990460;
+            state[1] = 0.96300 * state[1] + w * 0.2965164;
+            state[2] = 0.57000 * state[2] + w * 1.0526913;
+            (state[0] + state[1] + state[2] + w * 0.1848) * 0.25
+          }
+          // A leaky integrator, for -6dB per octave.
+          NoiseColor::Brown => {
+            state[0] = (state[0] + 0.02 * w) / 1.02;
+            state[0] * 3.5
+          }
+        };
+        (v.clamp(-1.0, 1.0) * i16::MAX as f32) as i16
+      })
+      .collect();
+    let sample =
+      AudioSample::from_pcm_buffer(&pcm, SoundFormat::kSound16bitMono, NOISE_SAMPLE_RATE as u32);
+    let frames = pcm.len() as u32;
+    Self::new_with_looping_sample(sample, frames)
+  }
+
+  /// Creates a new Synth that plays a wavetable, which is a single cycle of a waveform, looped.
+  ///
+  /// The `cycle` is played so that notes are at their pitch, which allows any waveform to be played
+  /// beyond those in `SoundWaveform`. Longer cycles can hold more detail in the waveform, but use
+  /// more memory. A cycle of 64 to 256 samples is usually enough.
+  ///
+  /// # Panics
+  ///
+  /// Panics if the `cycle` is empty.
+  ///
+  /// # Example
+  /// ```
+  /// // A narrow pulse wave, which is not one of the `SoundWaveform`s.
+  /// let cycle: Vec<i16> = (0..64).map(|i| if i < 8 { 20000 } else { -3000 }).collect();
+  /// let synth = Synth::new_wavetable(&cycle);
+  /// ```
+  pub fn new_wavetable(cycle: &[i16]) -> Synth {
+    assert!(!cycle.is_empty());
+    // UNCLEAR: This assumes that a Synth plays a sample at its own sample rate for middle C, so the
+    // sample rate is chosen to make one cycle last one period of middle C.
+    let sample_rate = (cycle.len() as f32 * Note::C4.frequency()) as u32;
+    let sample = AudioSample::from_pcm_buffer(cycle, SoundFormat::kSound16bitMono, sample_rate);
+    Self::new_with_looping_sample(sample, cycle.len() as u32)
+  }
+
+  /// Creates a new Synth that plays the first `frames` of the `sample` in a loop while a note is
+  /// on.
+  fn new_with_looping_sample(sample: AudioSample, frames: u32) -> Synth {
+    let mut synth = Self::new();
+    unsafe {
+      // setSample() takes a mutable pointer but doesn't mutate any visible state.
+      Self::fns().setSample.unwrap()(synth.cptr_mut(), sample.cptr() as *mut _, 0, frames)
+    };
+    synth.sample = Some(sample);
+    synth
+  }
+
   /// Creates a new Synth that plays from a `SynthGenerator`.
   ///
-  /// BUG: THIS DOES NOT WORK!! See
+  /// BUG: THIS DOES NOT WORK without the `synth-generator-fixed` feature!! See
   /// <https://devforum.play.date/t/c-api-playdate-sound-synth-setgenerator-has-incorrect-api/4482>
-  /// as this is due to a Playdate bug.
+  /// as this is due to a Playdate bug. The C Api headers for `setGenerator()` are missing its
+  /// `stereo` parameter, so the functions are passed in the wrong positions.
+  ///
+  /// With the `synth-generator-fixed` feature, `setGenerator()` is called with the signature from
+  /// newer SDKs, which includes the `stereo` parameter. The generator is always given both a left
+  /// and right buffer. The C Api has no way to ask for the SDK or firmware version, so the feature
+  /// can't be chosen at runtime, and must only be enabled when building for fixed firmware.
   ///
   /// The `SynthGenerator` is a set of functions that are called in order to fill the sample buffers
   /// with data and react to events on the Synth object.
   pub fn new_with_generator(generator: SynthGenerator) -> Self {
     let mut synth = Self::new();
+    // The generator vtable includes a dealloc function which will be responsible for dropping
+    // this `Box<SynthGenerator>`.
+    let userdata = Box::into_raw(Box::new(generator)) as *mut c_void;
+    #[cfg(not(feature = "synth-generator-fixed"))]
     unsafe {
       Self::fns().setGenerator.unwrap()(
         synth.cptr_mut(),
@@ -90,9 +188,35 @@ impl Synth {
         c_release_func as *mut Option<CReleaseFunc>,
         c_set_parameter_func as *mut Option<CSetParameterFunc>,
         c_dealloc_func as *mut Option<CDeallocFunc>,
-        // The generator vtable includes a dealloc function which will be responsible for dropping
-        // this `Box<SynthGenerator>`.
-        Box::into_raw(Box::new(generator)) as *mut c_void,
+        userdata,
+      )
+    };
+    #[cfg(feature = "synth-generator-fixed")]
+    unsafe {
+      type CSetGeneratorFunc = unsafe extern "C" fn(
+        *mut CSynth,
+        i32,
+        Option<CRenderFunc>,
+        Option<CNoteOnFunc>,
+        Option<CReleaseFunc>,
+        Option<CSetParameterFunc>,
+        Option<CDeallocFunc>,
+        *mut c_void,
+      );
+      // SAFETY: The function has this signature in the fixed firmware, and differs from the
+      // signature in the headers only in its parameters.
+      let set_generator: CSetGeneratorFunc =
+        core::mem::transmute(Self::fns().setGenerator.unwrap());
+      set_generator(
+        synth.cptr_mut(),
+        // Stereo, so that the right buffer given to the render function is never null.
+        1,
+        Some(c_render_func),
+        Some(c_note_on_func),
+        Some(c_release_func),
+        Some(c_set_parameter_func),
+        Some(c_dealloc_func),
+        userdata,
       )
     };
     synth
@@ -188,26 +312,52 @@ impl Synth {
   ///
   /// `i` is 0-based, so the first parameter is `0`, the second is `1`, etc. Returns
   /// `Error::NotFoundError` is the parameter `i` is not valid.
+  ///
+  /// The value is also kept in the Synth's `ParameterCache`, so it can be read back with
+  /// `parameter()`.
   pub fn set_parameter(&mut self, i: i32, value: f32) -> Result<(), Error> {
     let r = unsafe { Self::fns().setParameter.unwrap()(self.cptr_mut(), i, value) };
     match r {
       0 => Err(Error::NotFoundError),
-      _ => Ok(()),
+      _ => {
+        self.parameters.values.insert(i, value);
+        Ok(())
+      }
     }
   }
+  /// Gets the value last given to `set_parameter()` for the Synth's `i`th parameter, or `None` if
+  /// it has not been set.
+  ///
+  /// BUG: Playdate has no function to read a parameter from the Synth, so this does not include any
+  /// parameter modulator, or changes made to the parameter outside of `set_parameter()`.
+  pub fn parameter(&self, i: i32) -> Option<f32> {
+    self.parameters.value(i)
+  }
+  /// Gets the values that have been set on the Synth's parameters, along with any names given to
+  /// them.
+  pub fn parameters(&self) -> &ParameterCache {
+    &self.parameters
+  }
+  /// Gets the values that have been set on the Synth's parameters, in order to give names to them.
+  pub fn parameters_mut(&mut self) -> &mut ParameterCache {
+    &mut self.parameters
+  }
 
   /// Plays a note on the Synth, using the `frequency`.
   ///
   /// If `length` is `None`, the note will continue playing until a subsequent `stop()` call. If
   /// `when` is None, the note is played immediately, otherwise the note is scheduled for the given
   /// absolute time. Use `Sound::current_sound_time()` to get the current time.
+  ///
+  /// Returns a `PlayedNote` which can be used to wait for the note to finish.
   pub fn play_frequency_note(
     &mut self,
     frequency: f32,
     volume: Volume,
     length: Option<TimeDelta>,
     when: Option<TimeTicks>,
-  ) {
+  ) -> PlayedNote {
+    let played = PlayedNote::new(self.as_source().cptr());
     unsafe {
       Self::fns().playNote.unwrap()(
         self.cptr_mut(),
@@ -216,29 +366,34 @@ impl Synth {
         length.map_or(-1.0, |l| l.to_seconds()),
         when.map_or(0, |w| w.to_sample_frames()),
       )
-    }
+    };
+    played
   }
 
-  /// Plays a MIDI note on the Synth, where 'C4' is `60.0` for the `note`.
+  /// Plays a MIDI note on the Synth.
   ///
   /// If `length` is `None`, the note will continue playing until a subsequent `stop()` call. If
   /// `when` is None, the note is played immediately, otherwise the note is scheduled for the given
   /// absolute time. Use `Sound::current_sound_time()` to get the current time.
+  ///
+  /// Returns a `PlayedNote` which can be used to wait for the note to finish.
   pub fn play_midi_note(
     &mut self,
     note: TrackNote,
     length: Option<TimeDelta>,
     when: Option<TimeTicks>,
-  ) {
+  ) -> PlayedNote {
+    let played = PlayedNote::new(self.as_source().cptr());
     unsafe {
       Self::fns().playMIDINote.unwrap()(
         self.cptr_mut(),
-        note.midi_note as f32,
+        note.note.into(),
         note.velocity.into(),
         length.map_or(-1.0, |l| l.to_seconds()),
         when.map_or(0, |w| w.to_sample_frames()),
       )
-    }
+    };
+    played
   }
 
   /// Stops the currently play8iung note.
@@ -262,6 +417,21 @@ impl Synth {
   }
 }
 
+/// The sample rate, and the number of samples, of the noise generated for `Synth::new_noise()`.
+const NOISE_SAMPLE_RATE: usize = 44_100;
+
+/// The kinds of noise that `Synth::new_noise()` can play, which differ in how much of their energy
+/// is in low frequencies.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum NoiseColor {
+  /// Equal energy at every frequency, which sounds like hiss.
+  White,
+  /// Equal energy in every octave, which sounds like rain or a waterfall.
+  Pink,
+  /// Energy falling twice as fast as pink noise, which sounds like rumble or surf.
+  Brown,
+}
+
 impl Drop for Synth {
   fn drop(&mut self) {
     // Ensure the SoundSource has a chance to clean up before it is freed.
@@ -282,6 +452,40 @@ impl AsMut<SoundSource> for Synth {
   }
 }
 
+/// The values that have been set on a `Synth`'s parameters through `Synth::set_parameter()`, for
+/// showing them to the user.
+///
+/// BUG: Playdate does not provide names or ranges for the parameters, other than
+/// `Synth::parameter_count()`, so names can be given to them here to be shown alongside the values.
+#[derive(Debug, Default, Clone)]
+pub struct ParameterCache {
+  values: BTreeMap<i32, f32>,
+  names: BTreeMap<i32, String>,
+}
+impl ParameterCache {
+  fn new() -> Self {
+    Self::default()
+  }
+
+  /// Gets the value last set for the parameter `i`, or `None` if it has not been set.
+  pub fn value(&self, i: i32) -> Option<f32> {
+    self.values.get(&i).copied()
+  }
+  /// Returns the parameters which have been set, and their values, in order of the parameter index.
+  pub fn values(&self) -> impl Iterator<Item = (i32, f32)> + '_ {
+    self.values.iter().map(|(i, v)| (*i, *v))
+  }
+
+  /// Gives a name to the parameter `i`, to be shown with its value.
+  pub fn set_name(&mut self, i: i32, name: &str) {
+    self.names.insert(i, name.into());
+  }
+  /// Gets the name given to the parameter `i`, if any.
+  pub fn name(&self, i: i32) -> Option<&str> {
+    self.names.get(&i).map(|n| n.as_str())
+  }
+}
+
 /// Parameters for the SynthGeneraterRenderFunc.
 #[allow(dead_code)]
 pub struct SynthRender<'a> {
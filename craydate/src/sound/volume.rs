@@ -67,6 +67,17 @@ impl Volume {
   pub fn to_f32(self) -> f32 {
     self.0.to_f32()
   }
+
+  /// Constructs a Volume from a gain in decibels, where 0dB is a volume of 1 and each -6dB roughly
+  /// halves the volume. Gains above 0dB are clamped to a volume of 1.
+  pub fn from_decibels(db: f32) -> Self {
+    Self::new(libm::powf(10.0, db / 20.0))
+  }
+  /// Converts to a gain in decibels, where a volume of 1 is 0dB. A volume of 0 is negative
+  /// infinity.
+  pub fn to_decibels(self) -> f32 {
+    20.0 * libm::log10f(self.to_f32())
+  }
 }
 
 impl From<f32> for Volume {
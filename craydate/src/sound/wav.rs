@@ -0,0 +1,136 @@
+use alloc::format;
+use alloc::vec::Vec;
+
+use super::adpcm;
+use super::sound_format::{sound_format_is_16_bit, sound_format_is_stereo};
+use crate::ctypes::*;
+use crate::error::Error;
+
+/// Encodes uncompressed audio `data` in `format` as a PCM WAV file.
+pub(crate) fn encode_wav(data: &[u8], format: SoundFormat, sample_rate: u32) -> Vec<u8> {
+  let channels: u16 = if sound_format_is_stereo(format) { 2 } else { 1 };
+  let bits: u16 = if sound_format_is_16_bit(format) {
+    16
+  } else {
+    8
+  };
+  let block_align = channels * bits / 8;
+
+  let mut out = Vec::with_capacity(44 + data.len());
+  out.extend_from_slice(b"RIFF");
+  out.extend_from_slice(&(36 + data.len() as u32).to_le_bytes());
+  out.extend_from_slice(b"WAVE");
+  out.extend_from_slice(b"fmt ");
+  out.extend_from_slice(&16u32.to_le_bytes());
+  out.extend_from_slice(&1u16.to_le_bytes()); // PCM.
+  out.extend_from_slice(&channels.to_le_bytes());
+  out.extend_from_slice(&sample_rate.to_le_bytes());
+  out.extend_from_slice(&(sample_rate * block_align as u32).to_le_bytes());
+  out.extend_from_slice(&block_align.to_le_bytes());
+  out.extend_from_slice(&bits.to_le_bytes());
+  out.extend_from_slice(b"data");
+  out.extend_from_slice(&(data.len() as u32).to_le_bytes());
+  if bits == 16 {
+    // Samples are stored in native order, which is little-endian on the Playdate.
+    for pair in data.chunks_exact(2) {
+      out.extend_from_slice(&i16::from_ne_bytes([pair[0], pair[1]]).to_le_bytes());
+    }
+  } else {
+    // WAV stores 8 bit samples as unsigned. UNCLEAR: We assume Playdate's are signed.
+    out.extend(data.iter().map(|b| b ^ 0x80));
+  }
+  out
+}
+
+/// Compresses 16 bit samples, interleaved if `stereo`, into an IMA-ADPCM WAV file.
+pub(crate) fn encode_adpcm_wav(pcm: &[i16], stereo: bool, sample_rate: u32) -> Vec<u8> {
+  let channels: u16 = if stereo { 2 } else { 1 };
+  let frames = pcm.len() as u32 / channels as u32;
+  let block_align = (adpcm::BLOCK_BYTES_PER_CHANNEL * channels as usize) as u16;
+  let frames_per_block = adpcm::frames_per_block(block_align as usize, channels as usize) as u32;
+  let data = adpcm::encode_blocks(pcm, channels as usize);
+  let bytes_per_sec = sample_rate as u64 * block_align as u64 / frames_per_block as u64;
+
+  let mut out = Vec::with_capacity(60 + data.len());
+  out.extend_from_slice(b"RIFF");
+  out.extend_from_slice(&(52 + data.len() as u32).to_le_bytes());
+  out.extend_from_slice(b"WAVE");
+  out.extend_from_slice(b"fmt ");
+  out.extend_from_slice(&20u32.to_le_bytes());
+  out.extend_from_slice(&0x11u16.to_le_bytes()); // IMA-ADPCM.
+  out.extend_from_slice(&channels.to_le_bytes());
+  out.extend_from_slice(&sample_rate.to_le_bytes());
+  out.extend_from_slice(&(bytes_per_sec as u32).to_le_bytes());
+  out.extend_from_slice(&block_align.to_le_bytes());
+  out.extend_from_slice(&4u16.to_le_bytes());
+  out.extend_from_slice(&2u16.to_le_bytes()); // The size of the extra format data.
+  out.extend_from_slice(&(frames_per_block as u16).to_le_bytes());
+  // The fact chunk holds the number of frames, as the last block is padded.
+  out.extend_from_slice(b"fact");
+  out.extend_from_slice(&4u32.to_le_bytes());
+  out.extend_from_slice(&frames.to_le_bytes());
+  out.extend_from_slice(b"data");
+  out.extend_from_slice(&(data.len() as u32).to_le_bytes());
+  out.extend_from_slice(&data);
+  out
+}
+
+/// Decodes a PCM WAV file into audio data, along with its format and sample rate.
+pub(crate) fn decode_wav(bytes: &[u8]) -> Result<(Vec<u8>, SoundFormat, u32), Error> {
+  if bytes.len() < 12 || &bytes[0..4] != b"RIFF" || &bytes[8..12] != b"WAVE" {
+    return Err("decode_wav: not a WAV file".into());
+  }
+  let mut fmt = None;
+  let mut pos = 12;
+  while pos + 8 <= bytes.len() {
+    let id = &bytes[pos..pos + 4];
+    let len = u32::from_le_bytes([
+      bytes[pos + 4],
+      bytes[pos + 5],
+      bytes[pos + 6],
+      bytes[pos + 7],
+    ]);
+    let start = pos + 8;
+    let end = start.saturating_add(len as usize).min(bytes.len());
+    let chunk = &bytes[start..end];
+    match id {
+      b"fmt " if chunk.len() >= 16 => {
+        let u16_at = |i: usize| u16::from_le_bytes([chunk[i], chunk[i + 1]]);
+        let tag = u16_at(0);
+        let channels = u16_at(2);
+        let rate = u32::from_le_bytes([chunk[4], chunk[5], chunk[6], chunk[7]]);
+        let bits = u16_at(14);
+        fmt = Some((tag, channels, rate, bits));
+      }
+      b"data" => {
+        let (tag, channels, rate, bits) =
+          fmt.ok_or("decode_wav: the data comes before the format")?;
+        let format = match (tag, channels, bits) {
+          (1, 1, 8) => SoundFormat::kSound8bitMono,
+          (1, 2, 8) => SoundFormat::kSound8bitStereo,
+          (1, 1, 16) => SoundFormat::kSound16bitMono,
+          (1, 2, 16) => SoundFormat::kSound16bitStereo,
+          _ => {
+            return Err(Error::String(format!(
+              "decode_wav: unsupported format (type {}, {} channels, {} bits)",
+              tag, channels, bits
+            )));
+          }
+        };
+        let data = if bits == 16 {
+          chunk
+            .chunks_exact(2)
+            .flat_map(|pair| i16::from_le_bytes([pair[0], pair[1]]).to_ne_bytes())
+            .collect()
+        } else {
+          chunk.iter().map(|b| b ^ 0x80).collect()
+        };
+        return Ok((data, format, rate));
+      }
+      _ => (),
+    }
+    // Chunks are padded to an even length.
+    pos = end + (len as usize & 1);
+  }
+  Err("decode_wav: the file has no audio data".into())
+}
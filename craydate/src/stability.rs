@@ -0,0 +1,24 @@
+//! Macros for marking the stability of the crate's public APIs.
+
+/// Marks the items inside as experimental, so they are only compiled when the `unstable-api`
+/// feature is enabled.
+///
+/// The doc comment of each experimental item should start with "**Experimental:**" so that it's
+/// clear in the docs that the item can change in any release.
+///
+/// # Example
+/// ```
+/// crate::stability::experimental! {
+///   mod sprites;
+///   pub use sprites::*;
+/// }
+/// ```
+macro_rules! experimental {
+  ($($item:item)*) => {
+    $(
+      #[cfg(feature = "unstable-api")]
+      $item
+    )*
+  };
+}
+pub(crate) use experimental;
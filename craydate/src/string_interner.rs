@@ -0,0 +1,123 @@
+use alloc::collections::BTreeSet;
+use alloc::rc::Rc;
+
+/// A string stored in a `StringInterner`.
+///
+/// Cloning an `InternedStr` does not allocate, and two `InternedStr` from the same interner are
+/// equal exactly when they point to the same storage, so comparing them is a pointer comparison.
+#[derive(Clone)]
+pub struct InternedStr(Rc<str>);
+impl InternedStr {
+  /// Returns the string's contents.
+  pub fn as_str(&self) -> &str {
+    &self.0
+  }
+}
+
+impl core::ops::Deref for InternedStr {
+  type Target = str;
+
+  fn deref(&self) -> &str {
+    &self.0
+  }
+}
+impl AsRef<str> for InternedStr {
+  fn as_ref(&self) -> &str {
+    &self.0
+  }
+}
+impl core::fmt::Display for InternedStr {
+  fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+    core::fmt::Display::fmt(&*self.0, f)
+  }
+}
+impl core::fmt::Debug for InternedStr {
+  fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+    core::fmt::Debug::fmt(&*self.0, f)
+  }
+}
+impl PartialEq for InternedStr {
+  fn eq(&self, other: &Self) -> bool {
+    // Strings from the same interner share storage, but the contents are compared as a fallback
+    // for strings from different interners.
+    Rc::ptr_eq(&self.0, &other.0) || self.0 == other.0
+  }
+}
+impl Eq for InternedStr {}
+impl PartialEq<str> for InternedStr {
+  fn eq(&self, other: &str) -> bool {
+    &*self.0 == other
+  }
+}
+impl PartialEq<&str> for InternedStr {
+  fn eq(&self, other: &&str) -> bool {
+    &*self.0 == *other
+  }
+}
+impl core::hash::Hash for InternedStr {
+  fn hash<H: core::hash::Hasher>(&self, state: &mut H) {
+    self.0.hash(state)
+  }
+}
+
+/// Holds a single copy of each distinct string given to it, to avoid allocating identical strings
+/// over and over.
+///
+/// Interning is useful for text that is looked up or rebuilt every frame, such as localization
+/// keys, UI labels, or console commands. The first call to `intern()` for a string allocates it,
+/// and later calls with the same text return a handle to the same storage.
+///
+/// # Example
+/// ```
+/// let mut interner = StringInterner::new();
+/// let a = interner.intern("menu.start");
+/// let b = interner.intern("menu.start");
+/// assert_eq!(a, b); // Only one copy of "menu.start" was allocated.
+/// ```
+#[derive(Debug, Default)]
+pub struct StringInterner {
+  strings: BTreeSet<Rc<str>>,
+}
+impl StringInterner {
+  /// Constructs an empty `StringInterner`.
+  pub fn new() -> Self {
+    StringInterner {
+      strings: BTreeSet::new(),
+    }
+  }
+
+  /// Returns the interned copy of `s`, adding it to the interner if it's not already present.
+  pub fn intern(&mut self, s: &str) -> InternedStr {
+    match self.strings.get(s) {
+      Some(rc) => InternedStr(rc.clone()),
+      None => {
+        let rc: Rc<str> = Rc::from(s);
+        self.strings.insert(rc.clone());
+        InternedStr(rc)
+      }
+    }
+  }
+  /// Returns the interned copy of `s` if it has been interned, without adding it.
+  pub fn get(&self, s: &str) -> Option<InternedStr> {
+    self.strings.get(s).map(|rc| InternedStr(rc.clone()))
+  }
+
+  /// The number of distinct strings held in the interner.
+  pub fn len(&self) -> usize {
+    self.strings.len()
+  }
+  /// Whether the interner holds no strings.
+  pub fn is_empty(&self) -> bool {
+    self.strings.is_empty()
+  }
+
+  /// Frees the strings which are no longer referred to by any `InternedStr` outside the interner.
+  pub fn remove_unused(&mut self) {
+    self.strings.retain(|rc| Rc::strong_count(rc) > 1)
+  }
+  /// Removes all strings from the interner. Outstanding `InternedStr` values remain valid, but
+  /// interning the same text again will allocate a new copy.
+  pub fn clear(&mut self) {
+    self.strings.clear()
+  }
+}
@@ -1,9 +1,17 @@
+use alloc::borrow::ToOwned;
+use alloc::boxed::Box;
 use core::cell::Cell;
+use core::future::Future;
+use core::pin::Pin;
+use core::task::{Context, Poll};
 
+use crate::allocator::MemoryStats;
 use crate::capi_state::CApiState;
 use crate::ctypes::*;
-use crate::system_event::SystemEventWatcher;
-use crate::time::{HighResolutionTimer, TimeTicks, WallClockTime};
+use crate::executor::Executor;
+use crate::graphics::{Bitmap, BitmapRef};
+use crate::system_event::{SystemEventSubscriber, SystemEventWatcher};
+use crate::time::{DateTime, HighResolutionTimer, TimeDelta, TimeTicks, WallClockTime};
 
 /// The state of the auto-lock system.
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
@@ -25,15 +33,22 @@ pub enum CrankSounds {
 
 /// Access to Playdate device's system resources such as the menu, clock, battery state, and system
 /// settings.
+///
+/// BUG: Messages sent to the game over serial, from the simulator console or `pdutil msg`, can not
+/// be received, as `setSerialMessageCallback()` is missing from the Playdate C Api headers that
+/// craydate is built against.
 #[derive(Debug)]
 pub struct System {
   // Runtime tracking to ensure only one timer is active.
   timer_active: Cell<bool>,
+  // The bitmap given to Playdate for the system menu, which is kept alive while it is in use.
+  menu_image: Option<Bitmap>,
 }
 impl System {
   pub(crate) fn new() -> Self {
     System {
       timer_active: Cell::new(false),
+      menu_image: None,
     }
   }
 
@@ -42,6 +57,17 @@ impl System {
   pub fn system_event_watcher(&self) -> SystemEventWatcher {
     SystemEventWatcher::new()
   }
+  /// Returns a `SystemEventSubscriber`, which receives its own copy of each system event, to watch
+  /// for events alongside the `SystemEventWatcher`.
+  pub fn system_event_subscriber(&self) -> SystemEventSubscriber {
+    SystemEventSubscriber::new()
+  }
+
+  /// Returns statistics about the memory the game has allocated, such as to watch for memory
+  /// growth over time.
+  pub fn memory_stats(&self) -> MemoryStats {
+    MemoryStats::get()
+  }
 
   /// Returns the current time in milliseconds.
   pub fn current_time(&self) -> TimeTicks {
@@ -58,6 +84,13 @@ impl System {
     unsafe { Self::fns().getSecondsSinceEpoch.unwrap()(&mut time) };
     WallClockTime(time)
   }
+  /// Returns the current calendar date and time, in a timezone which is `utc_offset` ahead of UTC.
+  ///
+  /// This is the `wall_clock_time()` given to `WallClockTime::to_date_time()`, so it is subject to
+  /// the same drift.
+  pub fn date_time(&self, utc_offset: TimeDelta) -> DateTime {
+    self.wall_clock_time().to_date_time(utc_offset)
+  }
 
   /// Starts a high resolution timer, and returns an object representing it.
   ///
@@ -84,11 +117,46 @@ impl System {
   pub fn is_reduce_flashing_enabled(&self) -> bool {
     unsafe { Self::fns().getReduceFlashing.unwrap()() != 0 }
   }
+  /// Returns whether flashing effects should be softened, which follows the "reduce flashing"
+  /// system setting unless the game has overridden it with `set_reduce_flashing_override()`.
+  ///
+  /// The effects provided by the crate, such as `BitmapEffect::Invert`, check this when they are
+  /// applied.
+  pub fn should_reduce_flashing(&self) -> bool {
+    Self::reduce_flashing()
+  }
+  /// Overrides the system setting for whether flashing effects should be softened, such as to
+  /// follow a setting in the game's own options. Passing `None` follows the system setting again.
+  pub fn set_reduce_flashing_override(&mut self, reduce: Option<bool>) {
+    CApiState::get().reduce_flashing_override.set(reduce)
+  }
+  pub(crate) fn reduce_flashing() -> bool {
+    match CApiState::get().reduce_flashing_override.get() {
+      Some(reduce) => reduce,
+      None => unsafe { Self::fns().getReduceFlashing.unwrap()() != 0 },
+    }
+  }
 
   /// Returns the battery percentage, which is a value between 0 and 1.
+  ///
+  /// BUG: The Playdate C Api has no function to find if the battery is charging.
   pub fn battery_percentage(&self) -> f32 {
     unsafe { Self::fns().getBatteryPercentage.unwrap()() / 100f32 }
   }
+  /// Sets the battery percentage, between 0 and 1, below which a `SystemEvent::LowBattery` event
+  /// is sent. If `None`, which is the default, the event is not sent.
+  ///
+  /// The battery is checked once per frame while a threshold is set. If the battery is already
+  /// below the threshold, the event is sent before the next frame.
+  pub fn set_low_battery_threshold(&mut self, threshold: Option<f32>) {
+    let capi = CApiState::get();
+    capi.low_battery_threshold.set(threshold);
+    capi.low_battery_reported.set(false);
+  }
+  /// Returns the battery percentage below which a `SystemEvent::LowBattery` event is sent, if any.
+  pub fn low_battery_threshold(&self) -> Option<f32> {
+    CApiState::get().low_battery_threshold.get()
+  }
 
   /// Returns the battery voltage.
   pub fn battery_voltage(&self) -> f32 {
@@ -106,19 +174,52 @@ impl System {
   /// to the left by `xoffset` pixels. For example, if the offset is 200 then the right 200 pixels
   /// would be visible instead of the left 200 pixels while the menu is open.
   ///
-  /// The bitmap will be copied, so the reference is not held.
-  pub fn set_menu_image(&mut self, bitmap: &crate::graphics::BitmapRef, xoffset: i32) {
-    // SAFETY: Playdate makes a copy from the given pointer, so we can pass it in and then drop the
-    // reference on `bitmap` when we leave the function.
-    //
-    // setMenuItem() takes a mutable pointer but does not write to the data inside it.
-    unsafe { Self::fns().setMenuImage.unwrap()(bitmap.cptr() as *mut _, xoffset.clamp(0, 200)) }
+  /// The bitmap is copied, and the copy is kept alive until the menu image is replaced or cleared,
+  /// so the game can render a new image for each time the menu opens, and drop it afterward.
+  ///
+  /// # Example
+  /// ```
+  /// // Show the game's current state beside the menu when it opens.
+  /// let mut image = Bitmap::new(400, 240, SolidColor::kColorWhite);
+  /// draw_pause_screen(&mut image);
+  /// api.system.set_menu_image(&image, 0);
+  /// ```
+  pub fn set_menu_image(&mut self, bitmap: &BitmapRef, xoffset: i32) {
+    let mut image = bitmap.to_owned();
+    // UNCLEAR: It's not documented if Playdate copies the bitmap or holds the pointer, so we give
+    // it a copy which is kept alive while it is installed.
+    unsafe { Self::fns().setMenuImage.unwrap()(image.cptr_mut(), xoffset.clamp(0, 200)) }
+    // The old image is dropped only after Playdate is given the new one.
+    self.menu_image = Some(image);
+  }
+  /// Returns the bitmap that is displayed beside the system menu, if one was set with
+  /// `set_menu_image()`.
+  pub fn menu_image(&self) -> Option<&BitmapRef> {
+    self.menu_image.as_deref()
   }
 
   /// Removes the user-specified bitmap from beside the system menu. The default image is displayed
   /// instead.
   pub fn clear_menu_image(&mut self) {
     unsafe { Self::fns().setMenuImage.unwrap()(core::ptr::null_mut(), 0) }
+    self.menu_image = None;
+  }
+
+  /// Sets a function to be called when the game is about to be closed, such as to flush saves to
+  /// disk. It replaces any function set before.
+  ///
+  /// The function is called right before the `SystemEvent::WillTerminate` event. Unlike the event,
+  /// it runs even if the game is not waiting on the `SystemEventWatcher` at the time, such as when
+  /// the game is in the middle of awaiting a sound.
+  ///
+  /// BUG: The Playdate C Api headers that craydate is built against have no function to restart or
+  /// exit the game, so the game can only be closed by the player.
+  pub fn set_terminate_handler(&mut self, handler: impl FnMut() + 'static) {
+    *CApiState::get().terminate_handler.borrow_mut() = Some(Box::new(handler))
+  }
+  /// Removes the function set by `set_terminate_handler()`.
+  pub fn clear_terminate_handler(&mut self) {
+    *CApiState::get().terminate_handler.borrow_mut() = None
   }
 
   /// To use a peripheral, it must first be enabled via this function.
@@ -130,8 +231,34 @@ impl System {
     CApiState::get().peripherals_enabled.set(which);
     unsafe { Self::fns().setPeripheralsEnabled.unwrap()(which) }
   }
+  /// Returns the peripherals that were last enabled with `enable_peripherals()`.
+  pub fn peripherals_enabled(&self) -> Peripherals {
+    CApiState::get().peripherals_enabled.get()
+  }
+
+  /// Waits until the crank is undocked. Returns immediately if the crank is already undocked.
+  ///
+  /// The change is also reported as a `SystemEvent::CrankUndocked` event.
+  ///
+  /// # Example
+  /// ```
+  /// show_prompt("Undock the crank to continue");
+  /// api.system.crank_undocked().await;
+  /// hide_prompt();
+  /// ```
+  pub async fn crank_undocked(&self) {
+    CrankDockFuture { docked: false }.await
+  }
+  /// Waits until the crank is docked. Returns immediately if the crank is already docked.
+  ///
+  /// The change is also reported as a `SystemEvent::CrankDocked` event.
+  pub async fn crank_docked(&self) {
+    CrankDockFuture { docked: true }.await
+  }
 
   /// Returns the current language of the system.
+  ///
+  /// This can be given to `StringTables::select()` to choose the game's text for the language.
   pub fn get_language(&self) -> Language {
     unsafe { Self::fns().getLanguage.unwrap()() }
   }
@@ -145,10 +272,32 @@ impl System {
   /// Note that when disabling the timeout, developers should take care to re-enable the timeout
   /// when appropiate.
   pub fn set_auto_lock(&mut self, val: AutoLock) {
+    Self::set_auto_lock_state(val)
+  }
+  /// Returns the state of the auto-lock feature, as last set by the game.
+  pub fn auto_lock(&self) -> AutoLock {
+    CApiState::get().auto_lock.get()
+  }
+  /// Disables the auto-lock feature until the returned guard is dropped, at which point it is set
+  /// back to its previous state.
+  ///
+  /// # Example
+  /// ```
+  /// // Keep the device awake while the attract mode plays.
+  /// let _awake = api.system.disable_auto_lock();
+  /// play_attract_mode().await;
+  /// ```
+  pub fn disable_auto_lock(&mut self) -> AutoLockGuard {
+    let previous = self.auto_lock();
+    Self::set_auto_lock_state(AutoLock::Disabled);
+    AutoLockGuard { previous }
+  }
+  fn set_auto_lock_state(val: AutoLock) {
     let disabled = match val {
       AutoLock::Disabled => 1,
       AutoLock::Enabled => 0,
     };
+    CApiState::get().auto_lock.set(val);
     unsafe { Self::fns().setAutoLockDisabled.unwrap()(disabled) }
   }
 
@@ -163,6 +312,16 @@ impl System {
   ///
   /// The function returns the previous value for this setting.
   pub fn set_crank_sounds(&mut self, val: CrankSounds) -> CrankSounds {
+    Self::set_crank_sounds_state(val)
+  }
+  /// Silences the crank's docking sounds until the returned guard is dropped, at which point they
+  /// are set back to their previous state.
+  pub fn silence_crank_sounds(&mut self) -> CrankSoundsGuard {
+    CrankSoundsGuard {
+      previous: Self::set_crank_sounds_state(CrankSounds::Silent),
+    }
+  }
+  fn set_crank_sounds_state(val: CrankSounds) -> CrankSounds {
     let disabled = match val {
       CrankSounds::Silent => 1,
       CrankSounds::DockingSounds => 0,
@@ -178,3 +337,46 @@ impl System {
     CApiState::get().csystem
   }
 }
+
+/// Keeps the auto-lock feature disabled while it is alive. Made by `System::disable_auto_lock()`.
+#[must_use]
+#[derive(Debug)]
+pub struct AutoLockGuard {
+  previous: AutoLock,
+}
+impl Drop for AutoLockGuard {
+  fn drop(&mut self) {
+    System::set_auto_lock_state(self.previous)
+  }
+}
+
+/// Keeps the crank's docking sounds silent while it is alive. Made by
+/// `System::silence_crank_sounds()`.
+#[must_use]
+#[derive(Debug)]
+pub struct CrankSoundsGuard {
+  previous: CrankSounds,
+}
+impl Drop for CrankSoundsGuard {
+  fn drop(&mut self) {
+    System::set_crank_sounds_state(self.previous);
+  }
+}
+
+/// A future for which poll() waits for the crank to be `docked` or not, then returns Complete.
+struct CrankDockFuture {
+  docked: bool,
+}
+impl Future for CrankDockFuture {
+  type Output = ();
+
+  fn poll(self: Pin<&mut Self>, ctxt: &mut Context<'_>) -> Poll<()> {
+    let docked = unsafe { System::fns().isCrankDocked.unwrap()() != 0 };
+    if docked == self.docked {
+      Poll::Ready(())
+    } else {
+      Executor::add_waker_for_system_event(CApiState::get().executor, ctxt.waker());
+      Poll::Pending
+    }
+  }
+}
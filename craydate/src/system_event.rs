@@ -1,5 +1,5 @@
 use alloc::rc::Rc;
-use core::cell::Cell;
+use core::cell::{Cell, RefCell};
 use core::future::Future;
 use core::pin::Pin;
 use core::task::{Context, Poll};
@@ -7,36 +7,71 @@ use core::task::{Context, Poll};
 use crate::capi_state::CApiState;
 use crate::executor::Executor;
 use crate::inputs::Inputs;
+use crate::time::TimeDelta;
 
 /// Playdate device system events.
-#[derive(Debug)]
+///
+/// Events and the fields of `NextFrame` are added as more of the Playdate's behaviour becomes
+/// available, so a `match` on the events needs a `_` arm, and a `NextFrame` pattern needs `..`.
+/// Matches written before the `delta` field and the crank docking events were added no longer
+/// compile without them.
+#[derive(Debug, Clone)]
+#[non_exhaustive]
 pub enum SystemEvent {
   /// Event when the next frame should be prepared for display. Handle this event by running the
   /// game's update and draw routines.
+  #[non_exhaustive]
   NextFrame {
     /// The current frame number, which is monotonically increasing.
     frame_number: u64,
     /// All input events since the last frame, along with current input states.
     inputs: Inputs,
+    /// The time since the last `NextFrame` event, which is zero for the first frame.
+    ///
+    /// This follows the refresh rate set with `Display::set_refresh_rate()`, and is longer when
+    /// the game takes too long to draw a frame.
+    delta: TimeDelta,
   },
   /// Event when the player chooses to exit the game via the System Menu or Menu button.
   WillTerminate,
   /// Event before the device goes to low-power sleep mode because of a low battery.
+  ///
+  /// The game may not get to run again, so handling this event allows your game to save state.
   WillSleep,
+  /// Event when the battery charge falls below the threshold set with
+  /// `System::set_low_battery_threshold()`, which comes just before the `NextFrame` event. Handling
+  /// this event allows your game to save state or dim effects while power is low.
+  ///
+  /// The event happens again only after the battery charges back above the threshold.
+  LowBattery {
+    /// The battery percentage, which is a value between 0 and 1.
+    percentage: f32,
+  },
+  /// Event when the crank is pushed back into its dock, which comes just before the `NextFrame`
+  /// event.
+  ///
+  /// Playdate does not report docking as it happens, so it is found by checking the crank each
+  /// frame.
+  CrankDocked,
+  /// Event when the crank is pulled out of its dock, which comes just before the `NextFrame` event.
+  ///
+  /// Playdate does not report undocking as it happens, so it is found by checking the crank each
+  /// frame.
+  CrankUndocked,
   /// Event before the system pauses the game.
   ///
   /// In the current version of Playdate OS, this only happens when the device’s Menu button is
   /// pushed. Handling this event allows your game to take special action when it is paused, e.g.,
   /// updating the menu image.
   WillPause,
-  /// Event before the system resumes the game.
+  /// Event before the system resumes the game, when the System Menu is closed.
   WillResume,
   /// Event if your game is running on the Playdate when the device is locked.
   ///
   /// Implementing this function allows your game to take special action when the Playdate is
   /// locked, e.g., saving state.
   WillLock,
-  // Event if your game is running on the Playdate when the device is unlocked.
+  /// Event if your game is running on the Playdate when the device is unlocked.
   DidUnlock,
   /// Event when a key is pressed in the simulator. Does not occur on device.
   SimulatorKeyPressed {
@@ -52,6 +87,61 @@ pub enum SystemEvent {
   /// running their `Callbacks` object(s).
   Callback,
 }
+impl SystemEvent {
+  /// Returns whether the event comes before the game stops being played, when the game is paused,
+  /// the device is locked or goes to sleep, or the game exits.
+  ///
+  /// These are the events where a game would mute its audio and save a checkpoint.
+  ///
+  /// # Example
+  /// ```
+  /// let event = events.next().await;
+  /// if event.is_interruption() {
+  ///   save_checkpoint();
+  /// }
+  /// ```
+  pub fn is_interruption(&self) -> bool {
+    matches!(
+      self,
+      SystemEvent::WillPause
+        | SystemEvent::WillLock
+        | SystemEvent::WillSleep
+        | SystemEvent::WillTerminate
+    )
+  }
+  /// Returns whether the event comes when the game is played again after an interruption, when the
+  /// game resumes from the System Menu or the device is unlocked.
+  pub fn is_resumption(&self) -> bool {
+    matches!(self, SystemEvent::WillResume | SystemEvent::DidUnlock)
+  }
+
+  /// Returns the key as a `char` for a `SimulatorKeyPressed` or `SimulatorKeyReleased` event, if
+  /// the keycode is a printable character. Returns `None` for any other event.
+  ///
+  /// The simulator reports letter keys as lowercase, whether or not shift is held. The events are
+  /// never sent on the device, so debug shortcuts driven by them can be left in the game.
+  ///
+  /// # Example
+  /// ```
+  /// match events.next().await {
+  ///   e @ SystemEvent::SimulatorKeyPressed { .. } => match e.simulator_key_char() {
+  ///     Some('r') => restart_level(),
+  ///     Some('g') => god_mode = !god_mode,
+  ///     _ => (),
+  ///   },
+  ///   _ => (),
+  /// }
+  /// ```
+  pub fn simulator_key_char(&self) -> Option<char> {
+    match self {
+      SystemEvent::SimulatorKeyPressed { keycode }
+      | SystemEvent::SimulatorKeyReleased { keycode } => {
+        char::from_u32(*keycode).filter(|c| !c.is_control())
+      }
+      _ => None,
+    }
+  }
+}
 
 /// An object shared between the global `CApiState` and any `SystemEventWatcher` objects, where new
 /// system events are placed in order for the `Future` returned from a `SystemEventWatcher` to find
@@ -61,11 +151,18 @@ pub(crate) struct SystemEventWatcherState {
   /// `None` until `next()` is called and yields to the Playdate system which will generate the next
   /// event.
   pub next_event: Cell<Option<SystemEvent>>,
+  /// The last system event, which is copied to each `SystemEventSubscriber` waiting for an event.
+  pub last_event: RefCell<Option<SystemEvent>>,
+  /// Counts the system events, so that a `SystemEventSubscriber` can tell if `last_event` is one it
+  /// has not seen yet.
+  pub event_count: Cell<u64>,
 }
 impl SystemEventWatcherState {
   pub(crate) fn new() -> Self {
     SystemEventWatcherState {
       next_event: Cell::new(None),
+      last_event: RefCell::new(None),
+      event_count: Cell::new(0),
     }
   }
 }
@@ -113,3 +210,68 @@ impl Future for SystemEventFuture<'_> {
     }
   }
 }
+
+/// An object used to receive a copy of each system event, alongside the `SystemEventWatcher` and
+/// any other subscribers. Call `next()` to get the next event when it is ready.
+///
+/// Each event taken from a `SystemEventWatcher` is only returned to one caller, but every
+/// subscriber waiting in `next()` receives its own copy of each event. This allows separate parts
+/// of the game which are awaited together, such as a music task and the main loop, to each watch
+/// for `NextFrame` or `Callback` events.
+///
+/// A subscriber only receives events which occur while it is waiting in `next()`.
+///
+/// # Example
+/// ```
+/// let music_task = async {
+///   let mut events = api.system.system_event_subscriber();
+///   loop {
+///     if let SystemEvent::NextFrame { .. } = events.next().await {
+///       music.update();
+///     }
+///   }
+/// };
+/// ```
+pub struct SystemEventSubscriber {
+  state: Rc<SystemEventWatcherState>,
+}
+impl SystemEventSubscriber {
+  pub(crate) fn new() -> Self {
+    let state = CApiState::get().system_event_watcher_state.borrow().clone();
+    SystemEventSubscriber { state }
+  }
+
+  /// Runs until the next system event from the Playdate device, then returns a copy of it.
+  pub async fn next(&mut self) -> SystemEvent {
+    SystemEventSubscriberFuture {
+      subscriber: self,
+      after: None,
+    }
+    .await
+  }
+}
+
+/// A future for which poll() waits for a system event after the first poll, then returns a copy of
+/// it.
+struct SystemEventSubscriberFuture<'a> {
+  subscriber: &'a SystemEventSubscriber,
+  // The event count at the first poll.
+  after: Option<u64>,
+}
+
+impl Future for SystemEventSubscriberFuture<'_> {
+  type Output = SystemEvent;
+
+  fn poll(mut self: Pin<&mut Self>, ctxt: &mut Context<'_>) -> Poll<Self::Output> {
+    let state = &self.subscriber.state;
+    let count = state.event_count.get();
+    let after = *self.after.get_or_insert(count);
+    if count > after {
+      if let Some(event) = state.last_event.borrow().clone() {
+        return Poll::Ready(event);
+      }
+    }
+    Executor::add_waker_for_system_event(CApiState::get().executor, ctxt.waker());
+    Poll::Pending
+  }
+}
@@ -0,0 +1,198 @@
+use alloc::collections::{BTreeMap, BTreeSet};
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use euclid::default::Point2D;
+
+use crate::error::Error;
+use crate::files::File;
+
+/// **Experimental:** Streams the chunks of a large tile map from files on disk, keeping only the
+/// chunks near the camera in memory.
+///
+/// The map is split into square chunks of `chunk_size` by `chunk_size` tiles. Each chunk is stored
+/// in its own file at `"{dir}/{x}_{y}.chunk"`, where `x` and `y` are the chunk's coordinates (which
+/// may be negative). A chunk file holds a little-endian `u16` tile id for each tile, row by row. A
+/// chunk without a file is treated as empty, such as for the area outside of the world.
+///
+/// Each call to `update()` loads at most a fixed number of chunks, nearest to the camera first, so
+/// that moving into a new area spreads the cost of loading over several frames instead of causing
+/// a hitch. Chunks that move out of range are unloaded, and their memory is kept in a pool to be
+/// reused for the next chunks loaded.
+///
+/// # Example
+/// ```
+/// let mut world = TileStreamer::new("levels/overworld", 16, 16);
+/// world.load_all_around(&api.file, player_pos)?;
+/// // Each frame:
+/// world.update(&api.file, camera_center)?;
+/// if let Some(tile) = world.tile_at_pixel(player_pos) {
+///   // ...
+/// }
+/// ```
+#[derive(Debug)]
+pub struct TileStreamer {
+  dir: String,
+  chunk_size: i32,
+  tile_size: i32,
+  load_radius: i32,
+  loads_per_update: usize,
+  // The loaded chunks, which are `None` if the chunk has no file.
+  chunks: BTreeMap<(i32, i32), Option<Vec<u16>>>,
+  pool: Vec<Vec<u16>>,
+}
+impl TileStreamer {
+  /// Constructs a `TileStreamer` for the chunk files in `dir`, where each chunk is `chunk_size`
+  /// tiles square, and each tile is `tile_size` pixels square.
+  pub fn new(dir: &str, chunk_size: u32, tile_size: u32) -> Self {
+    assert!(chunk_size > 0 && tile_size > 0);
+    TileStreamer {
+      dir: String::from(dir.trim_end_matches('/')),
+      chunk_size: chunk_size as i32,
+      tile_size: tile_size as i32,
+      load_radius: 1,
+      loads_per_update: 1,
+      chunks: BTreeMap::new(),
+      pool: Vec::new(),
+    }
+  }
+
+  /// Sets how many chunks out from the camera's chunk are kept loaded. The default is 1, which
+  /// keeps a 3x3 square of chunks loaded.
+  ///
+  /// Chunks are unloaded once they are one chunk further away than this, so that moving back and
+  /// forth over a chunk boundary does not reload the same chunks.
+  pub fn set_load_radius(&mut self, chunks: u32) {
+    self.load_radius = chunks as i32;
+  }
+  /// Sets the most chunks that will be loaded by each call to `update()`. The default is 1.
+  pub fn set_loads_per_update(&mut self, loads: usize) {
+    self.loads_per_update = loads.max(1);
+  }
+
+  /// Loads the chunks near the `camera` position (in pixels) that are not loaded yet, up to the
+  /// limit set by `set_loads_per_update()`, and unloads chunks that are out of range.
+  ///
+  /// Returns the number of chunks that are in range but still waiting to be loaded. Returns an
+  /// error if a chunk file could not be read or is the wrong size.
+  pub fn update(&mut self, file: &File, camera: Point2D<i32>) -> Result<usize, Error> {
+    let center = self.chunk_for_pixel(camera);
+    self.unload_out_of_range(center);
+    let mut wanted = self.missing_chunks(center);
+    let now = wanted.len().min(self.loads_per_update);
+    for chunk in wanted.drain(..now) {
+      self.load_chunk(file, chunk)?;
+    }
+    Ok(wanted.len())
+  }
+  /// Loads every chunk in range of the `camera` position (in pixels) immediately, such as when
+  /// starting a level behind a loading screen.
+  pub fn load_all_around(&mut self, file: &File, camera: Point2D<i32>) -> Result<(), Error> {
+    let center = self.chunk_for_pixel(camera);
+    self.unload_out_of_range(center);
+    for chunk in self.missing_chunks(center) {
+      self.load_chunk(file, chunk)?;
+    }
+    Ok(())
+  }
+  /// Unloads all chunks, keeping their memory for reuse.
+  pub fn unload_all(&mut self) {
+    let chunks = core::mem::take(&mut self.chunks);
+    self.pool.extend(chunks.into_values().flatten());
+  }
+
+  /// Returns the tile id at the tile coordinates (`x`, `y`).
+  ///
+  /// Returns `None` if the tile's chunk is not loaded or has no file.
+  pub fn tile(&self, x: i32, y: i32) -> Option<u16> {
+    let chunk = (x.div_euclid(self.chunk_size), y.div_euclid(self.chunk_size));
+    let tiles = self.chunks.get(&chunk)?.as_ref()?;
+    let (tx, ty) = (x.rem_euclid(self.chunk_size), y.rem_euclid(self.chunk_size));
+    Some(tiles[(ty * self.chunk_size + tx) as usize])
+  }
+  /// Returns the tile id of the tile containing the pixel at `pos`.
+  ///
+  /// Returns `None` if the tile's chunk is not loaded or has no file.
+  pub fn tile_at_pixel(&self, pos: Point2D<i32>) -> Option<u16> {
+    self.tile(
+      pos.x.div_euclid(self.tile_size),
+      pos.y.div_euclid(self.tile_size),
+    )
+  }
+  /// Whether the chunk at chunk coordinates (`x`, `y`) has been loaded, or was found to have no
+  /// file.
+  pub fn is_chunk_loaded(&self, x: i32, y: i32) -> bool {
+    self.chunks.contains_key(&(x, y))
+  }
+  /// The number of chunks held in memory.
+  pub fn loaded_chunks_count(&self) -> usize {
+    self.chunks.values().filter(|c| c.is_some()).count()
+  }
+
+  fn chunk_for_pixel(&self, pos: Point2D<i32>) -> (i32, i32) {
+    let chunk_pixels = self.chunk_size * self.tile_size;
+    (
+      pos.x.div_euclid(chunk_pixels),
+      pos.y.div_euclid(chunk_pixels),
+    )
+  }
+
+  fn unload_out_of_range(&mut self, center: (i32, i32)) {
+    let keep = self.load_radius + 1;
+    let out_of_range: BTreeSet<(i32, i32)> = self
+      .chunks
+      .keys()
+      .filter(|(x, y)| (x - center.0).abs() > keep || (y - center.1).abs() > keep)
+      .copied()
+      .collect();
+    for chunk in out_of_range {
+      if let Some(Some(tiles)) = self.chunks.remove(&chunk) {
+        self.pool.push(tiles);
+      }
+    }
+  }
+
+  /// Returns the chunks in range of `center` that are not loaded, nearest first.
+  fn missing_chunks(&self, center: (i32, i32)) -> Vec<(i32, i32)> {
+    let r = self.load_radius;
+    let mut missing = Vec::new();
+    for y in center.1 - r..=center.1 + r {
+      for x in center.0 - r..=center.0 + r {
+        if !self.chunks.contains_key(&(x, y)) {
+          missing.push((x, y));
+        }
+      }
+    }
+    missing.sort_by_key(|(x, y)| (x - center.0).abs().max((y - center.1).abs()));
+    missing
+  }
+
+  fn load_chunk(&mut self, file: &File, chunk: (i32, i32)) -> Result<(), Error> {
+    let path = chunk_path(&self.dir, chunk.0, chunk.1);
+    if file.stat(&path).is_err() {
+      self.chunks.insert(chunk, None);
+      return Ok(());
+    }
+    let bytes = file.read_file(&path)?;
+    let len = (self.chunk_size * self.chunk_size) as usize;
+    if bytes.len() != len * 2 {
+      return Err(Error::String(format!(
+        "chunk {} has {} bytes, expected {}",
+        path,
+        bytes.len(),
+        len * 2
+      )));
+    }
+    let mut tiles = self.pool.pop().unwrap_or_default();
+    tiles.clear();
+    tiles.extend(bytes.chunks_exact(2).map(|b| u16::from_le_bytes([b[0], b[1]])));
+    self.chunks.insert(chunk, Some(tiles));
+    Ok(())
+  }
+}
+
+/// The path of the file holding the chunk at chunk coordinates (`x`, `y`) in `dir`.
+pub(crate) fn chunk_path(dir: &str, x: i32, y: i32) -> String {
+  format!("{}/{}_{}.chunk", dir.trim_end_matches('/'), x, y)
+}
@@ -1,12 +1,19 @@
 use core::cell::Cell;
+use core::future::Future;
+use core::pin::Pin;
+use core::task::{Context, Poll};
 
+use crate::capi_state::CApiState;
 use crate::ctypes::*;
+use crate::executor::Executor;
 
 /// Represents the current device time, which is a monotonically increasing value.
 ///
 /// At this time the highest resolution available is milliseconds, so callers that need a raw
 /// value should normally use `total_whole_milliseconds()`. However it is always preferable to
 /// retain the TimeTicks type instead of unwrapping a primitive type from it.
+///
+/// Similar to the standard library type `std::time::Instant`.
 #[repr(transparent)]
 #[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct TimeTicks(pub(crate) u32); // Stores milliseconds.
@@ -18,6 +25,34 @@ impl TimeTicks {
     TimeTicks((sec * 1000f32) as u32)
   }
 
+  /// Returns the current device time, the same as `System::current_time()`.
+  pub fn now() -> Self {
+    let csystem = CApiState::get().csystem;
+    TimeTicks(unsafe { csystem.getCurrentTimeMilliseconds.unwrap()() })
+  }
+  /// Returns the time passed since this time, which is negative if this time is in the future.
+  pub fn elapsed(&self) -> TimeDelta {
+    Self::now() - *self
+  }
+
+  /// Returns the time `delta` after this time, or `None` if it can not be represented.
+  pub fn checked_add(self, delta: TimeDelta) -> Option<TimeTicks> {
+    if delta.0 >= 0 {
+      self.0.checked_add(delta.0 as u32).map(TimeTicks)
+    } else {
+      self.0.checked_sub(delta.0.unsigned_abs()).map(TimeTicks)
+    }
+  }
+  /// Returns the time `delta` before this time, or `None` if it can not be represented.
+  pub fn checked_sub(self, delta: TimeDelta) -> Option<TimeTicks> {
+    delta.checked_neg().and_then(|d| self.checked_add(d))
+  }
+  /// Returns the time from `earlier` to this time, or `None` if it does not fit in a `TimeDelta`.
+  pub fn checked_since(self, earlier: TimeTicks) -> Option<TimeDelta> {
+    let millis = self.0 as i64 - earlier.0 as i64;
+    i32::try_from(millis).ok().map(TimeDelta)
+  }
+
   // Returns the number of hours passed in the time, truncating any non-whole hours.
   pub fn total_whole_hours(&self) -> u32 {
     self.0 / (1000 * 60 * 60)
@@ -42,15 +77,20 @@ impl TimeTicks {
 
   /// Constructs a time from the number of sound sample frames.
   pub(crate) fn from_sample_frames(frames: u32) -> Self {
-    TimeTicks(frames * 1000 / crate::sound::SAMPLE_FRAMES_PER_SEC as u32)
+    // Computed in 64 bits, as the sound time passes u32::MAX / 1000 frames in under 2 minutes.
+    TimeTicks((frames as u64 * 1000 / crate::sound::SAMPLE_FRAMES_PER_SEC as u64) as u32)
   }
   /// Returns the time in the number of sound sample frames.
   pub(crate) fn to_sample_frames(self) -> u32 {
-    self.total_whole_milliseconds() * crate::sound::SAMPLE_FRAMES_PER_SEC as u32 / 1000
+    let millis = self.total_whole_milliseconds() as u64;
+    (millis * crate::sound::SAMPLE_FRAMES_PER_SEC as u64 / 1000) as u32
   }
 }
 
 /// The difference between two TimeTicks.
+///
+/// Similar to the standard library type `core::time::Duration`, but it can be negative. It can be
+/// converted to and from a `Duration` with `TryFrom`.
 #[repr(transparent)]
 #[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct TimeDelta(i32); // Stores milliseconds.
@@ -102,14 +142,40 @@ impl TimeDelta {
     (self.0 as f32) / 1000f32
   }
 
+  /// Returns the sum of the two deltas, or `None` if it can not be represented.
+  pub fn checked_add(self, rhs: TimeDelta) -> Option<TimeDelta> {
+    self.0.checked_add(rhs.0).map(TimeDelta)
+  }
+  /// Returns the difference of the two deltas, or `None` if it can not be represented.
+  pub fn checked_sub(self, rhs: TimeDelta) -> Option<TimeDelta> {
+    self.0.checked_sub(rhs.0).map(TimeDelta)
+  }
+  /// Returns the delta multiplied by `rhs`, or `None` if it can not be represented.
+  pub fn checked_mul(self, rhs: i32) -> Option<TimeDelta> {
+    self.0.checked_mul(rhs).map(TimeDelta)
+  }
+  /// Returns the negated delta, or `None` if it can not be represented.
+  pub fn checked_neg(self) -> Option<TimeDelta> {
+    self.0.checked_neg().map(TimeDelta)
+  }
+  /// Returns the absolute value of the delta.
+  pub fn abs(self) -> TimeDelta {
+    TimeDelta(self.0.saturating_abs())
+  }
+  /// Returns whether the delta is less than zero.
+  pub fn is_negative(self) -> bool {
+    self.0 < 0
+  }
+
   /// Constructs a time delta from the number of sound sample frames.
   #[allow(dead_code)]  // Not currently used.
   pub(crate) fn from_sample_frames(frames: i32) -> Self {
-    TimeDelta(frames * 1000 / crate::sound::SAMPLE_FRAMES_PER_SEC)
+    TimeDelta((frames as i64 * 1000 / crate::sound::SAMPLE_FRAMES_PER_SEC as i64) as i32)
   }
   /// Returns the time delta in the number of sound sample frames.
   pub(crate) fn to_sample_frames(self) -> i32 {
-    self.total_whole_milliseconds() * crate::sound::SAMPLE_FRAMES_PER_SEC / 1000
+    let millis = self.total_whole_milliseconds() as i64;
+    (millis * crate::sound::SAMPLE_FRAMES_PER_SEC as i64 / 1000) as i32
   }
 }
 
@@ -150,6 +216,70 @@ impl core::ops::Sub<TimeTicks> for TimeTicks {
   }
 }
 
+impl core::ops::Add<TimeDelta> for TimeDelta {
+  type Output = TimeDelta;
+
+  fn add(self, rhs: TimeDelta) -> Self::Output {
+    self.checked_add(rhs).unwrap()
+  }
+}
+impl core::ops::Sub<TimeDelta> for TimeDelta {
+  type Output = TimeDelta;
+
+  fn sub(self, rhs: TimeDelta) -> Self::Output {
+    self.checked_sub(rhs).unwrap()
+  }
+}
+impl core::ops::Neg for TimeDelta {
+  type Output = TimeDelta;
+
+  fn neg(self) -> Self::Output {
+    self.checked_neg().unwrap()
+  }
+}
+impl core::ops::AddAssign<TimeDelta> for TimeTicks {
+  fn add_assign(&mut self, rhs: TimeDelta) {
+    *self = *self + rhs
+  }
+}
+impl core::ops::SubAssign<TimeDelta> for TimeTicks {
+  fn sub_assign(&mut self, rhs: TimeDelta) {
+    *self = *self - rhs
+  }
+}
+impl core::ops::AddAssign<TimeDelta> for TimeDelta {
+  fn add_assign(&mut self, rhs: TimeDelta) {
+    *self = *self + rhs
+  }
+}
+impl core::ops::SubAssign<TimeDelta> for TimeDelta {
+  fn sub_assign(&mut self, rhs: TimeDelta) {
+    *self = *self - rhs
+  }
+}
+
+impl TryFrom<core::time::Duration> for TimeDelta {
+  type Error = crate::error::Error;
+
+  /// Converts a `Duration` to a `TimeDelta`, truncating any part of a millisecond. Returns an error
+  /// if the duration is too long for a `TimeDelta`.
+  fn try_from(d: core::time::Duration) -> Result<Self, Self::Error> {
+    i32::try_from(d.as_millis())
+      .map(TimeDelta)
+      .map_err(|_| "Duration is too long for a TimeDelta".into())
+  }
+}
+impl TryFrom<TimeDelta> for core::time::Duration {
+  type Error = crate::error::Error;
+
+  /// Converts a `TimeDelta` to a `Duration`. Returns an error if the delta is negative.
+  fn try_from(d: TimeDelta) -> Result<Self, Self::Error> {
+    u64::try_from(d.0)
+      .map(core::time::Duration::from_millis)
+      .map_err(|_| "A negative TimeDelta can not be a Duration".into())
+  }
+}
+
 impl core::fmt::Display for TimeTicks {
   fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
     write!(f, "{} seconds", self.to_seconds())
@@ -232,43 +362,208 @@ impl WallClockTime {
   pub const PLAYDATE_EPOCH: WallClockTime = WallClockTime(0);
 }
 
+impl WallClockTime {
+  /// Constructs a `WallClockTime` from the number of seconds since `PLAYDATE_EPOCH`, such as a
+  /// value previously saved from `seconds_since_epoch()`.
+  pub fn from_seconds_since_epoch(seconds: u32) -> Self {
+    WallClockTime(seconds)
+  }
+  /// Returns the number of whole seconds since `PLAYDATE_EPOCH`.
+  pub fn seconds_since_epoch(&self) -> u32 {
+    self.0
+  }
+}
+
 impl core::ops::Add<TimeDelta> for WallClockTime {
-  type Output = TimeTicks;
+  type Output = WallClockTime;
 
   fn add(self, rhs: TimeDelta) -> Self::Output {
-    if rhs.0 >= 0 {
-      TimeTicks(self.0.checked_add(rhs.0 as u32).unwrap())
+    let seconds = rhs.total_whole_seconds();
+    if seconds >= 0 {
+      WallClockTime(self.0.checked_add(seconds as u32).unwrap())
     } else {
-      TimeTicks(self.0.checked_sub((-rhs.0) as u32).unwrap())
+      WallClockTime(self.0.checked_sub((-seconds) as u32).unwrap())
     }
   }
 }
 impl core::ops::Sub<TimeDelta> for WallClockTime {
-  type Output = TimeTicks;
+  type Output = WallClockTime;
 
   fn sub(self, rhs: TimeDelta) -> Self::Output {
-    if rhs.0 >= 0 {
-      TimeTicks(self.0.checked_sub(rhs.0 as u32).unwrap())
+    let seconds = rhs.total_whole_seconds();
+    if seconds >= 0 {
+      WallClockTime(self.0.checked_sub(seconds as u32).unwrap())
     } else {
-      TimeTicks(self.0.checked_add((-rhs.0) as u32).unwrap())
+      WallClockTime(self.0.checked_add((-seconds) as u32).unwrap())
     }
   }
 }
 
+/// The difference between two times, as a `TimeDelta`.
+///
+/// A `TimeDelta` holds milliseconds in an `i32`, so it can only represent about 24.8 days. A larger
+/// difference saturates to the largest or smallest `TimeDelta`.
 impl core::ops::Sub<WallClockTime> for WallClockTime {
   type Output = TimeDelta;
 
   fn sub(self, rhs: WallClockTime) -> Self::Output {
-    if self > rhs {
-      let positive_val = self.0 - rhs.0;
-      TimeDelta(positive_val as i32)
+    // WallClockTime stores seconds, while TimeDelta stores milliseconds.
+    let millis = (self.0 as i64 - rhs.0 as i64) * 1000;
+    let millis = i32::try_from(millis).unwrap_or(if millis > 0 { i32::MAX } else { i32::MIN });
+    TimeDelta::from_milliseconds(millis)
+  }
+}
+
+impl WallClockTime {
+  /// Returns the calendar date and time of the `WallClockTime`, in a timezone which is
+  /// `utc_offset` ahead of UTC.
+  ///
+  /// BUG: The Playdate C Api has no function to get the device's timezone, so the offset must come
+  /// from the game, such as from a setting chosen by the player. Use `TimeDelta::from_seconds(0)`
+  /// for UTC.
+  pub fn to_date_time(&self, utc_offset: TimeDelta) -> DateTime {
+    let seconds = self.0 as i64 + utc_offset.total_whole_seconds() as i64;
+    let days = seconds.div_euclid(SECONDS_PER_DAY);
+    let time = seconds.rem_euclid(SECONDS_PER_DAY);
+    let (year, month, day) = civil_from_days(days + DAYS_FROM_UNIX_TO_PLAYDATE_EPOCH);
+    DateTime {
+      year: year as u16,
+      month: month as u8,
+      day: day as u8,
+      // The unix epoch was a Thursday, which is the 4th day of the week.
+      weekday: ((days + DAYS_FROM_UNIX_TO_PLAYDATE_EPOCH + 3).rem_euclid(7) + 1) as u8,
+      hour: (time / 3600) as u8,
+      minute: (time / 60 % 60) as u8,
+      second: (time % 60) as u8,
+    }
+  }
+}
+
+const SECONDS_PER_DAY: i64 = 24 * 60 * 60;
+/// The number of days from January 1, 1970 to January 1, 2000.
+const DAYS_FROM_UNIX_TO_PLAYDATE_EPOCH: i64 = 10957;
+
+/// Returns the (year, month, day) of the day that is `days` after January 1, 1970.
+fn civil_from_days(days: i64) -> (i64, i64, i64) {
+  // From <http://howardhinnant.github.io/date_algorithms.html#civil_from_days>, where years are
+  // counted from March so that the leap day is at the end.
+  let z = days + 719468;
+  let era = z.div_euclid(146097);
+  let doe = z - era * 146097;
+  let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+  let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+  let mp = (5 * doy + 2) / 153;
+  let day = doy - (153 * mp + 2) / 5 + 1;
+  let month = if mp < 10 { mp + 3 } else { mp - 9 };
+  let year = yoe + era * 400 + (month <= 2) as i64;
+  (year, month, day)
+}
+/// Returns the number of days from January 1, 1970 to the `year`, `month` and `day`.
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+  let year = if month <= 2 { year - 1 } else { year };
+  let era = year.div_euclid(400);
+  let yoe = year - era * 400;
+  let doy = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+  let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+  era * 146097 + doe - 719468
+}
+
+/// A calendar date and time of day, such as for showing a clock or finding when a new day starts.
+///
+/// Made from a `WallClockTime` with `WallClockTime::to_date_time()`, or from
+/// `System::date_time()`. The `Display` format is `2000-01-31 13:05:09`.
+///
+/// # Example
+/// ```
+/// let now = api.system.date_time(utc_offset);
+/// api.graphics.draw_text(&now.format_time(player_prefers_24_hour), 10, 10)?;
+/// // Daily events can compare the day number to the one saved last time the game ran.
+/// if now.days_since_epoch() != saved_day { give_daily_reward(); }
+/// ```
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct DateTime {
+  /// The year, such as 2022.
+  pub year: u16,
+  /// The month, from 1 for January to 12 for December.
+  pub month: u8,
+  /// The day of the month, starting at 1.
+  pub day: u8,
+  /// The day of the week, from 1 for Monday to 7 for Sunday.
+  pub weekday: u8,
+  /// The hour, from 0 to 23.
+  pub hour: u8,
+  /// The minute, from 0 to 59.
+  pub minute: u8,
+  /// The second, from 0 to 59.
+  pub second: u8,
+}
+impl DateTime {
+  /// Constructs a `DateTime` from a calendar date and time of day, and finds the day of the week.
+  pub fn new(year: u16, month: u8, day: u8, hour: u8, minute: u8, second: u8) -> Self {
+    let days = days_from_civil(year as i64, month as i64, day as i64);
+    DateTime {
+      year,
+      month,
+      day,
+      // The unix epoch was a Thursday, which is the 4th day of the week.
+      weekday: ((days + 3).rem_euclid(7) + 1) as u8,
+      hour,
+      minute,
+      second,
+    }
+  }
+
+  /// Returns the `WallClockTime` of the date and time, where the date and time are in a timezone
+  /// which is `utc_offset` ahead of UTC.
+  ///
+  /// The `weekday` is ignored. Returns `None` if the time is before `WallClockTime::PLAYDATE_EPOCH`
+  /// or too far after it to be represented.
+  pub fn to_wall_clock_time(&self, utc_offset: TimeDelta) -> Option<WallClockTime> {
+    let seconds = self.days_since_epoch() as i64 * SECONDS_PER_DAY
+      + self.hour as i64 * 3600
+      + self.minute as i64 * 60
+      + self.second as i64
+      - utc_offset.total_whole_seconds() as i64;
+    u32::try_from(seconds).ok().map(WallClockTime)
+  }
+
+  /// Returns the number of days from January 1, 2000 to the date, which changes at midnight.
+  ///
+  /// This can be compared with an earlier value to find if the day has changed.
+  pub fn days_since_epoch(&self) -> i32 {
+    let days = days_from_civil(self.year as i64, self.month as i64, self.day as i64);
+    (days - DAYS_FROM_UNIX_TO_PLAYDATE_EPOCH) as i32
+  }
+
+  /// Formats the time of day as hours and minutes, such as `13:05` if `twenty_four_hour` is true,
+  /// or `1:05 PM` if it is false.
+  ///
+  /// BUG: The Playdate C Api has no function to get the device's 24 hour time setting, so the game
+  /// must choose the format.
+  pub fn format_time(&self, twenty_four_hour: bool) -> alloc::string::String {
+    if twenty_four_hour {
+      alloc::format!("{:02}:{:02}", self.hour, self.minute)
     } else {
-      let positive_val = rhs.0 - self.0;
-      TimeDelta(-(positive_val as i32))
+      let hour = match self.hour % 12 {
+        0 => 12,
+        h => h,
+      };
+      let am_pm = if self.hour < 12 { "AM" } else { "PM" };
+      alloc::format!("{}:{:02} {}", hour, self.minute, am_pm)
     }
   }
 }
 
+impl core::fmt::Display for DateTime {
+  fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+    write!(
+      f,
+      "{:04}-{:02}-{:02} {:02}:{:02}:{:02}",
+      self.year, self.month, self.day, self.hour, self.minute, self.second
+    )
+  }
+}
+
 /// A span of time with an absolute (unsigned) start and end.
 #[derive(Debug)]
 pub struct TimeSpan {
@@ -282,3 +577,46 @@ pub struct RelativeTimeSpan {
   pub start: TimeDelta,
   pub end: TimeDelta,
 }
+
+/// Waits until `duration` has passed. Returns immediately if the `duration` is not positive.
+///
+/// The wait ends at the first system event after the `duration` has passed.
+///
+/// # Example
+/// ```
+/// show_banner("Level 1");
+/// craydate::sleep(TimeDelta::from_seconds(2)).await;
+/// hide_banner();
+/// ```
+pub async fn sleep(duration: TimeDelta) {
+  if duration <= TimeDelta::from_milliseconds(0) {
+    return;
+  }
+  // A positive delta too large to add is far enough in the future to never end.
+  let deadline = TimeTicks::now().checked_add(duration).unwrap_or(TimeTicks(u32::MAX));
+  SleepFuture { deadline }.await
+}
+/// Waits until the device time reaches `deadline`. Returns immediately if it is already past.
+///
+/// The wait ends at the first system event after the `deadline`.
+pub async fn sleep_until(deadline: TimeTicks) {
+  SleepFuture { deadline }.await
+}
+
+/// A future for which poll() waits for the device time to reach the `deadline`, then returns
+/// Complete.
+struct SleepFuture {
+  deadline: TimeTicks,
+}
+impl Future for SleepFuture {
+  type Output = ();
+
+  fn poll(self: Pin<&mut Self>, ctxt: &mut Context<'_>) -> Poll<()> {
+    if TimeTicks::now() >= self.deadline {
+      Poll::Ready(())
+    } else {
+      Executor::add_waker_for_system_event(CApiState::get().executor, ctxt.waker());
+      Poll::Pending
+    }
+  }
+}